@@ -20,6 +20,48 @@ pub const ROOCH_INDEXER_DB_FILENAME: &str = "indexer.sqlite";
 pub struct IndexerConfig {
     // #[clap(skip)]
     // pub db_url: Option<String>,
+    /// Decode each transaction to JSON and store it alongside the raw transaction.
+    #[serde(default)]
+    #[clap(long = "indexer-decode-transaction")]
+    pub decode_transaction: bool,
+
+    /// Commit the indexed data in batches aligned to blocks of this many transactions,
+    /// the block of a transaction is `tx_order / block_size`. These blocks only group the `tx_order`s,
+    /// they are not the blocks of the proposer. A block is committed when the next block starts, or by
+    /// the periodic flush. If not set, every transaction is committed on its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "indexer-block-size")]
+    pub block_size: Option<u64>,
+
+    /// Commit the partial block every this many seconds when `block_size` is set, so the indexed data
+    /// of a quiet chain is not held back until the next block starts. If not set, the default interval is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "indexer-block-flush-interval-secs")]
+    pub block_flush_interval_secs: Option<u64>,
+
+    /// The capacity of the indexer input channel. When it is full, the execution waits for the indexer
+    /// to catch up instead of buffering the writes. If not set, the default capacity is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "indexer-channel-capacity")]
+    pub channel_capacity: Option<usize>,
+
+    /// Replace the indexed transaction and its events when a transaction arrives at an already indexed
+    /// `tx_order`, instead of failing on the duplicate order. For the sequencer corrections, such as recovery.
+    #[serde(default)]
+    #[clap(long = "indexer-upsert-transactions")]
+    pub upsert_transactions: bool,
+
+    /// Only index the transactions calling the functions of these modules, in `<address>` or
+    /// `<address>::<module>` format, an address matches all its modules. If not set, all the transactions are indexed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[clap(long = "indexer-module")]
+    pub modules: Vec<String>,
+
+    /// Also index the module publishing transactions when `modules` is set.
+    #[serde(default)]
+    #[clap(long = "indexer-include-module-publish")]
+    pub include_module_publish: bool,
+
     #[serde(skip)]
     #[clap(skip)]
     base: Option<Arc<BaseConfig>>,
@@ -70,8 +112,29 @@ impl IndexerConfig {
 }
 
 impl ConfigModule for IndexerConfig {
-    fn merge_with_opt(&mut self, _opt: &RoochOpt, base: Arc<BaseConfig>) -> Result<()> {
+    fn merge_with_opt(&mut self, opt: &RoochOpt, base: Arc<BaseConfig>) -> Result<()> {
         self.base = Some(base);
+        if opt.indexer.decode_transaction {
+            self.decode_transaction = true;
+        }
+        if opt.indexer.block_size.is_some() {
+            self.block_size = opt.indexer.block_size;
+        }
+        if opt.indexer.block_flush_interval_secs.is_some() {
+            self.block_flush_interval_secs = opt.indexer.block_flush_interval_secs;
+        }
+        if opt.indexer.channel_capacity.is_some() {
+            self.channel_capacity = opt.indexer.channel_capacity;
+        }
+        if opt.indexer.upsert_transactions {
+            self.upsert_transactions = true;
+        }
+        if !opt.indexer.modules.is_empty() {
+            self.modules = opt.indexer.modules.clone();
+        }
+        if opt.indexer.include_module_publish {
+            self.include_module_publish = true;
+        }
 
         Ok(())
     }
@@ -3,9 +3,12 @@
 
 pub mod config;
 pub mod indexer_config;
+pub mod relayer_config;
 pub mod server_config;
 pub mod store_config;
 
+use crate::indexer_config::IndexerConfig;
+use crate::relayer_config::RelayerConfig;
 use crate::store_config::StoreConfig;
 use anyhow::Result;
 use clap::Parser;
@@ -73,6 +76,9 @@ pub struct RoochOpt {
     #[clap(flatten)]
     pub store: StoreConfig,
 
+    #[clap(flatten)]
+    pub indexer: IndexerConfig,
+
     /// Optional custom port, which the rooch server should listen on.
     /// The port on which the server should listen defaults to `50051`
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -85,6 +91,9 @@ pub struct RoochOpt {
     #[clap(long)]
     pub eth_rpc_url: Option<String>,
 
+    #[clap(flatten)]
+    pub relayer: RelayerConfig,
+
     /// The address of the sequencer account
     #[clap(long)]
     pub sequencer_account: Option<String>,
@@ -112,8 +121,10 @@ impl RoochOpt {
             base_data_dir: Some(PathBuf::from("TMP")),
             chain_id: Some(RoochChainID::LOCAL),
             store: StoreConfig::default(),
+            indexer: IndexerConfig::default(),
             port: None,
             eth_rpc_url: None,
+            relayer: RelayerConfig::default(),
             sequencer_account: None,
             proposer_account: None,
             relayer_account: None,
@@ -0,0 +1,231 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{BaseConfig, ConfigModule, RoochOpt};
+use anyhow::Result;
+use clap::Parser;
+use moveos_types::h256::H256;
+use moveos_types::move_types::FunctionId;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A trusted Ethereum block hash at a given block number.
+/// The command line format is `<block_number>:<block_hash>`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct EthereumCheckpoint {
+    pub number: u64,
+    pub hash: H256,
+}
+
+impl FromStr for EthereumCheckpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, hash) = s.split_once(':').ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid checkpoint {}, expect format <block_number>:<block_hash>",
+                s
+            )
+        })?;
+        Ok(Self {
+            number: number.trim().parse()?,
+            hash: H256::from_str(hash.trim())?,
+        })
+    }
+}
+
+impl std::fmt::Display for EthereumCheckpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{:?}", self.number, self.hash)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Parser)]
+pub struct RelayerConfig {
+    /// Trusted Ethereum block checkpoints, in `<block_number>:<block_hash>` format.
+    /// The relayer refuses to relay a block at a checkpointed number if its hash does not match.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[clap(long = "eth-checkpoint")]
+    pub eth_checkpoints: Vec<EthereumCheckpoint>,
+
+    /// The expected chain id of the Ethereum RPC endpoint.
+    /// If not set, the chain id returned by the endpoint at startup is expected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-chain-id")]
+    pub eth_chain_id: Option<u64>,
+
+    /// The first Ethereum block number to relay, the earlier blocks are skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-start-block")]
+    pub eth_start_block: Option<u64>,
+
+    /// The last Ethereum block number to relay, the relayer stops once the chain passes it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-end-block")]
+    pub eth_end_block: Option<u64>,
+
+    /// Only relay the blocks finalized by the consensus layer, with the `finalized` block tag.
+    #[serde(default)]
+    #[clap(long = "eth-finalized-only")]
+    pub eth_finalized_only: bool,
+
+    /// If the Ethereum RPC does not support the `finalized` block tag, relay the block with this many confirmations instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-fallback-confirmations")]
+    pub eth_fallback_confirmations: Option<u64>,
+
+    /// The backup Ethereum RPC URLs, tried in order when the primary `eth_rpc_url` fails.
+    /// All of them must return the same chain id as the primary.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[clap(long = "eth-backup-rpc-url")]
+    pub eth_backup_rpc_urls: Vec<String>,
+
+    /// An Ethereum RPC endpoint is skipped after this many consecutive failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-endpoint-failure-threshold")]
+    pub eth_endpoint_failure_threshold: Option<u32>,
+
+    /// How long a repeatedly failing Ethereum RPC endpoint is skipped, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-endpoint-cooldown-secs")]
+    pub eth_endpoint_cooldown_secs: Option<u64>,
+
+    /// Fetch the receipts of each block and verify they match the block's `receipts_root` before relaying it.
+    /// A mismatch means the RPC returns logs the block does not commit to. It costs one RPC call per transaction.
+    #[serde(default)]
+    #[clap(long = "eth-verify-receipts-root")]
+    pub eth_verify_receipts_root: bool,
+
+    /// Send a heartbeat to the light client when no block has been submitted for this many seconds,
+    /// an on-chain liveness signal while the chain is quiet. If not set, no heartbeat is sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-heartbeat-interval-secs")]
+    pub eth_heartbeat_interval_secs: Option<u64>,
+
+    /// Track the logs of each relayed block, and notify the light client of the logs removed by a reorg,
+    /// so the log consumers can revert them. It costs one RPC call per block.
+    #[serde(default)]
+    #[clap(long = "eth-notify-removed-logs")]
+    pub eth_notify_removed_logs: bool,
+
+    /// The functions which receive each relayed block, in `<address>::<module>::<function>` format.
+    /// Each function takes the BCS bytes of the block header, and is called in the given order.
+    /// If not set, the blocks are submitted to the Ethereum light client only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[clap(long = "eth-destination")]
+    pub eth_destinations: Vec<FunctionId>,
+
+    /// Persist the hashes of the recently processed blocks to this file, and reload them on restart,
+    /// so the reorgs of the recent blocks are still detected. The cache keeps the blocks within the confirmation depth,
+    /// a corrupt cache is ignored and rebuilt. If not set, the processed blocks are only kept in memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-block-cache-path")]
+    pub eth_block_cache_path: Option<PathBuf>,
+
+    /// A block fetch slower than this many milliseconds is logged and counted as a failure of the endpoint,
+    /// so a degrading endpoint trips the circuit breaker and the relayer fails over.
+    /// The fetch latency is always tracked in the relayer status, if not set no fetch is treated as slow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "eth-slow-fetch-threshold-ms")]
+    pub eth_slow_fetch_threshold_ms: Option<u64>,
+
+    /// The fixed max gas amount of the transaction wrapping each relayed call, a submission which runs out of gas is not retried.
+    /// It can not be combined with `relayer_fee_estimate_percent`. If neither is set, the default max gas amount is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "relayer-fee-max-gas-amount")]
+    pub relayer_fee_max_gas_amount: Option<u64>,
+
+    /// Set the max gas amount of each relay transaction to this percent of the gas used by the last executed one,
+    /// and raise it when a submission runs out of gas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "relayer-fee-estimate-percent")]
+    pub relayer_fee_estimate_percent: Option<u64>,
+
+    /// The max gas amount estimated with `relayer_fee_estimate_percent` is never raised above this cap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[clap(long = "relayer-fee-max-gas-cap")]
+    pub relayer_fee_max_gas_cap: Option<u64>,
+}
+
+impl ConfigModule for RelayerConfig {
+    fn merge_with_opt(&mut self, opt: &RoochOpt, _base: Arc<BaseConfig>) -> Result<()> {
+        let relayer_config = opt.relayer.clone();
+        if !relayer_config.eth_checkpoints.is_empty() {
+            self.eth_checkpoints = relayer_config.eth_checkpoints;
+        }
+        if relayer_config.eth_chain_id.is_some() {
+            self.eth_chain_id = relayer_config.eth_chain_id;
+        }
+        if relayer_config.eth_start_block.is_some() {
+            self.eth_start_block = relayer_config.eth_start_block;
+        }
+        if relayer_config.eth_end_block.is_some() {
+            self.eth_end_block = relayer_config.eth_end_block;
+        }
+        if relayer_config.eth_finalized_only {
+            self.eth_finalized_only = true;
+        }
+        if relayer_config.eth_fallback_confirmations.is_some() {
+            self.eth_fallback_confirmations = relayer_config.eth_fallback_confirmations;
+        }
+        if !relayer_config.eth_backup_rpc_urls.is_empty() {
+            self.eth_backup_rpc_urls = relayer_config.eth_backup_rpc_urls;
+        }
+        if relayer_config.eth_endpoint_failure_threshold.is_some() {
+            self.eth_endpoint_failure_threshold = relayer_config.eth_endpoint_failure_threshold;
+        }
+        if relayer_config.eth_endpoint_cooldown_secs.is_some() {
+            self.eth_endpoint_cooldown_secs = relayer_config.eth_endpoint_cooldown_secs;
+        }
+        if relayer_config.eth_verify_receipts_root {
+            self.eth_verify_receipts_root = true;
+        }
+        if relayer_config.eth_heartbeat_interval_secs.is_some() {
+            self.eth_heartbeat_interval_secs = relayer_config.eth_heartbeat_interval_secs;
+        }
+        if relayer_config.eth_notify_removed_logs {
+            self.eth_notify_removed_logs = true;
+        }
+        if !relayer_config.eth_destinations.is_empty() {
+            self.eth_destinations = relayer_config.eth_destinations;
+        }
+        if relayer_config.eth_block_cache_path.is_some() {
+            self.eth_block_cache_path = relayer_config.eth_block_cache_path;
+        }
+        if relayer_config.eth_slow_fetch_threshold_ms.is_some() {
+            self.eth_slow_fetch_threshold_ms = relayer_config.eth_slow_fetch_threshold_ms;
+        }
+        if relayer_config.relayer_fee_max_gas_amount.is_some() {
+            self.relayer_fee_max_gas_amount = relayer_config.relayer_fee_max_gas_amount;
+        }
+        if relayer_config.relayer_fee_estimate_percent.is_some() {
+            self.relayer_fee_estimate_percent = relayer_config.relayer_fee_estimate_percent;
+        }
+        if relayer_config.relayer_fee_max_gas_cap.is_some() {
+            self.relayer_fee_max_gas_cap = relayer_config.relayer_fee_max_gas_cap;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for RelayerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            serde_json::to_string(self).map_err(|_e| std::fmt::Error)?
+        )
+    }
+}
+
+impl FromStr for RelayerConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let deserialized: RelayerConfig = serde_json::from_str(s)?;
+        Ok(deserialized)
+    }
+}
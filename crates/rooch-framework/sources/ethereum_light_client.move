@@ -9,10 +9,13 @@ module rooch_framework::ethereum_light_client{
     use rooch_framework::ethereum_address::ETHAddress;
     use rooch_framework::timestamp;    
     use moveos_std::bcs;
+    use moveos_std::event;
+    use std::signer;
 
     friend rooch_framework::genesis;
 
     const ErrorBlockNotFound:u64 = 1;
+    const ErrorBlockHashMismatch:u64 = 2;
 
     #[data_struct]
     struct BlockHeader has store, copy, drop {
@@ -50,6 +53,26 @@ module rooch_framework::ethereum_light_client{
         blocks: Table<u64, BlockHeader>,
     }
 
+    /// The liveness record of a relayer, updated by its heartbeats.
+    struct RelayerHeartbeat has key{
+        /// The global time of the last heartbeat, in milliseconds.
+        last_heartbeat_ms: u64,
+        /// The number of heartbeats sent by the relayer.
+        count: u64,
+    }
+
+    /// Event emitted when a reorg removes a block whose logs may have been relayed.
+    /// The logs are identified by the block number and their log index in the block,
+    /// the modules which consumed them should revert their effects.
+    struct LogsRemovedEvent has drop, store {
+        /// The number of the orphaned block
+        block_number: u64,
+        /// The hash of the orphaned block
+        block_hash: vector<u8>,
+        /// The log indexes of the removed logs in the orphaned block
+        log_indexes: vector<u64>,
+    }
+
     public(friend) fun genesis_init(ctx: &mut Context, genesis_account: &signer){
         let block_store = BlockStore{
             blocks: context::new_table(ctx),
@@ -79,6 +102,55 @@ module rooch_framework::ethereum_light_client{
         process_block(ctx, block_header_bytes);
     }
 
+    /// The relay server rolls back a block orphaned by a reorg, so the block which replaces it can be submitted.
+    /// The block header stored at the block number must be the orphaned block.
+    public entry fun rollback_block(ctx: &mut Context, block_number: u64, block_hash: vector<u8>){
+        remove_orphaned_block(ctx, block_number, &block_hash);
+    }
+
+    fun remove_orphaned_block(ctx: &mut Context, block_number: u64, block_hash: &vector<u8>){
+        let block_store = context::borrow_mut_resource<BlockStore>(ctx, @rooch_framework);
+        assert!(table::contains(&block_store.blocks, block_number), error::invalid_argument(ErrorBlockNotFound));
+        let block_header = table::borrow(&block_store.blocks, block_number);
+        assert!(&block_header.hash == block_hash, error::invalid_argument(ErrorBlockHashMismatch));
+        table::remove(&mut block_store.blocks, block_number);
+    }
+
+    /// The relay server notifies the logs of a block orphaned by a reorg are removed, and rolls back the block.
+    /// The block header stored at the block number must be the orphaned block.
+    public entry fun remove_logs(ctx: &mut Context, block_number: u64, block_hash: vector<u8>, log_indexes: vector<u64>){
+        remove_orphaned_block(ctx, block_number, &block_hash);
+        event::emit<LogsRemovedEvent>(LogsRemovedEvent{
+            block_number,
+            block_hash,
+            log_indexes,
+        });
+    }
+
+    /// The relay server reports it is alive while it has no new block to submit.
+    public entry fun heartbeat(ctx: &mut Context, relayer: &signer){
+        let relayer_address = signer::address_of(relayer);
+        let now = timestamp::now_milliseconds(ctx);
+        if(context::exists_resource<RelayerHeartbeat>(ctx, relayer_address)){
+            let heartbeat = context::borrow_mut_resource<RelayerHeartbeat>(ctx, relayer_address);
+            heartbeat.last_heartbeat_ms = now;
+            heartbeat.count = heartbeat.count + 1;
+        }else{
+            context::move_resource_to(ctx, relayer, RelayerHeartbeat{
+                last_heartbeat_ms: now,
+                count: 1,
+            });
+        }
+    }
+
+    /// Returns the global time in milliseconds of the last heartbeat of the relayer, 0 if it never sent one.
+    public fun last_heartbeat(ctx: &Context, relayer: address): u64{
+        if(!context::exists_resource<RelayerHeartbeat>(ctx, relayer)){
+            return 0
+        };
+        context::borrow_resource<RelayerHeartbeat>(ctx, relayer).last_heartbeat_ms
+    }
+
     /// Get block via block_number
     public fun get_block(ctx: &Context, block_number: u64): &BlockHeader{
         let block_store = context::borrow_resource<BlockStore>(ctx, @rooch_framework);
@@ -18,4 +18,99 @@ crate::natives::gas_parameter::native::define_gas_parameters_for_natives!(GasPar
     [.remove_box.per_byte_serialized, "remove_box.per_byte_serialized", (5 + 1) * MUL],
     [.drop_unchecked_box.base, "drop_unchecked_box.base", (5 + 1) * MUL],
     [.box_length.base, "box_length.base", (5 + 1) * MUL],
+    [.replace_all_box.base, "replace_all_box.base", (5 + 1) * MUL],
+    [.replace_all_box.per_byte_serialized, "replace_all_box.per_byte_serialized", (5 + 1) * MUL],
+    [.replace_all_box.per_entry, "replace_all_box.per_entry", (5 + 1) * MUL],
+    [.contains_boxes.base, "contains_boxes.base", (5 + 1) * MUL],
+    [.contains_boxes.per_byte_serialized, "contains_boxes.per_byte_serialized", (5 + 1) * MUL],
+    [.content_root_box.base, "content_root_box.base", (5 + 1) * MUL],
+    [.content_root_box.per_byte_hashed, "content_root_box.per_byte_hashed", (5 + 1) * MUL],
+    [.decrement_u64_box.base, "decrement_u64_box.base", (5 + 1) * MUL],
+    [.decrement_u64_box.per_byte_serialized, "decrement_u64_box.per_byte_serialized", (5 + 1) * MUL],
+    [.saturating_decrement_u64_box.base, "saturating_decrement_u64_box.base", (5 + 1) * MUL],
+    [.saturating_decrement_u64_box.per_byte_serialized, "saturating_decrement_u64_box.per_byte_serialized", (5 + 1) * MUL],
+    [.new_table_count_box.base, "new_table_count_box.base", (5 + 1) * MUL],
+    [.iter_next_entry_box.base, "iter_next_entry_box.base", (5 + 1) * MUL],
+    [.iter_next_entry_box.per_byte_serialized, "iter_next_entry_box.per_byte_serialized", (5 + 1) * MUL],
+    [.content_proof_box.base, "content_proof_box.base", (5 + 1) * MUL],
+    [.content_proof_box.per_byte_serialized, "content_proof_box.per_byte_serialized", (5 + 1) * MUL],
+    [.content_proof_box.per_proof_node, "content_proof_box.per_proof_node", (5 + 1) * MUL],
+    [.is_empty_box.base, "is_empty_box.base", (5 + 1) * MUL],
+    [.dirty_keys_box.base, "dirty_keys_box.base", (5 + 1) * MUL],
+    [.dirty_keys_box.per_dirty_key, "dirty_keys_box.per_dirty_key", (5 + 1) * MUL],
+    [.cas_box.base, "cas_box.base", (5 + 1) * MUL],
+    [.cas_box.per_byte_serialized, "cas_box.per_byte_serialized", (5 + 1) * MUL],
+    [.export_box.base, "export_box.base", (5 + 1) * MUL],
+    [.export_box.per_byte_serialized, "export_box.per_byte_serialized", (5 + 1) * MUL],
+    [.import_box.base, "import_box.base", (5 + 1) * MUL],
+    [.import_box.per_byte_deserialized, "import_box.per_byte_deserialized", (5 + 1) * MUL],
+    [.get_or_insert_box.base, "get_or_insert_box.base", (5 + 1) * MUL],
+    [.get_or_insert_box.per_byte_serialized, "get_or_insert_box.per_byte_serialized", (5 + 1) * MUL],
+    [.add_box_many_same.base, "add_box_many_same.base", (5 + 1) * MUL],
+    [.add_box_many_same.per_byte_serialized, "add_box_many_same.per_byte_serialized", (5 + 1) * MUL],
+    [.pending_ops_box.base, "pending_ops_box.base", (5 + 1) * MUL],
+    [.pending_ops_box.per_op, "pending_ops_box.per_op", (5 + 1) * MUL],
+    [.pending_ops_box.per_byte, "pending_ops_box.per_byte", (5 + 1) * MUL],
+    [.transfer_u64_box.base, "transfer_u64_box.base", (5 + 1) * MUL],
+    [.transfer_u64_box.per_byte_serialized, "transfer_u64_box.per_byte_serialized", (5 + 1) * MUL],
+    [.is_readonly_context_box.base, "is_readonly_context_box.base", (5 + 1) * MUL],
+    [.last_inserted_box.base, "last_inserted_box.base", (5 + 1) * MUL],
+    [.last_inserted_box.per_byte_serialized, "last_inserted_box.per_byte_serialized", (5 + 1) * MUL],
+    [.enable_last_inserted_box.base, "enable_last_inserted_box.base", (5 + 1) * MUL],
+    [.move_box.base, "move_box.base", (5 + 1) * MUL],
+    [.move_box.per_byte_serialized, "move_box.per_byte_serialized", (5 + 1) * MUL],
+    [.batch_ops_box.base, "batch_ops_box.base", (5 + 1) * MUL],
+    [.batch_ops_box.per_byte_deserialized, "batch_ops_box.per_byte_deserialized", (5 + 1) * MUL],
+    [.reserve_handle_box.base, "reserve_handle_box.base", (5 + 1) * MUL],
+    [.push_box.base, "push_box.base", (5 + 1) * MUL],
+    [.push_box.per_byte_serialized, "push_box.per_byte_serialized", (5 + 1) * MUL],
+    [.init_box.base, "init_box.base", (5 + 1) * MUL],
+    [.init_box.per_byte_serialized, "init_box.per_byte_serialized", (5 + 1) * MUL],
+    [.multi_read_box.base, "multi_read_box.base", (5 + 1) * MUL],
+    [.multi_read_box.per_read, "multi_read_box.per_read", (5 + 1) * MUL],
+    [.multi_read_box.per_byte, "multi_read_box.per_byte", (5 + 1) * MUL],
+    [.create_and_add_box.base, "create_and_add_box.base", (5 + 1) * MUL],
+    [.create_and_add_box.per_byte_serialized, "create_and_add_box.per_byte_serialized", (5 + 1) * MUL],
+    [.create_and_add_box.new_table, "create_and_add_box.new_table", (5 + 1) * MUL],
+    [.table_layouts_box.base, "table_layouts_box.base", (5 + 1) * MUL],
+    [.toggle_bool_box.base, "toggle_bool_box.base", (5 + 1) * MUL],
+    [.toggle_bool_box.per_byte_serialized, "toggle_bool_box.per_byte_serialized", (5 + 1) * MUL],
+    [.toggle_bool_box.write, "toggle_bool_box.write", (5 + 1) * MUL],
+    [.borrow_if_version_box.base, "borrow_if_version_box.base", (5 + 1) * MUL],
+    [.borrow_if_version_box.per_byte_serialized, "borrow_if_version_box.per_byte_serialized", (5 + 1) * MUL],
+    [.borrow_if_version_box.compare, "borrow_if_version_box.compare", (5 + 1) * MUL],
+    [.merge_box.base, "merge_box.base", (5 + 1) * MUL],
+    [.merge_box.per_byte_serialized, "merge_box.per_byte_serialized", (5 + 1) * MUL],
+    [.merge_box.write, "merge_box.write", (5 + 1) * MUL],
+    [.destroy_if_version_box.base, "destroy_if_version_box.base", (5 + 1) * MUL],
+    [.destroy_if_version_box.per_byte_serialized, "destroy_if_version_box.per_byte_serialized", (5 + 1) * MUL],
+    [.destroy_if_version_box.per_entry, "destroy_if_version_box.per_entry", (5 + 1) * MUL],
+    [.missing_keys_box.base, "missing_keys_box.base", (5 + 1) * MUL],
+    [.missing_keys_box.per_byte_serialized, "missing_keys_box.per_byte_serialized", (5 + 1) * MUL],
+    [.set_if_greater_u64_box.base, "set_if_greater_u64_box.base", (5 + 1) * MUL],
+    [.set_if_greater_u64_box.per_byte_serialized, "set_if_greater_u64_box.per_byte_serialized", (5 + 1) * MUL],
+    [.set_if_greater_u64_box.write, "set_if_greater_u64_box.write", (5 + 1) * MUL],
+    [.remove_prefix_box.base, "remove_prefix_box.base", (5 + 1) * MUL],
+    [.remove_prefix_box.per_byte_serialized, "remove_prefix_box.per_byte_serialized", (5 + 1) * MUL],
+    [.remove_prefix_box.per_entry, "remove_prefix_box.per_entry", (5 + 1) * MUL],
+    [.push_bounded_box.base, "push_bounded_box.base", (5 + 1) * MUL],
+    [.push_bounded_box.per_byte_serialized, "push_bounded_box.per_byte_serialized", (5 + 1) * MUL],
+    [.push_bounded_box.evict, "push_bounded_box.evict", (5 + 1) * MUL],
+    [.table_bytes_box.base, "table_bytes_box.base", (5 + 1) * MUL],
+    [.table_bytes_box.per_entry_scanned, "table_bytes_box.per_entry_scanned", (5 + 1) * MUL],
+    [.table_bytes_box.per_byte_scanned, "table_bytes_box.per_byte_scanned", (5 + 1) * MUL],
+    [.claim_slot_box.base, "claim_slot_box.base", (5 + 1) * MUL],
+    [.claim_slot_box.per_byte_serialized, "claim_slot_box.per_byte_serialized", (5 + 1) * MUL],
+    [.enable_last_modified_box.base, "enable_last_modified_box.base", (5 + 1) * MUL],
+    [.last_modified_box.base, "last_modified_box.base", (5 + 1) * MUL],
+    [.last_modified_box.per_byte_serialized, "last_modified_box.per_byte_serialized", (5 + 1) * MUL],
+    [.rotate_box.base, "rotate_box.base", (5 + 1) * MUL],
+    [.rotate_box.per_byte_serialized, "rotate_box.per_byte_serialized", (5 + 1) * MUL],
+    [.rotate_box.write, "rotate_box.write", (5 + 1) * MUL],
+    [.increment_many_u64_box.base, "increment_many_u64_box.base", (5 + 1) * MUL],
+    [.increment_many_u64_box.per_byte_serialized, "increment_many_u64_box.per_byte_serialized", (5 + 1) * MUL],
+    [.increment_many_u64_box.per_entry, "increment_many_u64_box.per_entry", (5 + 1) * MUL],
+    [.borrow_box_typed.base, "borrow_box_typed.base", (5 + 1) * MUL],
+    [.borrow_box_typed.per_byte_serialized, "borrow_box_typed.per_byte_serialized", (5 + 1) * MUL],
+    [.borrow_box_typed.per_tag_byte, "borrow_box_typed.per_tag_byte", (5 + 1) * MUL],
 ]);
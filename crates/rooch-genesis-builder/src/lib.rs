@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use move_package::BuildConfig;
-use moveos_stdlib_builder::{Stdlib, StdlibBuildConfig};
+use moveos_stdlib_builder::{Stdlib, StdlibBuildConfig, StdlibBuildOptions, StdlibDiagnostic};
 use once_cell::sync::Lazy;
 
 static STDLIB_BUILD_CONFIGS: Lazy<Vec<StdlibBuildConfig>> = Lazy::new(|| {
@@ -49,13 +49,39 @@ static STDLIB_BUILD_CONFIGS: Lazy<Vec<StdlibBuildConfig>> = Lazy::new(|| {
     ]
 });
 
-pub fn build_stdlib() -> Result<Stdlib> {
-    moveos_stdlib_builder::Stdlib::build(STDLIB_BUILD_CONFIGS.clone())
+/// Build the stdlib, `StdlibBuildOptions::default()` is the full build with documents and error code maps.
+pub fn build_stdlib(options: &StdlibBuildOptions) -> Result<Stdlib> {
+    moveos_stdlib_builder::Stdlib::build_with_options(STDLIB_BUILD_CONFIGS.clone(), options)
+}
+
+/// Build the stdlib, and return the compiler diagnostics (such as unused or deprecated warnings) alongside it.
+/// Warnings do not fail the build, the caller can decide how strict to be.
+pub fn build_stdlib_with_diagnostics(
+    options: &StdlibBuildOptions,
+) -> Result<(Stdlib, Vec<StdlibDiagnostic>)> {
+    moveos_stdlib_builder::Stdlib::build_with_diagnostics(STDLIB_BUILD_CONFIGS.clone(), options)
+}
+
+/// Returns the name and the canonical source path of each stdlib package, in build order,
+/// such as `("moveos-stdlib", <path>)`. The name is the directory name of the package.
+pub fn stdlib_package_paths() -> Vec<(String, PathBuf)> {
+    STDLIB_BUILD_CONFIGS
+        .iter()
+        .map(|config| {
+            let name = config
+                .path
+                .file_name()
+                .expect("stdlib package path should have a directory name")
+                .to_string_lossy()
+                .into_owned();
+            (name, config.path.clone())
+        })
+        .collect()
 }
 
 pub fn build_and_save_stdlib() -> Result<()> {
     std::fs::create_dir_all(generated_dir())?;
-    let stdlib = build_stdlib()?;
+    let stdlib = build_stdlib(&StdlibBuildOptions::default())?;
     stdlib.save_to_file(stdlib_output_file())
 }
 
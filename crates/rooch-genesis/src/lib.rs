@@ -6,7 +6,7 @@ use move_binary_format::{errors::Location, CompiledModule};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use move_vm_runtime::{config::VMConfig, native_functions::NativeFunction};
 use moveos::moveos::{MoveOS, MoveOSConfig};
-use moveos_stdlib_builder::Stdlib;
+use moveos_stdlib_builder::{Stdlib, StdlibBuildOptions};
 use moveos_store::{config_store::ConfigDBStore, MoveOSStore};
 use moveos_types::genesis_info::GenesisInfo;
 use moveos_types::h256;
@@ -63,10 +63,14 @@ impl RoochGenesis {
     pub fn build_with_option(genesis_ctx: GenesisContext, option: BuildOption) -> Result<Self> {
         let config = MoveOSConfig {
             vm_config: VMConfig::default(),
+            max_entries_per_table: None,
+            slow_table_load_threshold: None,
         };
 
         let config_for_test = MoveOSConfig {
             vm_config: VMConfig::default(),
+            max_entries_per_table: None,
+            slow_table_load_threshold: None,
         };
 
         let gas_params = rooch_framework::natives::GasParameters::zeros();
@@ -168,6 +172,8 @@ impl GenesisPackage {
         let gas_parameters = rooch_framework::natives::GasParameters::initial();
         let vm_config = MoveOSConfig {
             vm_config: VMConfig::default(),
+            max_entries_per_table: None,
+            slow_table_load_threshold: None,
         };
         let mut moveos = MoveOS::new(
             MoveOSStore::mock_moveos_store()?,
@@ -189,7 +195,7 @@ impl GenesisPackage {
     }
 
     pub fn build_stdlib() -> Result<Stdlib> {
-        rooch_genesis_builder::build_stdlib()
+        rooch_genesis_builder::build_stdlib(&StdlibBuildOptions::default())
     }
 
     pub fn load_stdlib() -> Result<Stdlib> {
@@ -0,0 +1,2 @@
+ALTER TABLE transactions DROP COLUMN transaction_json_decode_failed;
+ALTER TABLE transactions DROP COLUMN transaction_json;
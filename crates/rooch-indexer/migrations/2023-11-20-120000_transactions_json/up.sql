@@ -0,0 +1,2 @@
+ALTER TABLE transactions ADD COLUMN transaction_json VARCHAR;
+ALTER TABLE transactions ADD COLUMN transaction_json_decode_failed BOOLEAN NOT NULL DEFAULT FALSE;
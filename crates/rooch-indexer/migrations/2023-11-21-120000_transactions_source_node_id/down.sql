@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_transactions_source_node_id;
+
+ALTER TABLE transactions DROP COLUMN source_node_id;
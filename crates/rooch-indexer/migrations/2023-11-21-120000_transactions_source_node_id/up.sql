@@ -0,0 +1,3 @@
+ALTER TABLE transactions ADD COLUMN source_node_id VARCHAR;
+
+CREATE INDEX idx_transactions_source_node_id ON transactions (source_node_id);
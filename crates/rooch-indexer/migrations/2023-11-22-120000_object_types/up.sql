@@ -0,0 +1,7 @@
+CREATE TABLE object_types
+(
+    object_id          VARCHAR      NOT NULL PRIMARY KEY,
+    object_type        VARCHAR      NOT NULL,
+    deleted            BOOLEAN      NOT NULL DEFAULT FALSE,
+    tx_order           BIGINT       NOT NULL
+);
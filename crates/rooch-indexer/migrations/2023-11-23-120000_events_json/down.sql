@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_events_event_type_tx_order;
+
+ALTER TABLE events DROP COLUMN event_json;
@@ -0,0 +1,3 @@
+ALTER TABLE events ADD COLUMN event_json VARCHAR;
+
+CREATE INDEX idx_events_event_type_tx_order ON events (event_type, tx_order);
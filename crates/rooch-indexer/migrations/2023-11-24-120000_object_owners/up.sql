@@ -0,0 +1,8 @@
+CREATE TABLE object_owners
+(
+    object_id          VARCHAR      NOT NULL PRIMARY KEY,
+    owner              VARCHAR      NOT NULL,
+    tx_order           BIGINT       NOT NULL
+);
+
+CREATE INDEX idx_object_owners_owner ON object_owners (owner, object_id);
@@ -0,0 +1 @@
+CREATE INDEX idx_transactions_created_at_type ON transactions (created_at, transaction_type);
@@ -0,0 +1,2 @@
+ALTER TABLE transactions DROP COLUMN action_size_bytes;
+ALTER TABLE transactions DROP COLUMN tx_size_bytes;
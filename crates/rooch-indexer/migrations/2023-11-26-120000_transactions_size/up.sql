@@ -0,0 +1,2 @@
+ALTER TABLE transactions ADD COLUMN tx_size_bytes BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transactions ADD COLUMN action_size_bytes BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+CREATE INDEX idx_transactions_sender_tx_order ON transactions (sender, tx_order, created_at);
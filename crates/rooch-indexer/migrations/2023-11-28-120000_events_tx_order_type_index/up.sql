@@ -0,0 +1 @@
+CREATE INDEX idx_events_tx_order_event_type ON events (tx_order, event_type);
@@ -0,0 +1,9 @@
+CREATE TABLE object_changes
+(
+    object_id          VARCHAR      NOT NULL,
+    tx_order           BIGINT       NOT NULL,
+    deleted            BOOLEAN      NOT NULL,
+    object_type        VARCHAR,
+    owner              VARCHAR,
+    PRIMARY KEY (object_id, tx_order)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE indexer_stats
+(
+    name               VARCHAR      NOT NULL PRIMARY KEY,
+    value              BIGINT       NOT NULL
+);
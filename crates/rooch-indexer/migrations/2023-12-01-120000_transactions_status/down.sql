@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_transactions_is_success_tx_order;
+ALTER TABLE transactions DROP COLUMN abort_code;
+ALTER TABLE transactions DROP COLUMN is_success;
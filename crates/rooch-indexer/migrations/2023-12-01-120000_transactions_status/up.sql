@@ -0,0 +1,4 @@
+ALTER TABLE transactions ADD COLUMN is_success BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE transactions ADD COLUMN abort_code BIGINT;
+UPDATE transactions SET is_success = (status = '"Executed"'), abort_code = json_extract(status, '$.MoveAbort[1]');
+CREATE INDEX idx_transactions_is_success_tx_order ON transactions (is_success, tx_order);
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_object_changes_tx_order_object_id;
@@ -0,0 +1 @@
+CREATE INDEX idx_object_changes_tx_order_object_id ON object_changes (tx_order, object_id);
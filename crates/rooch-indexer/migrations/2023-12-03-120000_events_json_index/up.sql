@@ -0,0 +1 @@
+CREATE INDEX idx_events_event_type_json ON events (event_type, tx_order) WHERE event_json IS NOT NULL;
@@ -2,42 +2,132 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::actor::messages::{
-    IndexerEventsMessage, IndexerTransactionMessage, QueryIndexerEventsMessage,
-    QueryIndexerTransactionsMessage,
+    FlushIndexerBlockMessage, IndexerEventsMessage, IndexerObjectChangesMessage,
+    IndexerTransactionMessage, PauseIndexerMessage, QueryIndexerAccountNonceHistoryMessage,
+    QueryIndexerEventsMessage, QueryIndexerLatestOrderMessage, QueryIndexerObjectTypeMessage,
+    QueryIndexerObjectsOwnedByMessage, QueryIndexerPausedMessage, QueryIndexerTransactionsMessage,
+    ResumeIndexerMessage, SubscribeObjectChangesMessage,
 };
 use crate::indexer_reader::IndexerReader;
 use crate::store::traits::IndexerStoreTrait;
-use crate::types::{IndexedEvent, IndexedTransaction};
+use crate::types::{
+    IndexedEvent, IndexedObjectChange, IndexedObjectType, IndexedTransaction,
+    TransactionModuleFilter,
+};
 use crate::IndexerStore;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use coerce::actor::{context::ActorContext, message::Handler, Actor};
+use moveos_types::moveos_std::object::ObjectID;
+use moveos_types::transaction::VerifiedMoveOSTransaction;
 use rooch_types::indexer::event_filter::IndexerEvent;
 use rooch_types::transaction::TransactionWithInfo;
+use std::collections::BTreeMap;
+use tokio::sync::mpsc;
+
+/// The capacity of the channel of an object changes subscriber,
+/// a subscriber which falls behind by more changes is dropped.
+pub const OBJECT_CHANGES_SUBSCRIBER_CAPACITY: usize = 128;
+
+/// The default interval of the periodic commit of the partial block, in seconds.
+pub const DEFAULT_BLOCK_FLUSH_INTERVAL_SECS: u64 = 5;
 
 pub struct IndexerActor {
     indexer_store: IndexerStore,
     indexer_reader: IndexerReader,
+    decode_transaction: bool,
+    /// If set, the indexed data is committed in batches aligned to blocks of `block_size` transactions.
+    /// The blocks only group the `tx_order`s, the indexer does not receive the blocks of the proposer.
+    block_size: Option<u64>,
+    pending_block: Option<u64>,
+    pending_transactions: Vec<IndexedTransaction>,
+    pending_events: Vec<IndexedEvent>,
+    pending_object_changes: Vec<IndexedObjectChange>,
+    /// If set, a transaction at an already indexed `tx_order` replaces the indexed one and its events.
+    upsert_transactions: bool,
+    /// While paused, the `IndexerProxy` stops forwarding the write messages, and a write message
+    /// which reaches the actor is rejected.
+    paused: bool,
+    /// If set, only the transactions matching the filter are indexed, None indexes all.
+    module_filter: Option<TransactionModuleFilter>,
+    /// The `tx_order` of the last skipped transaction, its object changes arrive after it and are skipped too.
+    last_skipped_tx_order: Option<u64>,
+    /// The subscribers of the committed changes of each object.
+    object_subscribers: BTreeMap<ObjectID, Vec<mpsc::Sender<IndexedObjectChange>>>,
 }
 
 impl IndexerActor {
-    pub fn new(indexer_store: IndexerStore, indexer_reader: IndexerReader) -> Result<Self> {
+    pub fn new(
+        indexer_store: IndexerStore,
+        indexer_reader: IndexerReader,
+        decode_transaction: bool,
+        block_size: Option<u64>,
+        upsert_transactions: bool,
+        module_filter: Option<TransactionModuleFilter>,
+    ) -> Result<Self> {
         Ok(Self {
             indexer_store,
             indexer_reader,
+            decode_transaction,
+            block_size: block_size.filter(|block_size| *block_size > 0),
+            pending_block: None,
+            pending_transactions: vec![],
+            pending_events: vec![],
+            pending_object_changes: vec![],
+            upsert_transactions,
+            paused: false,
+            module_filter,
+            last_skipped_tx_order: None,
+            object_subscribers: BTreeMap::new(),
         })
     }
-}
 
-impl Actor for IndexerActor {}
+    fn is_filtered_out(&self, moveos_tx: &VerifiedMoveOSTransaction) -> bool {
+        self.module_filter
+            .as_ref()
+            .map_or(false, |filter| !filter.matches(&moveos_tx.action))
+    }
 
-#[async_trait]
-impl Handler<IndexerTransactionMessage> for IndexerActor {
-    async fn handle(
-        &mut self,
-        msg: IndexerTransactionMessage,
-        _ctx: &mut ActorContext,
-    ) -> Result<()> {
+    /// Commit the pending block if the data of `tx_order` belongs to a new block.
+    /// Returns false if the block batching is disabled.
+    fn prepare_block(&mut self, tx_order: u64) -> Result<bool> {
+        let block_size = match self.block_size {
+            Some(block_size) => block_size,
+            None => return Ok(false),
+        };
+        let block = tx_order / block_size;
+        if self.pending_block.map_or(false, |pending| pending != block) {
+            self.commit_pending_block()?;
+        }
+        self.pending_block = Some(block);
+        Ok(true)
+    }
+
+    fn commit_pending_block(&mut self) -> Result<()> {
+        let transactions = std::mem::take(&mut self.pending_transactions);
+        let events = std::mem::take(&mut self.pending_events);
+        let object_changes = std::mem::take(&mut self.pending_object_changes);
+        if !transactions.is_empty() {
+            self.persist_transactions(transactions)?;
+        }
+        if !events.is_empty() {
+            self.indexer_store.persist_events(events)?;
+        }
+        if !object_changes.is_empty() {
+            self.persist_object_changes(object_changes)?;
+        }
+        self.pending_block = None;
+        Ok(())
+    }
+
+    fn ensure_not_paused(&self) -> Result<()> {
+        if self.paused {
+            bail!("The indexer is paused");
+        }
+        Ok(())
+    }
+
+    fn index_transaction(&mut self, msg: IndexerTransactionMessage) -> Result<()> {
         let IndexerTransactionMessage {
             transaction,
             sequence_info,
@@ -45,38 +135,176 @@ impl Handler<IndexerTransactionMessage> for IndexerActor {
             moveos_tx,
         } = msg;
 
-        let indexed_transaction =
-            IndexedTransaction::new(transaction, sequence_info, execution_info, moveos_tx)?;
+        // Evaluate the filter before building the indexed transaction, a skipped one costs no decoding.
+        if self.is_filtered_out(&moveos_tx) {
+            self.last_skipped_tx_order = Some(sequence_info.tx_order);
+            return Ok(());
+        }
+        let indexed_transaction = IndexedTransaction::new(
+            transaction,
+            sequence_info,
+            execution_info,
+            moveos_tx,
+            self.decode_transaction,
+        )?;
+        if self.prepare_block(indexed_transaction.tx_order)? {
+            self.pending_transactions.push(indexed_transaction);
+            return Ok(());
+        }
         let transactions = vec![indexed_transaction];
-        self.indexer_store.persist_transactions(transactions)?;
+        self.persist_transactions(transactions)?;
         Ok(())
     }
-}
 
-#[async_trait]
-impl Handler<IndexerEventsMessage> for IndexerActor {
-    async fn handle(&mut self, msg: IndexerEventsMessage, _ctx: &mut ActorContext) -> Result<()> {
+    fn persist_transactions(&self, transactions: Vec<IndexedTransaction>) -> Result<()> {
+        if self.upsert_transactions {
+            self.indexer_store.upsert_transactions(transactions)?;
+        } else {
+            self.indexer_store.persist_transactions(transactions)?;
+        }
+        Ok(())
+    }
+
+    fn index_events(&mut self, msg: IndexerEventsMessage) -> Result<()> {
         let IndexerEventsMessage {
             events,
+            event_jsons,
             transaction,
             sequence_info,
             moveos_tx,
         } = msg;
 
+        if self.is_filtered_out(&moveos_tx) {
+            return Ok(());
+        }
+        let tx_order = sequence_info.tx_order;
         let events: Vec<_> = events
             .into_iter()
-            .map(|event| {
+            .zip(event_jsons)
+            .map(|(event, event_json)| {
                 IndexedEvent::new(
                     event,
+                    event_json,
                     transaction.clone(),
                     sequence_info.clone(),
                     moveos_tx.clone(),
                 )
             })
             .collect();
+        if self.prepare_block(tx_order)? {
+            self.pending_events.extend(events);
+            return Ok(());
+        }
         self.indexer_store.persist_events(events)?;
         Ok(())
     }
+
+    fn index_object_changes(&mut self, msg: IndexerObjectChangesMessage) -> Result<()> {
+        let IndexerObjectChangesMessage {
+            object_changes,
+            tx_order,
+        } = msg;
+
+        if self.last_skipped_tx_order == Some(tx_order) {
+            return Ok(());
+        }
+        if self.prepare_block(tx_order)? {
+            self.pending_object_changes.extend(object_changes);
+            return Ok(());
+        }
+        self.persist_object_changes(object_changes)
+    }
+
+    fn persist_object_changes(&mut self, object_changes: Vec<IndexedObjectChange>) -> Result<()> {
+        if self.object_subscribers.is_empty() {
+            return self.indexer_store.persist_object_changes(object_changes);
+        }
+        self.indexer_store
+            .persist_object_changes(object_changes.clone())?;
+        for change in object_changes {
+            self.notify_object_subscribers(change);
+        }
+        Ok(())
+    }
+
+    /// Deliver a committed change to the subscribers of its object.
+    /// A lagged or closed subscriber is dropped, and all the subscribers are dropped
+    /// after the deletion of the object, which ends their receivers.
+    fn notify_object_subscribers(&mut self, change: IndexedObjectChange) {
+        let object_id = change.object_id;
+        let deleted = change.object_type.is_none();
+        let subscribers = match self.object_subscribers.get_mut(&object_id) {
+            Some(subscribers) => subscribers,
+            None => return,
+        };
+        subscribers.retain(|subscriber| match subscriber.try_send(change.clone()) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                tracing::warn!(
+                    "Indexer object changes subscriber of {} lagged, dropped",
+                    object_id
+                );
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        });
+        if deleted || subscribers.is_empty() {
+            self.object_subscribers.remove(&object_id);
+        }
+    }
+}
+
+#[async_trait]
+impl Actor for IndexerActor {
+    async fn stopped(&mut self, _ctx: &mut ActorContext) {
+        if let Err(e) = self.commit_pending_block() {
+            tracing::error!("Indexer commit pending block failed when stopping: {:?}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<IndexerTransactionMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        msg: IndexerTransactionMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<()> {
+        self.ensure_not_paused()?;
+        self.index_transaction(msg)
+    }
+}
+
+#[async_trait]
+impl Handler<IndexerEventsMessage> for IndexerActor {
+    async fn handle(&mut self, msg: IndexerEventsMessage, _ctx: &mut ActorContext) -> Result<()> {
+        self.ensure_not_paused()?;
+        self.index_events(msg)
+    }
+}
+
+#[async_trait]
+impl Handler<IndexerObjectChangesMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        msg: IndexerObjectChangesMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<()> {
+        self.ensure_not_paused()?;
+        self.index_object_changes(msg)
+    }
+}
+
+#[async_trait]
+impl Handler<FlushIndexerBlockMessage> for IndexerActor {
+    async fn handle(&mut self, _msg: FlushIndexerBlockMessage, _ctx: &mut ActorContext) {
+        if self.pending_block.is_none() {
+            return;
+        }
+        if let Err(e) = self.commit_pending_block() {
+            tracing::error!("Indexer commit pending block failed when flushing: {:?}", e);
+        }
+    }
 }
 
 #[async_trait]
@@ -116,3 +344,119 @@ impl Handler<QueryIndexerEventsMessage> for IndexerActor {
             .map_err(|e| anyhow!(format!("Failed to query indexer events: {:?}", e)))
     }
 }
+
+#[async_trait]
+impl Handler<QueryIndexerLatestOrderMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        _msg: QueryIndexerLatestOrderMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<Option<u64>> {
+        self.indexer_reader
+            .latest_indexed_order()
+            .map_err(|e| anyhow!(format!("Failed to query indexer latest order: {:?}", e)))
+    }
+}
+
+#[async_trait]
+impl Handler<QueryIndexerObjectTypeMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        msg: QueryIndexerObjectTypeMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<Option<IndexedObjectType>> {
+        self.indexer_reader
+            .object_type(msg.object_id)
+            .map_err(|e| anyhow!(format!("Failed to query indexer object type: {:?}", e)))
+    }
+}
+
+#[async_trait]
+impl Handler<QueryIndexerObjectsOwnedByMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        msg: QueryIndexerObjectsOwnedByMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<Vec<ObjectID>> {
+        self.indexer_reader
+            .objects_owned_by(msg.owner)
+            .map_err(|e| anyhow!(format!("Failed to query indexer objects owned by: {:?}", e)))
+    }
+}
+
+#[async_trait]
+impl Handler<QueryIndexerAccountNonceHistoryMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        msg: QueryIndexerAccountNonceHistoryMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<Vec<(u64, u64)>> {
+        let QueryIndexerAccountNonceHistoryMessage {
+            sender,
+            cursor,
+            limit,
+        } = msg;
+        self.indexer_reader
+            .account_nonce_history(sender, 0..u64::MAX, cursor, limit)
+            .map_err(|e| {
+                anyhow!(format!(
+                    "Failed to query indexer account nonce history: {:?}",
+                    e
+                ))
+            })
+    }
+}
+
+#[async_trait]
+impl Handler<PauseIndexerMessage> for IndexerActor {
+    async fn handle(&mut self, _msg: PauseIndexerMessage, _ctx: &mut ActorContext) -> Result<()> {
+        if self.paused {
+            return Ok(());
+        }
+        // Finish the in-flight block, so the indexed data is consistent while paused.
+        self.commit_pending_block()?;
+        self.paused = true;
+        tracing::info!("Indexer paused");
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<ResumeIndexerMessage> for IndexerActor {
+    async fn handle(&mut self, _msg: ResumeIndexerMessage, _ctx: &mut ActorContext) -> Result<()> {
+        if !self.paused {
+            return Ok(());
+        }
+        self.paused = false;
+        tracing::info!("Indexer resumed");
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<QueryIndexerPausedMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        _msg: QueryIndexerPausedMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<bool> {
+        Ok(self.paused)
+    }
+}
+
+#[async_trait]
+impl Handler<SubscribeObjectChangesMessage> for IndexerActor {
+    async fn handle(
+        &mut self,
+        msg: SubscribeObjectChangesMessage,
+        _ctx: &mut ActorContext,
+    ) -> Result<mpsc::Receiver<IndexedObjectChange>> {
+        let SubscribeObjectChangesMessage { object_id } = msg;
+        let (sender, receiver) = mpsc::channel(OBJECT_CHANGES_SUBSCRIBER_CAPACITY);
+        self.object_subscribers
+            .entry(object_id)
+            .or_default()
+            .push(sender);
+        Ok(receiver)
+    }
+}
@@ -1,14 +1,19 @@
 // Copyright (c) RoochNetwork
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::types::{IndexedObjectChange, IndexedObjectType};
 use anyhow::Result;
 use coerce::actor::message::Message;
+use coerce::actor::scheduler::timer::TimerTick;
+use move_core_types::account_address::AccountAddress;
 use moveos_types::moveos_std::event::Event;
+use moveos_types::moveos_std::object::ObjectID;
 use moveos_types::transaction::{TransactionExecutionInfo, VerifiedMoveOSTransaction};
 use rooch_types::indexer::event_filter::{EventFilter, IndexerEvent, IndexerEventID};
 use rooch_types::indexer::transaction_filter::TransactionFilter;
 use rooch_types::transaction::{TransactionSequenceInfo, TransactionWithInfo, TypedTransaction};
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 /// Indexer Transaction write Message
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,6 +32,8 @@ impl Message for IndexerTransactionMessage {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct IndexerEventsMessage {
     pub events: Vec<Event>,
+    /// The decoded data of each event in JSON, None if the event can not be decoded.
+    pub event_jsons: Vec<Option<String>>,
     pub transaction: TypedTransaction,
     pub sequence_info: TransactionSequenceInfo,
     pub moveos_tx: VerifiedMoveOSTransaction,
@@ -36,6 +43,27 @@ impl Message for IndexerEventsMessage {
     type Result = Result<()>;
 }
 
+/// Indexer Object Changes write Message
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexerObjectChangesMessage {
+    pub object_changes: Vec<IndexedObjectChange>,
+    pub tx_order: u64,
+}
+
+impl Message for IndexerObjectChangesMessage {
+    type Result = Result<()>;
+}
+
+/// Commit the partial block of the indexer, sent periodically so a quiet chain does not hold it back.
+#[derive(Clone, Debug)]
+pub struct FlushIndexerBlockMessage {}
+
+impl Message for FlushIndexerBlockMessage {
+    type Result = ();
+}
+
+impl TimerTick for FlushIndexerBlockMessage {}
+
 /// Query Indexer Transactions Message
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueryIndexerTransactionsMessage {
@@ -63,3 +91,78 @@ pub struct QueryIndexerEventsMessage {
 impl Message for QueryIndexerEventsMessage {
     type Result = Result<Vec<IndexerEvent>>;
 }
+
+/// Query Indexer Latest Indexed Order Message
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryIndexerLatestOrderMessage {}
+
+impl Message for QueryIndexerLatestOrderMessage {
+    type Result = Result<Option<u64>>;
+}
+
+/// Query the latest known type of an object Message
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryIndexerObjectTypeMessage {
+    pub object_id: ObjectID,
+}
+
+impl Message for QueryIndexerObjectTypeMessage {
+    type Result = Result<Option<IndexedObjectType>>;
+}
+
+/// Query the ids of the active objects owned by an address Message
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryIndexerObjectsOwnedByMessage {
+    pub owner: AccountAddress,
+}
+
+impl Message for QueryIndexerObjectsOwnedByMessage {
+    type Result = Result<Vec<ObjectID>>;
+}
+
+/// Query the `(tx_order, sequence_number)` of the transactions sent by an address Message
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryIndexerAccountNonceHistoryMessage {
+    pub sender: AccountAddress,
+    // exclusive cursor if `Some`, otherwise start from the beginning
+    pub cursor: Option<u64>,
+    pub limit: usize,
+}
+
+impl Message for QueryIndexerAccountNonceHistoryMessage {
+    type Result = Result<Vec<(u64, u64)>>;
+}
+
+/// Pause indexing, the pending block is committed and the new write messages are rejected
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PauseIndexerMessage {}
+
+impl Message for PauseIndexerMessage {
+    type Result = Result<()>;
+}
+
+/// Resume indexing, the write messages are accepted again
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResumeIndexerMessage {}
+
+impl Message for ResumeIndexerMessage {
+    type Result = Result<()>;
+}
+
+/// Query whether the indexer is paused Message
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryIndexerPausedMessage {}
+
+impl Message for QueryIndexerPausedMessage {
+    type Result = Result<bool>;
+}
+
+/// Subscribe to the committed changes of an object Message
+#[derive(Debug)]
+pub struct SubscribeObjectChangesMessage {
+    pub object_id: ObjectID,
+}
+
+impl Message for SubscribeObjectChangesMessage {
+    type Result = Result<mpsc::Receiver<IndexedObjectChange>>;
+}
@@ -63,6 +63,15 @@ pub enum IndexerError {
 
     #[error("`{0}`: `{1}`")]
     ErrorWithContext(String, Box<IndexerError>),
+
+    #[error("Indexer failed to encode transaction action with error: `{0}`")]
+    ActionEncode(#[source] anyhow::Error),
+
+    #[error("Indexer failed to extract transaction authenticator with error: `{0}`")]
+    AuthenticatorExtract(#[source] anyhow::Error),
+
+    #[error("Indexer failed to serialize transaction status with error: `{0}`")]
+    StatusSerialize(#[source] serde_json::Error),
 }
 
 pub trait Context<T> {
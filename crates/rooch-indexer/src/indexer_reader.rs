@@ -1,27 +1,56 @@
 // Copyright (c) RoochNetwork
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::types::IndexerResult;
+use crate::types::{
+    sql_quote, DailyGasSummary, DecodedFunctionArgs, EventJsonPredicate, GasPercentiles,
+    GasSummary, IndexedEvent, IndexedObjectType, IndexedTransaction, IndexerResult, TimeBucket,
+    TransactionTypeCount, TransactionWindow, ValidationFailure, ValidationSummary,
+};
 use crate::{
     errors::IndexerError, models::transactions::StoredTransaction, SqliteConnectionConfig,
     SqliteConnectionPoolConfig, SqlitePoolConnection,
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use diesel::{
-    r2d2::ConnectionManager, Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection,
+    r2d2::ConnectionManager, Connection, ExpressionMethods, OptionalExtension, QueryDsl,
+    RunQueryDsl, SqliteConnection,
 };
-use std::ops::DerefMut;
+use futures::{stream, Stream};
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::{DerefMut, Range};
+use std::str::FromStr;
 
-use crate::models::events::StoredEvent;
-use crate::schema::{events, transactions};
+use crate::models::events::{StoredEvent, StoredEventTypeCount};
+use crate::models::indexer_stats::{
+    event_count_stat, EVENT_HANDLE_COUNTS_STAT, TOTAL_EVENTS_STAT, TOTAL_TRANSACTIONS_STAT,
+};
+use crate::models::object_changes::StoredObjectChangeCount;
+use crate::models::object_types::StoredObjectType;
+use crate::models::transactions::{
+    StoredDailyGasSummary, StoredGasSummary, StoredGasUsed, StoredSenderTransactionCount,
+    StoredTransactionTypeAvgGas, StoredTransactionTypeCount, StoredTxHashCount,
+};
+use crate::schema::{
+    events, indexer_stats, object_changes, object_owners, object_types, transactions,
+};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::StructTag;
+use moveos_types::h256::H256;
+use moveos_types::moveos_std::event::{Event, EventID};
+use moveos_types::moveos_std::object::ObjectID;
+use moveos_types::state_resolver::MoveOSResolver;
+use moveos_types::transaction::{MoveAction, VerifiedMoveAction, VerifiedMoveOSTransaction};
 use rooch_types::indexer::event_filter::{EventFilter, IndexerEvent, IndexerEventID};
 use rooch_types::indexer::transaction_filter::TransactionFilter;
-use rooch_types::transaction::TransactionWithInfo;
+use rooch_types::transaction::{AbstractTransaction, TransactionType, TransactionWithInfo};
 
 pub const TX_ORDER_STR: &str = "tx_order";
 pub const TX_HASH_STR: &str = "tx_hash";
 pub const TX_SENDER_STR: &str = "sender";
 pub const CREATED_AT_STR: &str = "created_at";
+pub const TX_SOURCE_NODE_ID_STR: &str = "source_node_id";
+pub const TX_GAS_USED_STR: &str = "gas_used";
+pub const TX_TYPE_STR: &str = "transaction_type";
 
 pub const TRANSACTION_ORIGINAL_ADDRESS_STR: &str = "multichain_original_address";
 
@@ -29,6 +58,17 @@ pub const EVENT_HANDLE_ID_STR: &str = "event_handle_id";
 pub const EVENT_INDEX_STR: &str = "event_index";
 pub const EVENT_SEQ_STR: &str = "event_seq";
 pub const EVENT_TYPE_STR: &str = "event_type";
+pub const EVENT_JSON_STR: &str = "event_json";
+
+pub const DEFAULT_STREAM_CHUNK_SIZE: usize = 1000;
+/// The max number of transactions returned by `recent_transactions`.
+pub const MAX_RECENT_TRANSACTIONS_LIMIT: usize = 200;
+
+/// The `created_at` of the transactions is in milliseconds.
+pub const MILLISECONDS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+/// Whether the indexed transactions record their `created_at`, the time based queries fail until they do.
+//TODO set it once `IndexedTransaction::new` records the transaction timestamp.
+const CREATED_AT_RECORDED: bool = false;
 
 #[derive(Clone)]
 pub(crate) struct InnerIndexerReader {
@@ -112,7 +152,7 @@ impl IndexerReader {
         descending_order: bool,
     ) -> IndexerResult<Vec<TransactionWithInfo>> {
         let tx_order = if let Some(cursor) = cursor {
-            cursor as i64
+            clamp_to_i64(cursor)
         } else if descending_order {
             let max_tx_order: i64 = self.inner_indexer_reader.run_query(|conn| {
                 transactions::dsl::transactions
@@ -158,6 +198,12 @@ impl IndexerReader {
                     from_order, to_order
                 )
             }
+            TransactionFilter::SourceNodeId(source_node_id) => {
+                format!(
+                    "{TX_SOURCE_NODE_ID_STR} = {}",
+                    sql_quote(&source_node_id.to_string())
+                )
+            }
         };
 
         let cursor_clause = if descending_order {
@@ -209,7 +255,7 @@ impl IndexerReader {
                 tx_order,
                 event_index,
             } = cursor;
-            (tx_order as i64, event_index as i64)
+            (clamp_to_i64(tx_order), clamp_to_i64(event_index))
         } else if descending_order {
             let (max_tx_order, event_index): (i64, i64) =
                 self.inner_indexer_reader.run_query(|conn| {
@@ -297,4 +343,1172 @@ impl IndexerReader {
 
         Ok(result)
     }
+
+    /// Returns the events of `event_type` in the `tx_order` range whose decoded JSON matches the predicate,
+    /// in ascending order. The event data is decoded to JSON when the event is indexed, the events which
+    /// can not be decoded have no JSON and never match.
+    /// The scan is bounded by the partial `idx_events_event_type_json` index over the decoded events.
+    pub fn events_by_type_filtered(
+        &self,
+        event_type: StructTag,
+        json_predicate: EventJsonPredicate,
+        range: Range<u64>,
+        limit: usize,
+    ) -> IndexerResult<Vec<IndexerEvent>> {
+        let event_type_str = format!("0x{}", event_type.to_canonical_string());
+        let predicate_clause = json_predicate.to_sql(EVENT_JSON_STR)?;
+        let query = format!(
+            "
+                SELECT * FROM events \
+                WHERE {EVENT_TYPE_STR} = \"{}\" AND {TX_ORDER_STR} >= {} AND {TX_ORDER_STR} < {} \
+                AND {EVENT_JSON_STR} IS NOT NULL AND {} \
+                ORDER BY {TX_ORDER_STR} ASC, {EVENT_INDEX_STR} ASC \
+                LIMIT {}
+            ",
+            event_type_str,
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+            predicate_clause,
+            limit,
+        );
+
+        tracing::debug!("query events by type filtered: {}", query);
+        let stored_events = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredEvent>(conn))?;
+
+        stored_events
+            .into_iter()
+            .map(|ev| ev.try_into_indexer_event())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer events failed: {:?}", e))
+            })
+    }
+
+    /// Returns the max `tx_order` persisted in the indexer, None if no transaction is indexed yet.
+    /// The `tx_order` is the primary key, so the max query is cheap.
+    pub fn latest_indexed_order(&self) -> IndexerResult<Option<u64>> {
+        let max_tx_order: Option<i64> = self.inner_indexer_reader.run_query(|conn| {
+            transactions::dsl::transactions
+                .select(diesel::dsl::max(transactions::tx_order))
+                .first::<Option<i64>>(conn)
+        })?;
+        Ok(max_tx_order.map(|tx_order| tx_order as u64))
+    }
+
+    /// Returns the total number of indexed transactions from the maintained counter,
+    /// it falls back to a full count if the counter is not reconciled yet.
+    pub fn total_transactions(&self) -> IndexerResult<u64> {
+        self.total(TOTAL_TRANSACTIONS_STAT, |conn| {
+            transactions::table.count().get_result::<i64>(conn)
+        })
+    }
+
+    /// Returns the total number of indexed events from the maintained counter,
+    /// it falls back to a full count if the counter is not reconciled yet.
+    pub fn total_events(&self) -> IndexerResult<u64> {
+        self.total(TOTAL_EVENTS_STAT, |conn| {
+            events::table.count().get_result::<i64>(conn)
+        })
+    }
+
+    /// Returns the number of indexed events emitted by the event handle, from the counter maintained
+    /// on commit. It falls back to a count over the `(event_handle_id, event_seq)` index if the counters
+    /// are not reconciled yet.
+    pub fn event_count_by_handle(&self, event_handle_id: ObjectID) -> IndexerResult<u64> {
+        let event_handle_id = event_handle_id.to_string();
+        let value = self.inner_indexer_reader.run_query(|conn| {
+            let reconciled = indexer_stats::table
+                .filter(indexer_stats::name.eq(EVENT_HANDLE_COUNTS_STAT))
+                .select(indexer_stats::value)
+                .first::<i64>(conn)
+                .optional()?
+                .is_some();
+            if !reconciled {
+                return events::table
+                    .filter(events::event_handle_id.eq(event_handle_id.as_str()))
+                    .count()
+                    .get_result::<i64>(conn);
+            }
+            Ok(indexer_stats::table
+                .filter(indexer_stats::name.eq(event_count_stat(&event_handle_id)))
+                .select(indexer_stats::value)
+                .first::<i64>(conn)
+                .optional()?
+                .unwrap_or_default())
+        })?;
+        Ok(value.max(0) as u64)
+    }
+
+    fn total<F>(&self, stat: &str, full_count: F) -> IndexerResult<u64>
+    where
+        F: FnOnce(&mut SqliteConnection) -> Result<i64, diesel::result::Error>,
+    {
+        let value = self.inner_indexer_reader.run_query(|conn| {
+            let value = indexer_stats::table
+                .filter(indexer_stats::name.eq(stat))
+                .select(indexer_stats::value)
+                .first::<i64>(conn)
+                .optional()?;
+            match value {
+                Some(value) => Ok(value),
+                None => full_count(conn),
+            }
+        })?;
+        Ok(value.max(0) as u64)
+    }
+
+    /// Returns how many transactions the indexer is behind the sequencer's latest order,
+    /// None if the sequencer's latest order is unknown.
+    pub fn indexing_lag(&self, sequencer_latest_order: Option<u64>) -> IndexerResult<Option<u64>> {
+        if sequencer_latest_order.is_none() {
+            return Ok(None);
+        }
+        Ok(indexing_lag(
+            self.latest_indexed_order()?,
+            sequencer_latest_order,
+        ))
+    }
+
+    /// Returns the number of transactions and the total gas used in the `tx_order` range.
+    /// The aggregate runs in SQLite over the `tx_order` primary key, no transaction row is loaded.
+    pub fn sum_gas(&self, range: Range<u64>) -> IndexerResult<GasSummary> {
+        let query = format!(
+            "
+                SELECT COUNT(*) AS tx_count, COALESCE(SUM({TX_GAS_USED_STR}), 0) AS total_gas_used \
+                FROM transactions \
+                WHERE {TX_ORDER_STR} >= {} AND {TX_ORDER_STR} < {}
+            ",
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+        );
+
+        tracing::debug!("sum gas: {}", query);
+        let summary = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).get_result::<StoredGasSummary>(conn))?;
+        Ok(summary.into())
+    }
+
+    /// Returns the p50, p90 and p99 of the gas used by the transactions in the `window`.
+    /// The percentiles are exact, each is a single row read at its rank in the `gas_used` order,
+    /// so SQLite sorts the window once per percentile but loads no other row. No approximation is
+    /// used, a very large window costs the sorts, bound it by `tx_order` to keep it cheap.
+    /// A `created_at` window fails until the transaction timestamp is recorded.
+    pub fn gas_percentiles(&self, window: TransactionWindow) -> IndexerResult<GasPercentiles> {
+        let (column, range) = window_filter(window, "gas percentiles")?;
+        let filter = format!(
+            "{column} >= {} AND {column} < {}",
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end)
+        );
+        let count_query = format!(
+            "
+                SELECT COUNT(*) AS tx_count, COALESCE(SUM({TX_GAS_USED_STR}), 0) AS total_gas_used \
+                FROM transactions \
+                WHERE {filter}
+            "
+        );
+
+        tracing::debug!("gas percentiles: {}", count_query);
+        self.inner_indexer_reader.run_query(
+            |conn| -> Result<GasPercentiles, diesel::result::Error> {
+                let tx_count = diesel::sql_query(count_query)
+                    .get_result::<StoredGasSummary>(conn)?
+                    .tx_count as u64;
+                if tx_count == 0 {
+                    return Ok(GasPercentiles::default());
+                }
+                let mut percentile = |percent: u64| -> Result<u64, diesel::result::Error> {
+                    // The nearest rank is ceil(percent / 100 * tx_count), counted from 1.
+                    let offset = (percent * tx_count + 99) / 100 - 1;
+                    let query = format!(
+                        "
+                        SELECT {TX_GAS_USED_STR} AS gas_used \
+                        FROM transactions \
+                        WHERE {filter} \
+                        ORDER BY {TX_GAS_USED_STR} ASC \
+                        LIMIT 1 OFFSET {offset}
+                    "
+                    );
+                    Ok(diesel::sql_query(query)
+                        .get_result::<StoredGasUsed>(conn)?
+                        .gas_used as u64)
+                };
+                Ok(GasPercentiles {
+                    tx_count,
+                    p50: percentile(50)?,
+                    p90: percentile(90)?,
+                    p99: percentile(99)?,
+                })
+            },
+        )
+    }
+
+    /// Returns the number of transactions and the total gas used of each day, in ascending order of the day.
+    /// The days are grouped by `created_at`, the grouping scans every transaction.
+    /// It fails until the transaction timestamp is recorded, as every `created_at` is 0 so far.
+    pub fn gas_by_day(&self) -> IndexerResult<Vec<DailyGasSummary>> {
+        if !CREATED_AT_RECORDED {
+            return Err(created_at_not_supported("gas by day"));
+        }
+        let query = format!(
+            "
+                SELECT {CREATED_AT_STR} / {MILLISECONDS_PER_DAY} AS day, COUNT(*) AS tx_count, \
+                COALESCE(SUM({TX_GAS_USED_STR}), 0) AS total_gas_used \
+                FROM transactions \
+                GROUP BY day \
+                ORDER BY day ASC
+            ",
+        );
+
+        tracing::debug!("gas by day: {}", query);
+        let summaries = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredDailyGasSummary>(conn))?;
+        Ok(summaries.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns the number of transactions of each type in each time bucket, for the transactions whose
+    /// `created_at` is in the `range` (in milliseconds), in ascending order of the bucket and then the type.
+    /// The buckets without transactions are omitted. The aggregate is backed by the
+    /// `idx_transactions_created_at_type` index.
+    /// It fails until the transaction timestamp is recorded, as every `created_at` is 0 so far.
+    pub fn tx_type_distribution(
+        &self,
+        range: Range<u64>,
+        bucket: TimeBucket,
+    ) -> IndexerResult<Vec<TransactionTypeCount>> {
+        if !CREATED_AT_RECORDED {
+            return Err(created_at_not_supported("tx type distribution"));
+        }
+        let query = format!(
+            "
+                SELECT {CREATED_AT_STR} / {} AS bucket, {TX_TYPE_STR} AS transaction_type, \
+                COUNT(*) AS tx_count \
+                FROM transactions \
+                WHERE {CREATED_AT_STR} >= {} AND {CREATED_AT_STR} < {} \
+                GROUP BY bucket, {TX_TYPE_STR} \
+                ORDER BY bucket ASC, {TX_TYPE_STR} ASC
+            ",
+            bucket.milliseconds(),
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+        );
+
+        tracing::debug!("tx type distribution: {}", query);
+        let counts = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredTransactionTypeCount>(conn))?;
+        counts
+            .into_iter()
+            .map(|count| count.try_into_transaction_type_count())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!(
+                    "Cast indexer transaction type count failed: {:?}",
+                    e
+                ))
+            })
+    }
+
+    /// Returns the average gas used per transaction of each transaction type in the `window`.
+    /// The types without transactions in the `window` are omitted. A `tx_order` window is backed by the
+    /// primary key, a `created_at` window fails until the transaction timestamp is recorded.
+    pub fn avg_gas_by_type(
+        &self,
+        window: TransactionWindow,
+    ) -> IndexerResult<BTreeMap<TransactionType, f64>> {
+        let (column, range) = window_filter(window, "avg gas by type")?;
+        let query = format!(
+            "
+                SELECT {TX_TYPE_STR} AS transaction_type, AVG({TX_GAS_USED_STR}) AS avg_gas_used \
+                FROM transactions \
+                WHERE {column} >= {} AND {column} < {} \
+                GROUP BY {TX_TYPE_STR}
+            ",
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+        );
+
+        tracing::debug!("avg gas by type: {}", query);
+        let averages = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredTransactionTypeAvgGas>(conn))?;
+        averages
+            .into_iter()
+            .map(|average| average.try_into_type_avg_gas())
+            .collect::<Result<BTreeMap<_, _>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!(
+                    "Cast indexer transaction type average gas failed: {:?}",
+                    e
+                ))
+            })
+    }
+
+    /// Returns at most `limit` senders with the most transactions in the `window`, with their transaction count,
+    /// in descending order of the count, the ties are broken by the ascending order of the sender address.
+    /// The aggregate is backed by the `idx_transactions_sender_tx_order` index, a `created_at` window fails
+    /// until the transaction timestamp is recorded.
+    /// The senders are stored as the hex literals without the leading zeros, so they are ordered by
+    /// the length first to get the address order.
+    pub fn top_senders(
+        &self,
+        window: TransactionWindow,
+        limit: usize,
+    ) -> IndexerResult<Vec<(AccountAddress, u64)>> {
+        let (column, range) = window_filter(window, "top senders")?;
+        let query = format!(
+            "
+                SELECT {TX_SENDER_STR} AS sender, COUNT(*) AS tx_count \
+                FROM transactions \
+                WHERE {column} >= {} AND {column} < {} \
+                GROUP BY {TX_SENDER_STR} \
+                ORDER BY tx_count DESC, LENGTH({TX_SENDER_STR}) ASC, {TX_SENDER_STR} ASC \
+                LIMIT {}
+            ",
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+            limit,
+        );
+
+        tracing::debug!("top senders: {}", query);
+        let counts = self.inner_indexer_reader.run_query(|conn| {
+            diesel::sql_query(query).load::<StoredSenderTransactionCount>(conn)
+        })?;
+        counts
+            .into_iter()
+            .map(|count| count.try_into_sender_count())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!(
+                    "Cast indexer sender transaction count failed: {:?}",
+                    e
+                ))
+            })
+    }
+
+    /// Returns the most recent transactions in descending order of `tx_order`, only the transactions before
+    /// `before_order` if set, so a feed scrolls by passing the last returned order.
+    /// The limit is capped to `MAX_RECENT_TRANSACTIONS_LIMIT`.
+    pub fn recent_transactions(
+        &self,
+        before_order: Option<u64>,
+        limit: usize,
+    ) -> IndexerResult<Vec<IndexedTransaction>> {
+        let limit = limit.min(MAX_RECENT_TRANSACTIONS_LIMIT) as i64;
+        let before_order = before_order.map_or(i64::MAX, clamp_to_i64);
+        let stored_transactions = self.inner_indexer_reader.run_query(|conn| {
+            transactions::dsl::transactions
+                .filter(transactions::tx_order.lt(before_order))
+                .order_by(transactions::tx_order.desc())
+                .limit(limit)
+                .load::<StoredTransaction>(conn)
+        })?;
+        stored_transactions
+            .into_iter()
+            .map(|transaction| transaction.try_into_indexed_transaction())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer transactions failed: {:?}", e))
+            })
+    }
+
+    /// Returns the successful or the failed transactions in the `tx_order` range, in descending order of
+    /// `tx_order`, at most `limit` of them, only the ones which aborted with `abort_code` if set.
+    /// The next page ends before the last returned order. The query is scoped by the
+    /// `idx_transactions_is_success_tx_order` index, so the recent failures are cheap to find.
+    pub fn transactions_by_status(
+        &self,
+        success: bool,
+        abort_code: Option<u64>,
+        range: Range<u64>,
+        limit: usize,
+    ) -> IndexerResult<Vec<IndexedTransaction>> {
+        let start = clamp_to_i64(range.start);
+        let end = clamp_to_i64(range.end);
+        let stored_transactions = self.inner_indexer_reader.run_query(|conn| {
+            let mut query = transactions::dsl::transactions
+                .filter(transactions::is_success.eq(success))
+                .filter(transactions::tx_order.ge(start))
+                .filter(transactions::tx_order.lt(end))
+                .into_boxed();
+            if let Some(abort_code) = abort_code {
+                query = query.filter(transactions::abort_code.eq(abort_code as i64));
+            }
+            query
+                .order_by(transactions::tx_order.desc())
+                .limit(limit as i64)
+                .load::<StoredTransaction>(conn)
+        })?;
+        stored_transactions
+            .into_iter()
+            .map(|transaction| transaction.try_into_indexed_transaction())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer transactions failed: {:?}", e))
+            })
+    }
+
+    /// Returns a page of the events emitted by the transaction, in ascending order of the event index,
+    /// skipping the first `offset` events. The page is empty if the transaction is not indexed.
+    /// The query is scoped by the `idx_events_tx_hash` index.
+    pub fn events_for_tx_paged(
+        &self,
+        tx_hash: H256,
+        offset: u64,
+        limit: usize,
+    ) -> IndexerResult<Vec<IndexedEvent>> {
+        let stored_events = self.inner_indexer_reader.run_query(|conn| {
+            events::dsl::events
+                .filter(events::tx_hash.eq(format!("{:?}", tx_hash)))
+                .order_by(events::event_index.asc())
+                .offset(clamp_to_i64(offset))
+                .limit(limit as i64)
+                .load::<StoredEvent>(conn)
+        })?;
+        stored_events
+            .into_iter()
+            .map(|event| event.try_into_indexed_event())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer events failed: {:?}", e))
+            })
+    }
+
+    /// Returns the number of events of each type emitted by the transactions in the `tx_order` range.
+    /// The counts are keyed by the canonical string of the event type as it is indexed, for a stable ordering.
+    /// The aggregate is backed by the `idx_events_tx_order_event_type` index.
+    pub fn event_type_counts(&self, range: Range<u64>) -> IndexerResult<BTreeMap<String, u64>> {
+        let query = format!(
+            "
+                SELECT {EVENT_TYPE_STR} AS event_type, COUNT(*) AS event_count \
+                FROM events \
+                WHERE {TX_ORDER_STR} >= {} AND {TX_ORDER_STR} < {} \
+                GROUP BY {EVENT_TYPE_STR}
+            ",
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+        );
+
+        tracing::debug!("event type counts: {}", query);
+        let counts = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredEventTypeCount>(conn))?;
+        Ok(counts
+            .into_iter()
+            .map(|count| (count.event_type, count.event_count as u64))
+            .collect())
+    }
+
+    /// Returns the latest known type of the object, None if no change of the object is indexed.
+    /// A deleted object keeps its last known type, with the `deleted` flag set.
+    pub fn object_type(&self, object_id: ObjectID) -> IndexerResult<Option<IndexedObjectType>> {
+        let stored_object_type = self.inner_indexer_reader.run_query(|conn| {
+            object_types::dsl::object_types
+                .filter(object_types::object_id.eq(object_id.to_string()))
+                .first::<StoredObjectType>(conn)
+                .optional()
+        })?;
+
+        stored_object_type
+            .map(|object_type| object_type.try_into_indexed_object_type())
+            .transpose()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer object type failed: {:?}", e))
+            })
+    }
+
+    /// Returns the transactions which changed the object in the `tx_order` range, in ascending order of
+    /// `tx_order`, at most `limit` of them. The next page starts after the last returned order.
+    /// The transactions are found by the object history, which is keyed by the object id.
+    pub fn transactions_for_object(
+        &self,
+        object_id: ObjectID,
+        range: Range<u64>,
+        limit: usize,
+    ) -> IndexerResult<Vec<IndexedTransaction>> {
+        let start = clamp_to_i64(range.start);
+        let end = clamp_to_i64(range.end);
+        let stored_transactions = self.inner_indexer_reader.run_query(|conn| {
+            let tx_orders = object_changes::dsl::object_changes
+                .select(object_changes::tx_order)
+                .filter(object_changes::object_id.eq(object_id.to_string()))
+                .filter(object_changes::tx_order.ge(start))
+                .filter(object_changes::tx_order.lt(end));
+            transactions::dsl::transactions
+                .filter(transactions::tx_order.eq_any(tx_orders))
+                .order_by(transactions::tx_order.asc())
+                .limit(limit as i64)
+                .load::<StoredTransaction>(conn)
+        })?;
+        stored_transactions
+            .into_iter()
+            .map(|transaction| transaction.try_into_indexed_transaction())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer transactions failed: {:?}", e))
+            })
+    }
+
+    /// Returns at most `limit` objects with the most changes in the `window`, with their change count,
+    /// in descending order of the count, the ties are broken by the ascending order of the object id.
+    /// A `tx_order` window is backed by the `idx_object_changes_tx_order_object_id` index, a `created_at` window
+    /// selects the transactions by the `idx_transactions_created_at` index first, and is not supported
+    /// until the transaction timestamp is recorded.
+    pub fn most_active_objects(
+        &self,
+        window: TransactionWindow,
+        limit: usize,
+    ) -> IndexerResult<Vec<(ObjectID, u64)>> {
+        let (column, range) = window_filter(window, "most active objects")?;
+        let bounds = format!(
+            "{column} >= {} AND {column} < {}",
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+        );
+        // The object changes only record the tx_order, other windows select the transactions first.
+        let filter = if column == TX_ORDER_STR {
+            bounds
+        } else {
+            format!("{TX_ORDER_STR} IN (SELECT {TX_ORDER_STR} FROM transactions WHERE {bounds})")
+        };
+        let query = format!(
+            "
+                SELECT object_id, COUNT(*) AS change_count \
+                FROM object_changes \
+                WHERE {filter} \
+                GROUP BY object_id \
+                ORDER BY change_count DESC, object_id ASC \
+                LIMIT {limit}
+            "
+        );
+
+        tracing::debug!("most active objects: {}", query);
+        let counts = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredObjectChangeCount>(conn))?;
+        counts
+            .into_iter()
+            .map(|count| count.try_into_object_change_count())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!(
+                    "Cast indexer object change count failed: {:?}",
+                    e
+                ))
+            })
+    }
+
+    /// Returns the state root after each transaction in the `tx_order` range, in ascending order of `tx_order`,
+    /// at most `limit` of them. The next page starts after the last returned order.
+    /// Only the two columns are read, so following the state root transitions of a long range is cheap,
+    /// such as to find where the state roots of two nodes diverge.
+    pub fn state_roots(&self, range: Range<u64>, limit: usize) -> IndexerResult<Vec<(u64, H256)>> {
+        let start = clamp_to_i64(range.start);
+        let end = clamp_to_i64(range.end);
+        let state_roots = self.inner_indexer_reader.run_query(|conn| {
+            transactions::dsl::transactions
+                .select((transactions::tx_order, transactions::state_root))
+                .filter(transactions::tx_order.ge(start))
+                .filter(transactions::tx_order.lt(end))
+                .order_by(transactions::tx_order.asc())
+                .limit(limit as i64)
+                .load::<(i64, String)>(conn)
+        })?;
+        state_roots
+            .into_iter()
+            .map(|(tx_order, state_root)| Ok((tx_order as u64, H256::from_str(&state_root)?)))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer state root failed: {:?}", e))
+            })
+    }
+
+    /// Returns the `(tx_order, sequence_number)` of the transactions sent by `sender` in the `tx_order` range,
+    /// in ascending order of `tx_order`, to spot the nonce gaps or reuses of the account.
+    /// At most `limit` of them are returned, only the ones after the exclusive `cursor` if set, so the next page
+    /// starts after the last returned order.
+    /// The query is scoped by the `idx_transactions_sender_tx_order` index and only reads the two columns.
+    pub fn account_nonce_history(
+        &self,
+        sender: AccountAddress,
+        range: Range<u64>,
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> IndexerResult<Vec<(u64, u64)>> {
+        let start = match cursor {
+            Some(cursor) => clamp_to_i64(range.start.max(cursor.saturating_add(1))),
+            None => clamp_to_i64(range.start),
+        };
+        let end = clamp_to_i64(range.end);
+        let nonces = self.inner_indexer_reader.run_query(|conn| {
+            transactions::dsl::transactions
+                .select((transactions::tx_order, transactions::sequence_number))
+                .filter(transactions::sender.eq(sender.to_hex_literal()))
+                .filter(transactions::tx_order.ge(start))
+                .filter(transactions::tx_order.lt(end))
+                .order_by(transactions::tx_order.asc())
+                .limit(limit as i64)
+                .load::<(i64, i64)>(conn)
+        })?;
+        Ok(nonces
+            .into_iter()
+            .map(|(tx_order, sequence_number)| (tx_order as u64, sequence_number as u64))
+            .collect())
+    }
+
+    /// Returns the ids of the active objects currently owned by `owner`, in ascending order of the object id.
+    /// The ownership is maintained incrementally from the indexed object changes, a transferred object
+    /// moves to its new owner and a deleted object is removed.
+    pub fn objects_owned_by(&self, owner: AccountAddress) -> IndexerResult<Vec<ObjectID>> {
+        let object_ids = self.inner_indexer_reader.run_query(|conn| {
+            object_owners::dsl::object_owners
+                .select(object_owners::object_id)
+                .filter(object_owners::owner.eq(owner.to_hex_literal()))
+                .order_by(object_owners::object_id.asc())
+                .load::<String>(conn)
+        })?;
+
+        object_ids
+            .iter()
+            .map(|object_id| ObjectID::from_str(object_id.as_str()))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer object id failed: {:?}", e))
+            })
+    }
+
+    /// Returns the arguments of the function call transaction, decoded with the parameter types
+    /// of the function resolved by the `resolver`.
+    /// None if the transaction is not indexed or is not a function call.
+    pub fn decoded_function_args<R: MoveOSResolver>(
+        &self,
+        tx_hash: H256,
+        resolver: &R,
+    ) -> IndexerResult<Option<DecodedFunctionArgs>> {
+        let action_raw = self.inner_indexer_reader.run_query(|conn| {
+            transactions::dsl::transactions
+                .select(transactions::action_raw)
+                .filter(transactions::tx_hash.eq(format!("{:?}", tx_hash)))
+                .first::<Vec<u8>>(conn)
+                .optional()
+        })?;
+        let action_raw = match action_raw {
+            Some(action_raw) => action_raw,
+            None => return Ok(None),
+        };
+        let action = bcs::from_bytes::<MoveAction>(&action_raw).map_err(|e| {
+            IndexerError::SQLiteReadError(format!(
+                "Cast indexer transaction action failed: {:?}",
+                e
+            ))
+        })?;
+        Ok(match action {
+            MoveAction::Function(call) => Some(DecodedFunctionArgs::decode(&call, resolver)),
+            _ => None,
+        })
+    }
+
+    /// Stream the transactions in the `tx_order` range in ascending order.
+    /// The rows are read page by page with a `tx_order` cursor, so no connection is held across the whole range.
+    /// The page queries run on the Tokio blocking pool, so the stream must be polled within a Tokio runtime.
+    /// A row that fails to decode is yielded as an `Err` item and the stream goes on,
+    /// a failed page query is yielded as an `Err` item and ends the stream.
+    pub fn stream_transactions(
+        &self,
+        range: Range<u64>,
+        chunk_size: usize,
+    ) -> impl Stream<Item = IndexerResult<IndexedTransaction>> {
+        let state = TransactionStreamState {
+            reader: self.clone(),
+            next_tx_order: range.start,
+            end_tx_order: range.end,
+            chunk_size: chunk_size.max(1),
+            buffer: VecDeque::new(),
+            finished: range.is_empty(),
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(stored_transaction) = state.buffer.pop_front() {
+                    let item = stored_transaction
+                        .try_into_indexed_transaction()
+                        .map_err(|e| {
+                            IndexerError::SQLiteReadError(format!(
+                                "Cast indexer transaction failed: {:?}",
+                                e
+                            ))
+                        });
+                    return Some((item, state));
+                }
+                if state.finished {
+                    return None;
+                }
+                // The page query blocks on the SQLite connection, so it runs off the async runtime.
+                let reader = state.reader.clone();
+                let (next_tx_order, end_tx_order, chunk_size) =
+                    (state.next_tx_order, state.end_tx_order, state.chunk_size);
+                let page = tokio::task::spawn_blocking(move || {
+                    reader.query_stored_transactions_in_range(
+                        next_tx_order,
+                        end_tx_order,
+                        chunk_size,
+                    )
+                })
+                .await
+                .map_err(|e| {
+                    IndexerError::SQLiteReadError(format!(
+                        "Stream transactions page query failed: {:?}",
+                        e
+                    ))
+                })
+                .and_then(|page| page);
+                match page {
+                    Ok(page) => {
+                        if page.len() < state.chunk_size {
+                            state.finished = true;
+                        }
+                        if let Some(last) = page.last() {
+                            state.next_tx_order = last.tx_order as u64 + 1;
+                        }
+                        state.buffer.extend(page);
+                    }
+                    Err(e) => {
+                        state.finished = true;
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Stream the events of `event_type` in the `tx_order` range in ascending order of `(tx_order, event_index)`,
+    /// for exporting an event type. The rows are read page by page with a `(tx_order, event_index)` cursor
+    /// over the `idx_events_event_type_tx_order` index, so no connection is held across the whole range.
+    /// The page queries run on the Tokio blocking pool, so the stream must be polled within a Tokio runtime.
+    /// A row that fails to decode is yielded as an `Err` item and the stream goes on,
+    /// a failed page query is yielded as an `Err` item and ends the stream.
+    pub fn stream_events_by_type(
+        &self,
+        event_type: StructTag,
+        range: Range<u64>,
+        chunk_size: usize,
+    ) -> impl Stream<Item = IndexerResult<IndexedEvent>> {
+        let state = EventStreamState {
+            reader: self.clone(),
+            event_type: format!("0x{}", event_type.to_canonical_string()),
+            cursor: None,
+            start_tx_order: range.start,
+            end_tx_order: range.end,
+            chunk_size: chunk_size.max(1),
+            buffer: VecDeque::new(),
+            finished: range.is_empty(),
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(stored_event) = state.buffer.pop_front() {
+                    let item = stored_event.try_into_indexed_event().map_err(|e| {
+                        IndexerError::SQLiteReadError(format!("Cast indexer event failed: {:?}", e))
+                    });
+                    return Some((item, state));
+                }
+                if state.finished {
+                    return None;
+                }
+                // The page query blocks on the SQLite connection, so it runs off the async runtime.
+                let reader = state.reader.clone();
+                let event_type = state.event_type.clone();
+                let (cursor, start_tx_order, end_tx_order, chunk_size) = (
+                    state.cursor,
+                    state.start_tx_order,
+                    state.end_tx_order,
+                    state.chunk_size,
+                );
+                let page = tokio::task::spawn_blocking(move || {
+                    reader.query_stored_events_by_type_after(
+                        event_type.as_str(),
+                        cursor,
+                        start_tx_order,
+                        end_tx_order,
+                        chunk_size,
+                    )
+                })
+                .await
+                .map_err(|e| {
+                    IndexerError::SQLiteReadError(format!(
+                        "Stream events page query failed: {:?}",
+                        e
+                    ))
+                })
+                .and_then(|page| page);
+                match page {
+                    Ok(page) => {
+                        if page.len() < state.chunk_size {
+                            state.finished = true;
+                        }
+                        if let Some(last) = page.last() {
+                            state.cursor = Some((last.tx_order as u64, last.event_index as u64));
+                        }
+                        state.buffer.extend(page);
+                    }
+                    Err(e) => {
+                        state.finished = true;
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Validate the indexed rows in the `tx_order` range without writing anything, for example before a
+    /// schema change or a reindex. Each transaction is rebuilt from its row and dry-runs the write path,
+    /// `IndexedTransaction::new` with the JSON decoding, and each event dry-runs `IndexedEvent::new` with the
+    /// rebuilt inputs of its transaction. The rebuilt rows must match the stored ones, and the stored rows
+    /// must convert on the read path too. The transactions and the events are read in pages of
+    /// `DEFAULT_STREAM_CHUNK_SIZE` rows. The rows which fail are reported in the summary instead of failing
+    /// the validation.
+    pub fn validate_range(&self, range: Range<u64>) -> IndexerResult<ValidationSummary> {
+        let mut summary = ValidationSummary::default();
+        let mut next_tx_order = range.start;
+        while next_tx_order < range.end {
+            let page = self.query_stored_transactions_in_range(
+                next_tx_order,
+                range.end,
+                DEFAULT_STREAM_CHUNK_SIZE,
+            )?;
+            let page_end = match page.last() {
+                Some(last) if page.len() == DEFAULT_STREAM_CHUNK_SIZE => last.tx_order as u64 + 1,
+                _ => range.end,
+            };
+            // The write path inputs of the valid transactions of the page, their events are rebuilt with them.
+            let mut write_inputs = BTreeMap::new();
+            for stored_transaction in page {
+                let tx_order = stored_transaction.tx_order as u64;
+                match dry_run_stored_transaction(stored_transaction) {
+                    Ok(inputs) => {
+                        summary.transactions_ok += 1;
+                        write_inputs.insert(tx_order, inputs);
+                    }
+                    Err(e) => summary.failures.push(ValidationFailure {
+                        tx_order,
+                        event_index: None,
+                        error: format!("{:?}", e),
+                    }),
+                }
+            }
+            let mut event_cursor = None;
+            loop {
+                let events = self.query_stored_events_in_range(
+                    event_cursor,
+                    next_tx_order,
+                    page_end,
+                    DEFAULT_STREAM_CHUNK_SIZE,
+                )?;
+                let events_len = events.len();
+                if let Some(last) = events.last() {
+                    event_cursor = Some((last.tx_order as u64, last.event_index as u64));
+                }
+                for stored_event in events {
+                    let tx_order = stored_event.tx_order as u64;
+                    let event_index = stored_event.event_index as u64;
+                    match dry_run_stored_event(stored_event, write_inputs.get(&tx_order)) {
+                        Ok(()) => summary.events_ok += 1,
+                        Err(e) => summary.failures.push(ValidationFailure {
+                            tx_order,
+                            event_index: Some(event_index),
+                            error: format!("{:?}", e),
+                        }),
+                    }
+                }
+                if events_len < DEFAULT_STREAM_CHUNK_SIZE {
+                    break;
+                }
+            }
+            next_tx_order = page_end;
+        }
+        summary
+            .failures
+            .sort_by_key(|failure| (failure.tx_order, failure.event_index));
+        Ok(summary)
+    }
+
+    /// Returns the tx hashes which are indexed more than once in the `tx_order` range, with their number of
+    /// occurrences, in ascending order of the hash. A hash should never repeat, so a clean index returns empty,
+    /// the repeats point at an indexing bug or a mishandled reorg. The `tx_hash` column has a unique constraint,
+    /// so the check only finds repeats in a database whose constraint is lost, such as a rebuilt table.
+    pub fn find_duplicate_tx_hashes(&self, range: Range<u64>) -> IndexerResult<Vec<(H256, u64)>> {
+        let query = format!(
+            "
+                SELECT {TX_HASH_STR} AS tx_hash, COUNT(*) AS tx_count \
+                FROM transactions \
+                WHERE {TX_ORDER_STR} >= {} AND {TX_ORDER_STR} < {} \
+                GROUP BY {TX_HASH_STR} \
+                HAVING COUNT(*) > 1 \
+                ORDER BY {TX_HASH_STR} ASC
+            ",
+            clamp_to_i64(range.start),
+            clamp_to_i64(range.end),
+        );
+
+        tracing::debug!("find duplicate tx hashes: {}", query);
+        let counts = self
+            .inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredTxHashCount>(conn))?;
+        counts
+            .into_iter()
+            .map(|count| count.try_into_tx_hash_count())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| {
+                IndexerError::SQLiteReadError(format!("Cast indexer tx hash count failed: {:?}", e))
+            })
+    }
+
+    /// The events in the `tx_order` range after the `(tx_order, event_index)` cursor.
+    fn query_stored_events_in_range(
+        &self,
+        cursor: Option<(u64, u64)>,
+        from_order: u64,
+        to_order: u64,
+        limit: usize,
+    ) -> IndexerResult<Vec<StoredEvent>> {
+        let cursor_clause = match cursor {
+            Some((tx_order, event_index)) => format!(
+                "AND ({TX_ORDER_STR} > {tx_order} OR ({TX_ORDER_STR} = {tx_order} AND {EVENT_INDEX_STR} > {event_index}))"
+            ),
+            None => "".to_owned(),
+        };
+        let query = format!(
+            "
+                SELECT * FROM events \
+                WHERE {TX_ORDER_STR} >= {} AND {TX_ORDER_STR} < {} \
+                {} \
+                ORDER BY {TX_ORDER_STR} ASC, {EVENT_INDEX_STR} ASC \
+                LIMIT {}
+            ",
+            clamp_to_i64(from_order),
+            clamp_to_i64(to_order),
+            cursor_clause,
+            limit,
+        );
+
+        tracing::debug!("query events in range: {}", query);
+        self.inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredEvent>(conn))
+    }
+
+    /// The events of `event_type` in the `tx_order` range after the `(tx_order, event_index)` cursor.
+    fn query_stored_events_by_type_after(
+        &self,
+        event_type: &str,
+        cursor: Option<(u64, u64)>,
+        from_order: u64,
+        to_order: u64,
+        limit: usize,
+    ) -> IndexerResult<Vec<StoredEvent>> {
+        let cursor_clause = match cursor {
+            Some((tx_order, event_index)) => format!(
+                "AND ({TX_ORDER_STR} > {tx_order} OR ({TX_ORDER_STR} = {tx_order} AND {EVENT_INDEX_STR} > {event_index}))"
+            ),
+            None => "".to_owned(),
+        };
+        let query = format!(
+            "
+                SELECT * FROM events \
+                WHERE {EVENT_TYPE_STR} = \"{}\" AND {TX_ORDER_STR} >= {} AND {TX_ORDER_STR} < {} \
+                {} \
+                ORDER BY {TX_ORDER_STR} ASC, {EVENT_INDEX_STR} ASC \
+                LIMIT {}
+            ",
+            event_type,
+            clamp_to_i64(from_order),
+            clamp_to_i64(to_order),
+            cursor_clause,
+            limit,
+        );
+
+        tracing::debug!("query events by type after cursor: {}", query);
+        self.inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredEvent>(conn))
+    }
+
+    fn query_stored_transactions_in_range(
+        &self,
+        from_order: u64,
+        to_order: u64,
+        limit: usize,
+    ) -> IndexerResult<Vec<StoredTransaction>> {
+        let query = format!(
+            "
+                SELECT * FROM transactions \
+                WHERE {TX_ORDER_STR} >= {} AND {TX_ORDER_STR} < {} \
+                ORDER BY {TX_ORDER_STR} ASC \
+                LIMIT {}
+            ",
+            clamp_to_i64(from_order),
+            clamp_to_i64(to_order),
+            limit,
+        );
+
+        tracing::debug!("query transactions in range: {}", query);
+        self.inner_indexer_reader
+            .run_query(|conn| diesel::sql_query(query).load::<StoredTransaction>(conn))
+    }
+}
+
+/// The write path inputs of a transaction, rebuilt from its stored row.
+type TransactionWriteInputs = (TransactionWithInfo, VerifiedMoveOSTransaction);
+
+/// Rebuild the write path inputs of a stored transaction and dry-run `IndexedTransaction::new` with them,
+/// the JSON decoding included. The rebuilt row must match the stored one, and the stored row must convert
+/// on the read path.
+fn dry_run_stored_transaction(
+    stored_transaction: StoredTransaction,
+) -> Result<TransactionWriteInputs> {
+    let transaction_with_info = stored_transaction
+        .clone()
+        .try_into_transaction_with_info()?;
+    let stored = stored_transaction.try_into_indexed_transaction()?;
+    let moveos_tx = transaction_with_info
+        .transaction
+        .clone()
+        .construct_moveos_transaction(stored.sender)?;
+    let moveos_tx = VerifiedMoveOSTransaction {
+        ctx: moveos_tx.ctx,
+        action: unverified_action(moveos_tx.action),
+        pre_execute_functions: moveos_tx.pre_execute_functions,
+        post_execute_functions: moveos_tx.post_execute_functions,
+        tx_order: Some(stored.tx_order),
+    };
+    let rebuilt = IndexedTransaction::new(
+        transaction_with_info.transaction.clone(),
+        transaction_with_info.sequence_info.clone(),
+        transaction_with_info.execution_info.clone(),
+        moveos_tx.clone(),
+        true,
+    )?;
+    if rebuilt.transaction_json_decode_failed {
+        bail!("Decode the transaction to json failed");
+    }
+    if rebuilt.tx_hash != stored.tx_hash
+        || rebuilt.sender != stored.sender
+        || rebuilt.sequence_number != stored.sequence_number
+        || rebuilt.action_raw != stored.action_raw
+        || rebuilt.transaction_raw != stored.transaction_raw
+    {
+        bail!("The rebuilt transaction does not match the stored row");
+    }
+    Ok((transaction_with_info, moveos_tx))
+}
+
+/// Dry-run `IndexedEvent::new` with the rebuilt write path inputs of the transaction of the event, None if
+/// the transaction is not indexed or fails the validation. The rebuilt row must match the stored one, and
+/// the stored row must convert on the read path.
+fn dry_run_stored_event(
+    stored_event: StoredEvent,
+    write_inputs: Option<&TransactionWriteInputs>,
+) -> Result<()> {
+    stored_event.try_into_indexer_event()?;
+    let stored = stored_event.try_into_indexed_event()?;
+    let (transaction_with_info, moveos_tx) = write_inputs.ok_or_else(|| {
+        anyhow!(
+            "The transaction {} of the event is not indexed or fails the validation",
+            stored.tx_order
+        )
+    })?;
+    let event = Event::new(
+        EventID::new(stored.event_handle_id, stored.event_seq),
+        stored.event_type.clone(),
+        stored.event_data.clone(),
+        stored.event_index,
+    );
+    let rebuilt = IndexedEvent::new(
+        event,
+        stored.event_json.clone(),
+        transaction_with_info.transaction.clone(),
+        transaction_with_info.sequence_info.clone(),
+        moveos_tx.clone(),
+    );
+    if rebuilt.tx_hash != stored.tx_hash
+        || rebuilt.tx_order != stored.tx_order
+        || rebuilt.sender != stored.sender
+    {
+        bail!("The rebuilt event does not match the stored row");
+    }
+    Ok(())
+}
+
+/// The action of an indexed transaction, it was verified when the transaction was executed.
+fn unverified_action(action: MoveAction) -> VerifiedMoveAction {
+    match action {
+        MoveAction::Script(call) => VerifiedMoveAction::Script { call },
+        MoveAction::Function(call) => VerifiedMoveAction::Function { call },
+        MoveAction::ModuleBundle(module_bundle) => VerifiedMoveAction::ModuleBundle {
+            module_bundle,
+            init_function_modules: vec![],
+        },
+    }
+}
+
+/// Returns how many transactions the indexer is behind the sequencer's latest order,
+/// None if the sequencer's latest order is unknown.
+pub fn indexing_lag(
+    latest_indexed_order: Option<u64>,
+    sequencer_latest_order: Option<u64>,
+) -> Option<u64> {
+    let sequencer_latest_order = sequencer_latest_order?;
+    Some(match latest_indexed_order {
+        Some(latest_indexed_order) => sequencer_latest_order.saturating_sub(latest_indexed_order),
+        // The tx_order starts from 0, so nothing indexed means all the transactions are behind.
+        None => sequencer_latest_order.saturating_add(1),
+    })
+}
+
+/// The column and the range of the window.
+fn window_filter(
+    window: TransactionWindow,
+    query: &str,
+) -> IndexerResult<(&'static str, Range<u64>)> {
+    match window {
+        TransactionWindow::TxOrder(range) => Ok((TX_ORDER_STR, range)),
+        TransactionWindow::CreatedAt(_) if !CREATED_AT_RECORDED => {
+            Err(created_at_not_supported(query))
+        }
+        TransactionWindow::CreatedAt(range) => Ok((CREATED_AT_STR, range)),
+    }
+}
+
+fn created_at_not_supported(query: &str) -> IndexerError {
+    IndexerError::NotSupportedError(format!(
+        "{} by created_at, the transaction timestamp is not recorded yet",
+        query
+    ))
+}
+
+/// The `u64` bound as the SQLite integer, clamped to `i64::MAX` so an open range end such as `u64::MAX`
+/// covers every row instead of wrapping to a negative bound.
+fn clamp_to_i64(value: u64) -> i64 {
+    value.min(i64::MAX as u64) as i64
+}
+
+struct EventStreamState {
+    reader: IndexerReader,
+    event_type: String,
+    /// The `(tx_order, event_index)` of the last read event, None before the first page.
+    cursor: Option<(u64, u64)>,
+    start_tx_order: u64,
+    end_tx_order: u64,
+    chunk_size: usize,
+    buffer: VecDeque<StoredEvent>,
+    finished: bool,
+}
+
+struct TransactionStreamState {
+    reader: IndexerReader,
+    next_tx_order: u64,
+    end_tx_order: u64,
+    chunk_size: usize,
+    buffer: VecDeque<StoredTransaction>,
+    finished: bool,
 }
@@ -10,7 +10,7 @@ use diesel::sqlite::SqliteConnection;
 
 use crate::store::sqlite_store::SqliteIndexerStore;
 use crate::store::traits::IndexerStoreTrait;
-use crate::types::{IndexedEvent, IndexedTransaction};
+use crate::types::{IndexedEvent, IndexedObjectChange, IndexedTransaction};
 use crate::utils::create_all_tables_if_not_exists;
 use errors::IndexerError;
 
@@ -21,6 +21,8 @@ pub mod models;
 pub mod proxy;
 pub mod schema;
 pub mod store;
+#[cfg(test)]
+mod tests;
 pub mod types;
 pub mod utils;
 
@@ -65,6 +67,11 @@ impl IndexerStore {
         let mut connection = get_sqlite_pool_connection(&self.sqlite_store.connection_pool)?;
         create_all_tables_if_not_exists(&mut connection)
     }
+
+    /// Initialize the missing stats with a full count, see `SqliteIndexerStore::reconcile_stats`.
+    pub fn reconcile_stats(&self) -> Result<()> {
+        Ok(self.sqlite_store.reconcile_stats()?)
+    }
 }
 
 impl Display for IndexerStore {
@@ -90,9 +97,23 @@ impl IndexerStoreTrait for IndexerStore {
         self.sqlite_store.persist_transactions(transactions)
     }
 
+    fn upsert_transactions(
+        &self,
+        transactions: Vec<IndexedTransaction>,
+    ) -> Result<(), IndexerError> {
+        self.sqlite_store.upsert_transactions(transactions)
+    }
+
     fn persist_events(&self, events: Vec<IndexedEvent>) -> Result<(), IndexerError> {
         self.sqlite_store.persist_events(events)
     }
+
+    fn persist_object_changes(
+        &self,
+        object_changes: Vec<IndexedObjectChange>,
+    ) -> Result<(), IndexerError> {
+        self.sqlite_store.persist_object_changes(object_changes)
+    }
 }
 
 pub fn new_sqlite_connection_pool_impl(
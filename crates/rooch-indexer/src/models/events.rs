@@ -43,6 +43,9 @@ pub struct StoredEvent {
 
     #[diesel(sql_type = diesel::sql_types::BigInt)]
     pub created_at: i64,
+    /// the decoded event data in JSON, None if the event is not decoded
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub event_json: Option<String>,
 }
 
 impl From<IndexedEvent> for StoredEvent {
@@ -58,6 +61,7 @@ impl From<IndexedEvent> for StoredEvent {
             tx_order: event.tx_order as i64,
             sender: event.sender.to_hex_literal(),
             created_at: event.created_at as i64,
+            event_json: event.event_json,
         }
     }
 }
@@ -80,4 +84,30 @@ impl StoredEvent {
         };
         Ok(indexer_event)
     }
+
+    pub fn try_into_indexed_event(self) -> Result<IndexedEvent, anyhow::Error> {
+        Ok(IndexedEvent {
+            event_handle_id: ObjectID::from_str(self.event_handle_id.as_str())?,
+            event_seq: self.event_seq as u64,
+            event_type: StructTag::from_str(self.event_type.as_str())?,
+            event_data: self.event_data,
+            event_index: self.event_index as u64,
+
+            tx_hash: H256::from_str(self.tx_hash.as_str())?,
+            tx_order: self.tx_order as u64,
+            sender: AccountAddress::from_hex_literal(self.sender.as_str())?,
+
+            created_at: self.created_at as u64,
+            event_json: self.event_json,
+        })
+    }
+}
+
+/// The row of the per-type event count aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredEventTypeCount {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub event_type: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub event_count: i64,
 }
@@ -0,0 +1,31 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::indexer_stats;
+use diesel::prelude::*;
+
+/// The stat of the total number of indexed transactions.
+pub const TOTAL_TRANSACTIONS_STAT: &str = "total_transactions";
+/// The stat of the total number of indexed events.
+pub const TOTAL_EVENTS_STAT: &str = "total_events";
+/// The marker stat set once the per-handle event counts are reconciled.
+pub const EVENT_HANDLE_COUNTS_STAT: &str = "event_handle_counts";
+/// The prefix of the stat of the number of indexed events of each event handle.
+pub const EVENT_COUNT_STAT_PREFIX: &str = "event_count:";
+
+/// The stat name of the number of indexed events of the event handle.
+pub fn event_count_stat(event_handle_id: &str) -> String {
+    format!("{}{}", EVENT_COUNT_STAT_PREFIX, event_handle_id)
+}
+
+/// A counter maintained by the indexer store, updated in the same db transaction as the indexed rows.
+#[derive(Queryable, QueryableByName, Insertable, Debug, Clone)]
+#[diesel(table_name = indexer_stats)]
+pub struct StoredIndexerStat {
+    /// the stat name
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub name: String,
+    /// the counter value
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub value: i64,
+}
@@ -2,4 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod events;
+pub mod indexer_stats;
+pub mod object_changes;
+pub mod object_owners;
+pub mod object_types;
 pub mod transactions;
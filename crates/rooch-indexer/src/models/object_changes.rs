@@ -0,0 +1,46 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::object_changes;
+use diesel::prelude::*;
+use moveos_types::moveos_std::object::ObjectID;
+use std::str::FromStr;
+
+/// A change of an object by a transaction, the history of the object is the changes in `tx_order`.
+#[derive(Queryable, QueryableByName, Insertable, Debug, Clone)]
+#[diesel(table_name = object_changes)]
+pub struct StoredObjectChange {
+    /// the object id
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub object_id: String,
+    /// the tx order of the transaction which changed the object
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_order: i64,
+    /// whether the transaction deleted the object
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub deleted: bool,
+    /// the struct tag of the object value after the change, None if the object is deleted
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub object_type: Option<String>,
+    /// the owner of the object after the change, None if the object is deleted
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub owner: Option<String>,
+}
+
+/// The row of the per-object change count aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredObjectChangeCount {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub object_id: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub change_count: i64,
+}
+
+impl StoredObjectChangeCount {
+    pub fn try_into_object_change_count(self) -> Result<(ObjectID, u64), anyhow::Error> {
+        Ok((
+            ObjectID::from_str(self.object_id.as_str())?,
+            self.change_count as u64,
+        ))
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::object_owners;
+use diesel::prelude::*;
+
+/// The current owner of an active object, the row is removed when the object is deleted.
+#[derive(Queryable, QueryableByName, Insertable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = object_owners)]
+pub struct StoredObjectOwner {
+    /// the object id
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub object_id: String,
+    /// the current owner of the object
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub owner: String,
+    /// the tx order of the transaction which last changed the object
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_order: i64,
+}
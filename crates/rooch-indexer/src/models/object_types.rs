@@ -0,0 +1,48 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::object_types;
+use crate::types::IndexedObjectType;
+use diesel::prelude::*;
+use move_core_types::language_storage::StructTag;
+use moveos_types::moveos_std::object::ObjectID;
+use std::str::FromStr;
+
+#[derive(Queryable, QueryableByName, Insertable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = object_types)]
+pub struct StoredObjectType {
+    /// the object id
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub object_id: String,
+    /// the latest known struct tag of the object value
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub object_type: String,
+    /// whether the object is deleted
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub deleted: bool,
+    /// the tx order of the transaction which last changed the object
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_order: i64,
+}
+
+impl From<IndexedObjectType> for StoredObjectType {
+    fn from(object_type: IndexedObjectType) -> Self {
+        Self {
+            object_id: object_type.object_id.to_string(),
+            object_type: format!("0x{}", object_type.object_type.to_canonical_string()),
+            deleted: object_type.deleted,
+            tx_order: object_type.tx_order as i64,
+        }
+    }
+}
+
+impl StoredObjectType {
+    pub fn try_into_indexed_object_type(&self) -> Result<IndexedObjectType, anyhow::Error> {
+        Ok(IndexedObjectType {
+            object_id: ObjectID::from_str(self.object_id.as_str())?,
+            object_type: StructTag::from_str(self.object_type.as_str())?,
+            deleted: self.deleted,
+            tx_order: self.tx_order as u64,
+        })
+    }
+}
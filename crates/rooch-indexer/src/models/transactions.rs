@@ -2,14 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use diesel::prelude::*;
+use move_core_types::account_address::AccountAddress;
 use move_core_types::vm_status::KeptVMStatus;
 use moveos_types::h256::H256;
 use std::str::FromStr;
 
 use crate::schema::transactions;
-use crate::types::IndexedTransaction;
+use crate::types::{DailyGasSummary, GasSummary, IndexedTransaction, TransactionTypeCount};
 
-use moveos_types::transaction::TransactionExecutionInfo;
+use moveos_types::transaction::{MoveAction, TransactionExecutionInfo};
+use rooch_types::multichain_id::MultiChainID;
 use rooch_types::transaction::authenticator::Authenticator;
 use rooch_types::transaction::{RawTransaction, TransactionType, TransactionWithInfo};
 use rooch_types::transaction::{TransactionSequenceInfo, TypedTransaction};
@@ -69,9 +71,32 @@ pub struct StoredTransaction {
     pub tx_order_auth_validator_id: i64,
     #[diesel(sql_type = diesel::sql_types::Blob)]
     pub tx_order_authenticator_payload: Vec<u8>,
+    /// The identity of the node which sequenced the transaction
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub source_node_id: Option<String>,
+
+    /// The decoded transaction in JSON
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub transaction_json: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub transaction_json_decode_failed: bool,
 
     #[diesel(sql_type = diesel::sql_types::BigInt)]
     pub created_at: i64,
+
+    /// The size of the raw transaction in bytes.
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_size_bytes: i64,
+    /// The size of the raw action in bytes.
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub action_size_bytes: i64,
+
+    /// Whether the transaction executed successfully.
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub is_success: bool,
+    /// The abort code if the transaction aborted in Move.
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    pub abort_code: Option<i64>,
 }
 
 impl From<IndexedTransaction> for StoredTransaction {
@@ -100,8 +125,18 @@ impl From<IndexedTransaction> for StoredTransaction {
 
             tx_order_auth_validator_id: transaction.tx_order_auth_validator_id as i64,
             tx_order_authenticator_payload: transaction.tx_order_authenticator_payload,
+            source_node_id: transaction.source_node_id,
+
+            transaction_json: transaction.transaction_json,
+            transaction_json_decode_failed: transaction.transaction_json_decode_failed,
 
             created_at: transaction.created_at as i64,
+
+            tx_size_bytes: transaction.tx_size_bytes as i64,
+            action_size_bytes: transaction.action_size_bytes as i64,
+
+            is_success: transaction.is_success,
+            abort_code: transaction.abort_code.map(|abort_code| abort_code as i64),
         }
     }
 }
@@ -136,4 +171,166 @@ impl StoredTransaction {
             execution_info,
         })
     }
+
+    pub fn try_into_indexed_transaction(self) -> Result<IndexedTransaction, anyhow::Error> {
+        let action: MoveAction = bcs::from_bytes(self.action_raw.as_slice())?;
+        Ok(IndexedTransaction {
+            tx_hash: H256::from_str(self.tx_hash.as_str())?,
+            tx_order: self.tx_order as u64,
+
+            transaction_type: TransactionType::from_str(self.transaction_type.as_str())?,
+            sequence_number: self.sequence_number as u64,
+            multichain_id: MultiChainID::from(self.multichain_id as u64),
+            multichain_address: self.multichain_address,
+            multichain_original_address: self.multichain_original_address,
+            sender: AccountAddress::from_hex_literal(self.sender.as_str())?,
+            action,
+            action_type: self.action_type as u8,
+            action_raw: self.action_raw,
+            auth_validator_id: self.auth_validator_id as u64,
+            authenticator_payload: self.authenticator_payload,
+            tx_accumulator_root: H256::from_str(self.tx_accumulator_root.as_str())?,
+            transaction_raw: self.transaction_raw,
+
+            state_root: H256::from_str(self.state_root.as_str())?,
+            event_root: H256::from_str(self.event_root.as_str())?,
+            gas_used: self.gas_used as u64,
+            status: self.status,
+
+            tx_order_auth_validator_id: self.tx_order_auth_validator_id as u64,
+            tx_order_authenticator_payload: self.tx_order_authenticator_payload,
+            source_node_id: self.source_node_id,
+
+            transaction_json: self.transaction_json,
+            transaction_json_decode_failed: self.transaction_json_decode_failed,
+
+            created_at: self.created_at as u64,
+
+            tx_size_bytes: self.tx_size_bytes as u64,
+            action_size_bytes: self.action_size_bytes as u64,
+
+            is_success: self.is_success,
+            abort_code: self.abort_code.map(|abort_code| abort_code as u64),
+        })
+    }
+}
+
+/// The row of the gas aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredGasSummary {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_count: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub total_gas_used: i64,
+}
+
+impl From<StoredGasSummary> for GasSummary {
+    fn from(summary: StoredGasSummary) -> Self {
+        GasSummary {
+            tx_count: summary.tx_count as u64,
+            total_gas_used: summary.total_gas_used as u64,
+        }
+    }
+}
+
+/// The row of the gas percentile query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredGasUsed {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub gas_used: i64,
+}
+
+/// The row of the per-day gas aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredDailyGasSummary {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub day: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_count: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub total_gas_used: i64,
+}
+
+impl From<StoredDailyGasSummary> for DailyGasSummary {
+    fn from(summary: StoredDailyGasSummary) -> Self {
+        DailyGasSummary {
+            day: summary.day as u64,
+            summary: GasSummary {
+                tx_count: summary.tx_count as u64,
+                total_gas_used: summary.total_gas_used as u64,
+            },
+        }
+    }
+}
+
+/// The row of the per-bucket transaction type aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredTransactionTypeCount {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub bucket: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub transaction_type: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_count: i64,
+}
+
+impl StoredTransactionTypeCount {
+    pub fn try_into_transaction_type_count(self) -> Result<TransactionTypeCount, anyhow::Error> {
+        Ok(TransactionTypeCount {
+            bucket: self.bucket as u64,
+            transaction_type: TransactionType::from_str(self.transaction_type.as_str())?,
+            tx_count: self.tx_count as u64,
+        })
+    }
+}
+
+/// The row of the duplicate tx hash aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredTxHashCount {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub tx_hash: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_count: i64,
+}
+
+impl StoredTxHashCount {
+    pub fn try_into_tx_hash_count(self) -> Result<(H256, u64), anyhow::Error> {
+        Ok((H256::from_str(self.tx_hash.as_str())?, self.tx_count as u64))
+    }
+}
+
+/// The row of the per-type average gas aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredTransactionTypeAvgGas {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub transaction_type: String,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub avg_gas_used: f64,
+}
+
+impl StoredTransactionTypeAvgGas {
+    pub fn try_into_type_avg_gas(self) -> Result<(TransactionType, f64), anyhow::Error> {
+        Ok((
+            TransactionType::from_str(self.transaction_type.as_str())?,
+            self.avg_gas_used,
+        ))
+    }
+}
+
+/// The row of the per-sender transaction count aggregate query.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct StoredSenderTransactionCount {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub sender: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub tx_count: i64,
+}
+
+impl StoredSenderTransactionCount {
+    pub fn try_into_sender_count(self) -> Result<(AccountAddress, u64), anyhow::Error> {
+        Ok((
+            AccountAddress::from_hex_literal(self.sender.as_str())?,
+            self.tx_count as u64,
+        ))
+    }
 }
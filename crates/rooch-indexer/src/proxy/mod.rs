@@ -3,25 +3,170 @@
 
 use crate::actor::indexer::IndexerActor;
 use crate::actor::messages::{
-    IndexerEventsMessage, IndexerTransactionMessage, QueryIndexerEventsMessage,
-    QueryIndexerTransactionsMessage,
+    IndexerEventsMessage, IndexerObjectChangesMessage, IndexerTransactionMessage,
+    PauseIndexerMessage, QueryIndexerAccountNonceHistoryMessage, QueryIndexerEventsMessage,
+    QueryIndexerLatestOrderMessage, QueryIndexerObjectTypeMessage,
+    QueryIndexerObjectsOwnedByMessage, QueryIndexerPausedMessage, QueryIndexerTransactionsMessage,
+    ResumeIndexerMessage, SubscribeObjectChangesMessage,
 };
+use crate::types::{encode_event_json, IndexedObjectChange, IndexedObjectType};
 use anyhow::Result;
 use coerce::actor::ActorRef;
+use move_core_types::account_address::AccountAddress;
+use move_resource_viewer::AnnotatedMoveStruct;
 use moveos_types::moveos_std::event::Event;
+use moveos_types::moveos_std::object::ObjectID;
+use moveos_types::state::StateChangeSet;
 use moveos_types::transaction::{TransactionExecutionInfo, VerifiedMoveOSTransaction};
 use rooch_types::indexer::event_filter::{EventFilter, IndexerEvent, IndexerEventID};
 use rooch_types::indexer::transaction_filter::TransactionFilter;
 use rooch_types::transaction::{TransactionSequenceInfo, TransactionWithInfo, TypedTransaction};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tracing::error;
+
+/// The default capacity of the indexer input channel.
+pub const DEFAULT_INDEXER_CHANNEL_CAPACITY: usize = 1024;
+
+/// A write to the indexer, queued in the input channel.
+enum IndexerWrite {
+    Transaction(IndexerTransactionMessage),
+    Events(IndexerEventsMessage),
+    ObjectChanges(IndexerObjectChangesMessage),
+}
+
+/// A control of the forwarding, handled between two forwarded writes.
+enum IndexerControl {
+    Pause(oneshot::Sender<Result<()>>),
+    Resume(oneshot::Sender<Result<()>>),
+}
 
 #[derive(Clone)]
 pub struct IndexerProxy {
     pub actor: ActorRef<IndexerActor>,
+    /// The bounded input channel of the writes, forwarded to the actor in order.
+    writes: mpsc::Sender<IndexerWrite>,
+    controls: mpsc::Sender<IndexerControl>,
+    /// The writes queued in the input channel or being indexed by the actor.
+    in_flight: Arc<AtomicUsize>,
+}
+
+/// Forwards the writes queued by the `IndexerProxy` to the actor in order, it must be run for the
+/// writes to be indexed, such as spawned on the runtime at startup.
+pub struct IndexerForwarder {
+    actor: ActorRef<IndexerActor>,
+    receiver: mpsc::Receiver<IndexerWrite>,
+    control_receiver: mpsc::Receiver<IndexerControl>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl IndexerForwarder {
+    /// Forward the writes to the actor in order until the proxy is dropped. While paused no write is
+    /// taken from the input channel, so the channel fills up and the writers wait.
+    /// A failed write is logged, the writers do not wait for the result.
+    pub async fn run(mut self) {
+        let mut paused = false;
+        loop {
+            tokio::select! {
+                biased;
+                Some(control) = self.control_receiver.recv() => {
+                    // No write is in flight, the actor commits its pending block before it is paused.
+                    let _ = match control {
+                        IndexerControl::Pause(result) => {
+                            let pause_result =
+                                flatten(self.actor.send(PauseIndexerMessage {}).await);
+                            paused = paused || pause_result.is_ok();
+                            result.send(pause_result)
+                        }
+                        IndexerControl::Resume(result) => {
+                            let resume_result =
+                                flatten(self.actor.send(ResumeIndexerMessage {}).await);
+                            paused = paused && resume_result.is_err();
+                            result.send(resume_result)
+                        }
+                    };
+                }
+                Some(write) = self.receiver.recv(), if !paused => {
+                    let (kind, result) = match write {
+                        IndexerWrite::Transaction(msg) => {
+                            ("transaction", flatten(self.actor.send(msg).await))
+                        }
+                        IndexerWrite::Events(msg) => {
+                            ("events", flatten(self.actor.send(msg).await))
+                        }
+                        IndexerWrite::ObjectChanges(msg) => {
+                            ("object changes", flatten(self.actor.send(msg).await))
+                        }
+                    };
+                    self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                    if let Err(e) = result {
+                        error!("Indexer failed to index the {}: {:?}", kind, e);
+                    }
+                }
+                else => break,
+            }
+        }
+    }
+}
+
+fn flatten(result: Result<Result<()>, coerce::actor::ActorRefErr>) -> Result<()> {
+    result?
 }
 
 impl IndexerProxy {
-    pub fn new(actor: ActorRef<IndexerActor>) -> Self {
-        Self { actor }
+    /// Create the proxy with a bounded input channel of `channel_capacity` writes, and the forwarder
+    /// of the channel to the actor, which the caller runs. A write returns once it is queued, and waits
+    /// while the channel is full, so a lagging indexer slows down the producer instead of buffering
+    /// the writes unboundedly.
+    pub fn new(actor: ActorRef<IndexerActor>, channel_capacity: usize) -> (Self, IndexerForwarder) {
+        let (writes, receiver) = mpsc::channel::<IndexerWrite>(channel_capacity.max(1));
+        let (controls, control_receiver) = mpsc::channel::<IndexerControl>(1);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let forwarder = IndexerForwarder {
+            actor: actor.clone(),
+            receiver,
+            control_receiver,
+            in_flight: in_flight.clone(),
+        };
+        let proxy = Self {
+            actor,
+            writes,
+            controls,
+            in_flight,
+        };
+        (proxy, forwarder)
+    }
+
+    async fn control(
+        &self,
+        control: impl FnOnce(oneshot::Sender<Result<()>>) -> IndexerControl,
+    ) -> Result<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.controls
+            .send(control(sender))
+            .await
+            .map_err(|_| anyhow::anyhow!("The indexer control channel is closed"))?;
+        receiver.await?
+    }
+
+    /// Queue the write, waiting while the input channel is full. The write is only counted once its
+    /// slot is reserved, so a writer which gives up waiting leaves the pending depth unchanged.
+    async fn write(&self, write: IndexerWrite) -> Result<()> {
+        let permit = self
+            .writes
+            .reserve()
+            .await
+            .map_err(|_| anyhow::anyhow!("The indexer input channel is closed"))?;
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        permit.send(write);
+        Ok(())
+    }
+
+    /// Returns the number of writes not indexed yet, the ones queued in the input channel and the one
+    /// being indexed by the actor.
+    pub fn pending_depth(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
     }
 
     pub async fn indexer_transaction(
@@ -31,31 +176,56 @@ impl IndexerProxy {
         execution_info: TransactionExecutionInfo,
         moveos_tx: VerifiedMoveOSTransaction,
     ) -> Result<()> {
-        self.actor
-            .send(IndexerTransactionMessage {
-                transaction,
-                sequence_info,
-                execution_info,
-                moveos_tx,
-            })
-            .await?
+        let msg = IndexerTransactionMessage {
+            transaction,
+            sequence_info,
+            execution_info,
+            moveos_tx,
+        };
+        self.write(IndexerWrite::Transaction(msg)).await
     }
 
+    /// Index the events, with the decoded data of each event in the same order for the JSON queries.
+    /// An event without decoded data is indexed without JSON.
     pub async fn indexer_events(
         &self,
         events: Vec<Event>,
+        decoded_events: Vec<Option<AnnotatedMoveStruct>>,
         transaction: TypedTransaction,
         sequence_info: TransactionSequenceInfo,
         moveos_tx: VerifiedMoveOSTransaction,
     ) -> Result<()> {
-        self.actor
-            .send(IndexerEventsMessage {
-                events,
-                transaction,
-                sequence_info,
-                moveos_tx,
-            })
-            .await?
+        let mut decoded_events = decoded_events.into_iter();
+        let event_jsons = events
+            .iter()
+            .map(|_| decoded_events.next().flatten().and_then(encode_event_json))
+            .collect();
+        let msg = IndexerEventsMessage {
+            events,
+            event_jsons,
+            transaction,
+            sequence_info,
+            moveos_tx,
+        };
+        self.write(IndexerWrite::Events(msg)).await
+    }
+
+    /// Index the types and the owners of the objects changed by the transaction.
+    pub async fn indexer_object_changes(
+        &self,
+        state_change_set: &StateChangeSet,
+        sequence_info: &TransactionSequenceInfo,
+    ) -> Result<()> {
+        let tx_order = sequence_info.tx_order;
+        let object_changes = IndexedObjectChange::from_state_change_set(state_change_set, tx_order);
+        if object_changes.is_empty() {
+            return Ok(());
+        }
+        let msg = IndexerObjectChangesMessage {
+            object_changes,
+            tx_order,
+        };
+        self.write(IndexerWrite::ObjectChanges(msg)).await
     }
 
     pub async fn query_transactions(
@@ -93,4 +263,69 @@ impl IndexerProxy {
             })
             .await?
     }
+
+    /// Returns the max `tx_order` persisted in the indexer, None if no transaction is indexed yet.
+    pub async fn latest_indexed_order(&self) -> Result<Option<u64>> {
+        self.actor.send(QueryIndexerLatestOrderMessage {}).await?
+    }
+
+    /// Returns the latest known type of the object, None if no change of the object is indexed.
+    /// A deleted object keeps its last known type, with the `deleted` flag set.
+    pub async fn object_type(&self, object_id: ObjectID) -> Result<Option<IndexedObjectType>> {
+        self.actor
+            .send(QueryIndexerObjectTypeMessage { object_id })
+            .await?
+    }
+
+    /// Returns the ids of the active objects currently owned by `owner`, in ascending order of the object id.
+    pub async fn objects_owned_by(&self, owner: AccountAddress) -> Result<Vec<ObjectID>> {
+        self.actor
+            .send(QueryIndexerObjectsOwnedByMessage { owner })
+            .await?
+    }
+
+    /// Returns the `(tx_order, sequence_number)` of the transactions sent by `sender`, in ascending order of
+    /// `tx_order`, at most `limit` of them after the exclusive `cursor`.
+    pub async fn account_nonce_history(
+        &self,
+        sender: AccountAddress,
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<(u64, u64)>> {
+        self.actor
+            .send(QueryIndexerAccountNonceHistoryMessage {
+                sender,
+                cursor,
+                limit,
+            })
+            .await?
+    }
+
+    /// Pause indexing for maintenance. The in-flight write and block are committed first, then
+    /// no write is taken from the input channel until resume. The writes stay queued in the channel,
+    /// and the writers wait once it is full, so the backpressure reaches the producer.
+    pub async fn pause(&self) -> Result<()> {
+        self.control(IndexerControl::Pause).await
+    }
+
+    /// Resume indexing from the last committed `tx_order`, the queued writes are indexed in order.
+    pub async fn resume(&self) -> Result<()> {
+        self.control(IndexerControl::Resume).await
+    }
+
+    pub async fn is_paused(&self) -> Result<bool> {
+        self.actor.send(QueryIndexerPausedMessage {}).await?
+    }
+
+    /// Subscribe to the committed changes of the object, the changes of other objects are not delivered.
+    /// A subscriber which falls behind is dropped, and the receiver ends after the deletion of the object
+    /// is delivered.
+    pub async fn subscribe_object_changes(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<mpsc::Receiver<IndexedObjectChange>> {
+        self.actor
+            .send(SubscribeObjectChangesMessage { object_id })
+            .await?
+    }
 }
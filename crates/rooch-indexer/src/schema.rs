@@ -14,6 +14,41 @@ diesel::table! {
         tx_order -> BigInt,
         sender -> Text,
         created_at -> BigInt,
+        event_json -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    indexer_stats (name) {
+        name -> Text,
+        value -> BigInt,
+    }
+}
+
+diesel::table! {
+    object_changes (object_id, tx_order) {
+        object_id -> Text,
+        tx_order -> BigInt,
+        deleted -> Bool,
+        object_type -> Nullable<Text>,
+        owner -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    object_owners (object_id) {
+        object_id -> Text,
+        owner -> Text,
+        tx_order -> BigInt,
+    }
+}
+
+diesel::table! {
+    object_types (object_id) {
+        object_id -> Text,
+        object_type -> Text,
+        deleted -> Bool,
+        tx_order -> BigInt,
     }
 }
 
@@ -41,7 +76,21 @@ diesel::table! {
         tx_order_auth_validator_id -> BigInt,
         tx_order_authenticator_payload -> Binary,
         created_at -> BigInt,
+        transaction_json -> Nullable<Text>,
+        transaction_json_decode_failed -> Bool,
+        source_node_id -> Nullable<Text>,
+        tx_size_bytes -> BigInt,
+        action_size_bytes -> BigInt,
+        is_success -> Bool,
+        abort_code -> Nullable<BigInt>,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(events, transactions,);
+diesel::allow_tables_to_appear_in_same_query!(
+    events,
+    indexer_stats,
+    object_changes,
+    object_owners,
+    object_types,
+    transactions,
+);
@@ -2,14 +2,27 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
-use diesel::RunQueryDsl;
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection,
+    TextExpressionMethods,
+};
+use std::collections::BTreeMap;
 
 use crate::errors::{Context, IndexerError};
 
 use crate::models::events::StoredEvent;
+use crate::models::indexer_stats::{
+    event_count_stat, StoredIndexerStat, EVENT_COUNT_STAT_PREFIX, EVENT_HANDLE_COUNTS_STAT,
+    TOTAL_EVENTS_STAT, TOTAL_TRANSACTIONS_STAT,
+};
+use crate::models::object_changes::StoredObjectChange;
+use crate::models::object_owners::StoredObjectOwner;
+use crate::models::object_types::StoredObjectType;
 use crate::models::transactions::StoredTransaction;
-use crate::schema::{events, transactions};
-use crate::types::{IndexedEvent, IndexedTransaction};
+use crate::schema::{
+    events, indexer_stats, object_changes, object_owners, object_types, transactions,
+};
+use crate::types::{IndexedEvent, IndexedObjectChange, IndexedObjectType, IndexedTransaction};
 use crate::{get_sqlite_pool_connection, SqliteConnectionPool};
 
 #[derive(Clone)]
@@ -36,15 +49,83 @@ impl SqliteIndexerStore {
             .map(StoredTransaction::from)
             .collect::<Vec<_>>();
 
-        diesel::insert_into(transactions::table)
-            .values(transactions.as_slice())
-            .execute(&mut connection)
+        connection
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let inserted = diesel::insert_into(transactions::table)
+                    .values(transactions.as_slice())
+                    .execute(conn)?;
+                increment_stat(conn, TOTAL_TRANSACTIONS_STAT, inserted as i64)
+            })
             .map_err(IndexerError::from)
             .context("Failed to write transactions to SQLiteDB")?;
 
         Ok(())
     }
 
+    /// Persist the transactions, a transaction at an already indexed `tx_order` replaces the indexed one,
+    /// and a transaction whose hash is already indexed at another `tx_order` moves to the new order.
+    /// The events and the object history of the replaced or moved transaction are removed, and the types
+    /// and the owners of the objects it changed are rebuilt from their remaining history, all in the same
+    /// db transaction. The events and the object changes of the new transaction are persisted after it as usual.
+    pub fn upsert_transactions(
+        &self,
+        transactions: Vec<IndexedTransaction>,
+    ) -> Result<(), IndexerError> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        let mut connection = get_sqlite_pool_connection(&self.connection_pool)?;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let mut transaction_delta = 0i64;
+                let mut removed_events = 0i64;
+                for transaction in transactions {
+                    let transaction = StoredTransaction::from(transaction);
+                    let replaced_tx_hash = transactions::table
+                        .filter(transactions::tx_order.eq(transaction.tx_order))
+                        .select(transactions::tx_hash)
+                        .first::<String>(conn)
+                        .optional()?;
+                    if let Some(replaced_tx_hash) = replaced_tx_hash {
+                        tracing::warn!(
+                            "Indexer replaces transaction {} at tx_order {} with transaction {}",
+                            replaced_tx_hash,
+                            transaction.tx_order,
+                            transaction.tx_hash
+                        );
+                        removed_events += remove_transaction(conn, transaction.tx_order)?;
+                        transaction_delta -= 1;
+                    }
+                    let moved_tx_order = transactions::table
+                        .filter(transactions::tx_hash.eq(transaction.tx_hash.as_str()))
+                        .select(transactions::tx_order)
+                        .first::<i64>(conn)
+                        .optional()?;
+                    if let Some(moved_tx_order) = moved_tx_order {
+                        tracing::warn!(
+                            "Indexer moves transaction {} from tx_order {} to tx_order {}",
+                            transaction.tx_hash,
+                            moved_tx_order,
+                            transaction.tx_order
+                        );
+                        removed_events += remove_transaction(conn, moved_tx_order)?;
+                        transaction_delta -= 1;
+                    }
+                    diesel::insert_into(transactions::table)
+                        .values(&transaction)
+                        .execute(conn)?;
+                    transaction_delta += 1;
+                }
+                increment_stat(conn, TOTAL_TRANSACTIONS_STAT, transaction_delta)?;
+                increment_stat(conn, TOTAL_EVENTS_STAT, -removed_events)
+            })
+            .map_err(IndexerError::from)
+            .context("Failed to upsert transactions to SQLiteDB")?;
+
+        Ok(())
+    }
+
     pub fn persist_events(&self, events: Vec<IndexedEvent>) -> Result<(), IndexerError> {
         if events.is_empty() {
             return Ok(());
@@ -55,13 +136,324 @@ impl SqliteIndexerStore {
             .into_iter()
             .map(StoredEvent::from)
             .collect::<Vec<_>>();
+        let handle_counts = count_by_handle(
+            events
+                .iter()
+                .map(|event| event.event_handle_id.clone())
+                .collect(),
+        );
 
-        diesel::insert_into(events::table)
-            .values(events.as_slice())
-            .execute(&mut connection)
+        connection
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let inserted = diesel::insert_into(events::table)
+                    .values(events.as_slice())
+                    .execute(conn)?;
+                for (event_handle_id, count) in &handle_counts {
+                    increment_stat(conn, &event_count_stat(event_handle_id), *count)?;
+                }
+                increment_stat(conn, TOTAL_EVENTS_STAT, inserted as i64)
+            })
             .map_err(IndexerError::from)
             .context("Failed to write events to SQLiteDB")?;
 
         Ok(())
     }
+
+    /// Initialize the missing stats with a full count of the indexed rows, for the db indexed before
+    /// the stats are maintained. Call it on startup, before any write.
+    pub fn reconcile_stats(&self) -> Result<(), IndexerError> {
+        let mut connection = get_sqlite_pool_connection(&self.connection_pool)?;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                if !stat_exists(conn, TOTAL_TRANSACTIONS_STAT)? {
+                    let total = transactions::table.count().get_result::<i64>(conn)?;
+                    tracing::info!(
+                        "Indexer reconciles {} with {}",
+                        TOTAL_TRANSACTIONS_STAT,
+                        total
+                    );
+                    increment_stat(conn, TOTAL_TRANSACTIONS_STAT, total)?;
+                }
+                if !stat_exists(conn, TOTAL_EVENTS_STAT)? {
+                    let total = events::table.count().get_result::<i64>(conn)?;
+                    tracing::info!("Indexer reconciles {} with {}", TOTAL_EVENTS_STAT, total);
+                    increment_stat(conn, TOTAL_EVENTS_STAT, total)?;
+                }
+                if !stat_exists(conn, EVENT_HANDLE_COUNTS_STAT)? {
+                    // Replace the counts written before the reconciliation, the full count covers them.
+                    diesel::delete(
+                        indexer_stats::table.filter(
+                            indexer_stats::name.like(format!("{}%", EVENT_COUNT_STAT_PREFIX)),
+                        ),
+                    )
+                    .execute(conn)?;
+                    let handle_counts = events::table
+                        .group_by(events::event_handle_id)
+                        .select((events::event_handle_id, diesel::dsl::count_star()))
+                        .load::<(String, i64)>(conn)?;
+                    tracing::info!(
+                        "Indexer reconciles the event counts of {} handles",
+                        handle_counts.len()
+                    );
+                    for (event_handle_id, count) in handle_counts {
+                        increment_stat(conn, &event_count_stat(&event_handle_id), count)?;
+                    }
+                    increment_stat(conn, EVENT_HANDLE_COUNTS_STAT, 0)?;
+                }
+                Ok(())
+            })
+            .map_err(IndexerError::from)
+            .context("Failed to reconcile stats in SQLiteDB")?;
+
+        Ok(())
+    }
+
+    /// Apply the object changes in order, a deleted object keeps its last known type.
+    /// The owner of each active object is maintained alongside, a deleted object is removed from its owner.
+    /// Each change is also recorded in the object history, the last change of an object in a transaction wins.
+    pub fn persist_object_changes(
+        &self,
+        object_changes: Vec<IndexedObjectChange>,
+    ) -> Result<(), IndexerError> {
+        if object_changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut connection = get_sqlite_pool_connection(&self.connection_pool)?;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                for object_change in object_changes {
+                    let object_id = object_change.object_id.to_string();
+                    let stored_object_change = StoredObjectChange {
+                        object_id: object_id.clone(),
+                        tx_order: object_change.tx_order as i64,
+                        deleted: object_change.object_type.is_none(),
+                        object_type: object_change
+                            .object_type
+                            .as_ref()
+                            .map(|object_type| format!("0x{}", object_type.to_canonical_string())),
+                        owner: object_change.owner.map(|owner| owner.to_hex_literal()),
+                    };
+                    diesel::insert_into(object_changes::table)
+                        .values(&stored_object_change)
+                        .on_conflict((object_changes::object_id, object_changes::tx_order))
+                        .do_update()
+                        .set((
+                            object_changes::deleted.eq(stored_object_change.deleted),
+                            object_changes::object_type
+                                .eq(stored_object_change.object_type.as_deref()),
+                            object_changes::owner.eq(stored_object_change.owner.as_deref()),
+                        ))
+                        .execute(conn)?;
+                    match (object_change.object_type, object_change.owner) {
+                        (Some(object_type), Some(owner)) => {
+                            let object_type = StoredObjectType::from(IndexedObjectType {
+                                object_id: object_change.object_id,
+                                object_type,
+                                deleted: false,
+                                tx_order: object_change.tx_order,
+                            });
+                            diesel::insert_into(object_types::table)
+                                .values(&object_type)
+                                .on_conflict(object_types::object_id)
+                                .do_update()
+                                .set(&object_type)
+                                .execute(conn)?;
+                            let object_owner = StoredObjectOwner {
+                                object_id,
+                                owner: owner.to_hex_literal(),
+                                tx_order: object_change.tx_order as i64,
+                            };
+                            diesel::insert_into(object_owners::table)
+                                .values(&object_owner)
+                                .on_conflict(object_owners::object_id)
+                                .do_update()
+                                .set(&object_owner)
+                                .execute(conn)?;
+                        }
+                        _ => {
+                            diesel::update(
+                                object_types::table
+                                    .filter(object_types::object_id.eq(object_id.as_str())),
+                            )
+                            .set((
+                                object_types::deleted.eq(true),
+                                object_types::tx_order.eq(object_change.tx_order as i64),
+                            ))
+                            .execute(conn)?;
+                            diesel::delete(
+                                object_owners::table
+                                    .filter(object_owners::object_id.eq(object_id.as_str())),
+                            )
+                            .execute(conn)?;
+                        }
+                    }
+                }
+                Ok(())
+            })
+            .map_err(IndexerError::from)
+            .context("Failed to write object changes to SQLiteDB")?;
+
+        Ok(())
+    }
+}
+
+/// Remove the indexed transaction at `tx_order`, with its events and its object history.
+/// The types and the owners of the objects it changed are rebuilt from the remaining history.
+/// Returns the number of removed events.
+fn remove_transaction(
+    conn: &mut SqliteConnection,
+    tx_order: i64,
+) -> Result<i64, diesel::result::Error> {
+    let removed_handles = events::table
+        .filter(events::tx_order.eq(tx_order))
+        .select(events::event_handle_id)
+        .load::<String>(conn)?;
+    for (event_handle_id, count) in count_by_handle(removed_handles) {
+        increment_stat(conn, &event_count_stat(&event_handle_id), -count)?;
+    }
+    let removed_events =
+        diesel::delete(events::table.filter(events::tx_order.eq(tx_order))).execute(conn)?;
+    let changed_objects = object_changes::table
+        .filter(object_changes::tx_order.eq(tx_order))
+        .select(object_changes::object_id)
+        .load::<String>(conn)?;
+    diesel::delete(object_changes::table.filter(object_changes::tx_order.eq(tx_order)))
+        .execute(conn)?;
+    for object_id in changed_objects {
+        rebuild_object(conn, object_id.as_str())?;
+    }
+    diesel::delete(transactions::table.filter(transactions::tx_order.eq(tx_order)))
+        .execute(conn)?;
+    Ok(removed_events as i64)
+}
+
+/// Rebuild the type and the owner of the object from the latest change in its history.
+/// An object without history is removed, a deleted object keeps its last known type.
+fn rebuild_object(
+    conn: &mut SqliteConnection,
+    object_id: &str,
+) -> Result<(), diesel::result::Error> {
+    let latest_change = object_changes::table
+        .filter(object_changes::object_id.eq(object_id))
+        .order_by(object_changes::tx_order.desc())
+        .first::<StoredObjectChange>(conn)
+        .optional()?;
+    let latest_change = match latest_change {
+        Some(latest_change) => latest_change,
+        None => {
+            diesel::delete(object_types::table.filter(object_types::object_id.eq(object_id)))
+                .execute(conn)?;
+            diesel::delete(object_owners::table.filter(object_owners::object_id.eq(object_id)))
+                .execute(conn)?;
+            return Ok(());
+        }
+    };
+    if latest_change.deleted {
+        let last_known_type = object_changes::table
+            .filter(object_changes::object_id.eq(object_id))
+            .filter(object_changes::object_type.is_not_null())
+            .order_by(object_changes::tx_order.desc())
+            .select(object_changes::object_type)
+            .first::<Option<String>>(conn)
+            .optional()?
+            .flatten();
+        match last_known_type {
+            Some(object_type) => {
+                let object_type = StoredObjectType {
+                    object_id: object_id.to_owned(),
+                    object_type,
+                    deleted: true,
+                    tx_order: latest_change.tx_order,
+                };
+                diesel::insert_into(object_types::table)
+                    .values(&object_type)
+                    .on_conflict(object_types::object_id)
+                    .do_update()
+                    .set(&object_type)
+                    .execute(conn)?;
+            }
+            None => {
+                diesel::update(object_types::table.filter(object_types::object_id.eq(object_id)))
+                    .set((
+                        object_types::deleted.eq(true),
+                        object_types::tx_order.eq(latest_change.tx_order),
+                    ))
+                    .execute(conn)?;
+            }
+        }
+        diesel::delete(object_owners::table.filter(object_owners::object_id.eq(object_id)))
+            .execute(conn)?;
+        return Ok(());
+    }
+    match (latest_change.object_type, latest_change.owner) {
+        (Some(object_type), Some(owner)) => {
+            let object_type = StoredObjectType {
+                object_id: object_id.to_owned(),
+                object_type,
+                deleted: false,
+                tx_order: latest_change.tx_order,
+            };
+            diesel::insert_into(object_types::table)
+                .values(&object_type)
+                .on_conflict(object_types::object_id)
+                .do_update()
+                .set(&object_type)
+                .execute(conn)?;
+            let object_owner = StoredObjectOwner {
+                object_id: object_id.to_owned(),
+                owner,
+                tx_order: latest_change.tx_order,
+            };
+            diesel::insert_into(object_owners::table)
+                .values(&object_owner)
+                .on_conflict(object_owners::object_id)
+                .do_update()
+                .set(&object_owner)
+                .execute(conn)?;
+        }
+        // The changes indexed before the type and the owner are recorded in the history can not be rebuilt.
+        _ => tracing::warn!(
+            "Indexer can not rebuild object {} from the change at tx_order {} without its type and owner",
+            object_id,
+            latest_change.tx_order
+        ),
+    }
+    Ok(())
+}
+
+fn stat_exists(conn: &mut SqliteConnection, name: &str) -> Result<bool, diesel::result::Error> {
+    Ok(indexer_stats::table
+        .filter(indexer_stats::name.eq(name))
+        .select(indexer_stats::name)
+        .first::<String>(conn)
+        .optional()?
+        .is_some())
+}
+
+/// Count the events of each event handle.
+fn count_by_handle(event_handle_ids: Vec<String>) -> BTreeMap<String, i64> {
+    let mut counts = BTreeMap::new();
+    for event_handle_id in event_handle_ids {
+        *counts.entry(event_handle_id).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Add `delta` to the stat, a missing stat starts from 0.
+fn increment_stat(
+    conn: &mut SqliteConnection,
+    name: &str,
+    delta: i64,
+) -> Result<(), diesel::result::Error> {
+    diesel::insert_into(indexer_stats::table)
+        .values(&StoredIndexerStat {
+            name: name.to_string(),
+            value: delta,
+        })
+        .on_conflict(indexer_stats::name)
+        .do_update()
+        .set(indexer_stats::value.eq(indexer_stats::value + delta))
+        .execute(conn)?;
+    Ok(())
 }
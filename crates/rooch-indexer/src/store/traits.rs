@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::errors::IndexerError;
-use crate::types::{IndexedEvent, IndexedTransaction};
+use crate::types::{IndexedEvent, IndexedObjectChange, IndexedTransaction};
 
 pub trait IndexerStoreTrait: Send + Sync {
     fn persist_transactions(
@@ -10,5 +10,17 @@ pub trait IndexerStoreTrait: Send + Sync {
         transactions: Vec<IndexedTransaction>,
     ) -> Result<(), IndexerError>;
 
+    /// Persist the transactions, replacing the indexed transaction and its events at the same `tx_order`,
+    /// or at another `tx_order` with the same hash.
+    fn upsert_transactions(
+        &self,
+        transactions: Vec<IndexedTransaction>,
+    ) -> Result<(), IndexerError>;
+
     fn persist_events(&self, events: Vec<IndexedEvent>) -> Result<(), IndexerError>;
+
+    fn persist_object_changes(
+        &self,
+        object_changes: Vec<IndexedObjectChange>,
+    ) -> Result<(), IndexerError>;
 }
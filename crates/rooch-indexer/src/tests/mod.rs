@@ -0,0 +1,186 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+mod test_indexer_actor;
+mod test_indexer_reader;
+mod test_types;
+
+use crate::indexer_reader::IndexerReader;
+use crate::types::{IndexedEvent, IndexedObjectChange, IndexedTransaction};
+use crate::IndexerStore;
+use anyhow::{anyhow, Result};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::vm_status::KeptVMStatus;
+use moveos_config::DataDirPath;
+use moveos_types::h256::H256;
+use moveos_types::move_types::FunctionId;
+use moveos_types::moveos_std::event::{Event, EventID};
+use moveos_types::moveos_std::object::ObjectID;
+use moveos_types::transaction::{
+    MoveAction, TransactionExecutionInfo, VerifiedMoveAction, VerifiedMoveOSTransaction,
+};
+use rooch_config::indexer_config::ROOCH_INDEXER_DB_FILENAME;
+use rooch_types::crypto::{Ed25519RoochSignature, Signature};
+use rooch_types::transaction::authenticator::Authenticator;
+use rooch_types::transaction::rooch::{RoochTransaction, RoochTransactionData};
+use rooch_types::transaction::{AbstractTransaction, TransactionSequenceInfo, TypedTransaction};
+use std::str::FromStr;
+
+/// An indexer db with all the tables created in a temp dir.
+pub(crate) struct TestIndexerDB {
+    pub store: IndexerStore,
+    pub reader: IndexerReader,
+    // The temp dir is removed when it is dropped, so keep it with the db.
+    _data_dir: DataDirPath,
+}
+
+impl TestIndexerDB {
+    pub fn new() -> Result<Self> {
+        let data_dir = moveos_config::temp_dir();
+        let db_path = data_dir.path().join(ROOCH_INDEXER_DB_FILENAME);
+        let db_url = db_path.to_str().ok_or(anyhow!("Invalid indexer db path"))?;
+        let store = IndexerStore::new(db_url)?;
+        store.create_all_tables_if_not_exists()?;
+        store.reconcile_stats()?;
+        let reader = IndexerReader::new(db_url)?;
+        Ok(Self {
+            store,
+            reader,
+            _data_dir: data_dir,
+        })
+    }
+}
+
+pub(crate) fn function_call_action(address: AccountAddress, module: &str) -> MoveAction {
+    MoveAction::new_function_call(
+        FunctionId::new(
+            ModuleId::new(address, Identifier::new(module).unwrap()),
+            Identifier::new("test").unwrap(),
+        ),
+        vec![],
+        vec![],
+    )
+}
+
+/// The action as verified by the executor, the tests do not publish any module.
+pub(crate) fn verified_action(action: MoveAction) -> VerifiedMoveAction {
+    match action {
+        MoveAction::Script(call) => VerifiedMoveAction::Script { call },
+        MoveAction::Function(call) => VerifiedMoveAction::Function { call },
+        MoveAction::ModuleBundle(module_bundle) => VerifiedMoveAction::ModuleBundle {
+            module_bundle,
+            init_function_modules: vec![],
+        },
+    }
+}
+
+pub(crate) fn object_id(hex_literal: &str) -> ObjectID {
+    ObjectID::from(AccountAddress::from_hex_literal(hex_literal).unwrap())
+}
+
+pub(crate) fn struct_tag(canonical: &str) -> StructTag {
+    StructTag::from_str(canonical).unwrap()
+}
+
+/// The inputs of the indexer for a sequenced and executed transaction.
+#[derive(Clone)]
+pub(crate) struct TestTransaction {
+    pub transaction: TypedTransaction,
+    pub sequence_info: TransactionSequenceInfo,
+    pub execution_info: TransactionExecutionInfo,
+    pub moveos_tx: VerifiedMoveOSTransaction,
+}
+
+impl TestTransaction {
+    pub fn new(
+        tx_order: u64,
+        sender: AccountAddress,
+        sequence_number: u64,
+        action: MoveAction,
+    ) -> Self {
+        let data = RoochTransactionData::new_for_test(sender.into(), sequence_number, action);
+        let authenticator = Authenticator::rooch(Signature::Ed25519RoochSignature(
+            Ed25519RoochSignature::default(),
+        ));
+        let transaction = TypedTransaction::Rooch(RoochTransaction::new(data, authenticator));
+        let moveos_tx = transaction
+            .clone()
+            .construct_moveos_transaction(sender)
+            .unwrap();
+        let moveos_tx = VerifiedMoveOSTransaction {
+            ctx: moveos_tx.ctx,
+            action: verified_action(moveos_tx.action),
+            pre_execute_functions: moveos_tx.pre_execute_functions,
+            post_execute_functions: moveos_tx.post_execute_functions,
+            tx_order: Some(tx_order),
+        };
+        let sequence_info =
+            TransactionSequenceInfo::new(tx_order, Authenticator::new(0, vec![]), H256::random());
+        let execution_info = TransactionExecutionInfo::new(
+            transaction.tx_hash(),
+            H256::random(),
+            H256::random(),
+            100,
+            KeptVMStatus::Executed,
+        );
+        Self {
+            transaction,
+            sequence_info,
+            execution_info,
+            moveos_tx,
+        }
+    }
+
+    pub fn tx_order(&self) -> u64 {
+        self.sequence_info.tx_order
+    }
+
+    pub fn indexed(&self) -> IndexedTransaction {
+        IndexedTransaction::new(
+            self.transaction.clone(),
+            self.sequence_info.clone(),
+            self.execution_info.clone(),
+            self.moveos_tx.clone(),
+            false,
+        )
+        .unwrap()
+    }
+
+    pub fn event(
+        &self,
+        event_handle_id: ObjectID,
+        event_seq: u64,
+        event_type: StructTag,
+        event_index: u64,
+        event_json: Option<String>,
+    ) -> IndexedEvent {
+        IndexedEvent::new(
+            Event::new(
+                EventID::new(event_handle_id, event_seq),
+                event_type,
+                vec![],
+                event_index,
+            ),
+            event_json,
+            self.transaction.clone(),
+            self.sequence_info.clone(),
+            self.moveos_tx.clone(),
+        )
+    }
+
+    pub fn object_change(
+        &self,
+        object_id: ObjectID,
+        object_type: Option<StructTag>,
+        owner: Option<AccountAddress>,
+    ) -> IndexedObjectChange {
+        IndexedObjectChange {
+            object_id,
+            object_type,
+            owner,
+            tx_order: self.tx_order(),
+        }
+    }
+}
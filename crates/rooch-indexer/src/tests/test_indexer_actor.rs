@@ -0,0 +1,233 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::actor::indexer::IndexerActor;
+use crate::actor::messages::{
+    FlushIndexerBlockMessage, IndexerObjectChangesMessage, IndexerTransactionMessage,
+};
+use crate::proxy::IndexerProxy;
+use crate::tests::{function_call_action, object_id, struct_tag, TestIndexerDB, TestTransaction};
+use crate::types::TransactionModuleFilter;
+use coerce::actor::system::ActorSystem;
+use coerce::actor::IntoActor;
+use move_core_types::account_address::AccountAddress;
+use std::time::Duration;
+
+/// Start the indexer actor over the test db, with the proxy of capacity `channel_capacity` and its forwarder.
+async fn start_indexer(
+    db: &TestIndexerDB,
+    block_size: Option<u64>,
+    module_filter: Option<TransactionModuleFilter>,
+    channel_capacity: usize,
+) -> IndexerProxy {
+    let actor_system = ActorSystem::new();
+    let actor = IndexerActor::new(
+        db.store.clone(),
+        db.reader.clone(),
+        false,
+        block_size,
+        false,
+        module_filter,
+    )
+    .unwrap()
+    .into_actor(Some("Indexer"), &actor_system)
+    .await
+    .unwrap();
+    let (proxy, forwarder) = IndexerProxy::new(actor.into(), channel_capacity);
+    tokio::spawn(forwarder.run());
+    proxy
+}
+
+async fn index_transaction(proxy: &IndexerProxy, tx: TestTransaction) {
+    proxy
+        .indexer_transaction(
+            tx.transaction,
+            tx.sequence_info,
+            tx.execution_info,
+            tx.moveos_tx,
+        )
+        .await
+        .unwrap();
+}
+
+/// Wait until the queued writes are indexed by the actor.
+async fn wait_indexed(proxy: &IndexerProxy) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while proxy.pending_depth() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+fn transaction(tx_order: u64, sender: AccountAddress) -> TestTransaction {
+    TestTransaction::new(
+        tx_order,
+        sender,
+        tx_order,
+        function_call_action(sender, format!("m{}", tx_order).as_str()),
+    )
+}
+
+#[tokio::test]
+async fn test_block_batching() {
+    let db = TestIndexerDB::new().unwrap();
+    let proxy = start_indexer(&db, Some(10), None, 16).await;
+    let sender = AccountAddress::random();
+
+    for tx_order in 0..3 {
+        index_transaction(&proxy, transaction(tx_order, sender)).await;
+    }
+    wait_indexed(&proxy).await;
+    // The block 0 is still pending.
+    assert_eq!(db.reader.latest_indexed_order().unwrap(), None);
+
+    // The first transaction of the block 1 commits the block 0.
+    index_transaction(&proxy, transaction(10, sender)).await;
+    wait_indexed(&proxy).await;
+    assert_eq!(db.reader.latest_indexed_order().unwrap(), Some(2));
+    assert_eq!(db.reader.total_transactions().unwrap(), 3);
+
+    proxy.actor.send(FlushIndexerBlockMessage {}).await.unwrap();
+    assert_eq!(db.reader.latest_indexed_order().unwrap(), Some(10));
+    assert_eq!(db.reader.total_transactions().unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_pause_and_resume() {
+    let db = TestIndexerDB::new().unwrap();
+    let proxy = start_indexer(&db, None, None, 16).await;
+    let sender = AccountAddress::random();
+
+    index_transaction(&proxy, transaction(0, sender)).await;
+    wait_indexed(&proxy).await;
+    proxy.pause().await.unwrap();
+    assert!(proxy.is_paused().await.unwrap());
+    // The writes after the pause stay queued.
+    index_transaction(&proxy, transaction(1, sender)).await;
+    index_transaction(&proxy, transaction(2, sender)).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(proxy.pending_depth(), 2);
+    assert_eq!(db.reader.latest_indexed_order().unwrap(), Some(0));
+
+    // A write which reaches the actor while paused is rejected.
+    let tx = transaction(3, sender);
+    assert!(proxy
+        .actor
+        .send(IndexerTransactionMessage {
+            transaction: tx.transaction,
+            sequence_info: tx.sequence_info,
+            execution_info: tx.execution_info,
+            moveos_tx: tx.moveos_tx,
+        })
+        .await
+        .unwrap()
+        .is_err());
+
+    proxy.resume().await.unwrap();
+    assert!(!proxy.is_paused().await.unwrap());
+    wait_indexed(&proxy).await;
+    assert_eq!(db.reader.latest_indexed_order().unwrap(), Some(2));
+    assert_eq!(db.reader.total_transactions().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_backpressure() {
+    let db = TestIndexerDB::new().unwrap();
+    let proxy = start_indexer(&db, None, None, 1).await;
+    let sender = AccountAddress::random();
+    proxy.pause().await.unwrap();
+
+    index_transaction(&proxy, transaction(0, sender)).await;
+    // The channel is full, the writer waits until the indexer resumes.
+    let blocked = transaction(1, sender);
+    let result = tokio::time::timeout(
+        Duration::from_millis(100),
+        index_transaction(&proxy, blocked.clone()),
+    )
+    .await;
+    assert!(result.is_err());
+    // The write given up is not counted.
+    assert_eq!(proxy.pending_depth(), 1);
+
+    proxy.resume().await.unwrap();
+    index_transaction(&proxy, blocked).await;
+    wait_indexed(&proxy).await;
+    assert_eq!(db.reader.latest_indexed_order().unwrap(), Some(1));
+}
+
+#[tokio::test]
+async fn test_module_filter() {
+    let db = TestIndexerDB::new().unwrap();
+    let filter = TransactionModuleFilter::new(&["0x2::coin".to_owned()], false).unwrap();
+    let proxy = start_indexer(&db, None, Some(filter), 16).await;
+    let sender = AccountAddress::random();
+    let framework = AccountAddress::from_hex_literal("0x2").unwrap();
+    let object_type = struct_tag("0x2::coin::CoinStore");
+    let indexed = TestTransaction::new(0, sender, 0, function_call_action(framework, "coin"));
+    let skipped = TestTransaction::new(1, sender, 1, function_call_action(framework, "account"));
+
+    for tx in [indexed.clone(), skipped.clone()] {
+        let object_change =
+            tx.object_change(object_id("0xa1"), Some(object_type.clone()), Some(sender));
+        index_transaction(&proxy, tx.clone()).await;
+        wait_indexed(&proxy).await;
+        proxy
+            .actor
+            .send(IndexerObjectChangesMessage {
+                object_changes: vec![object_change],
+                tx_order: tx.tx_order(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    assert_eq!(db.reader.latest_indexed_order().unwrap(), Some(0));
+    assert_eq!(db.reader.total_transactions().unwrap(), 1);
+    // The object change of the skipped transaction is skipped with it.
+    let indexed_object_type = db.reader.object_type(object_id("0xa1")).unwrap().unwrap();
+    assert_eq!(indexed_object_type.tx_order, 0);
+}
+
+#[tokio::test]
+async fn test_subscribe_object_changes() {
+    let db = TestIndexerDB::new().unwrap();
+    let proxy = start_indexer(&db, None, None, 16).await;
+    let sender = AccountAddress::random();
+    let subscribed = object_id("0xa1");
+    let other = object_id("0xa2");
+    let object_type = struct_tag("0x42::counter::Counter");
+    let mut receiver = proxy.subscribe_object_changes(subscribed).await.unwrap();
+
+    let created = transaction(0, sender);
+    let deleted = transaction(1, sender);
+    let changes = [
+        vec![
+            created.object_change(subscribed, Some(object_type.clone()), Some(sender)),
+            created.object_change(other, Some(object_type), Some(sender)),
+        ],
+        vec![deleted.object_change(subscribed, None, None)],
+    ];
+    for (tx_order, object_changes) in changes.into_iter().enumerate() {
+        proxy
+            .actor
+            .send(IndexerObjectChangesMessage {
+                object_changes,
+                tx_order: tx_order as u64,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    let change = receiver.recv().await.unwrap();
+    assert_eq!((change.object_id, change.tx_order), (subscribed, 0));
+    assert_eq!(change.owner, Some(sender));
+    let change = receiver.recv().await.unwrap();
+    assert_eq!((change.object_id, change.tx_order), (subscribed, 1));
+    assert_eq!(change.object_type, None);
+    // The receiver ends after the deletion.
+    assert!(receiver.recv().await.is_none());
+}
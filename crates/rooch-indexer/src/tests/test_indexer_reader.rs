@@ -0,0 +1,433 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::errors::IndexerError;
+use crate::get_sqlite_pool_connection;
+use crate::store::traits::IndexerStoreTrait;
+use crate::tests::{function_call_action, object_id, struct_tag, TestIndexerDB, TestTransaction};
+use crate::types::{EventJsonPredicate, JsonCompareOp, JsonPredicateValue, TransactionWindow};
+use diesel::RunQueryDsl;
+use futures::StreamExt;
+use move_core_types::account_address::AccountAddress;
+use std::collections::BTreeMap;
+
+/// A function call transaction of `sender`, each `tx_order` calls another module so the hashes differ.
+fn transaction(tx_order: u64, sender: AccountAddress, sequence_number: u64) -> TestTransaction {
+    TestTransaction::new(
+        tx_order,
+        sender,
+        sequence_number,
+        function_call_action(sender, format!("m{}", tx_order).as_str()),
+    )
+}
+
+fn persist(db: &TestIndexerDB, transactions: &[TestTransaction]) {
+    db.store
+        .persist_transactions(transactions.iter().map(TestTransaction::indexed).collect())
+        .unwrap();
+}
+
+#[test]
+fn test_account_nonce_history() {
+    let db = TestIndexerDB::new().unwrap();
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    // Alice reuses the sequence number 1 and skips 2.
+    persist(
+        &db,
+        &[
+            transaction(0, alice, 0),
+            transaction(1, bob, 0),
+            transaction(2, alice, 1),
+            transaction(3, bob, 1),
+            transaction(4, alice, 1),
+            transaction(6, alice, 3),
+        ],
+    );
+
+    let reader = &db.reader;
+    let page = reader
+        .account_nonce_history(alice, 0..u64::MAX, None, 2)
+        .unwrap();
+    assert_eq!(page, vec![(0, 0), (2, 1)]);
+    let page = reader
+        .account_nonce_history(alice, 0..u64::MAX, Some(2), 2)
+        .unwrap();
+    assert_eq!(page, vec![(4, 1), (6, 3)]);
+    let page = reader
+        .account_nonce_history(alice, 0..u64::MAX, Some(6), 2)
+        .unwrap();
+    assert!(page.is_empty());
+
+    let page = reader.account_nonce_history(alice, 1..5, None, 10).unwrap();
+    assert_eq!(page, vec![(2, 1), (4, 1)]);
+    // A cursor before the range does not extend it.
+    let page = reader
+        .account_nonce_history(bob, 2..5, Some(0), 10)
+        .unwrap();
+    assert_eq!(page, vec![(3, 1)]);
+}
+
+#[test]
+fn test_totals_and_event_counts() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let transactions = [
+        transaction(0, sender, 0),
+        transaction(1, sender, 1),
+        transaction(2, sender, 2),
+    ];
+    persist(&db, &transactions);
+    let handle = object_id("0xe1");
+    let other_handle = object_id("0xe2");
+    let deposit = struct_tag("0x3::coin::DepositEvent");
+    db.store
+        .persist_events(vec![
+            transactions[0].event(handle, 0, deposit.clone(), 0, None),
+            transactions[0].event(other_handle, 0, deposit.clone(), 1, None),
+            transactions[1].event(handle, 1, deposit.clone(), 0, None),
+            transactions[2].event(handle, 2, deposit, 0, None),
+        ])
+        .unwrap();
+
+    let reader = &db.reader;
+    assert_eq!(reader.total_transactions().unwrap(), 3);
+    assert_eq!(reader.total_events().unwrap(), 4);
+    assert_eq!(reader.event_count_by_handle(handle).unwrap(), 3);
+    assert_eq!(reader.event_count_by_handle(other_handle).unwrap(), 1);
+    assert_eq!(reader.event_count_by_handle(object_id("0xe3")).unwrap(), 0);
+    assert_eq!(reader.latest_indexed_order().unwrap(), Some(2));
+}
+
+#[test]
+fn test_event_type_counts() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let transactions = [
+        transaction(0, sender, 0),
+        transaction(1, sender, 1),
+        transaction(5, sender, 2),
+    ];
+    persist(&db, &transactions);
+    let handle = object_id("0xe1");
+    let deposit = struct_tag("0x3::coin::DepositEvent");
+    let withdraw = struct_tag("0x3::coin::WithdrawEvent");
+    db.store
+        .persist_events(vec![
+            transactions[0].event(handle, 0, deposit.clone(), 0, None),
+            transactions[1].event(handle, 1, deposit.clone(), 0, None),
+            transactions[1].event(handle, 2, withdraw.clone(), 1, None),
+            transactions[2].event(handle, 3, deposit.clone(), 0, None),
+        ])
+        .unwrap();
+
+    let counts = db.reader.event_type_counts(0..2).unwrap();
+    let expected = BTreeMap::from([
+        (format!("0x{}", deposit.to_canonical_string()), 2),
+        (format!("0x{}", withdraw.to_canonical_string()), 1),
+    ]);
+    assert_eq!(counts, expected);
+    assert!(db.reader.event_type_counts(2..5).unwrap().is_empty());
+}
+
+#[test]
+fn test_events_by_type_filtered() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let transactions = [transaction(0, sender, 0), transaction(1, sender, 1)];
+    persist(&db, &transactions);
+    let handle = object_id("0xe1");
+    let deposit = struct_tag("0x3::coin::DepositEvent");
+    let amount_json = |amount: u64| Some(format!("{{\"value\":{{\"amount\":\"{}\"}}}}", amount));
+    db.store
+        .persist_events(vec![
+            transactions[0].event(handle, 0, deposit.clone(), 0, amount_json(500)),
+            transactions[0].event(handle, 1, deposit.clone(), 1, None),
+            transactions[1].event(handle, 2, deposit.clone(), 0, amount_json(1500)),
+        ])
+        .unwrap();
+
+    let predicate = EventJsonPredicate::new(
+        "value.amount",
+        JsonCompareOp::Gt,
+        JsonPredicateValue::Integer(1000),
+    );
+    let events = db
+        .reader
+        .events_by_type_filtered(deposit.clone(), predicate, 0..u64::MAX, 10)
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].indexer_event_id.tx_order, 1);
+
+    let invalid = EventJsonPredicate::new(
+        "value.amount'",
+        JsonCompareOp::Eq,
+        JsonPredicateValue::Integer(0),
+    );
+    assert!(matches!(
+        db.reader
+            .events_by_type_filtered(deposit, invalid, 0..u64::MAX, 10),
+        Err(IndexerError::InvalidArgumentError(_))
+    ));
+}
+
+#[test]
+fn test_objects_owned_by() {
+    let db = TestIndexerDB::new().unwrap();
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    let transactions = [
+        transaction(0, alice, 0),
+        transaction(1, alice, 1),
+        transaction(2, alice, 2),
+    ];
+    persist(&db, &transactions);
+    let nft = object_id("0xa1");
+    let coin = object_id("0xa2");
+    let nft_type = struct_tag("0x42::nft::NFT");
+    let coin_type = struct_tag("0x3::coin::CoinStore");
+
+    db.store
+        .persist_object_changes(vec![
+            transactions[0].object_change(nft, Some(nft_type.clone()), Some(alice)),
+            transactions[0].object_change(coin, Some(coin_type.clone()), Some(alice)),
+        ])
+        .unwrap();
+    assert_eq!(db.reader.objects_owned_by(alice).unwrap(), vec![nft, coin]);
+
+    // The nft is transferred to bob, then the coin store is deleted.
+    db.store
+        .persist_object_changes(vec![transactions[1].object_change(
+            nft,
+            Some(nft_type),
+            Some(bob),
+        )])
+        .unwrap();
+    db.store
+        .persist_object_changes(vec![transactions[2].object_change(coin, None, None)])
+        .unwrap();
+
+    assert!(db.reader.objects_owned_by(alice).unwrap().is_empty());
+    assert_eq!(db.reader.objects_owned_by(bob).unwrap(), vec![nft]);
+    let coin_object_type = db.reader.object_type(coin).unwrap().unwrap();
+    assert!(coin_object_type.deleted);
+    assert_eq!(coin_object_type.object_type, coin_type);
+    assert_eq!(coin_object_type.tx_order, 2);
+}
+
+#[test]
+fn test_most_active_objects() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let transactions = [
+        transaction(0, sender, 0),
+        transaction(1, sender, 1),
+        transaction(2, sender, 2),
+    ];
+    persist(&db, &transactions);
+    let object_type = struct_tag("0x42::counter::Counter");
+    let a = object_id("0xa1");
+    let b = object_id("0xa2");
+    let c = object_id("0xa3");
+    let change =
+        |tx: &TestTransaction, id| tx.object_change(id, Some(object_type.clone()), Some(sender));
+    db.store
+        .persist_object_changes(vec![
+            change(&transactions[0], a),
+            change(&transactions[1], b),
+            change(&transactions[1], a),
+            change(&transactions[2], c),
+            change(&transactions[2], b),
+            change(&transactions[2], a),
+        ])
+        .unwrap();
+
+    let reader = &db.reader;
+    assert_eq!(
+        reader
+            .most_active_objects(TransactionWindow::TxOrder(0..3), 2)
+            .unwrap(),
+        vec![(a, 3), (b, 2)]
+    );
+    // The ties are broken by the object id.
+    assert_eq!(
+        reader
+            .most_active_objects(TransactionWindow::TxOrder(1..3), 10)
+            .unwrap(),
+        vec![(a, 2), (b, 2), (c, 1)]
+    );
+    assert!(reader
+        .most_active_objects(TransactionWindow::TxOrder(3..u64::MAX), 10)
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        reader.most_active_objects(TransactionWindow::CreatedAt(0..u64::MAX), 10),
+        Err(IndexerError::NotSupportedError(_))
+    ));
+}
+
+#[test]
+fn test_window_filter_created_at_not_supported() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    persist(&db, &[transaction(0, sender, 0), transaction(1, sender, 1)]);
+
+    let reader = &db.reader;
+    let window = TransactionWindow::CreatedAt(0..u64::MAX);
+    assert!(matches!(
+        reader.gas_percentiles(window.clone()),
+        Err(IndexerError::NotSupportedError(_))
+    ));
+    assert!(matches!(
+        reader.avg_gas_by_type(window.clone()),
+        Err(IndexerError::NotSupportedError(_))
+    ));
+    assert!(matches!(
+        reader.top_senders(window, 10),
+        Err(IndexerError::NotSupportedError(_))
+    ));
+    assert_eq!(
+        reader
+            .top_senders(TransactionWindow::TxOrder(0..u64::MAX), 10)
+            .unwrap(),
+        vec![(sender, 2)]
+    );
+}
+
+#[test]
+fn test_upsert_reorged_transactions() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let reorged = transaction(0, sender, 0);
+    persist(&db, &[reorged.clone()]);
+    let handle = object_id("0xe1");
+    let deposit = struct_tag("0x3::coin::DepositEvent");
+    db.store
+        .persist_events(vec![reorged.event(handle, 0, deposit, 0, None)])
+        .unwrap();
+
+    // Another transaction is sequenced at the same tx_order, it replaces the reorged one and its events.
+    let replacement = TestTransaction::new(0, sender, 0, function_call_action(sender, "other"));
+    db.store
+        .upsert_transactions(vec![replacement.indexed()])
+        .unwrap();
+    let reader = &db.reader;
+    let indexed = reader.recent_transactions(None, 10).unwrap();
+    assert_eq!(indexed.len(), 1);
+    assert_eq!(indexed[0].tx_hash, replacement.indexed().tx_hash);
+    assert_eq!(reader.total_transactions().unwrap(), 1);
+    assert_eq!(reader.total_events().unwrap(), 0);
+    assert_eq!(reader.event_count_by_handle(handle).unwrap(), 0);
+
+    // The same transaction sequenced again at a later tx_order moves there.
+    let mut moved = replacement.clone();
+    moved.sequence_info.tx_order = 1;
+    db.store.upsert_transactions(vec![moved.indexed()]).unwrap();
+    let indexed = reader.recent_transactions(None, 10).unwrap();
+    assert_eq!(indexed.len(), 1);
+    assert_eq!(indexed[0].tx_order, 1);
+    assert_eq!(reader.total_transactions().unwrap(), 1);
+}
+
+#[test]
+fn test_validate_range() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let transactions = [
+        transaction(0, sender, 0),
+        transaction(1, sender, 1),
+        transaction(2, sender, 2),
+    ];
+    persist(&db, &transactions);
+    let handle = object_id("0xe1");
+    let deposit = struct_tag("0x3::coin::DepositEvent");
+    // The event at tx_order 3 has no indexed transaction.
+    let orphan = transaction(3, sender, 3);
+    db.store
+        .persist_events(vec![
+            transactions[0].event(handle, 0, deposit.clone(), 0, None),
+            transactions[1].event(handle, 1, deposit.clone(), 0, None),
+            orphan.event(handle, 2, deposit, 0, None),
+        ])
+        .unwrap();
+
+    let summary = db.reader.validate_range(0..3).unwrap();
+    assert!(summary.is_valid(), "{:?}", summary.failures);
+    assert_eq!(summary.transactions_ok, 3);
+    assert_eq!(summary.events_ok, 2);
+
+    // Corrupt the row at tx_order 1, it no longer matches the transaction it is rebuilt from.
+    let mut connection =
+        get_sqlite_pool_connection(&db.store.sqlite_store.connection_pool).unwrap();
+    diesel::sql_query("UPDATE transactions SET sequence_number = 100 WHERE tx_order = 1")
+        .execute(&mut connection)
+        .unwrap();
+    let summary = db.reader.validate_range(0..u64::MAX).unwrap();
+    assert!(!summary.is_valid());
+    assert_eq!(summary.transactions_ok, 2);
+    // The event of the failed transaction and the orphan event fail too.
+    assert_eq!(summary.events_ok, 1);
+    let failures = summary
+        .failures
+        .iter()
+        .map(|failure| (failure.tx_order, failure.event_index))
+        .collect::<Vec<_>>();
+    assert_eq!(failures, vec![(1, None), (1, Some(0)), (3, Some(0))]);
+}
+
+#[tokio::test]
+async fn test_stream_transactions() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let transactions = (0..5)
+        .map(|tx_order| transaction(tx_order, sender, tx_order))
+        .collect::<Vec<_>>();
+    persist(&db, &transactions);
+
+    // The chunk size does not divide the range, so the last page is partial.
+    let streamed = db
+        .reader
+        .stream_transactions(1..u64::MAX, 3)
+        .map(|transaction| transaction.unwrap().tx_order)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(streamed, vec![1, 2, 3, 4]);
+    let streamed = db
+        .reader
+        .stream_transactions(2..2, 3)
+        .collect::<Vec<_>>()
+        .await;
+    assert!(streamed.is_empty());
+}
+
+#[tokio::test]
+async fn test_stream_events_by_type() {
+    let db = TestIndexerDB::new().unwrap();
+    let sender = AccountAddress::random();
+    let transactions = (0..3)
+        .map(|tx_order| transaction(tx_order, sender, tx_order))
+        .collect::<Vec<_>>();
+    persist(&db, &transactions);
+    let handle = object_id("0xe1");
+    let deposit = struct_tag("0x3::coin::DepositEvent");
+    let withdraw = struct_tag("0x3::coin::WithdrawEvent");
+    db.store
+        .persist_events(vec![
+            transactions[0].event(handle, 0, deposit.clone(), 0, None),
+            transactions[0].event(handle, 1, withdraw, 1, None),
+            transactions[0].event(handle, 2, deposit.clone(), 2, None),
+            transactions[2].event(handle, 3, deposit.clone(), 0, None),
+        ])
+        .unwrap();
+
+    let streamed = db
+        .reader
+        .stream_events_by_type(deposit, 0..u64::MAX, 1)
+        .map(|event| {
+            let event = event.unwrap();
+            (event.tx_order, event.event_index)
+        })
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(streamed, vec![(0, 0), (0, 2), (2, 0)]);
+}
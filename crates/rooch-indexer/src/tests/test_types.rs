@@ -0,0 +1,184 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::errors::IndexerError;
+use crate::tests::{function_call_action, verified_action, TestTransaction};
+use crate::types::{
+    EventJsonPredicate, IndexedTransaction, JsonCompareOp, JsonPredicateValue,
+    TransactionModuleFilter,
+};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::vm_status::{AbortLocation, KeptVMStatus};
+use moveos_types::transaction::MoveAction;
+use rooch_types::transaction::ethereum::EthereumTransaction;
+use rooch_types::transaction::TypedTransaction;
+
+#[test]
+fn test_indexed_transaction_new() {
+    let sender = AccountAddress::random();
+    let tx = TestTransaction::new(3, sender, 7, function_call_action(sender, "counter"));
+    let indexed = IndexedTransaction::new(
+        tx.transaction.clone(),
+        tx.sequence_info.clone(),
+        tx.execution_info.clone(),
+        tx.moveos_tx.clone(),
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(indexed.tx_order, 3);
+    assert_eq!(indexed.sender, sender);
+    assert_eq!(indexed.sequence_number, 7);
+    assert!(indexed.is_success);
+    assert_eq!(indexed.abort_code, None);
+    assert!(indexed.transaction_json.is_some());
+    assert!(!indexed.transaction_json_decode_failed);
+    assert_eq!(indexed.tx_size_bytes, indexed.transaction_raw.len() as u64);
+    assert_eq!(indexed.action_size_bytes, indexed.action_raw.len() as u64);
+    // The tx order signature of the test transactions is not signed by a sequencer.
+    assert_eq!(indexed.source_node_id, None);
+}
+
+#[test]
+fn test_indexed_transaction_abort_code() {
+    let sender = AccountAddress::random();
+    let mut tx = TestTransaction::new(0, sender, 0, function_call_action(sender, "counter"));
+    tx.execution_info.status = KeptVMStatus::MoveAbort(AbortLocation::Script, 7);
+    let indexed = tx.indexed();
+
+    assert!(!indexed.is_success);
+    assert_eq!(indexed.abort_code, Some(7));
+    let status: KeptVMStatus = serde_json::from_str(indexed.status.as_str()).unwrap();
+    assert_eq!(status, KeptVMStatus::MoveAbort(AbortLocation::Script, 7));
+}
+
+#[test]
+fn test_indexed_transaction_authenticator_extract_error() {
+    let sender = AccountAddress::random();
+    let tx = TestTransaction::new(0, sender, 0, function_call_action(sender, "counter"));
+    let mut eth = EthereumTransaction::new_for_test(
+        sender.into(),
+        Default::default(),
+        Vec::<u8>::new().into(),
+    );
+    // The authenticator of an Ethereum transaction needs its chain id.
+    eth.0.chain_id = None;
+    let result = IndexedTransaction::new(
+        TypedTransaction::Ethereum(eth),
+        tx.sequence_info,
+        tx.execution_info,
+        tx.moveos_tx,
+        false,
+    );
+
+    assert!(matches!(result, Err(IndexerError::AuthenticatorExtract(_))));
+}
+
+#[test]
+fn test_indexed_transaction_decode_failed() {
+    let sender = AccountAddress::random();
+    let tx = TestTransaction::new(0, sender, 0, function_call_action(sender, "counter"));
+    // The calldata is not a MoveAction, so the transaction can not be decoded to JSON.
+    let eth =
+        EthereumTransaction::new_for_test(sender.into(), Default::default(), vec![0xffu8].into());
+    let indexed = IndexedTransaction::new(
+        TypedTransaction::Ethereum(eth),
+        tx.sequence_info,
+        tx.execution_info,
+        tx.moveos_tx,
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(indexed.transaction_json, None);
+    assert!(indexed.transaction_json_decode_failed);
+}
+
+#[test]
+fn test_event_json_predicate_to_sql() {
+    let integer = EventJsonPredicate::new(
+        "value.amount",
+        JsonCompareOp::Gt,
+        JsonPredicateValue::Integer(1000),
+    );
+    assert_eq!(
+        integer.to_sql("event_json").unwrap(),
+        "CAST(json_extract(event_json, '$.value.amount') AS INTEGER) > 1000"
+    );
+
+    let bool_value = EventJsonPredicate::new(
+        "value.paid",
+        JsonCompareOp::Eq,
+        JsonPredicateValue::Bool(true),
+    );
+    assert_eq!(
+        bool_value.to_sql("event_json").unwrap(),
+        "json_extract(event_json, '$.value.paid') = 1"
+    );
+
+    // The quotes of a text constant are escaped, so it can not close the SQL literal.
+    let text = EventJsonPredicate::new(
+        "value.name",
+        JsonCompareOp::Ne,
+        JsonPredicateValue::Text("x' OR '1'='1".to_owned()),
+    );
+    assert_eq!(
+        text.to_sql("event_json").unwrap(),
+        "json_extract(event_json, '$.value.name') != 'x'' OR ''1''=''1'"
+    );
+}
+
+#[test]
+fn test_event_json_predicate_invalid_path() {
+    for path in [
+        "",
+        "value..amount",
+        "value.",
+        "$.value",
+        "value.amount') > 0 OR json_extract(event_json, '$.value",
+        "value.amount[0]",
+    ] {
+        let predicate =
+            EventJsonPredicate::new(path, JsonCompareOp::Eq, JsonPredicateValue::Integer(0));
+        assert!(
+            matches!(
+                predicate.to_sql("event_json"),
+                Err(IndexerError::InvalidArgumentError(_))
+            ),
+            "path {:?} should be rejected",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_transaction_module_filter() {
+    let filter =
+        TransactionModuleFilter::new(&["0x1".to_owned(), "0x2::coin".to_owned()], false).unwrap();
+    let framework = AccountAddress::from_hex_literal("0x1").unwrap();
+    let other = AccountAddress::from_hex_literal("0x2").unwrap();
+
+    assert_eq!(filter.addresses, vec![framework]);
+    assert!(filter.matches(&verified_action(function_call_action(framework, "account"))));
+    assert!(filter.matches(&verified_action(function_call_action(other, "coin"))));
+    assert!(!filter.matches(&verified_action(function_call_action(other, "account"))));
+    assert!(
+        !filter.matches(&verified_action(MoveAction::new_script_call(
+            vec![],
+            vec![],
+            vec![]
+        )))
+    );
+
+    let publish = verified_action(MoveAction::new_module_bundle(vec![]));
+    assert!(!filter.matches(&publish));
+    let filter = TransactionModuleFilter::new(&[], true).unwrap();
+    assert!(filter.matches(&publish));
+}
+
+#[test]
+fn test_transaction_module_filter_invalid() {
+    assert!(TransactionModuleFilter::new(&["coin".to_owned()], false).is_err());
+    assert!(TransactionModuleFilter::new(&["0x2::".to_owned()], false).is_err());
+    assert!(TransactionModuleFilter::new(&["0x2::coin::transfer".to_owned()], false).is_err());
+}
@@ -2,17 +2,42 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::errors::IndexerError;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use fastcrypto::traits::ToFromBytes;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::SignatureToken;
+use move_binary_format::CompiledModule;
 use move_core_types::account_address::AccountAddress;
+use move_core_types::effects::Op;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::ModuleId;
 use move_core_types::language_storage::StructTag;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::vm_status::KeptVMStatus;
+use move_resource_viewer::{AnnotatedMoveStruct, MoveValueAnnotator};
 use moveos_types::h256::H256;
 use moveos_types::moveos_std::event::Event;
-use moveos_types::moveos_std::object::ObjectID;
-use moveos_types::transaction::{MoveAction, TransactionExecutionInfo, VerifiedMoveOSTransaction};
+use moveos_types::moveos_std::object::{Object, ObjectID};
+use moveos_types::state::StateChangeSet;
+use moveos_types::state::{MoveStructType, MoveType, PlaceholderStruct};
+use moveos_types::state_resolver::{MoveOSResolver, GLOBAL_OBJECT_STORAGE_HANDLE};
+use moveos_types::transaction::{
+    FunctionCall, MoveAction, TransactionExecutionInfo, VerifiedMoveAction,
+    VerifiedMoveOSTransaction,
+};
+use rooch_rpc_api::jsonrpc_types::{
+    AnnotatedMoveStructView, AnnotatedMoveValueView, TransactionView,
+};
+use rooch_types::address::RoochAddress;
+use rooch_types::crypto::Signature;
+use rooch_types::framework::auth_validator::BuiltinAuthValidator;
 use rooch_types::multichain_id::MultiChainID;
 use rooch_types::transaction::{
     AbstractTransaction, TransactionSequenceInfo, TransactionType, TypedTransaction,
 };
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use tracing::warn;
 
 pub type IndexerResult<T> = Result<T, IndexerError>;
 
@@ -48,8 +73,26 @@ pub struct IndexedTransaction {
     /// The tx order signature,
     pub tx_order_auth_validator_id: u64,
     pub tx_order_authenticator_payload: Vec<u8>,
+    /// The identity of the node which sequenced the transaction, it is the address of the tx order signer.
+    /// None if the signer can not be recognized.
+    pub source_node_id: Option<String>,
+
+    /// The decoded transaction in JSON, only present if the indexer decodes transactions.
+    pub transaction_json: Option<String>,
+    /// Whether the transaction failed to decode to JSON.
+    pub transaction_json_decode_failed: bool,
 
     pub created_at: u64,
+
+    /// The size of `transaction_raw` in bytes.
+    pub tx_size_bytes: u64,
+    /// The size of `action_raw` in bytes, the payload part of the transaction size.
+    pub action_size_bytes: u64,
+
+    /// Whether the transaction executed successfully, derived from the vm status.
+    pub is_success: bool,
+    /// The abort code if the transaction aborted in Move, derived from the vm status.
+    pub abort_code: Option<u64>,
 }
 
 impl IndexedTransaction {
@@ -58,11 +101,40 @@ impl IndexedTransaction {
         sequence_info: TransactionSequenceInfo,
         execution_info: TransactionExecutionInfo,
         moveos_tx: VerifiedMoveOSTransaction,
-    ) -> Result<Self> {
+        decode_transaction: bool,
+    ) -> IndexerResult<Self> {
         let move_action = MoveAction::from(moveos_tx.action);
-        let action_raw = move_action.encode()?;
-        let transaction_authenticator_info = transaction.authenticator_info()?;
-        let status = serde_json::to_string(&execution_info.status)?;
+        let action_raw = move_action.encode().map_err(IndexerError::ActionEncode)?;
+        let transaction_authenticator_info = transaction
+            .authenticator_info()
+            .map_err(IndexerError::AuthenticatorExtract)?;
+        let status =
+            serde_json::to_string(&execution_info.status).map_err(IndexerError::StatusSerialize)?;
+        let (is_success, abort_code) = match &execution_info.status {
+            KeptVMStatus::Executed => (true, None),
+            KeptVMStatus::MoveAbort(_, abort_code) => (false, Some(*abort_code)),
+            _ => (false, None),
+        };
+        let source_node_id = tx_order_signer(&sequence_info);
+        let (transaction_json, transaction_json_decode_failed) = if decode_transaction {
+            match decode_transaction_json(&transaction) {
+                Ok(transaction_json) => (Some(transaction_json), false),
+                Err(e) => {
+                    warn!(
+                        "Decode transaction {:?} to json failed: {:?}",
+                        transaction.tx_hash(),
+                        e
+                    );
+                    (None, true)
+                }
+            }
+        } else {
+            (None, false)
+        };
+
+        let transaction_raw = transaction.encode();
+        let tx_size_bytes = transaction_raw.len() as u64;
+        let action_size_bytes = action_raw.len() as u64;
 
         let indexed_transaction = IndexedTransaction {
             tx_hash: transaction.tx_hash(),
@@ -84,7 +156,7 @@ impl IndexedTransaction {
                 .auth_validator_id,
             authenticator_payload: transaction_authenticator_info.authenticator.payload,
             tx_accumulator_root: sequence_info.tx_accumulator_root,
-            transaction_raw: transaction.encode(),
+            transaction_raw,
 
             state_root: execution_info.state_root,
             event_root: execution_info.event_root,
@@ -96,14 +168,279 @@ impl IndexedTransaction {
             /// The tx order signature,
             tx_order_auth_validator_id: sequence_info.tx_order_signature.auth_validator_id,
             tx_order_authenticator_payload: sequence_info.tx_order_signature.payload,
+            source_node_id,
+
+            transaction_json,
+            transaction_json_decode_failed,
 
             //TODO record transaction timestamp
             created_at: 0,
+
+            tx_size_bytes,
+            action_size_bytes,
+
+            is_success,
+            abort_code,
         };
         Ok(indexed_transaction)
     }
 }
 
+/// Recover the address of the sequencer from the tx order signature.
+fn tx_order_signer(sequence_info: &TransactionSequenceInfo) -> Option<String> {
+    let tx_order_signature = &sequence_info.tx_order_signature;
+    if tx_order_signature.auth_validator_id != u64::from(BuiltinAuthValidator::Rooch.flag()) {
+        return None;
+    }
+    let signature = Signature::from_bytes(&tx_order_signature.payload).ok()?;
+    let public_key = signature.to_public_key().ok()?;
+    Some(RoochAddress::from(&public_key).to_string())
+}
+
+/// Decode the transaction to the same JSON as the RPC transaction view.
+fn decode_transaction_json(transaction: &TypedTransaction) -> Result<String> {
+    if let TypedTransaction::Ethereum(eth) = transaction {
+        // The TransactionView panics if the calldata can not be decoded, so check it first.
+        eth.decode_calldata_to_action()?;
+    }
+    Ok(serde_json::to_string(&TransactionView::from(
+        transaction.clone(),
+    ))?)
+}
+
+/// The arguments of a function call transaction, decoded with the parameter types of the function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedFunctionArgs {
+    /// One json value per argument, null if the argument can not be decoded.
+    pub args: Vec<serde_json::Value>,
+    /// True if any of the arguments can not be decoded.
+    pub decode_failed: bool,
+}
+
+impl DecodedFunctionArgs {
+    /// Decode the arguments of the function call, with the function module resolved by the `resolver`.
+    /// If the function signature can not be resolved, every argument is null.
+    pub(crate) fn decode<R: MoveOSResolver>(call: &FunctionCall, resolver: &R) -> Self {
+        let arg_types = match function_arg_types(call, resolver) {
+            Ok(arg_types) => arg_types,
+            Err(e) => {
+                warn!(
+                    "Resolve the argument types of function {:?} failed: {:?}",
+                    call.function_id, e
+                );
+                return Self {
+                    args: vec![serde_json::Value::Null; call.args.len()],
+                    decode_failed: !call.args.is_empty(),
+                };
+            }
+        };
+        let annotator = MoveValueAnnotator::new(resolver);
+        let mut decode_failed = false;
+        let args = call
+            .args
+            .iter()
+            .zip(arg_types.iter())
+            .map(|(arg, arg_type)| {
+                let decoded = annotator.view_value(arg_type, arg).and_then(|value| {
+                    Ok(serde_json::to_value(AnnotatedMoveValueView::from(value))?)
+                });
+                decoded.unwrap_or_else(|e| {
+                    warn!("Decode argument of type {} failed: {:?}", arg_type, e);
+                    decode_failed = true;
+                    serde_json::Value::Null
+                })
+            })
+            .collect();
+        Self {
+            args,
+            decode_failed,
+        }
+    }
+}
+
+/// The types of the arguments of the function call.
+/// The leading signer and Context parameters are not passed as arguments, so the arguments
+/// match the trailing parameters. The Object parameters are passed as ObjectID.
+fn function_arg_types<R: MoveOSResolver>(
+    call: &FunctionCall,
+    resolver: &R,
+) -> Result<Vec<TypeTag>> {
+    let module_id = &call.function_id.module_id;
+    let module_bytes = match resolver.get_module(module_id)? {
+        Some(module_bytes) => module_bytes,
+        None => bail!("Module {} not found", module_id),
+    };
+    let module = CompiledModule::deserialize(&module_bytes)?;
+    let function_handle = module
+        .function_defs()
+        .iter()
+        .map(|def| module.function_handle_at(def.function))
+        .find(|handle| {
+            module.identifier_at(handle.name) == call.function_id.function_name.as_ident_str()
+        })
+        .ok_or_else(|| anyhow::anyhow!("Function {:?} not found", call.function_id))?;
+    let params = &module.signature_at(function_handle.parameters).0;
+    if params.len() < call.args.len() {
+        bail!(
+            "Function {:?} has {} parameters, but {} arguments are passed",
+            call.function_id,
+            params.len(),
+            call.args.len()
+        );
+    }
+    params[params.len() - call.args.len()..]
+        .iter()
+        .map(|param| {
+            let type_tag = signature_token_to_type_tag(&module, param, &call.ty_args)?;
+            Ok(match &type_tag {
+                TypeTag::Struct(struct_tag)
+                    if Object::<PlaceholderStruct>::struct_tag_match_without_type_param(
+                        struct_tag,
+                    ) =>
+                {
+                    ObjectID::type_tag()
+                }
+                _ => type_tag,
+            })
+        })
+        .collect()
+}
+
+/// Convert the signature token to the type tag, the references are converted to the referenced type.
+fn signature_token_to_type_tag(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    ty_args: &[TypeTag],
+) -> Result<TypeTag> {
+    let struct_tag = |handle_idx, type_params: Vec<TypeTag>| {
+        let handle = module.struct_handle_at(handle_idx);
+        let module_handle = module.module_handle_at(handle.module);
+        TypeTag::Struct(Box::new(StructTag {
+            address: *module.address_identifier_at(module_handle.address),
+            module: module.identifier_at(module_handle.name).to_owned(),
+            name: module.identifier_at(handle.name).to_owned(),
+            type_params,
+        }))
+    };
+    Ok(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U16 => TypeTag::U16,
+        SignatureToken::U32 => TypeTag::U32,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::U256 => TypeTag::U256,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Signer => TypeTag::Signer,
+        SignatureToken::Vector(element) => TypeTag::Vector(Box::new(signature_token_to_type_tag(
+            module, element, ty_args,
+        )?)),
+        SignatureToken::Struct(handle_idx) => struct_tag(*handle_idx, vec![]),
+        SignatureToken::StructInstantiation(handle_idx, type_params) => struct_tag(
+            *handle_idx,
+            type_params
+                .iter()
+                .map(|type_param| signature_token_to_type_tag(module, type_param, ty_args))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner) => {
+            signature_token_to_type_tag(module, inner, ty_args)?
+        }
+        SignatureToken::TypeParameter(idx) => match ty_args.get(*idx as usize) {
+            Some(ty_arg) => ty_arg.clone(),
+            None => bail!("Type argument {} is missing", idx),
+        },
+    })
+}
+
+/// A row which fails the validation of `validate_range`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationFailure {
+    pub tx_order: u64,
+    /// The event index if the row is an event, None if it is a transaction.
+    pub event_index: Option<u64>,
+    pub error: String,
+}
+
+/// The summary of a validation run over a `tx_order` range, nothing is written by the validation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationSummary {
+    pub transactions_ok: u64,
+    pub events_ok: u64,
+    /// The rows which fail, in ascending order of the tx order and then the event index.
+    pub failures: Vec<ValidationFailure>,
+}
+
+impl ValidationSummary {
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// The gas used by the transactions in a window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasSummary {
+    /// The number of transactions in the window.
+    pub tx_count: u64,
+    /// The total gas used by the transactions in the window.
+    pub total_gas_used: u64,
+}
+
+/// The percentiles of the gas used by the transactions in a window, all zero if the window is empty.
+/// A percentile is the nearest-rank value, the smallest gas used which at least the percent of the transactions do not exceed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasPercentiles {
+    /// The number of transactions in the window.
+    pub tx_count: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+/// The gas used by the transactions created in a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyGasSummary {
+    /// The number of days since the unix epoch.
+    pub day: u64,
+    pub summary: GasSummary,
+}
+
+/// The granularity of the time buckets of an aggregate query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBucket {
+    Hour,
+    Day,
+}
+
+impl TimeBucket {
+    /// The length of the bucket in milliseconds, the unit of the transaction `created_at`.
+    pub fn milliseconds(&self) -> u64 {
+        match self {
+            TimeBucket::Hour => 60 * 60 * 1000,
+            TimeBucket::Day => 24 * 60 * 60 * 1000,
+        }
+    }
+}
+
+/// The window of the transactions of an aggregate query, the ranges are half-open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionWindow {
+    /// The transactions whose `tx_order` is in the range.
+    TxOrder(Range<u64>),
+    /// The transactions whose `created_at` (in milliseconds) is in the range.
+    /// Not supported until the transaction timestamp is recorded.
+    CreatedAt(Range<u64>),
+}
+
+/// The number of transactions of a type created in a time bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionTypeCount {
+    /// The number of buckets since the unix epoch.
+    pub bucket: u64,
+    pub transaction_type: TransactionType,
+    pub tx_count: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct IndexedEvent {
     /// event handle id
@@ -125,11 +462,15 @@ pub struct IndexedEvent {
     pub sender: AccountAddress,
 
     pub created_at: u64,
+    /// The decoded event data in JSON, used by the JSON predicate queries.
+    pub event_json: Option<String>,
 }
 
 impl IndexedEvent {
+    /// The `event_json` is the decoded event data, see `encode_event_json`.
     pub fn new(
         event: Event,
+        event_json: Option<String>,
         transaction: TypedTransaction,
         sequence_info: TransactionSequenceInfo,
         moveos_tx: VerifiedMoveOSTransaction,
@@ -147,6 +488,197 @@ impl IndexedEvent {
 
             //TODO record transaction timestamp
             created_at: 0,
+            event_json,
+        }
+    }
+}
+
+/// Encode the decoded event data to the same JSON as the RPC event view, the fields are under `value`.
+pub fn encode_event_json(decoded_event_data: AnnotatedMoveStruct) -> Option<String> {
+    serde_json::to_string(&AnnotatedMoveStructView::from(decoded_event_data))
+        .map_err(|e| warn!("Encode event data to json failed: {:?}", e))
+        .ok()
+}
+
+/// A change of an object in a transaction, only the type of the object is indexed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedObjectChange {
+    pub object_id: ObjectID,
+    /// the struct tag of the object value, None if the object is deleted
+    pub object_type: Option<StructTag>,
+    /// the owner of the object, None if the object is deleted
+    pub owner: Option<AccountAddress>,
+    /// the tx order of the transaction which changed the object
+    pub tx_order: u64,
+}
+
+impl IndexedObjectChange {
+    /// Extract the object changes from the changes of the global object storage.
+    pub fn from_state_change_set(state_change_set: &StateChangeSet, tx_order: u64) -> Vec<Self> {
+        let table_change = match state_change_set.changes.get(&GLOBAL_OBJECT_STORAGE_HANDLE) {
+            Some(table_change) => table_change,
+            None => return vec![],
+        };
+        table_change
+            .entries
+            .iter()
+            .filter_map(|(key, op)| {
+                let object_id = ObjectID::from_bytes(key).ok()?;
+                let (object_type, owner) = match op {
+                    Op::New(state) | Op::Modify(state) => {
+                        let raw_object = state.as_raw_object().ok()?;
+                        (Some(raw_object.value.struct_tag), Some(raw_object.owner))
+                    }
+                    Op::Delete => (None, None),
+                };
+                Some(IndexedObjectChange {
+                    object_id,
+                    object_type,
+                    owner,
+                    tx_order,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The latest known type of an object, a deleted object keeps its last known type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedObjectType {
+    pub object_id: ObjectID,
+    /// the struct tag of the object value
+    pub object_type: StructTag,
+    /// whether the object is deleted
+    pub deleted: bool,
+    /// the tx order of the transaction which last changed the object
+    pub tx_order: u64,
+}
+
+/// The allowlist of the modules whose function calls are indexed, the other transactions are skipped
+/// with their events and object changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionModuleFilter {
+    /// Every module at these addresses matches.
+    pub addresses: Vec<AccountAddress>,
+    pub modules: Vec<ModuleId>,
+    /// Whether the module publishing transactions are indexed.
+    pub include_publish: bool,
+}
+
+impl TransactionModuleFilter {
+    /// Parse the filter from `<address>` or `<address>::<module>` strings.
+    pub fn new(modules: &[String], include_publish: bool) -> Result<Self> {
+        let mut filter = Self {
+            include_publish,
+            ..Default::default()
+        };
+        for module in modules {
+            match module.split_once("::") {
+                Some((address, name)) => filter.modules.push(ModuleId::new(
+                    AccountAddress::from_hex_literal(address)?,
+                    Identifier::new(name)?,
+                )),
+                None => filter
+                    .addresses
+                    .push(AccountAddress::from_hex_literal(module)?),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Whether the transaction is indexed, a script call never matches as it has no target module.
+    pub fn matches(&self, action: &VerifiedMoveAction) -> bool {
+        match action {
+            VerifiedMoveAction::Function { call } => {
+                let module_id = &call.function_id.module_id;
+                self.addresses.contains(module_id.address()) || self.modules.contains(module_id)
+            }
+            VerifiedMoveAction::ModuleBundle { .. } => self.include_publish,
+            VerifiedMoveAction::Script { .. } => false,
+        }
+    }
+}
+
+/// The comparison operator of an `EventJsonPredicate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonCompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl JsonCompareOp {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            JsonCompareOp::Eq => "=",
+            JsonCompareOp::Ne => "!=",
+            JsonCompareOp::Lt => "<",
+            JsonCompareOp::Le => "<=",
+            JsonCompareOp::Gt => ">",
+            JsonCompareOp::Ge => ">=",
+        }
+    }
+}
+
+/// Quote `value` as an SQL string literal, the quotes in it are escaped.
+pub(crate) fn sql_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// The constant an `EventJsonPredicate` compares the field with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonPredicateValue {
+    /// Compare the field as an integer, the Move integers are decoded as strings in JSON.
+    Integer(i64),
+    Text(String),
+    Bool(bool),
+}
+
+/// A predicate on a field of the decoded event JSON, such as `amount > 1000`.
+/// Only a field path and a constant are accepted, so no arbitrary expression is evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventJsonPredicate {
+    /// The dot separated field names, such as `value.amount`.
+    pub path: String,
+    pub op: JsonCompareOp,
+    pub value: JsonPredicateValue,
+}
+
+impl EventJsonPredicate {
+    pub fn new(path: impl Into<String>, op: JsonCompareOp, value: JsonPredicateValue) -> Self {
+        Self {
+            path: path.into(),
+            op,
+            value,
+        }
+    }
+
+    /// Returns the SQL condition on the JSON `column`.
+    /// The path must be dot separated identifiers, the text constant is quoted.
+    pub fn to_sql(&self, column: &str) -> IndexerResult<String> {
+        let valid_path = !self.path.is_empty()
+            && self.path.split('.').all(|field| {
+                !field.is_empty() && field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+        if !valid_path {
+            return Err(IndexerError::InvalidArgumentError(format!(
+                "Invalid event JSON path: {}",
+                self.path
+            )));
         }
+        let field = format!("json_extract({}, '$.{}')", column, self.path);
+        let op = self.op.as_sql();
+        Ok(match &self.value {
+            JsonPredicateValue::Integer(value) => {
+                format!("CAST({} AS INTEGER) {} {}", field, op, value)
+            }
+            JsonPredicateValue::Text(value) => {
+                format!("{} {} {}", field, op, sql_quote(value))
+            }
+            JsonPredicateValue::Bool(value) => format!("{} {} {}", field, op, *value as u8),
+        })
     }
 }
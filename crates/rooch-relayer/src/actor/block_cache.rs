@@ -0,0 +1,85 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small on-disk cache of the recently processed Ethereum blocks, so a restarted relayer keeps
+//! detecting the reorgs of the recent blocks without fetching them again.
+
+use anyhow::Result;
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::warn;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BlockCacheFile {
+    chain_id: u64,
+    /// The processed block hashes, in ascending order of the block number.
+    blocks: Vec<(u64, H256)>,
+}
+
+/// The cache keeps the hashes of the latest `capacity` processed blocks of a chain in a JSON file.
+pub struct BlockCache {
+    path: PathBuf,
+    chain_id: u64,
+    capacity: usize,
+}
+
+impl BlockCache {
+    pub fn new(path: PathBuf, chain_id: u64, capacity: usize) -> Self {
+        Self {
+            path,
+            chain_id,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Returns the cached block hashes by block number, empty if the cache does not exist yet,
+    /// is corrupt or belongs to another chain, then the blocks are processed again from the chain.
+    pub fn load(&self) -> BTreeMap<u64, H256> {
+        if !self.path.exists() {
+            return BTreeMap::new();
+        }
+        let cache = std::fs::read(&self.path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<BlockCacheFile>(&bytes)?));
+        match cache {
+            Ok(cache) if cache.chain_id == self.chain_id => cache.blocks.into_iter().collect(),
+            Ok(cache) => {
+                warn!(
+                    "EthereumRelayer block cache {:?} is of chain id {}, expected {}, ignore it",
+                    self.path, cache.chain_id, self.chain_id
+                );
+                BTreeMap::new()
+            }
+            Err(e) => {
+                warn!(
+                    "EthereumRelayer block cache {:?} is corrupt, ignore it: {:?}",
+                    self.path, e
+                );
+                BTreeMap::new()
+            }
+        }
+    }
+
+    /// Persist the latest `capacity` blocks. The cache is written to a temporary file and renamed,
+    /// so a crash never leaves a truncated cache.
+    pub fn save(&self, blocks: &BTreeMap<u64, H256>) -> Result<()> {
+        let skip = blocks.len().saturating_sub(self.capacity);
+        let cache = BlockCacheFile {
+            chain_id: self.chain_id,
+            blocks: blocks
+                .iter()
+                .skip(skip)
+                .map(|(number, hash)| (*number, *hash))
+                .collect(),
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec(&cache)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
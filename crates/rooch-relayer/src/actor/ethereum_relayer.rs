@@ -1,35 +1,780 @@
 // Copyright (c) RoochNetwork
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::Relayer;
+use crate::actor::block_cache::BlockCache;
+use crate::actor::receipts_root::receipts_root;
+use crate::{Relayer, RelayerStatus};
 use anyhow::Result;
 use async_trait::async_trait;
 use ethers::prelude::*;
+use moveos_types::move_types::FunctionId;
 use moveos_types::transaction::FunctionCall;
+use rooch_config::relayer_config::RelayerConfig;
 use rooch_types::framework::ethereum_light_client::{BlockHeader, EthereumLightClientModule};
-use std::collections::BTreeMap;
-use tracing::info;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
 
-pub struct EthereumRelayer {
+/// Re-verify the chain id of the RPC endpoint every this many relays.
+const CHAIN_ID_CHECK_INTERVAL: u64 = 10;
+
+/// The consecutive failures after which an RPC endpoint is skipped.
+pub const DEFAULT_ENDPOINT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long a repeatedly failing RPC endpoint is skipped, in seconds.
+pub const DEFAULT_ENDPOINT_COOLDOWN_SECS: u64 = 60;
+
+/// The confirmations used when the RPC does not support the `finalized` block tag,
+/// about two epochs, the usual finalization delay of the beacon chain.
+pub const DEFAULT_FALLBACK_CONFIRMATIONS: u64 = 64;
+
+/// Which block the relayer fetches on each relay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EthereumBlockMode {
+    /// The latest block, which may be reorganized.
+    Latest,
+    /// The block finalized by the consensus layer, which can not be reorganized.
+    /// If the RPC does not support the `finalized` block tag, the block `fallback_confirmations` behind the latest is used.
+    Finalized { fallback_confirmations: u64 },
+}
+
+/// When to temporarily skip a repeatedly failing RPC endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// The endpoint is skipped after this many consecutive failures.
+    pub failure_threshold: u32,
+    /// How long the endpoint is skipped, it is tried again after the cooldown.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_ENDPOINT_FAILURE_THRESHOLD,
+            cooldown: Duration::from_secs(DEFAULT_ENDPOINT_COOLDOWN_SECS),
+        }
+    }
+}
+
+/// The result of probing one RPC capability the relayer needs on an endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapabilityCheck {
+    pub endpoint: String,
+    /// The RPC method or feature, such as `eth_getLogs` or the `finalized` block tag.
+    pub capability: &'static str,
+    pub supported: bool,
+    pub details: String,
+}
+
+/// The capabilities of the RPC endpoints, as probed by `EthereumRelayer::self_test`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CapabilityReport {
+    pub checks: Vec<CapabilityCheck>,
+}
+
+impl CapabilityReport {
+    /// Returns true if every endpoint supports every capability the relayer needs.
+    pub fn is_supported(&self) -> bool {
+        self.checks.iter().all(|check| check.supported)
+    }
+
+    fn record(&mut self, endpoint: &str, capability: &'static str, result: Result<String>) {
+        let (supported, details) = match result {
+            Ok(details) => (true, details),
+            Err(e) => (false, e.to_string()),
+        };
+        self.checks.push(CapabilityCheck {
+            endpoint: endpoint.to_string(),
+            capability,
+            supported,
+            details,
+        });
+    }
+}
+
+struct EthereumEndpoint {
+    url: String,
     rpc_client: Provider<Http>,
+    /// Whether the endpoint's chain id has been checked against the expected chain id.
+    chain_id_verified: bool,
+    /// Set when the endpoint returns an unexpected chain id, the endpoint is never used again.
+    chain_id_mismatch: Option<String>,
+    consecutive_failures: u32,
+    /// The endpoint is skipped until this instant.
+    open_until: Option<Instant>,
+}
+
+impl EthereumEndpoint {
+    fn is_available(&self, now: Instant) -> bool {
+        self.chain_id_mismatch.is_none()
+            && !matches!(self.open_until, Some(open_until) if now < open_until)
+    }
+
+    fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.open_until = None;
+    }
+
+    fn record_failure(&mut self, circuit_breaker: &CircuitBreakerConfig) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= circuit_breaker.failure_threshold {
+            warn!(
+                "EthereumRelayer endpoint {} failed {} times in a row, skip it for {:?}",
+                self.url, self.consecutive_failures, circuit_breaker.cooldown
+            );
+            self.open_until = Some(Instant::now() + circuit_breaker.cooldown);
+        }
+    }
+}
+
+pub struct EthereumRelayer {
+    /// The RPC endpoints, tried in order on each relay.
+    endpoints: Vec<EthereumEndpoint>,
+    /// The index of the endpoint which served the last relay.
+    active_endpoint: usize,
+    circuit_breaker: CircuitBreakerConfig,
     processed_blocks: BTreeMap<H256, Block<H256>>,
+    /// The hash of the processed block at each block number, used to detect reorgs.
+    processed_block_numbers: BTreeMap<u64, H256>,
+    /// Trusted block hashes, keyed by block number.
+    checkpoints: BTreeMap<u64, H256>,
+    /// The relayed calls waiting for the submission result in order, with the block number of a block submission.
+    pending_submissions: VecDeque<Option<u64>>,
+    status: RelayerStatus,
+    /// The chain id all the RPC endpoints must keep returning.
+    expected_chain_id: u64,
+    relay_count: u64,
+    /// Only the blocks in `[start_block, end_block]` are relayed, None means unbounded.
+    start_block: Option<u64>,
+    end_block: Option<u64>,
+    /// Set once the chain passes the end block.
+    complete: bool,
+    block_mode: EthereumBlockMode,
+    /// Verify the receipts of each block against its receipts root before relaying it.
+    verify_receipts_root: bool,
+    /// Send a heartbeat when nothing has been submitted for this long, None means disabled.
+    heartbeat_interval: Option<Duration>,
+    /// The time of the last submitted block or heartbeat.
+    last_activity: Instant,
+    /// Track the log indexes of each processed block, and notify the light client when a reorg removes them.
+    notify_removed_logs: bool,
+    /// The log indexes of each processed block, keyed by the block hash.
+    processed_block_logs: BTreeMap<H256, Vec<u64>>,
+    /// The calls waiting to be relayed in order, with the block number of a block submission.
+    queued_calls: VecDeque<(FunctionCall, Option<u64>)>,
+    /// The functions which receive each block, in order. The result of the first one counts for the status.
+    destinations: Vec<FunctionId>,
+    /// Persist the processed block hashes, None keeps them in memory only.
+    block_cache: Option<BlockCache>,
+    /// A block fetch slower than this counts as a failure of the endpoint, None means never.
+    slow_fetch_threshold: Option<Duration>,
 }
 
 impl EthereumRelayer {
-    pub fn new(eth_rpc_url: &str) -> Result<Self> {
-        let rpc_client = Provider::<Http>::try_from(eth_rpc_url)?;
+    /// Create the relayer with the RPC endpoints, tried in order on each relay and failed over on error.
+    /// If `eth_chain_id` is configured, every endpoint's chain id must match it, otherwise the chain id
+    /// returned by the first reachable endpoint is expected. An endpoint unreachable at startup is
+    /// verified before its first use.
+    /// Only the blocks in `[eth_start_block, eth_end_block]` are relayed, None means unbounded.
+    /// Each block is submitted to every function of `eth_destinations` in order, or to the light client if it is empty.
+    pub async fn new(
+        eth_rpc_urls: &[String],
+        relayer_config: &RelayerConfig,
+        block_mode: EthereumBlockMode,
+        circuit_breaker: CircuitBreakerConfig,
+    ) -> Result<Self> {
+        if eth_rpc_urls.is_empty() {
+            anyhow::bail!("At least one Ethereum RPC endpoint is required");
+        }
+        let mut expected_chain_id = relayer_config.eth_chain_id;
+        let mut endpoints = Vec::with_capacity(eth_rpc_urls.len());
+        for eth_rpc_url in eth_rpc_urls {
+            let rpc_client = Provider::<Http>::try_from(eth_rpc_url.as_str())?;
+            let chain_id_verified = match rpc_client.get_chainid().await {
+                Ok(chain_id) => {
+                    let chain_id = chain_id.as_u64();
+                    let expected = *expected_chain_id.get_or_insert(chain_id);
+                    if chain_id != expected {
+                        anyhow::bail!(
+                            "The Ethereum RPC {} chain id {} does not match the expected chain id {}",
+                            eth_rpc_url,
+                            chain_id,
+                            expected
+                        );
+                    }
+                    true
+                }
+                Err(e) => {
+                    warn!(
+                        "EthereumRelayer endpoint {} is unreachable at startup, verify it before use: {:?}",
+                        eth_rpc_url, e
+                    );
+                    false
+                }
+            };
+            endpoints.push(EthereumEndpoint {
+                url: eth_rpc_url.clone(),
+                rpc_client,
+                chain_id_verified,
+                chain_id_mismatch: None,
+                consecutive_failures: 0,
+                open_until: None,
+            });
+        }
+        let expected_chain_id = match expected_chain_id {
+            Some(chain_id) if endpoints.iter().any(|endpoint| endpoint.chain_id_verified) => {
+                chain_id
+            }
+            _ => anyhow::bail!("None of the Ethereum RPC endpoints is reachable"),
+        };
+        info!(
+            "EthereumRelayer connected to chain id {} with {} endpoints",
+            expected_chain_id,
+            endpoints.len()
+        );
+        let checkpoints = relayer_config
+            .eth_checkpoints
+            .iter()
+            .map(|checkpoint| (checkpoint.number, H256::from(checkpoint.hash.0)))
+            .collect();
+        let destinations = if relayer_config.eth_destinations.is_empty() {
+            vec![EthereumLightClientModule::function_id(
+                EthereumLightClientModule::SUBMIT_NEW_BLOCK_ENTRY_FUNCTION_NAME,
+            )]
+        } else {
+            relayer_config.eth_destinations.clone()
+        };
+        // The reorgs are detected within the confirmation depth, so the cache keeps as many blocks.
+        let confirmation_depth = match block_mode {
+            EthereumBlockMode::Finalized {
+                fallback_confirmations,
+            } => fallback_confirmations,
+            EthereumBlockMode::Latest => relayer_config
+                .eth_fallback_confirmations
+                .unwrap_or(DEFAULT_FALLBACK_CONFIRMATIONS),
+        };
+        let block_cache = relayer_config
+            .eth_block_cache_path
+            .clone()
+            .map(|path| BlockCache::new(path, expected_chain_id, confirmation_depth as usize));
+        let processed_block_numbers = block_cache
+            .as_ref()
+            .map(|block_cache| block_cache.load())
+            .unwrap_or_default();
+        // Only the hashes are cached, the reloaded blocks carry no other field.
+        let processed_blocks = processed_block_numbers
+            .iter()
+            .map(|(number, hash)| {
+                let block = Block {
+                    hash: Some(*hash),
+                    number: Some((*number).into()),
+                    ..Default::default()
+                };
+                (*hash, block)
+            })
+            .collect();
+        if !processed_block_numbers.is_empty() {
+            info!(
+                "EthereumRelayer reloaded {} processed blocks from the block cache",
+                processed_block_numbers.len()
+            );
+        }
         Ok(Self {
-            rpc_client,
+            endpoints,
+            active_endpoint: 0,
+            circuit_breaker,
             //TODO load processed block from Move state
-            processed_blocks: BTreeMap::new(),
+            processed_blocks,
+            processed_block_numbers,
+            checkpoints,
+            pending_submissions: VecDeque::new(),
+            status: RelayerStatus::default(),
+            expected_chain_id,
+            relay_count: 0,
+            start_block: relayer_config.eth_start_block,
+            end_block: relayer_config.eth_end_block,
+            complete: false,
+            block_mode,
+            verify_receipts_root: relayer_config.eth_verify_receipts_root,
+            heartbeat_interval: relayer_config
+                .eth_heartbeat_interval_secs
+                .map(Duration::from_secs),
+            last_activity: Instant::now(),
+            notify_removed_logs: relayer_config.eth_notify_removed_logs,
+            processed_block_logs: BTreeMap::new(),
+            queued_calls: VecDeque::new(),
+            destinations,
+            block_cache,
+            slow_fetch_threshold: relayer_config
+                .eth_slow_fetch_threshold_ms
+                .map(Duration::from_millis),
         })
     }
 
-    async fn relay_ethereum(&mut self) -> Result<Option<FunctionCall>> {
-        let block = self
+    /// Probe every RPC endpoint for the methods the relayer needs, without changing any state:
+    /// `eth_chainId`, `eth_getBlockByNumber` with all the block header fields, `eth_getLogs` with the log
+    /// indexes, the `finalized` block tag in the finalized block mode, and `eth_getTransactionReceipt`
+    /// if the receipts root is verified. A capability which depends on the latest block is reported as
+    /// unsupported if the block can not be fetched.
+    pub async fn self_test(&self) -> Result<CapabilityReport> {
+        let mut report = CapabilityReport::default();
+        for endpoint in &self.endpoints {
+            let url = endpoint.url.as_str();
+            let rpc_client = &endpoint.rpc_client;
+            let chain_id = rpc_client
+                .get_chainid()
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|chain_id| {
+                    let chain_id = chain_id.as_u64();
+                    if chain_id != self.expected_chain_id {
+                        anyhow::bail!(
+                            "chain id {} does not match the expected chain id {}",
+                            chain_id,
+                            self.expected_chain_id
+                        );
+                    }
+                    Ok(format!("chain id {}", chain_id))
+                });
+            report.record(url, "eth_chainId", chain_id);
+
+            let latest_block = rpc_client
+                .get_block(BlockId::Number(BlockNumber::Latest))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|block| block.ok_or_else(|| anyhow::format_err!("no latest block")));
+            let block_fields = latest_block
+                .as_ref()
+                .map_err(clone_error)
+                .and_then(|block| {
+                    let missing_fields = missing_block_fields(block);
+                    if !missing_fields.is_empty() {
+                        anyhow::bail!("missing block fields: {}", missing_fields.join(", "));
+                    }
+                    Ok(format!(
+                        "all the block header fields are present in block {:?}",
+                        block.number
+                    ))
+                });
+            report.record(url, "eth_getBlockByNumber", block_fields);
+
+            let logs = match &latest_block {
+                Ok(block) => match block.hash {
+                    Some(block_hash) => Self::probe_logs(rpc_client, block_hash).await,
+                    None => Err(anyhow::format_err!("the latest block has no hash")),
+                },
+                Err(e) => Err(clone_error(e)),
+            };
+            report.record(url, "eth_getLogs", logs);
+
+            if let EthereumBlockMode::Finalized { .. } = self.block_mode {
+                let finalized = rpc_client
+                    .get_block(BlockId::Number(BlockNumber::Finalized))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|block| match block {
+                        Some(block) => Ok(format!("finalized block {:?}", block.number)),
+                        None => Err(anyhow::format_err!("no finalized block")),
+                    });
+                report.record(url, "finalized block tag", finalized);
+            }
+
+            if self.verify_receipts_root {
+                let receipts = match &latest_block {
+                    Ok(block) => Self::probe_receipts(rpc_client, block).await,
+                    Err(e) => Err(clone_error(e)),
+                };
+                report.record(url, "eth_getTransactionReceipt", receipts);
+            }
+        }
+        for check in &report.checks {
+            if check.supported {
+                info!(
+                    "EthereumRelayer self test endpoint {} supports {}: {}",
+                    check.endpoint, check.capability, check.details
+                );
+            } else {
+                warn!(
+                    "EthereumRelayer self test endpoint {} does not support {}: {}",
+                    check.endpoint, check.capability, check.details
+                );
+            }
+        }
+        Ok(report)
+    }
+
+    async fn probe_logs(rpc_client: &Provider<Http>, block_hash: H256) -> Result<String> {
+        let logs = rpc_client
+            .get_logs(&Filter::new().at_block_hash(block_hash))
+            .await?;
+        if logs.iter().any(|log| log.log_index.is_none()) {
+            anyhow::bail!("the logs of block {:?} have no log index", block_hash);
+        }
+        Ok(format!("{} logs in block {:?}", logs.len(), block_hash))
+    }
+
+    async fn probe_receipts(rpc_client: &Provider<Http>, block: &Block<H256>) -> Result<String> {
+        let tx_hash = match block.transactions.first() {
+            Some(tx_hash) => *tx_hash,
+            None => anyhow::bail!("the latest block has no transaction to probe"),
+        };
+        match rpc_client.get_transaction_receipt(tx_hash).await? {
+            Some(_) => Ok(format!("receipt of transaction {:?}", tx_hash)),
+            None => anyhow::bail!("the receipt of transaction {:?} is not found", tx_hash),
+        }
+    }
+
+    /// Verify the block hash against the trusted checkpoint at the same height, if any.
+    fn verify_checkpoint(&self, block_number: u64, block_hash: H256) -> Result<()> {
+        if let Some(expected_hash) = self.checkpoints.get(&block_number) {
+            if *expected_hash != block_hash {
+                error!(
+                    "EthereumRelayer checkpoint mismatch at block {}, expected hash: {:?}, actual hash: {:?}",
+                    block_number, expected_hash, block_hash
+                );
+                anyhow::bail!(
+                    "The block {} hash {:?} does not match the trusted checkpoint {:?}",
+                    block_number,
+                    block_hash,
+                    expected_hash
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// If a different block was processed at the same number, the chain has been reorganized.
+    /// Forget the orphaned blocks at and above that number, and return their numbers and hashes for rollback.
+    fn detect_reorg(&mut self, block_number: u64, block_hash: H256) -> BTreeMap<u64, H256> {
+        match self.processed_block_numbers.get(&block_number) {
+            Some(processed_hash) if *processed_hash != block_hash => {}
+            _ => return BTreeMap::new(),
+        }
+        let orphaned_blocks = self.processed_block_numbers.split_off(&block_number);
+        for orphaned_hash in orphaned_blocks.values() {
+            self.processed_blocks.remove(orphaned_hash);
+        }
+        warn!(
+            "EthereumRelayer detected reorg at block {}, new hash: {:?}, orphaned blocks: {:?}",
+            block_number, block_hash, orphaned_blocks
+        );
+        orphaned_blocks
+    }
+
+    /// Queue the rollback of each orphaned block in the light client, from the highest block number down,
+    /// so the blocks which replace them are not ignored as repeated block numbers. An orphaned block
+    /// with logs is rolled back by the notification of its removed logs.
+    fn queue_rollbacks(&mut self, orphaned_blocks: &BTreeMap<u64, H256>) {
+        for (block_number, block_hash) in orphaned_blocks.iter().rev() {
+            let log_indexes = self
+                .processed_block_logs
+                .remove(block_hash)
+                .unwrap_or_default();
+            let call = if log_indexes.is_empty() {
+                info!(
+                    "EthereumRelayer roll back orphaned block {}, hash: {:?}",
+                    block_number, block_hash
+                );
+                EthereumLightClientModule::create_rollback_block_call(
+                    *block_number,
+                    block_hash.as_bytes().to_vec(),
+                )
+            } else {
+                info!(
+                    "EthereumRelayer notify {} removed logs of orphaned block {}, hash: {:?}",
+                    log_indexes.len(),
+                    block_number,
+                    block_hash
+                );
+                EthereumLightClientModule::create_remove_logs_call(
+                    *block_number,
+                    block_hash.as_bytes().to_vec(),
+                    log_indexes,
+                )
+            };
+            self.queued_calls.push_back((call, None));
+        }
+    }
+
+    /// Fetch the log indexes of the block from the active endpoint.
+    async fn fetch_block_log_indexes(&self, block_hash: H256) -> Result<Vec<u64>> {
+        let endpoint = &self.endpoints[self.active_endpoint];
+        let logs = endpoint
             .rpc_client
-            .get_block(BlockId::Number(BlockNumber::Latest))
+            .get_logs(&Filter::new().at_block_hash(block_hash))
             .await?;
+        logs.into_iter()
+            .map(|log| {
+                log.log_index
+                    .map(|log_index| log_index.as_u64())
+                    .ok_or_else(|| {
+                        anyhow::format_err!("The log of block {:?} has no log index", block_hash)
+                    })
+            })
+            .collect()
+    }
+
+    /// Take all the queued calls in order, and wait for their submission results.
+    /// The results still pending from the last batch are dropped, their submission was aborted.
+    fn take_queued_calls(&mut self) -> Vec<FunctionCall> {
+        self.pending_submissions.clear();
+        self.queued_calls
+            .drain(..)
+            .map(|(call, block_number)| {
+                self.pending_submissions.push_back(block_number);
+                call
+            })
+            .collect()
+    }
+
+    /// Record whether the light client accepted the submitted block.
+    pub fn record_submission_result(&mut self, block_number: u64, accepted: bool) {
+        if accepted {
+            self.status.accepted_blocks += 1;
+        } else {
+            self.status.rejected_blocks += 1;
+            self.status.last_rejected_block = Some(block_number);
+            warn!(
+                "EthereumRelayer block {} is rejected by the light client, status: {:?}",
+                block_number, self.status
+            );
+        }
+    }
+
+    /// Verify the endpoint returns the expected chain id, before its first use and every
+    /// `CHAIN_ID_CHECK_INTERVAL` relays. A mismatch disables the endpoint, such as after it
+    /// fails over to a different network.
+    async fn verify_chain_id(&mut self, index: usize, periodic_check: bool) -> Result<()> {
+        let endpoint = &mut self.endpoints[index];
+        if endpoint.chain_id_verified && !periodic_check {
+            return Ok(());
+        }
+        let chain_id = endpoint.rpc_client.get_chainid().await?.as_u64();
+        if chain_id != self.expected_chain_id {
+            let mismatch = format!(
+                "the Ethereum RPC {} chain id changed to {}, expected {}",
+                endpoint.url, chain_id, self.expected_chain_id
+            );
+            error!("EthereumRelayer {}, stop using the endpoint", mismatch);
+            endpoint.chain_id_mismatch = Some(mismatch.clone());
+            anyhow::bail!("EthereumRelayer endpoint is disabled: {}", mismatch);
+        }
+        endpoint.chain_id_verified = true;
+        Ok(())
+    }
+
+    /// Returns false if the block is outside the relaying range, and marks the relayer complete
+    /// once the chain passes the end block.
+    fn in_block_range(&mut self, block_number: u64) -> bool {
+        if matches!(self.end_block, Some(end_block) if block_number > end_block) {
+            info!(
+                "EthereumRelayer passed the end block {:?} at block {}, relaying is complete",
+                self.end_block, block_number
+            );
+            self.complete = true;
+            return false;
+        }
+        if matches!(self.start_block, Some(start_block) if block_number < start_block) {
+            info!(
+                "EthereumRelayer skip block {} before the start block {:?}",
+                block_number, self.start_block
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Fetch the block to relay from the endpoints in order, skipping the endpoints whose circuit
+    /// breaker is open. A failed endpoint is recorded and the next one is tried, a successful
+    /// fetch resets the endpoint's breaker.
+    async fn fetch_block_with_failover(&mut self) -> Result<Option<Block<H256>>> {
+        let halted = self
+            .endpoints
+            .iter()
+            .all(|endpoint| endpoint.chain_id_mismatch.is_some());
+        if halted {
+            anyhow::bail!(
+                "EthereumRelayer is halted: all the Ethereum RPC endpoints return an unexpected chain id"
+            );
+        }
+        self.relay_count += 1;
+        let periodic_check = self.relay_count % CHAIN_ID_CHECK_INTERVAL == 0;
+        let now = Instant::now();
+        let mut last_error = None;
+        for index in 0..self.endpoints.len() {
+            if !self.endpoints[index].is_available(now) {
+                continue;
+            }
+            let result = match self.verify_chain_id(index, periodic_check).await {
+                Ok(()) => {
+                    let started_at = Instant::now();
+                    Self::fetch_block(&self.endpoints[index].rpc_client, self.block_mode)
+                        .await
+                        .map(|block| (block, started_at.elapsed()))
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok((block, latency)) => {
+                    self.record_fetch_latency(index, latency);
+                    if index != self.active_endpoint {
+                        info!(
+                            "EthereumRelayer switch to endpoint {}",
+                            self.endpoints[index].url
+                        );
+                        self.active_endpoint = index;
+                    }
+                    return Ok(block);
+                }
+                Err(e) => {
+                    warn!(
+                        "EthereumRelayer endpoint {} failed: {:?}",
+                        self.endpoints[index].url, e
+                    );
+                    self.endpoints[index].record_failure(&self.circuit_breaker);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| anyhow::format_err!("No Ethereum RPC endpoint is available")))
+    }
+
+    /// Record the latency of a successful block fetch in the status. A fetch slower than the slow fetch
+    /// threshold counts as a failure of the endpoint, so a degrading endpoint trips its circuit breaker.
+    fn record_fetch_latency(&mut self, index: usize, latency: Duration) {
+        self.status.block_fetches += 1;
+        self.status.total_block_fetch_latency += latency;
+        self.status.last_block_fetch_latency = Some(latency);
+        match self.slow_fetch_threshold {
+            Some(threshold) if latency > threshold => {
+                warn!(
+                    "EthereumRelayer endpoint {} fetched the block in {:?}, slower than {:?}",
+                    self.endpoints[index].url, latency, threshold
+                );
+                self.endpoints[index].record_failure(&self.circuit_breaker);
+            }
+            _ => self.endpoints[index].record_success(),
+        }
+    }
+
+    /// Fetch the block to relay according to the block mode.
+    async fn fetch_block(
+        rpc_client: &Provider<Http>,
+        block_mode: EthereumBlockMode,
+    ) -> Result<Option<Block<H256>>> {
+        let fallback_confirmations = match block_mode {
+            EthereumBlockMode::Latest => {
+                return Ok(rpc_client
+                    .get_block(BlockId::Number(BlockNumber::Latest))
+                    .await?)
+            }
+            EthereumBlockMode::Finalized {
+                fallback_confirmations,
+            } => fallback_confirmations,
+        };
+        match rpc_client
+            .get_block(BlockId::Number(BlockNumber::Finalized))
+            .await
+        {
+            Ok(Some(block)) => return Ok(Some(block)),
+            Ok(None) => warn!(
+                "EthereumRelayer the RPC returned no finalized block, fall back to {} confirmations",
+                fallback_confirmations
+            ),
+            Err(e) => warn!(
+                "EthereumRelayer the RPC does not support the finalized block tag, fall back to {} confirmations: {:?}",
+                fallback_confirmations, e
+            ),
+        }
+        let latest_number = rpc_client.get_block_number().await?.as_u64();
+        match latest_number.checked_sub(fallback_confirmations) {
+            Some(number) => Ok(rpc_client
+                .get_block(BlockId::Number(BlockNumber::Number(number.into())))
+                .await?),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the receipts of the block in the transaction index order, with one `eth_getBlockReceipts`
+    /// call if the endpoint supports it, otherwise with one `eth_getTransactionReceipt` call per transaction.
+    /// The block receipts are fetched by number, so they are only used if they belong to the same block.
+    async fn fetch_receipts(
+        rpc_client: &Provider<Http>,
+        block: &Block<H256>,
+    ) -> Result<Vec<TransactionReceipt>> {
+        if let Some(number) = block.number {
+            match rpc_client.get_block_receipts(number).await {
+                Ok(receipts)
+                    if receipts.len() == block.transactions.len()
+                        && receipts.iter().zip(&block.transactions).all(|(receipt, tx_hash)| {
+                            receipt.transaction_hash == *tx_hash
+                                && receipt.block_hash == block.hash
+                        }) =>
+                {
+                    return Ok(receipts)
+                }
+                Ok(_) => warn!(
+                    "The block receipts of block {:?} do not match its transactions, fetch the receipts one by one",
+                    block.hash
+                ),
+                Err(e) => info!(
+                    "Fetch the block receipts of block {:?} failed, fetch the receipts one by one: {:?}",
+                    block.hash, e
+                ),
+            }
+        }
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        for tx_hash in &block.transactions {
+            let receipt = rpc_client
+                .get_transaction_receipt(*tx_hash)
+                .await?
+                .ok_or_else(|| {
+                    anyhow::format_err!("The receipt of transaction {:?} is not found", tx_hash)
+                })?;
+            receipts.push(receipt);
+        }
+        Ok(receipts)
+    }
+
+    /// Fetch the receipts of the block from the active endpoint, and verify the receipts trie root
+    /// matches the block's `receipts_root`. A mismatch means the endpoint is lying or buggy, it is
+    /// recorded as a failure of the endpoint.
+    async fn verify_receipts_root(&mut self, block: &Block<H256>) -> Result<()> {
+        let endpoint = &self.endpoints[self.active_endpoint];
+        let receipts = Self::fetch_receipts(&endpoint.rpc_client, block).await?;
+        let computed_root = receipts_root(&receipts)?;
+        if computed_root != block.receipts_root {
+            let url = endpoint.url.clone();
+            self.endpoints[self.active_endpoint].record_failure(&self.circuit_breaker);
+            anyhow::bail!(
+                "The receipts root {:?} computed from the receipts of endpoint {} does not match the receipts root {:?} of block {:?}",
+                computed_root,
+                url,
+                block.receipts_root,
+                block.hash
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the heartbeat call if nothing has been submitted for the heartbeat interval.
+    /// A submitted block also counts as activity, so the heartbeats only fill the quiet periods.
+    fn heartbeat_call(&mut self) -> Option<FunctionCall> {
+        let heartbeat_interval = self.heartbeat_interval?;
+        if self.complete || self.last_activity.elapsed() < heartbeat_interval {
+            return None;
+        }
+        info!("EthereumRelayer has no new block, send heartbeat");
+        self.last_activity = Instant::now();
+        Some(EthereumLightClientModule::create_heartbeat_call())
+    }
+
+    async fn relay_ethereum(&mut self) -> Result<Vec<FunctionCall>> {
+        if self.complete {
+            return Ok(vec![]);
+        }
+        let block = self.fetch_block_with_failover().await?;
         match block {
             Some(block) => {
                 let block_hash = block
@@ -37,28 +782,131 @@ impl EthereumRelayer {
                     .ok_or_else(|| anyhow::format_err!("The block is a pending block"))?;
                 if self.processed_blocks.contains_key(&block_hash) {
                     info!("The block {} has already been processed", block_hash);
-                    return Ok(None);
+                    return Ok(vec![]);
                 }
                 let block_header = BlockHeader::try_from(&block)?;
-                let call = EthereumLightClientModule::create_submit_new_block_call(&block_header);
+                if !self.in_block_range(block_header.number) {
+                    return Ok(vec![]);
+                }
+                self.verify_checkpoint(block_header.number, block_hash)?;
+                if self.verify_receipts_root {
+                    self.verify_receipts_root(&block).await?;
+                }
+                let block_log_indexes = if self.notify_removed_logs {
+                    Some(self.fetch_block_log_indexes(block_hash).await?)
+                } else {
+                    None
+                };
+                let orphaned_blocks = self.detect_reorg(block_header.number, block_hash);
+                // The orphaned blocks are rolled back before the block which replaces them.
+                self.queue_rollbacks(&orphaned_blocks);
+                let calls = self
+                    .destinations
+                    .iter()
+                    .map(|function_id| {
+                        EthereumLightClientModule::create_block_header_call(
+                            function_id.clone(),
+                            &block_header,
+                        )
+                    })
+                    .collect::<Vec<_>>();
                 info!(
                     "EthereumRelayer process block, hash: {}, number: {}, timestamp: {}",
                     block_hash, block_header.number, block_header.timestamp
                 );
                 self.processed_blocks.insert(block_hash, block);
-                Ok(Some(call))
+                self.processed_block_numbers
+                    .insert(block_header.number, block_hash);
+                if let Some(block_cache) = &self.block_cache {
+                    if let Err(e) = block_cache.save(&self.processed_block_numbers) {
+                        warn!("EthereumRelayer save the block cache failed: {:?}", e);
+                    }
+                }
+                if let Some(block_log_indexes) = block_log_indexes {
+                    self.processed_block_logs
+                        .insert(block_hash, block_log_indexes);
+                }
+                for (index, call) in calls.into_iter().enumerate() {
+                    let block_number = (index == 0).then_some(block_header.number);
+                    self.queued_calls.push_back((call, block_number));
+                }
+                Ok(self.take_queued_calls())
             }
             None => {
                 info!("The RPC returned no block");
-                Ok(None)
+                Ok(vec![])
             }
         }
     }
 }
 
+/// The block fields the light client needs which are missing in the block response.
+fn missing_block_fields(block: &Block<H256>) -> Vec<&'static str> {
+    let mut missing_fields = vec![];
+    if block.hash.is_none() {
+        missing_fields.push("hash");
+    }
+    if block.number.is_none() {
+        missing_fields.push("number");
+    }
+    if block.author.is_none() {
+        missing_fields.push("miner");
+    }
+    if block.logs_bloom.is_none() {
+        missing_fields.push("logsBloom");
+    }
+    missing_fields
+}
+
+/// The probes which depend on a failed probe fail with its error.
+fn clone_error(e: &anyhow::Error) -> anyhow::Error {
+    anyhow::format_err!("{}", e)
+}
+
 #[async_trait]
 impl Relayer for EthereumRelayer {
-    async fn relay(&mut self) -> Result<Option<FunctionCall>> {
-        self.relay_ethereum().await
+    async fn relay(&mut self) -> Result<Vec<FunctionCall>> {
+        let mut calls = self.relay_ethereum().await?;
+        if calls.is_empty() && !self.queued_calls.is_empty() {
+            // The calls given back by an aborted submission are relayed again, even without a new block.
+            calls = self.take_queued_calls();
+        }
+        if !calls.is_empty() {
+            self.last_activity = Instant::now();
+            return Ok(calls);
+        }
+        Ok(self.heartbeat_call().into_iter().collect())
+    }
+
+    fn on_submission_result(&mut self, accepted: bool) {
+        if let Some(Some(block_number)) = self.pending_submissions.pop_front() {
+            self.record_submission_result(block_number, accepted);
+        }
+    }
+
+    fn on_submission_aborted(&mut self, calls: Vec<FunctionCall>) {
+        // The unsent calls are the last ones of the batch, so their block numbers are the last pending ones.
+        let unsent = self.pending_submissions.len().saturating_sub(calls.len());
+        let block_numbers = self.pending_submissions.split_off(unsent);
+        warn!(
+            "EthereumRelayer requeue {} unsent calls of the aborted submission",
+            calls.len()
+        );
+        let mut block_numbers = block_numbers.into_iter();
+        let requeued = calls
+            .into_iter()
+            .map(|call| (call, block_numbers.next().flatten()))
+            .collect::<Vec<_>>();
+        for requeued_call in requeued.into_iter().rev() {
+            self.queued_calls.push_front(requeued_call);
+        }
+    }
+
+    fn status(&self) -> RelayerStatus {
+        self.status.clone()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
     }
 }
@@ -0,0 +1,106 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use moveos_types::gas_config::GasConfig;
+use rooch_config::relayer_config::RelayerConfig;
+
+/// The max gas amount of a stuck submission is raised by this percent on each retry.
+pub const DEFAULT_FEE_BUMP_PERCENT: u64 = 150;
+/// The max number of retries of a submission which runs out of gas.
+pub const MAX_FEE_BUMPS: usize = 3;
+
+/// How the relayer sets the max gas amount of the Rooch transaction wrapping each relayed call.
+/// An underpriced submission runs out of gas and stalls the relay, an overpriced one locks more gas than needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeStrategy {
+    /// Always the same max gas amount, a submission which runs out of gas is not retried.
+    Fixed(u64),
+    /// The estimate times `percent` / 100, the default max gas amount until an estimate is known.
+    MultiplierOfEstimate { percent: u64 },
+    /// As `MultiplierOfEstimate`, but never more than `cap`, including the bumps.
+    Capped { percent: u64, cap: u64 },
+}
+
+impl Default for FeeStrategy {
+    fn default() -> Self {
+        FeeStrategy::Fixed(GasConfig::DEFAULT_MAX_GAS_AMOUNT)
+    }
+}
+
+impl TryFrom<&RelayerConfig> for FeeStrategy {
+    type Error = anyhow::Error;
+
+    /// The strategy configured by the `relayer_fee_*` options, the default if none is set.
+    fn try_from(relayer_config: &RelayerConfig) -> Result<Self> {
+        match (
+            relayer_config.relayer_fee_max_gas_amount,
+            relayer_config.relayer_fee_estimate_percent,
+            relayer_config.relayer_fee_max_gas_cap,
+        ) {
+            (None, None, None) => Ok(FeeStrategy::default()),
+            (Some(max_gas_amount), None, None) => Ok(FeeStrategy::Fixed(max_gas_amount)),
+            (None, Some(percent), None) => Ok(FeeStrategy::MultiplierOfEstimate { percent }),
+            (None, Some(percent), Some(cap)) => Ok(FeeStrategy::Capped { percent, cap }),
+            (Some(_), Some(_), _) => anyhow::bail!(
+                "The relayer fee max gas amount and estimate percent can not be set together"
+            ),
+            (_, None, Some(_)) => {
+                anyhow::bail!("The relayer fee max gas cap requires the estimate percent")
+            }
+        }
+    }
+}
+
+/// Applies the fee strategy, with the gas used by the last executed submission as the estimate.
+#[derive(Debug, Clone)]
+pub struct FeeEstimator {
+    strategy: FeeStrategy,
+    estimate: Option<u64>,
+}
+
+impl FeeEstimator {
+    pub fn new(strategy: FeeStrategy) -> Self {
+        Self {
+            strategy,
+            estimate: None,
+        }
+    }
+
+    /// The max gas amount of the next submission.
+    pub fn max_gas_amount(&self) -> u64 {
+        let estimated = |percent: u64| {
+            self.estimate
+                .map(|estimate| scale(estimate, percent))
+                .unwrap_or(GasConfig::DEFAULT_MAX_GAS_AMOUNT)
+        };
+        match self.strategy {
+            FeeStrategy::Fixed(max_gas_amount) => max_gas_amount,
+            FeeStrategy::MultiplierOfEstimate { percent } => estimated(percent),
+            FeeStrategy::Capped { percent, cap } => estimated(percent).min(cap),
+        }
+    }
+
+    /// Record the gas used by an executed submission as the estimate of the next submissions.
+    pub fn record_gas_used(&mut self, gas_used: u64) {
+        self.estimate = Some(gas_used);
+    }
+
+    /// Returns the max gas amount to retry a submission which ran out of `max_gas_amount`,
+    /// None if the strategy can not raise it. The exhausted amount becomes the new estimate,
+    /// so the next submissions start from it.
+    pub fn bump(&mut self, max_gas_amount: u64) -> Option<u64> {
+        let bumped = scale(max_gas_amount, DEFAULT_FEE_BUMP_PERCENT);
+        let bumped = match self.strategy {
+            FeeStrategy::Fixed(_) => return None,
+            FeeStrategy::MultiplierOfEstimate { .. } => bumped,
+            FeeStrategy::Capped { cap, .. } => bumped.min(cap),
+        };
+        self.estimate = Some(self.estimate.unwrap_or_default().max(max_gas_amount));
+        (bumped > max_gas_amount).then_some(bumped)
+    }
+}
+
+fn scale(value: u64, percent: u64) -> u64 {
+    (value as u128 * percent as u128 / 100).min(u64::MAX as u128) as u64
+}
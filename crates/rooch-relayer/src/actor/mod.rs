@@ -1,6 +1,9 @@
 // Copyright (c) RoochNetwork
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod block_cache;
 pub mod ethereum_relayer;
+pub mod fee_strategy;
 pub mod messages;
+pub mod receipts_root;
 pub mod relayer;
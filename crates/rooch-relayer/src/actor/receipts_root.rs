@@ -0,0 +1,279 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compute the receipts root of an Ethereum block, the root of the Merkle Patricia trie
+//! which maps the RLP encoded transaction index to the consensus encoded receipt.
+
+use anyhow::Result;
+use ethers::types::{TransactionReceipt, H256};
+use ethers::utils::keccak256;
+use ethers::utils::rlp::RlpStream;
+
+/// The consensus encoding of a receipt: `rlp([status_or_root, cumulative_gas_used, logs_bloom, logs])`,
+/// prefixed with the transaction type byte for the typed (EIP-2718) receipts.
+pub fn encode_receipt(receipt: &TransactionReceipt) -> Result<Vec<u8>> {
+    let mut stream = RlpStream::new_list(4);
+    match (receipt.status, receipt.root) {
+        (Some(status), _) => stream.append(&status.as_u64()),
+        // The receipts before Byzantium carry the intermediate state root instead of the status.
+        (None, Some(root)) => stream.append(&root),
+        (None, None) => anyhow::bail!(
+            "The receipt of transaction {:?} has neither status nor root",
+            receipt.transaction_hash
+        ),
+    };
+    stream.append(&receipt.cumulative_gas_used);
+    stream.append(&receipt.logs_bloom);
+    stream.begin_list(receipt.logs.len());
+    for log in &receipt.logs {
+        stream.begin_list(3);
+        stream.append(&log.address);
+        stream.append_list::<H256, H256>(&log.topics);
+        stream.append(&log.data.to_vec());
+    }
+    let mut encoded = match receipt.transaction_type {
+        Some(tx_type) if !tx_type.is_zero() => vec![tx_type.as_u64() as u8],
+        _ => vec![],
+    };
+    encoded.extend_from_slice(&stream.out());
+    Ok(encoded)
+}
+
+/// The receipts root of the receipts, which must be in the transaction index order of the block.
+pub fn receipts_root(receipts: &[TransactionReceipt]) -> Result<H256> {
+    let mut items = Vec::with_capacity(receipts.len());
+    for (index, receipt) in receipts.iter().enumerate() {
+        let mut key = RlpStream::new();
+        key.append(&(index as u64));
+        items.push((key.out().to_vec(), encode_receipt(receipt)?));
+    }
+    Ok(trie_root(items))
+}
+
+/// The root of the Merkle Patricia trie of the key value pairs, the keys must be distinct.
+fn trie_root(items: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+    let mut items = items
+        .into_iter()
+        .map(|(key, value)| (to_nibbles(&key), value))
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    H256::from(keccak256(encode_node(&items, 0)))
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// The hex prefix encoding of a partial path, flagged as a leaf or an extension.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+/// Append a reference to the child node: the node itself if its encoding is shorter than 32 bytes,
+/// otherwise its hash.
+fn append_child(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&H256::from(keccak256(node)));
+    }
+}
+
+/// Encode the trie node of the items, whose keys share the first `depth` nibbles.
+/// The items are sorted by the key.
+fn encode_node(items: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+    match items {
+        [] => {
+            let mut stream = RlpStream::new();
+            stream.append_empty_data();
+            stream.out().to_vec()
+        }
+        [(key, value)] => {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&hex_prefix(&key[depth..], true));
+            stream.append(value);
+            stream.out().to_vec()
+        }
+        _ => {
+            // The keys are sorted, so the common prefix of all is the common prefix of the first and the last.
+            let (first, last) = (&items[0].0, &items[items.len() - 1].0);
+            let common = first[depth..]
+                .iter()
+                .zip(&last[depth..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if common > 0 {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(&first[depth..depth + common], false));
+                append_child(&mut stream, &encode_node(items, depth + common));
+                return stream.out().to_vec();
+            }
+            let mut stream = RlpStream::new_list(17);
+            let mut branch_value = None;
+            let mut start = 0;
+            if items[0].0.len() == depth {
+                branch_value = Some(&items[0].1);
+                start = 1;
+            }
+            for nibble in 0..16u8 {
+                let end = start
+                    + items[start..]
+                        .iter()
+                        .take_while(|(key, _)| key[depth] == nibble)
+                        .count();
+                if start == end {
+                    stream.append_empty_data();
+                } else {
+                    append_child(&mut stream, &encode_node(&items[start..end], depth + 1));
+                }
+                start = end;
+            }
+            match branch_value {
+                Some(value) => stream.append(value),
+                None => stream.append_empty_data(),
+            };
+            stream.out().to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Bloom, Log, U256, U64};
+    use ethers::utils::rlp::Rlp;
+    use std::str::FromStr;
+
+    fn receipt(transaction_type: Option<u64>, cumulative_gas_used: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            status: Some(U64::from(1)),
+            cumulative_gas_used: U256::from(cumulative_gas_used),
+            logs_bloom: Bloom::repeat_byte(0x01),
+            logs: vec![Log {
+                address: Default::default(),
+                topics: vec![H256::repeat_byte(0x02)],
+                data: vec![0x03].into(),
+                ..Default::default()
+            }],
+            transaction_type: transaction_type.map(U64::from),
+            ..Default::default()
+        }
+    }
+
+    /// The trie leaf of the remaining nibbles of a key, referenced by its hash as it is 32 bytes or more.
+    fn hashed_leaf(nibbles: &[u8], value: &[u8]) -> H256 {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(nibbles, true));
+        stream.append(&value.to_vec());
+        H256::from(keccak256(stream.out()))
+    }
+
+    #[test]
+    fn test_empty_receipts_root() {
+        assert_eq!(
+            receipts_root(&[]).unwrap(),
+            H256::from_str("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trie_root() {
+        // The trie of the go-ethereum `TestInsert`, with an extension and a branch holding a value.
+        let items = [
+            ("doe", "reindeer"),
+            ("dog", "puppy"),
+            ("dogglesworth", "cat"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes().to_vec()))
+        .collect();
+        assert_eq!(
+            trie_root(items),
+            H256::from_str("0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encode_pre_byzantium_receipt() {
+        let root = H256::repeat_byte(0x11);
+        let mut receipt = receipt(None, 21000);
+        receipt.status = None;
+        receipt.root = Some(root);
+        let encoded = encode_receipt(&receipt).unwrap();
+        let rlp = Rlp::new(&encoded);
+        assert_eq!(rlp.item_count().unwrap(), 4);
+        assert_eq!(rlp.val_at::<H256>(0).unwrap(), root);
+        assert_eq!(rlp.val_at::<U256>(1).unwrap(), U256::from(21000));
+
+        receipt.root = None;
+        assert!(encode_receipt(&receipt).is_err());
+    }
+
+    #[test]
+    fn test_legacy_and_eip1559_receipts_root() {
+        let legacy = receipt(None, 21000);
+        let eip1559 = receipt(Some(2), 42000);
+        let legacy_encoded = encode_receipt(&legacy).unwrap();
+        let eip1559_encoded = encode_receipt(&eip1559).unwrap();
+        // A typed receipt is its legacy encoding prefixed with the type, the legacy type 0 has no prefix.
+        assert_eq!(
+            legacy_encoded,
+            encode_receipt(&receipt(Some(0), 21000)).unwrap()
+        );
+        assert_eq!(eip1559_encoded[0], 2);
+        assert_eq!(
+            eip1559_encoded[1..],
+            encode_receipt(&receipt(None, 42000)).unwrap()[..]
+        );
+
+        // The keys `rlp(0) = 0x80` and `rlp(1) = 0x01` split at the first nibble, under a branch.
+        let mut branch = RlpStream::new_list(17);
+        for nibble in 0..16 {
+            match nibble {
+                0 => branch.append(&hashed_leaf(&[1], &eip1559_encoded)),
+                8 => branch.append(&hashed_leaf(&[0], &legacy_encoded)),
+                _ => branch.append_empty_data(),
+            };
+        }
+        branch.append_empty_data();
+        assert_eq!(
+            receipts_root(&[legacy, eip1559]).unwrap(),
+            H256::from(keccak256(branch.out()))
+        );
+    }
+
+    #[test]
+    fn test_receipts_root_over_128_receipts() {
+        let receipts = (0..200)
+            .map(|index| receipt(Some(index % 3), 21000 * (index + 1)))
+            .collect::<Vec<_>>();
+        // From the index 128 the RLP encoded key takes two bytes, `rlp(128) = 0x8180`.
+        let items = receipts
+            .iter()
+            .enumerate()
+            .map(|(index, receipt)| {
+                let key = match index {
+                    0 => vec![0x80],
+                    1..=127 => vec![index as u8],
+                    _ => vec![0x81, index as u8],
+                };
+                (key, encode_receipt(receipt).unwrap())
+            })
+            .collect();
+        let root = receipts_root(&receipts).unwrap();
+        assert_eq!(root, trie_root(items));
+        assert_ne!(root, receipts_root(&receipts[..128]).unwrap());
+    }
+}
@@ -1,13 +1,18 @@
 // Copyright (c) RoochNetwork
 // SPDX-License-Identifier: Apache-2.0
 
-use super::ethereum_relayer::EthereumRelayer;
+use super::ethereum_relayer::{
+    CircuitBreakerConfig, EthereumBlockMode, EthereumRelayer, DEFAULT_ENDPOINT_COOLDOWN_SECS,
+    DEFAULT_ENDPOINT_FAILURE_THRESHOLD, DEFAULT_FALLBACK_CONFIRMATIONS,
+};
+use super::fee_strategy::{FeeEstimator, FeeStrategy, MAX_FEE_BUMPS};
 use super::messages::RelayTick;
 use crate::{Relayer, TxSubmiter};
 use anyhow::Result;
 use async_trait::async_trait;
 use coerce::actor::{context::ActorContext, message::Handler, Actor};
-use moveos_types::{gas_config::GasConfig, transaction::MoveAction};
+use moveos_types::transaction::{FunctionCall, MoveAction};
+use rooch_config::relayer_config::RelayerConfig;
 use rooch_rpc_api::jsonrpc_types::KeptVMStatusView;
 use rooch_rpc_client::ClientBuilder;
 use rooch_types::{
@@ -15,12 +20,13 @@ use rooch_types::{
     crypto::RoochKeyPair,
     transaction::{rooch::RoochTransactionData, AbstractTransaction},
 };
+use std::time::Duration;
 use tracing::{info, warn};
 
 pub struct RelayerActor {
     chain_id: u64,
     relayer_address: RoochAddress,
-    max_gas_amount: u64,
+    fee_estimator: FeeEstimator,
     relayer_key: RoochKeyPair,
     tx_submiter: Box<dyn TxSubmiter>,
     relayers: Vec<Box<dyn Relayer>>,
@@ -31,74 +37,167 @@ impl RelayerActor {
     pub async fn new_for_client(
         relayer_key: RoochKeyPair,
         eth_rpc_url: &str,
+        relayer_config: &RelayerConfig,
         rooch_rpc_url: &str,
+        fee_strategy: FeeStrategy,
     ) -> Result<Self> {
         let rooch_rpc_client = ClientBuilder::default().build(rooch_rpc_url).await?;
-        Self::new(relayer_key, eth_rpc_url, rooch_rpc_client).await
+        Self::new(
+            relayer_key,
+            eth_rpc_url,
+            relayer_config,
+            rooch_rpc_client,
+            fee_strategy,
+        )
+        .await
     }
 
+    /// The `fee_strategy` sets the max gas amount of the transaction wrapping each relayed call.
     pub async fn new<T: TxSubmiter + 'static>(
         relayer_key: RoochKeyPair,
         eth_rpc_url: &str,
+        relayer_config: &RelayerConfig,
         tx_submiter: T,
+        fee_strategy: FeeStrategy,
     ) -> Result<Self> {
         let chain_id = tx_submiter.get_chain_id().await?;
         let relayer_address = relayer_key.public().address();
-        let eth_relayer = EthereumRelayer::new(eth_rpc_url)?;
+        let block_mode = if relayer_config.eth_finalized_only {
+            EthereumBlockMode::Finalized {
+                fallback_confirmations: relayer_config
+                    .eth_fallback_confirmations
+                    .unwrap_or(DEFAULT_FALLBACK_CONFIRMATIONS),
+            }
+        } else {
+            EthereumBlockMode::Latest
+        };
+        let circuit_breaker = CircuitBreakerConfig {
+            failure_threshold: relayer_config
+                .eth_endpoint_failure_threshold
+                .unwrap_or(DEFAULT_ENDPOINT_FAILURE_THRESHOLD),
+            cooldown: Duration::from_secs(
+                relayer_config
+                    .eth_endpoint_cooldown_secs
+                    .unwrap_or(DEFAULT_ENDPOINT_COOLDOWN_SECS),
+            ),
+        };
+        let eth_rpc_urls = std::iter::once(eth_rpc_url.to_string())
+            .chain(relayer_config.eth_backup_rpc_urls.iter().cloned())
+            .collect::<Vec<_>>();
+        let eth_relayer =
+            EthereumRelayer::new(&eth_rpc_urls, relayer_config, block_mode, circuit_breaker)
+                .await?;
         let relayers: Vec<Box<dyn Relayer>> = vec![Box::new(eth_relayer)];
         Ok(Self {
             chain_id,
             relayer_address,
-            max_gas_amount: GasConfig::DEFAULT_MAX_GAS_AMOUNT,
+            fee_estimator: FeeEstimator::new(fee_strategy),
             relayer_key,
             relayers,
             tx_submiter: Box::new(tx_submiter),
         })
     }
 
-    async fn tick(&mut self) -> Result<()> {
-        for relayer in &mut self.relayers {
-            let relayer_name = relayer.name();
-            match relayer.relay().await {
-                Ok(Some(function_call)) => {
-                    let sequence_number = self
-                        .tx_submiter
-                        .get_sequence_number(self.relayer_address)
-                        .await?;
-                    let action = MoveAction::Function(function_call);
-                    let tx_data = RoochTransactionData::new(
-                        self.relayer_address,
-                        sequence_number,
-                        self.chain_id,
-                        self.max_gas_amount,
-                        action,
+    /// Submit the call in its own transaction, raising the max gas amount on out of gas as the fee
+    /// strategy allows. Returns whether the transaction is executed, or the error if it could not be submitted.
+    async fn submit_call(
+        &mut self,
+        relayer_name: &str,
+        function_call: FunctionCall,
+    ) -> Result<bool> {
+        let action = MoveAction::Function(function_call);
+        let mut max_gas_amount = self.fee_estimator.max_gas_amount();
+        let mut bumps = 0;
+        loop {
+            let sequence_number = self
+                .tx_submiter
+                .get_sequence_number(self.relayer_address)
+                .await?;
+            let tx_data = RoochTransactionData::new(
+                self.relayer_address,
+                sequence_number,
+                self.chain_id,
+                max_gas_amount,
+                action.clone(),
+            );
+            let tx = tx_data.sign(&self.relayer_key);
+            let tx_hash = tx.tx_hash();
+            let result = self.tx_submiter.submit_tx(tx).await?;
+            match result.execution_info.status {
+                KeptVMStatusView::Executed => {
+                    info!(
+                        "Relayer {} execute relay tx({}) success",
+                        relayer_name, tx_hash
                     );
-                    let tx = tx_data.sign(&self.relayer_key);
-                    let tx_hash = tx.tx_hash();
-                    let result = self.tx_submiter.submit_tx(tx).await?;
-                    match result.execution_info.status {
-                        KeptVMStatusView::Executed => {
-                            info!(
-                                "Relayer {} execute relay tx({}) success",
-                                relayer_name, tx_hash
-                            );
-                        }
-                        _ => {
-                            warn!(
-                                "Relayer {} execute relay tx({}) failed, status: {:?}",
-                                relayer_name, tx_hash, result.execution_info.status
-                            );
-                        }
+                    self.fee_estimator
+                        .record_gas_used(result.execution_info.gas_used);
+                    return Ok(true);
+                }
+                KeptVMStatusView::OutOfGas if bumps < MAX_FEE_BUMPS => {
+                    if let Some(bumped) = self.fee_estimator.bump(max_gas_amount) {
+                        warn!(
+                            "Relayer {} relay tx({}) ran out of gas {}, retry with max gas amount {}",
+                            relayer_name, tx_hash, max_gas_amount, bumped
+                        );
+                        max_gas_amount = bumped;
+                        bumps += 1;
+                        continue;
                     }
+                    warn!(
+                        "Relayer {} relay tx({}) ran out of gas {}, the fee strategy can not raise it",
+                        relayer_name, tx_hash, max_gas_amount
+                    );
+                    return Ok(false);
+                }
+                _ => {
+                    warn!(
+                        "Relayer {} execute relay tx({}) failed, status: {:?}",
+                        relayer_name, tx_hash, result.execution_info.status
+                    );
+                    return Ok(false);
                 }
-                Ok(None) => {
-                    //skip
+            }
+        }
+    }
+
+    async fn tick(&mut self) -> Result<()> {
+        for index in 0..self.relayers.len() {
+            let relayer_name = self.relayers[index].name();
+            match self.relayers[index].relay().await {
+                Ok(function_calls) => {
+                    // Submit the calls in order, each in its own transaction.
+                    let mut function_calls = function_calls.into_iter();
+                    while let Some(function_call) = function_calls.next() {
+                        match self.submit_call(relayer_name, function_call.clone()).await {
+                            Ok(accepted) => self.relayers[index].on_submission_result(accepted),
+                            Err(err) => {
+                                // The failed call and the rest of the batch are given back to be relayed again.
+                                let unsent = std::iter::once(function_call)
+                                    .chain(function_calls)
+                                    .collect::<Vec<_>>();
+                                warn!(
+                                    "Relayer {} submit relay tx failed, {} calls are not submitted: {:?}",
+                                    relayer_name,
+                                    unsent.len(),
+                                    err
+                                );
+                                self.relayers[index].on_submission_aborted(unsent);
+                                break;
+                            }
+                        }
+                    }
                 }
                 Err(err) => {
                     warn!("Relayer {} error: {:?}", relayer_name, err);
                 }
             }
         }
+        self.relayers.retain(|relayer| {
+            if relayer.is_complete() {
+                info!("Relayer {} is complete, stop relaying", relayer.name());
+            }
+            !relayer.is_complete()
+        });
         Ok(())
     }
 }
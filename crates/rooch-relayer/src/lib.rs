@@ -7,6 +7,7 @@ use moveos_types::transaction::FunctionCall;
 use rooch_rpc_api::jsonrpc_types::ExecuteTransactionResponseView;
 use rooch_rpc_client::Client;
 use rooch_types::{address::RoochAddress, transaction::rooch::RoochTransaction};
+use std::time::Duration;
 
 pub mod actor;
 
@@ -16,7 +17,54 @@ pub trait Relayer: Send + Sync {
         return std::any::type_name::<Self>();
     }
 
-    async fn relay(&mut self) -> Result<Option<FunctionCall>>;
+    /// Returns the calls to submit, in order, each in its own transaction. Empty if there is nothing to relay.
+    async fn relay(&mut self) -> Result<Vec<FunctionCall>>;
+
+    /// Returns true if the relayer has nothing more to relay, such as after passing its end block.
+    /// A complete relayer only returns no call from `relay`.
+    fn is_complete(&self) -> bool {
+        false
+    }
+
+    /// Inform the relayer whether the transaction of a relayed call is accepted,
+    /// once for each call returned by `relay`, in the returned order.
+    fn on_submission_result(&mut self, _accepted: bool) {}
+
+    /// Give back the calls returned by `relay` which were not submitted, in order, because the
+    /// submission failed before their transactions were executed. They get no submission result,
+    /// the relayer should return them again from a later `relay`.
+    fn on_submission_aborted(&mut self, _calls: Vec<FunctionCall>) {}
+
+    /// The running totals of the relayer's submissions.
+    fn status(&self) -> RelayerStatus {
+        RelayerStatus::default()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelayerStatus {
+    /// The number of blocks accepted by the light client
+    pub accepted_blocks: u64,
+    /// The number of blocks rejected by the light client, such as a parent hash mismatch
+    pub rejected_blocks: u64,
+    /// The number of the last rejected block
+    pub last_rejected_block: Option<u64>,
+    /// The number of successful block fetches from the RPC
+    pub block_fetches: u64,
+    /// The total latency of the successful block fetches
+    pub total_block_fetch_latency: Duration,
+    /// The latency of the last successful block fetch
+    pub last_block_fetch_latency: Option<Duration>,
+}
+
+impl RelayerStatus {
+    /// The running average latency of the block fetches, None if no block is fetched yet.
+    pub fn avg_block_fetch_latency(&self) -> Option<Duration> {
+        if self.block_fetches == 0 {
+            return None;
+        }
+        Some(self.total_block_fetch_latency / self.block_fetches.min(u32::MAX as u64) as u32)
+    }
 }
 
 #[async_trait]
@@ -5,10 +5,10 @@ use crate::jsonrpc_types::account_view::BalanceInfoView;
 use crate::jsonrpc_types::event_view::EventFilterView;
 use crate::jsonrpc_types::transaction_view::{TransactionFilterView, TransactionWithInfoView};
 use crate::jsonrpc_types::{
-    AccessPathView, AccountAddressView, AnnotatedFunctionResultView, BalanceInfoPageView,
-    BytesView, EventOptions, EventPageView, ExecuteTransactionResponseView, FunctionCallView,
-    H256View, IndexerEventPageView, StateOptions, StateView, StatesPageView, StrView,
-    StructTagView, TransactionWithInfoPageView,
+    AccessPathView, AccountAddressView, AccountNoncePageView, AnnotatedFunctionResultView,
+    BalanceInfoPageView, BytesView, EventOptions, EventPageView, ExecuteTransactionResponseView,
+    FunctionCallView, H256View, IndexerEventPageView, IndexerSyncStatusView, StateOptions,
+    StateView, StatesPageView, StrView, StructTagView, TransactionWithInfoPageView,
 };
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
@@ -124,4 +124,18 @@ pub trait RoochAPI {
         limit: Option<StrView<usize>>,
         descending_order: Option<bool>,
     ) -> RpcResult<IndexerEventPageView>;
+
+    /// Get the indexer sync status, the latest indexed tx_order and how far it is behind the sequencer
+    #[method(name = "getIndexerSyncStatus")]
+    async fn get_indexer_sync_status(&self) -> RpcResult<IndexerSyncStatusView>;
+
+    /// Get the sequence numbers used by the account in its transactions, in ascending order of tx_order
+    #[method(name = "getAccountNonceHistory")]
+    async fn get_account_nonce_history(
+        &self,
+        account_addr: AccountAddressView,
+        // exclusive cursor if `Some`, otherwise start from the beginning
+        cursor: Option<StrView<u64>>,
+        limit: Option<StrView<usize>>,
+    ) -> RpcResult<AccountNoncePageView>;
 }
@@ -21,6 +21,7 @@ pub type TransactionWithInfoPageView = PageView<TransactionWithInfoView, u64>;
 pub type StatesPageView = PageView<StateView, BytesView>;
 pub type BalanceInfoPageView = PageView<BalanceInfoView, BytesView>;
 pub type IndexerEventPageView = PageView<IndexerEventView, IndexerEventID>;
+pub type AccountNoncePageView = PageView<AccountNonceView, u64>;
 
 /// `next_cursor` points to the last item in the page;
 /// Reading with `next_cursor` will start from the next item after `next_cursor` if
@@ -32,6 +33,24 @@ pub struct PageView<T, C> {
     pub has_next_page: bool,
 }
 
+/// How far the indexer has progressed compared to the sequencer.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IndexerSyncStatusView {
+    /// The max tx_order persisted in the indexer, None if no transaction is indexed yet
+    pub latest_indexed_order: Option<StrView<u64>>,
+    /// The latest tx_order of the sequencer, None if unknown
+    pub sequencer_latest_order: Option<StrView<u64>>,
+    /// How many transactions the indexer is behind the sequencer, None if the sequencer order is unknown
+    pub indexing_lag: Option<StrView<u64>>,
+}
+
+/// The sequence number used by an account in a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AccountNonceView {
+    pub tx_order: StrView<u64>,
+    pub sequence_number: StrView<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TransactionTypeView {
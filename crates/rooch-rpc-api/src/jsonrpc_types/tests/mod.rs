@@ -2,3 +2,4 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod str_view_tests;
+mod transaction_filter_view_tests;
@@ -0,0 +1,32 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::jsonrpc_types::TransactionFilterView;
+use rooch_types::address::RoochAddress;
+use rooch_types::indexer::transaction_filter::TransactionFilter;
+use std::str::FromStr;
+
+#[test]
+fn test_source_node_id_filter() {
+    let address = "0x0000000000000000000000000000000000000000000000000000000000000042";
+    let view: TransactionFilterView =
+        serde_json::from_str(&format!("{{\"source_node_id\":\"{}\"}}", address)).unwrap();
+    match TransactionFilter::from(view) {
+        TransactionFilter::SourceNodeId(source_node_id) => {
+            assert_eq!(source_node_id, RoochAddress::from_str(address).unwrap())
+        }
+        filter => panic!("Unexpected filter {:?}", filter),
+    }
+}
+
+#[test]
+fn test_source_node_id_filter_rejects_invalid_address() {
+    for source_node_id in ["0x42\" OR 1=1 --", "0x42' OR '1'='1", "not an address"] {
+        let json = serde_json::json!({ "source_node_id": source_node_id }).to_string();
+        assert!(
+            serde_json::from_str::<TransactionFilterView>(&json).is_err(),
+            "The source node id {} should be rejected",
+            source_node_id
+        );
+    }
+}
@@ -51,6 +51,8 @@ pub enum TransactionFilterView {
         /// right endpoint of transaction order, exclusive
         to_order: u64,
     },
+    /// Query by the id of the node which sequenced the transaction, the address of its tx order signer.
+    SourceNodeId(AccountAddressView),
 }
 
 impl From<TransactionFilterView> for TransactionFilter {
@@ -75,6 +77,9 @@ impl From<TransactionFilterView> for TransactionFilter {
                 from_order,
                 to_order,
             },
+            TransactionFilterView::SourceNodeId(source_node_id) => {
+                Self::SourceNodeId(source_node_id.0.into())
+            }
         }
     }
 }
@@ -18,19 +18,23 @@ use raw_store::errors::RawStoreError;
 use raw_store::rocks::RocksDB;
 use raw_store::StoreInstance;
 use rooch_config::indexer_config::IndexerConfig;
+use rooch_config::relayer_config::RelayerConfig;
 use rooch_config::server_config::ServerConfig;
 use rooch_config::store_config::StoreConfig;
-use rooch_config::{BaseConfig, RoochOpt, ServerOpt};
+use rooch_config::{BaseConfig, ConfigModule, RoochOpt, ServerOpt};
 use rooch_executor::actor::executor::ExecutorActor;
 use rooch_executor::proxy::ExecutorProxy;
-use rooch_indexer::actor::indexer::IndexerActor;
+use rooch_indexer::actor::indexer::{IndexerActor, DEFAULT_BLOCK_FLUSH_INTERVAL_SECS};
+use rooch_indexer::actor::messages::FlushIndexerBlockMessage;
 use rooch_indexer::indexer_reader::IndexerReader;
-use rooch_indexer::proxy::IndexerProxy;
+use rooch_indexer::proxy::{IndexerProxy, DEFAULT_INDEXER_CHANNEL_CAPACITY};
+use rooch_indexer::types::TransactionModuleFilter;
 use rooch_indexer::IndexerStore;
 use rooch_key::key_derive::{generate_new_key_pair, retrieve_key_pair};
 use rooch_proposer::actor::messages::ProposeBlock;
 use rooch_proposer::actor::proposer::ProposerActor;
 use rooch_proposer::proxy::ProposerProxy;
+use rooch_relayer::actor::fee_strategy::FeeStrategy;
 use rooch_relayer::actor::messages::RelayTick;
 use rooch_relayer::actor::relayer::RelayerActor;
 use rooch_rpc_api::api::RoochRpcModule;
@@ -181,7 +185,7 @@ pub async fn run_start_server(opt: &RoochOpt, mut server_opt: ServerOpt) -> Resu
 
     //Init indexer store
     let mut indexer_config = IndexerConfig::default();
-    indexer_config.merge_with_opt_with_init(opt, Arc::new(base_config), true)?;
+    indexer_config.merge_with_opt_with_init(opt, Arc::new(base_config.clone()), true)?;
     let (indexer_store, indexer_reader) = init_indexer(&indexer_config)?;
 
     // Init executor
@@ -243,10 +247,43 @@ pub async fn run_start_server(opt: &RoochOpt, mut server_opt: ServerOpt) -> Resu
     timers.push(proposer_timer);
 
     // Init indexer
-    let indexer_executor = IndexerActor::new(indexer_store, indexer_reader)?
-        .into_actor(Some("Indexer"), &actor_system)
-        .await?;
-    let indexer_proxy = IndexerProxy::new(indexer_executor.into());
+    let module_filter = if indexer_config.modules.is_empty() {
+        None
+    } else {
+        Some(TransactionModuleFilter::new(
+            &indexer_config.modules,
+            indexer_config.include_module_publish,
+        )?)
+    };
+    let indexer_executor = IndexerActor::new(
+        indexer_store,
+        indexer_reader,
+        indexer_config.decode_transaction,
+        indexer_config.block_size,
+        indexer_config.upsert_transactions,
+        module_filter,
+    )?
+    .into_actor(Some("Indexer"), &actor_system)
+    .await?;
+    if indexer_config.block_size.is_some() {
+        let indexer_flush_timer = Timer::start(
+            indexer_executor.clone(),
+            Duration::from_secs(
+                indexer_config
+                    .block_flush_interval_secs
+                    .unwrap_or(DEFAULT_BLOCK_FLUSH_INTERVAL_SECS),
+            ),
+            FlushIndexerBlockMessage {},
+        );
+        timers.push(indexer_flush_timer);
+    }
+    let (indexer_proxy, indexer_forwarder) = IndexerProxy::new(
+        indexer_executor.into(),
+        indexer_config
+            .channel_capacity
+            .unwrap_or(DEFAULT_INDEXER_CHANNEL_CAPACITY),
+    );
+    tokio::spawn(indexer_forwarder.run());
 
     let rpc_service = RpcService::new(
         chain_id_opt.chain_id().id(),
@@ -261,10 +298,20 @@ pub async fn run_start_server(opt: &RoochOpt, mut server_opt: ServerOpt) -> Resu
         let relayer_keypair = server_opt.relayer_keypair.unwrap();
         let relayer_account: RoochAddress = (&relayer_keypair.public()).into();
         info!("RPC Server relayer address: {:?}", relayer_account);
-        let relayer = RelayerActor::new(relayer_keypair, eth_rpc_url, rpc_service.clone())
-            .await?
-            .into_actor(Some("Relayer"), &actor_system)
-            .await?;
+        let mut relayer_config = RelayerConfig::default();
+        relayer_config.merge_with_opt(opt, Arc::new(base_config))?;
+        let fee_strategy = FeeStrategy::try_from(&relayer_config)?;
+        info!("RPC Server relayer fee strategy: {:?}", fee_strategy);
+        let relayer = RelayerActor::new(
+            relayer_keypair,
+            eth_rpc_url,
+            &relayer_config,
+            rpc_service.clone(),
+            fee_strategy,
+        )
+        .await?
+        .into_actor(Some("Relayer"), &actor_system)
+        .await?;
         let relay_tick_in_seconds: u64 = 5;
         let relayer_timer = Timer::start(
             relayer,
@@ -385,6 +432,7 @@ fn init_indexer(indexer_config: &IndexerConfig) -> Result<(IndexerStore, Indexer
         .ok_or(anyhow::anyhow!("Invalid indexer db path"))?;
     let indexer_store = IndexerStore::new(indexer_db_url)?;
     indexer_store.create_all_tables_if_not_exists()?;
+    indexer_store.reconcile_stats()?;
     let indexer_reader = IndexerReader::new(indexer_db_url)?;
 
     Ok((indexer_store, indexer_reader))
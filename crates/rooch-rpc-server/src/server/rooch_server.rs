@@ -8,16 +8,17 @@ use jsonrpsee::{
     RpcModule,
 };
 use moveos_types::h256::H256;
+use rooch_indexer::indexer_reader::indexing_lag;
 use rooch_rpc_api::jsonrpc_types::event_view::{EventFilterView, EventView, IndexerEventView};
 use rooch_rpc_api::jsonrpc_types::transaction_view::TransactionFilterView;
 use rooch_rpc_api::jsonrpc_types::{
-    account_view::BalanceInfoView, IndexerEventPageView, StateOptions,
+    account_view::BalanceInfoView, IndexerEventPageView, IndexerSyncStatusView, StateOptions,
 };
 use rooch_rpc_api::jsonrpc_types::{transaction_view::TransactionWithInfoView, EventOptions};
 use rooch_rpc_api::jsonrpc_types::{
-    AccessPathView, AccountAddressView, BalanceInfoPageView, EventPageView,
-    ExecuteTransactionResponseView, FunctionCallView, H256View, StateView, StatesPageView, StrView,
-    StructTagView, TransactionWithInfoPageView,
+    AccessPathView, AccountAddressView, AccountNoncePageView, AccountNonceView,
+    BalanceInfoPageView, EventPageView, ExecuteTransactionResponseView, FunctionCallView, H256View,
+    StateView, StatesPageView, StrView, StructTagView, TransactionWithInfoPageView,
 };
 use rooch_rpc_api::{api::rooch_api::RoochAPIServer, api::DEFAULT_RESULT_LIMIT};
 use rooch_rpc_api::{
@@ -406,6 +407,57 @@ impl RoochAPIServer for RoochServer {
             has_next_page,
         })
     }
+
+    async fn get_indexer_sync_status(&self) -> RpcResult<IndexerSyncStatusView> {
+        let latest_indexed_order = self.rpc_service.latest_indexed_order().await?;
+        let sequencer_latest_order = self
+            .rpc_service
+            .get_sequencer_order()
+            .await?
+            .map(|order| order.last_order);
+        let indexing_lag = indexing_lag(latest_indexed_order, sequencer_latest_order);
+
+        Ok(IndexerSyncStatusView {
+            latest_indexed_order: latest_indexed_order.map(StrView),
+            sequencer_latest_order: sequencer_latest_order.map(StrView),
+            indexing_lag: indexing_lag.map(StrView),
+        })
+    }
+
+    async fn get_account_nonce_history(
+        &self,
+        account_addr: AccountAddressView,
+        // exclusive cursor if `Some`, otherwise start from the beginning
+        cursor: Option<StrView<u64>>,
+        limit: Option<StrView<usize>>,
+    ) -> RpcResult<AccountNoncePageView> {
+        let limit_of = min(
+            limit.map(Into::into).unwrap_or(DEFAULT_RESULT_LIMIT_USIZE),
+            MAX_RESULT_LIMIT_USIZE,
+        );
+        let cursor = cursor.map(|v| v.0);
+
+        let mut data = self
+            .rpc_service
+            .account_nonce_history(account_addr.into(), cursor, limit_of + 1)
+            .await?;
+
+        let has_next_page = data.len() > limit_of;
+        data.truncate(limit_of);
+        let next_cursor = data.last().map_or(cursor, |(tx_order, _)| Some(*tx_order));
+
+        Ok(AccountNoncePageView {
+            data: data
+                .into_iter()
+                .map(|(tx_order, sequence_number)| AccountNonceView {
+                    tx_order: StrView(tx_order),
+                    sequence_number: StrView(sequence_number),
+                })
+                .collect(),
+            next_cursor,
+            has_next_page,
+        })
+    }
 }
 
 impl RoochRpcModule for RoochServer {
@@ -70,6 +70,7 @@ impl RpcService {
         // First, validate the transactin
         let moveos_tx = self.executor.validate_transaction(tx.clone()).await?;
         let sequence_info = self.sequencer.sequence_transaction(tx.clone()).await?;
+        let moveos_tx = moveos_tx.with_tx_order(sequence_info.tx_order);
         // Then execute
         let (output, execution_info) = self.executor.execute_transaction(moveos_tx.clone()).await?;
         self.proposer
@@ -85,8 +86,30 @@ impl RpcService {
                 moveos_tx.clone(),
             )
             .await?;
+        // Decode the events for the JSON queries of the indexer, the events are indexed without JSON
+        // if they can not be decoded.
+        let event_ids = output.events.iter().map(|event| event.event_id).collect();
+        let decoded_events = match self.executor.get_events_by_event_ids(event_ids).await {
+            Ok(annotated_events) => annotated_events
+                .into_iter()
+                .map(|event| event.map(|event| event.decoded_event_data))
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Decode the events for the indexer failed: {:?}", e);
+                vec![]
+            }
+        };
         self.indexer
-            .indexer_events(output.events.clone(), tx, sequence_info.clone(), moveos_tx)
+            .indexer_events(
+                output.events.clone(),
+                decoded_events,
+                tx,
+                sequence_info.clone(),
+                moveos_tx,
+            )
+            .await?;
+        self.indexer
+            .indexer_object_changes(&output.state_changeset, &sequence_info)
             .await?;
 
         Ok(ExecuteTransactionResponse {
@@ -273,6 +296,25 @@ impl RpcService {
             .await?;
         Ok(resp)
     }
+
+    pub async fn latest_indexed_order(&self) -> Result<Option<u64>> {
+        let resp = self.indexer.latest_indexed_order().await?;
+        Ok(resp)
+    }
+
+    pub async fn account_nonce_history(
+        &self,
+        sender: AccountAddress,
+        // exclusive cursor if `Some`, otherwise start from the beginning
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<(u64, u64)>> {
+        let resp = self
+            .indexer
+            .account_nonce_history(sender, cursor, limit)
+            .await?;
+        Ok(resp)
+    }
 }
 
 //TODO we need to make the RpcService to an Actor, and implement TxSubmiter for it's actor proxy.
@@ -14,6 +14,7 @@ use move_core_types::{
 };
 use moveos_types::{
     module_binding::{ModuleBinding, MoveFunctionCaller},
+    move_types::FunctionId,
     moveos_std::tx_context::TxContext,
     transaction::FunctionCall,
 };
@@ -96,6 +97,9 @@ impl<'a> EthereumLightClientModule<'a> {
     pub const GET_BLOCK_FUNCTION_NAME: &'static IdentStr = ident_str!("get_block");
     pub const SUBMIT_NEW_BLOCK_ENTRY_FUNCTION_NAME: &'static IdentStr =
         ident_str!("submit_new_block");
+    pub const ROLLBACK_BLOCK_ENTRY_FUNCTION_NAME: &'static IdentStr = ident_str!("rollback_block");
+    pub const REMOVE_LOGS_ENTRY_FUNCTION_NAME: &'static IdentStr = ident_str!("remove_logs");
+    pub const HEARTBEAT_ENTRY_FUNCTION_NAME: &'static IdentStr = ident_str!("heartbeat");
 
     pub fn get_block(&self, block_number: u64) -> Result<BlockHeader> {
         let call = FunctionCall::new(
@@ -117,14 +121,62 @@ impl<'a> EthereumLightClientModule<'a> {
     }
 
     pub fn create_submit_new_block_call(block_header: &BlockHeader) -> FunctionCall {
-        Self::create_function_call(
-            Self::SUBMIT_NEW_BLOCK_ENTRY_FUNCTION_NAME,
+        Self::create_block_header_call(
+            Self::function_id(Self::SUBMIT_NEW_BLOCK_ENTRY_FUNCTION_NAME),
+            block_header,
+        )
+    }
+
+    /// Call the function with the BCS bytes of the block header as the only argument,
+    /// the same argument as `submit_new_block`, so any module can receive the relayed blocks.
+    pub fn create_block_header_call(
+        function_id: FunctionId,
+        block_header: &BlockHeader,
+    ) -> FunctionCall {
+        FunctionCall::new(
+            function_id,
             vec![],
             vec![MoveValue::vector_u8(
                 bcs::to_bytes(&block_header).expect("Serialize BlockHeader should success."),
-            )],
+            )
+            .simple_serialize()
+            .expect("Serialize MoveValue should success.")],
+        )
+    }
+
+    /// Roll back the block orphaned by a reorg in the light client.
+    pub fn create_rollback_block_call(block_number: u64, block_hash: Vec<u8>) -> FunctionCall {
+        Self::create_function_call(
+            Self::ROLLBACK_BLOCK_ENTRY_FUNCTION_NAME,
+            vec![],
+            vec![
+                MoveValue::U64(block_number),
+                MoveValue::vector_u8(block_hash),
+            ],
+        )
+    }
+
+    /// Notify the light client the logs of a block orphaned by a reorg are removed, which also rolls back the block.
+    pub fn create_remove_logs_call(
+        block_number: u64,
+        block_hash: Vec<u8>,
+        log_indexes: Vec<u64>,
+    ) -> FunctionCall {
+        Self::create_function_call(
+            Self::REMOVE_LOGS_ENTRY_FUNCTION_NAME,
+            vec![],
+            vec![
+                MoveValue::U64(block_number),
+                MoveValue::vector_u8(block_hash),
+                MoveValue::Vector(log_indexes.into_iter().map(MoveValue::U64).collect()),
+            ],
         )
     }
+
+    /// The heartbeat of the relayer, the relayer signer is the transaction sender.
+    pub fn create_heartbeat_call() -> FunctionCall {
+        Self::create_function_call(Self::HEARTBEAT_ENTRY_FUNCTION_NAME, vec![], vec![])
+    }
 }
 
 impl<'a> ModuleBinding<'a> for EthereumLightClientModule<'a> {
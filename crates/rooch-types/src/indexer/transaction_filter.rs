@@ -1,6 +1,7 @@
 // Copyright (c) RoochNetwork
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::address::RoochAddress;
 use move_core_types::account_address::AccountAddress;
 use moveos_types::h256::H256;
 use serde::{Deserialize, Serialize};
@@ -28,4 +29,6 @@ pub enum TransactionFilter {
         /// right endpoint of transaction order, exclusive
         to_order: u64,
     },
+    /// Query by the id of the node which sequenced the transaction, the address of its tx order signer.
+    SourceNodeId(RoochAddress),
 }
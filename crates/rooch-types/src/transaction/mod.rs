@@ -17,7 +17,7 @@ pub mod authenticator;
 pub mod ethereum;
 pub mod rooch;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum TransactionType {
     Rooch,
     Ethereum,
@@ -2,12 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{ensure, Result};
-use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use codespan_reporting::{
+    diagnostic::{Diagnostic, Severity},
+    files::FileId,
+    term::termcolor::{ColorChoice, NoColor, StandardStream},
+};
 use dependency_order::sort_by_dependency_order;
 use move_binary_format::{errors::Location, CompiledModule};
 use move_cli::base::reroot_path;
 use move_core_types::account_address::AccountAddress;
-use move_model::model::GlobalEnv;
+use move_model::model::{GlobalEnv, Loc};
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, ModelConfig};
 use moveos_verifier::build::run_verifier;
 use serde::{Deserialize, Serialize};
@@ -62,6 +66,72 @@ impl StdlibPackage {
     }
 }
 
+/// A diagnostic reported by the Move compiler while building a stdlib package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StdlibDiagnostic {
+    // The path of the stdlib project which reported the diagnostic
+    pub package: PathBuf,
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<String>,
+    // 1-based line and column of the primary label
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+impl StdlibDiagnostic {
+    fn new(package: &Path, model: &GlobalEnv, diag: &Diagnostic<FileId>) -> Self {
+        let location = diag.labels.first().and_then(|label| {
+            let loc = Loc::new(
+                label.file_id,
+                codespan::Span::new(label.range.start as u32, label.range.end as u32),
+            );
+            model.get_file_and_location(&loc)
+        });
+        let (file, line, column) = match location {
+            Some((file, location)) => (
+                Some(file),
+                Some(location.line.0 + 1),
+                Some(location.column.0 + 1),
+            ),
+            None => (None, None, None),
+        };
+        Self {
+            package: package.to_path_buf(),
+            severity: diag.severity,
+            message: diag.message.clone(),
+            file,
+            line,
+            column,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity >= Severity::Error
+    }
+}
+
+impl std::fmt::Display for StdlibDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.severity)?;
+        if let Some(file) = &self.file {
+            write!(f, " at {}", file)?;
+            if let (Some(line), Some(column)) = (self.line, self.column) {
+                write!(f, ":{}:{}", line, column)?;
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The options to build the stdlib, the default is the full build for releases.
+#[derive(Debug, Clone, Default)]
+pub struct StdlibBuildOptions {
+    /// Only compile the modules, skip the documents and the error code maps generation.
+    /// This speeds up the build in development.
+    pub skip_doc_and_errmap: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct StdlibBuildConfig {
     // The path of the stdlib project
@@ -74,7 +144,18 @@ pub struct StdlibBuildConfig {
 }
 
 impl StdlibBuildConfig {
-    pub fn build(self, _deps: &[StdlibBuildConfig]) -> Result<StdlibPackage> {
+    pub fn build(self, deps: &[StdlibBuildConfig]) -> Result<StdlibPackage> {
+        self.build_with_diagnostics(deps, &StdlibBuildOptions::default())
+            .map(|(package, _diagnostics)| package)
+    }
+
+    /// Build the stdlib package, and return the compiler diagnostics (warnings included) alongside it.
+    /// Only errors fail the build.
+    pub fn build_with_diagnostics(
+        self,
+        _deps: &[StdlibBuildConfig],
+        options: &StdlibBuildOptions,
+    ) -> Result<(StdlibPackage, Vec<StdlibDiagnostic>)> {
         println!("Build stdlib at {:?}", self.path);
         let original_current_dir = current_dir()?;
         let project_path = self.path.clone();
@@ -124,8 +205,11 @@ impl StdlibBuildConfig {
         //     })
         //     .collect::<Vec<String>>();
 
-        self.build_doc(&model, deps_doc_paths)?;
-        self.build_error_code_map(&model)?;
+        if !options.skip_doc_and_errmap {
+            self.build_doc(&model, deps_doc_paths)?;
+            self.build_error_code_map(&model)?;
+        }
+        let diagnostics = self.collect_diagnostics(&model);
 
         if model.has_errors() {
             let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
@@ -139,7 +223,18 @@ impl StdlibBuildConfig {
             "Errors encountered while build stdlib!"
         );
         std::env::set_current_dir(original_current_dir)?;
-        StdlibPackage::new(genesis_account, compiled_package)
+        let package = StdlibPackage::new(genesis_account, compiled_package)?;
+        Ok((package, diagnostics))
+    }
+
+    fn collect_diagnostics(&self, model: &GlobalEnv) -> Vec<StdlibDiagnostic> {
+        let mut diagnostics = vec![];
+        // The filter only records the diagnostics, nothing is written or marked as reported.
+        model.report_diag_with_filter(&mut NoColor::new(std::io::sink()), |diag| {
+            diagnostics.push(StdlibDiagnostic::new(&self.path, model, diag));
+            false
+        });
+        diagnostics
     }
 
     fn build_doc(&self, model: &GlobalEnv, deps_doc_paths: Vec<String>) -> Result<()> {
@@ -229,13 +324,34 @@ impl StdlibBuildConfig {
 impl Stdlib {
     /// Build the stdlib or framework packages
     pub fn build(build_configs: Vec<StdlibBuildConfig>) -> Result<Self> {
+        Self::build_with_options(build_configs, &StdlibBuildOptions::default())
+    }
+
+    /// Build the stdlib or framework packages with the given options
+    pub fn build_with_options(
+        build_configs: Vec<StdlibBuildConfig>,
+        options: &StdlibBuildOptions,
+    ) -> Result<Self> {
+        Self::build_with_diagnostics(build_configs, options).map(|(stdlib, _diagnostics)| stdlib)
+    }
+
+    /// Build the stdlib or framework packages, and collect the compiler diagnostics of all packages
+    pub fn build_with_diagnostics(
+        build_configs: Vec<StdlibBuildConfig>,
+        options: &StdlibBuildOptions,
+    ) -> Result<(Self, Vec<StdlibDiagnostic>)> {
         let mut packages = vec![];
+        let mut diagnostics = vec![];
         let mut deps = vec![];
         for build_config in build_configs {
-            packages.push(build_config.clone().build(&deps)?);
+            let (package, package_diagnostics) = build_config
+                .clone()
+                .build_with_diagnostics(&deps, options)?;
+            packages.push(package);
+            diagnostics.extend(package_diagnostics);
             deps.push(build_config);
         }
-        Ok(Self { packages })
+        Ok((Self { packages }, diagnostics))
     }
 
     pub fn decode(bytes: &[u8]) -> Result<Self> {
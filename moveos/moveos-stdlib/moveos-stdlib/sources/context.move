@@ -20,6 +20,7 @@ module moveos_std::context {
     use moveos_std::move_module::{Self, MoveModule};
     use moveos_std::table::{Self, Table};
     use moveos_std::type_table::{Self, TypeTable};
+    use moveos_std::raw_table;
 
     const ErrorObjectOwnerNotMatch: u64 = 1;
     const ErrorObjectNotShared: u64 = 2;
@@ -106,6 +107,12 @@ module moveos_std::context {
         tx_context::tx_result(&self.tx_context)
     }
 
+    /// Returns true if the current execution is read-only, such as a view function call or a simulation.
+    /// The state changes of a read-only execution are never committed.
+    public fun is_readonly_context(_self: &Context): bool {
+        raw_table::is_readonly_context()
+    }
+
     // === Table functions ===
 
     public fun new_table<K: copy + drop, V: store>(self: &mut Context): Table<K, V>{
@@ -387,6 +394,13 @@ module moveos_std::context {
         drop_test_context(ctx);
     }
 
+    #[test(sender = @0x42)]
+    fun test_is_readonly_context(sender: address){
+        let ctx = new_test_context(sender);
+        assert!(!is_readonly_context(&ctx), 1000);
+        drop_test_context(ctx);
+    }
+
     #[test(alice = @0x42)]
     fun test_borrow_object(alice: &signer){
         let alice_addr = signer::address_of(alice);
@@ -7,12 +7,17 @@
 
 module moveos_std::raw_table {
     
+    use std::option::{Self, Option};
+    use std::vector;
+    use moveos_std::bcs;
+
     friend moveos_std::table;
     friend moveos_std::type_table;
     friend moveos_std::storage_context;
     friend moveos_std::account_storage;
     friend moveos_std::object;
     friend moveos_std::object_table;
+    friend moveos_std::context;
 
     /// The key already exists in the table
     const ErrorAlreadyExists: u64 = 1;
@@ -22,12 +27,54 @@ module moveos_std::raw_table {
     const ErrorDuplicateOperation: u64 = 3;
     /// The table is not empty
     const ErrorNotEmpty: u64 = 4;
+    /// The length of keys and values do not match
+    const ErrorLengthNotMatch: u64 = 5;
+    /// The value is less than the amount to subtract
+    const ErrorUnderflow: u64 = 6;
+    /// The snapshot is malformed or does not match the key and value types
+    const ErrorInvalidSnapshot: u64 = 7;
+    /// The value would exceed the maximum of its type
+    const ErrorOverflow: u64 = 8;
+    /// The table has reached the max number of entries of the node
+    const ErrorTooManyEntries: u64 = 9;
+    /// The operation has an unknown tag, or its key or value does not match the table types
+    const ErrorInvalidOperation: u64 = 10;
+
+    /// Ensure the operation tags are consistent with the tags in the native implementation
+    const TABLE_OP_NEW: u8 = 0;
+    const TABLE_OP_MODIFY: u8 = 1;
+    const TABLE_OP_DELETE: u8 = 2;
+
+    /// Ensure the reducer tags are consistent with the tags in the native implementation
+    const REDUCER_SUM_U64: u8 = 0;
+    const REDUCER_MAX_U64: u8 = 1;
+    const REDUCER_MIN_U64: u8 = 2;
+    const REDUCER_APPEND_BYTES: u8 = 3;
 
     struct TableInfo has key {
         // Table SMT root
         state_root: address,
         // Table size, number of items
         size: u64,
+        // The BCS bytes of the TypeTag of the keys, kept when the table is created, empty if not known
+        key_type: vector<u8>,
+        // The BCS bytes of the TypeTag of the values, kept when the table is created, empty if not known
+        value_type: vector<u8>,
+    }
+
+    /// A pending operation of the current transaction on a table entry.
+    /// The tag is 0 for new, 1 for modify and 2 for delete.
+    /// The key and the value are the BCS bytes, the value of a delete operation is empty.
+    struct TableOp has copy, drop, store {
+        tag: u8,
+        key: vector<u8>,
+        value: vector<u8>,
+    }
+
+    /// A read of the entry of a key in a table, the key is the BCS bytes.
+    struct TableRead has copy, drop {
+        handle: TableHandle,
+        key: vector<u8>,
     }
 
     /// Add a new entry to the table. Aborts if an entry for this
@@ -97,8 +144,9 @@ module moveos_std::raw_table {
     }
 
     /// Returns true if the table is empty (if `length` returns `0`)
+    /// The emptiness is checked without counting the entries, so it is cheaper than `length`.
     public(friend) fun is_empty(table_handle: TableHandle): bool {
-        length(table_handle) == 0
+        is_empty_box(table_handle)
     }
 
     /// Drop a table even if it is not empty.
@@ -112,6 +160,431 @@ module moveos_std::raw_table {
         drop_unchecked_box(table_handle)
     }
 
+    /// Replace all the entries of the table with the given `keys` and `vals` in one step.
+    /// The existing entries are removed, then each key is mapped to the value at the same index.
+    /// Aborts if the lengths of `keys` and `vals` do not match, if `keys` contains duplicates,
+    /// or if the table holds more than 10000 entries.
+    public(friend) fun replace_all<K: copy + drop, V>(table_handle: TableHandle, keys: vector<K>, vals: vector<V>) {
+        let boxes = vector::empty<Box<V>>();
+        vector::reverse(&mut vals);
+        while (!vector::is_empty(&vals)) {
+            vector::push_back(&mut boxes, Box { val: vector::pop_back(&mut vals) });
+        };
+        vector::destroy_empty(vals);
+        replace_all_box<K, V, Box<V>>(table_handle, keys, boxes)
+    }
+
+    /// Returns whether `table` contains an entry for each of the `keys`, in the same order as `keys`.
+    public(friend) fun contains_all<K: copy + drop>(table_handle: TableHandle, keys: vector<K>): vector<bool> {
+        contains_boxes<K>(table_handle, keys)
+    }
+
+    /// Returns the merkle root over all the entries of the table, in ascending order of the BCS bytes of the keys.
+    /// The cost is proportional to the table size, so it is meant for small tables.
+    /// Aborts if the table has more than 10000 entries.
+    /// See the native implementation for the hashing scheme.
+    public(friend) fun content_root<K: copy + drop>(table_handle: TableHandle): vector<u8> {
+        content_root_box<K>(table_handle)
+    }
+
+    /// Returns the merkle proof of the entry which `key` maps to, against the root returned by `content_root`.
+    /// Aborts if there is no entry for `key`, or if the table has more than 10000 entries.
+    /// See the native implementation for the proof format.
+    public(friend) fun content_proof<K: copy + drop>(table_handle: TableHandle, key: K): vector<vector<u8>> {
+        content_proof_box<K>(table_handle, key)
+    }
+
+    /// Subtract `delta` from the `u64` value which `key` maps to, and return the new value.
+    /// Aborts if there is no entry for `key`, or if the value is less than `delta`.
+    public(friend) fun decrement_u64<K: copy + drop>(table_handle: TableHandle, key: K, delta: u64): u64 {
+        decrement_u64_box<K>(table_handle, key, delta)
+    }
+
+    /// Subtract `delta` from the `u64` value which `key` maps to, flooring at zero, and return the new value.
+    /// Aborts if there is no entry for `key`.
+    public(friend) fun saturating_decrement_u64<K: copy + drop>(table_handle: TableHandle, key: K, delta: u64): u64 {
+        saturating_decrement_u64_box<K>(table_handle, key, delta)
+    }
+
+    /// Returns the number of tables created in the current transaction, not including the pre-existing ones.
+    public(friend) fun new_table_count(): u64 {
+        new_table_count_box()
+    }
+
+    /// Returns the first entry after `cursor`, or the first entry of the table if `cursor` is none.
+    /// The persisted entries come first in the order of the storage, then the entries added in the current
+    /// transaction in ascending order of the BCS bytes of the keys. Returns (none, none) if there is no more entry.
+    /// The key and the value are returned together, so a full scan does not need a `borrow` per key.
+    public(friend) fun iter_next_entry<K: copy + drop, V: copy + drop>(table_handle: TableHandle, cursor: Option<K>): (Option<K>, Option<V>) {
+        let cursor_keys = vector::empty<K>();
+        if (option::is_some(&cursor)) {
+            vector::push_back(&mut cursor_keys, option::destroy_some(cursor));
+        };
+        let (keys, vals) = iter_next_entry_box<K, V>(table_handle, cursor_keys);
+        if (vector::is_empty(&keys)) {
+            (option::none(), option::none())
+        } else {
+            (option::some(vector::pop_back(&mut keys)), option::some(vector::pop_back(&mut vals)))
+        }
+    }
+
+    /// Returns the BCS bytes of the keys added, modified or removed earlier in the current transaction.
+    /// The result only reflects the in-flight state of the transaction, it is discarded after the transaction.
+    public(friend) fun dirty_keys(table_handle: TableHandle): vector<vector<u8>> {
+        dirty_keys_box(table_handle)
+    }
+
+    /// Replace the value which `key` maps to with `new` if it currently equals `expected`, comparing the BCS bytes.
+    /// Returns true if the value is replaced, false if it does not equal `expected` or there is no entry for `key`.
+    public(friend) fun compare_and_swap<K: copy + drop, V: drop>(table_handle: TableHandle, key: K, expected: V, new: V): bool {
+        cas_box<K, V>(table_handle, key, expected, new)
+    }
+
+    /// Returns a snapshot of all the entries of the table, in ascending order of the BCS bytes of the keys.
+    /// The snapshot is deterministic, and can be restored to an empty table by `import_entries`.
+    /// Aborts if the table has more than 10000 entries.
+    public(friend) fun export_entries<K: copy + drop, V>(table_handle: TableHandle): vector<u8> {
+        export_box<K, V>(table_handle)
+    }
+
+    /// Add all the entries of a snapshot returned by `export_entries` to the table.
+    /// Aborts if the table is not empty, or if the snapshot is malformed.
+    public(friend) fun import_entries<K: copy + drop, V>(table_handle: TableHandle, data: vector<u8>) {
+        import_box<K, V>(table_handle, data)
+    }
+
+    /// Returns the value which `key` maps to, inserting the pair (`key`, `default`) first if there is no entry for `key`.
+    /// Unlike `upsert`, an existing value is never overwritten.
+    public(friend) fun get_or_insert<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K, default: V): V {
+        get_or_insert_box<K, V>(table_handle, key, default)
+    }
+
+    /// Add an entry for each of the `keys`, all mapping to a copy of `val`.
+    /// Aborts if any of the `keys` already exists or `keys` contains duplicates, no entry is added then.
+    public(friend) fun add_many_same<K: copy + drop, V: copy + drop>(table_handle: TableHandle, keys: vector<K>, val: V) {
+        add_box_many_same<K, V>(table_handle, keys, val)
+    }
+
+    /// Returns the operations of the current transaction on the table, which are committed to the change set
+    /// with the transaction, in ascending order of the key bytes. A value changed back to its loaded content has no operation.
+    public(friend) fun pending_ops(table_handle: TableHandle): vector<TableOp> {
+        let (tags, keys, values) = pending_ops_box(table_handle);
+        let ops = vector::empty<TableOp>();
+        vector::reverse(&mut tags);
+        vector::reverse(&mut keys);
+        vector::reverse(&mut values);
+        while (!vector::is_empty(&tags)) {
+            vector::push_back(&mut ops, TableOp {
+                tag: vector::pop_back(&mut tags),
+                key: vector::pop_back(&mut keys),
+                value: vector::pop_back(&mut values),
+            });
+        };
+        ops
+    }
+
+    /// Returns the tag, the key bytes and the value bytes of the operation.
+    public(friend) fun unpack_table_op(op: TableOp): (u8, vector<u8>, vector<u8>) {
+        let TableOp { tag, key, value } = op;
+        (tag, key, value)
+    }
+
+    /// Move `amount` from the `u64` value which `from_key` maps to, to the `u64` value which `to_key` maps to.
+    /// Aborts if there is no entry for either key, if the value of `from_key` is less than `amount`,
+    /// or if the value of `to_key` would overflow. Either both values are updated or none.
+    public(friend) fun transfer_u64<K: copy + drop>(table_handle: TableHandle, from_key: K, to_key: K, amount: u64) {
+        transfer_u64_box<K>(table_handle, from_key, to_key, amount)
+    }
+
+    /// Returns true if the current session is read-only, such as a view function call.
+    public(friend) fun is_readonly_context(): bool {
+        is_readonly_context_box()
+    }
+
+    /// Opt the table in to persist the key inserted last, see `last_inserted`.
+    public(friend) fun enable_last_inserted<K: copy + drop>(table_handle: TableHandle) {
+        enable_last_inserted_box<K>(table_handle)
+    }
+
+    /// Returns the entry inserted last into the table, or (none, none) if there is no such entry.
+    /// The key inserted last is persisted only for a table opted in by `enable_last_inserted`, the entries
+    /// inserted before the opt-in are not considered, and a removed entry is not replaced by the one inserted before it.
+    public(friend) fun last_inserted<K: copy + drop, V: copy + drop>(table_handle: TableHandle): (Option<K>, Option<V>) {
+        let (keys, vals) = last_inserted_box<K, V>(table_handle);
+        if (vector::is_empty(&keys)) {
+            (option::none(), option::none())
+        } else {
+            (option::some(vector::pop_back(&mut keys)), option::some(vector::pop_back(&mut vals)))
+        }
+    }
+
+    /// Move the entry of `key` from the table `from_handle` to the table `to_handle`.
+    /// The stored bytes are transferred as is, so the destination keeps the exact encoding of the source.
+    /// Aborts if there is no entry for `key` in the source table, or if the destination table already has one.
+    public(friend) fun move_entry<K: copy + drop, V>(from_handle: TableHandle, to_handle: TableHandle, key: K) {
+        move_box<K, V>(from_handle, to_handle, key)
+    }
+
+    /// Returns a `TableOp` which adds the entry (`key`, `value`), to be applied by `batch_ops`.
+    public(friend) fun new_op<K: copy + drop, V: drop>(key: K, value: V): TableOp {
+        TableOp { tag: TABLE_OP_NEW, key: bcs::to_bytes(&key), value: bcs::to_bytes(&value) }
+    }
+
+    /// Returns a `TableOp` which replaces the value of `key` with `value`, to be applied by `batch_ops`.
+    public(friend) fun modify_op<K: copy + drop, V: drop>(key: K, value: V): TableOp {
+        TableOp { tag: TABLE_OP_MODIFY, key: bcs::to_bytes(&key), value: bcs::to_bytes(&value) }
+    }
+
+    /// Returns a `TableOp` which removes the entry of `key`, to be applied by `batch_ops`.
+    public(friend) fun delete_op<K: copy + drop>(key: K): TableOp {
+        TableOp { tag: TABLE_OP_DELETE, key: bcs::to_bytes(&key), value: vector::empty() }
+    }
+
+    /// Apply all the `ops` to the table. The whole batch is checked first, so either all the operations are applied or none.
+    /// Aborts if a key has more than one operation, a new key already exists, a modified or deleted key does not exist,
+    /// or an operation does not match the key and value types of the table.
+    public(friend) fun batch_ops<K: copy + drop, V>(table_handle: TableHandle, ops: vector<TableOp>) {
+        let tags = vector::empty<u8>();
+        let keys = vector::empty<vector<u8>>();
+        let values = vector::empty<vector<u8>>();
+        vector::reverse(&mut ops);
+        while (!vector::is_empty(&ops)) {
+            let TableOp { tag, key, value } = vector::pop_back(&mut ops);
+            vector::push_back(&mut tags, tag);
+            vector::push_back(&mut keys, key);
+            vector::push_back(&mut values, value);
+        };
+        batch_ops_box<K, V>(table_handle, tags, keys, values)
+    }
+
+    /// Reserve a table handle without creating the table, and return its id. The table is created by
+    /// the first `add` to the handle. The handle is derived from the transaction hash and the number of
+    /// handles reserved before in the session, so reserving twice yields distinct handles.
+    public(friend) fun reserve_handle(): address {
+        reserve_handle_box()
+    }
+
+    /// Add `val` to a table with `u64` keys under the next sequence number, and return the number.
+    /// The numbers start from 0 and the number of a removed entry is never reused.
+    public(friend) fun push<V>(table_handle: TableHandle, val: V): u64 {
+        push_box<V>(table_handle, val)
+    }
+
+    /// Add an entry for `key` mapping to `val` only if the table has no entries.
+    /// Returns true if the entry is added, false if the table is not empty, leaving it unchanged.
+    public(friend) fun init_once<K: copy + drop, V: drop>(table_handle: TableHandle, key: K, val: V): bool {
+        init_box<K, V>(table_handle, key, val)
+    }
+
+    /// Returns a read of the entry of `key` in the table, for `multi_read`.
+    public(friend) fun new_table_read<K: copy + drop>(table_handle: TableHandle, key: K): TableRead {
+        TableRead { handle: table_handle, key: bcs::to_bytes(&key) }
+    }
+
+    /// Returns the BCS bytes of the value of each read in the request order, None if there is no entry.
+    /// All the reads see the same state of the tables.
+    public(friend) fun multi_read(reads: vector<TableRead>): vector<Option<vector<u8>>> {
+        let handles = vector::empty<address>();
+        let keys = vector::empty<vector<u8>>();
+        let i = 0;
+        let len = vector::length(&reads);
+        while (i < len) {
+            let read = vector::borrow(&reads, i);
+            vector::push_back(&mut handles, read.handle.id);
+            vector::push_back(&mut keys, read.key);
+            i = i + 1;
+        };
+        let (found, values) = multi_read_box(handles, keys);
+        let results = vector::empty<Option<vector<u8>>>();
+        vector::reverse(&mut found);
+        vector::reverse(&mut values);
+        while (!vector::is_empty(&found)) {
+            let value = vector::pop_back(&mut values);
+            if (vector::pop_back(&mut found)) {
+                vector::push_back(&mut results, option::some(value));
+            } else {
+                vector::push_back(&mut results, option::none());
+            }
+        };
+        results
+    }
+
+    /// Add an entry for `key` mapping to `val`, creating the table first if it does not exist.
+    /// Aborts if the table exists and has an entry for `key`.
+    public(friend) fun create_and_add<K: copy + drop, V>(table_handle: TableHandle, key: K, val: V) {
+        create_and_add_box<K, V, Box<V>>(table_handle, key, Box {val})
+    }
+
+    /// Returns the BCS bytes of the `MoveTypeLayout` of the keys and of the values of the table, from the types
+    /// kept when the table is created. The value layout is empty if the value type is not known, such as for a
+    /// table without values. Aborts if the table does not exist or its key type is not known.
+    public(friend) fun table_layouts(table_handle: TableHandle): (vector<u8>, vector<u8>) {
+        table_layouts_box(table_handle)
+    }
+
+    /// Flip the `bool` value of `key` and return the new value.
+    /// Aborts if there is no entry for `key`.
+    public(friend) fun toggle_bool<K: copy + drop>(table_handle: TableHandle, key: K): bool {
+        toggle_bool_box<K>(table_handle, key)
+    }
+
+    /// Returns a copy of the value which `key` maps to if its version equals `expected_version`, otherwise none.
+    /// The version is the leading `u64` of the BCS bytes of the value, such as the first field of a struct,
+    /// carrying it is the caller's responsibility. Returns none if there is no entry for `key`.
+    public(friend) fun borrow_if_version<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K, expected_version: u64): Option<V> {
+        let values = borrow_if_version_box<K, V>(table_handle, key, expected_version);
+        if (vector::is_empty(&values)) {
+            option::none()
+        } else {
+            option::some(vector::pop_back(&mut values))
+        }
+    }
+
+    /// Merge `operand` into the value which `key` maps to by adding it, or add `operand` if there is no entry for `key`.
+    /// Aborts if the sum exceeds the max `u64`.
+    public(friend) fun merge_sum_u64<K: copy + drop>(table_handle: TableHandle, key: K, operand: u64) {
+        merge_box<K, u64>(table_handle, key, REDUCER_SUM_U64, operand)
+    }
+
+    /// Merge `operand` into the value which `key` maps to by keeping the larger, or add `operand` if there is no entry for `key`.
+    public(friend) fun merge_max_u64<K: copy + drop>(table_handle: TableHandle, key: K, operand: u64) {
+        merge_box<K, u64>(table_handle, key, REDUCER_MAX_U64, operand)
+    }
+
+    /// Merge `operand` into the value which `key` maps to by keeping the smaller, or add `operand` if there is no entry for `key`.
+    public(friend) fun merge_min_u64<K: copy + drop>(table_handle: TableHandle, key: K, operand: u64) {
+        merge_box<K, u64>(table_handle, key, REDUCER_MIN_U64, operand)
+    }
+
+    /// Merge `operand` into the value which `key` maps to by appending it, or add `operand` if there is no entry for `key`.
+    public(friend) fun merge_append_bytes<K: copy + drop>(table_handle: TableHandle, key: K, operand: vector<u8>) {
+        merge_box<K, vector<u8>>(table_handle, key, REDUCER_APPEND_BYTES, operand)
+    }
+
+    /// Remove all the entries and the table if the version of the sentinel entry of `sentinel_key` equals
+    /// `expected_version`, returns whether the table is destroyed. The version is the leading `u64` of the
+    /// sentinel value, as in `borrow_if_version`. Returns false without changes if there is no sentinel entry
+    /// or its version does not match. Aborts if the table has more than 10000 entries.
+    public(friend) fun destroy_if_version<K: copy + drop>(table_handle: TableHandle, sentinel_key: K, expected_version: u64): bool {
+        destroy_if_version_box<K>(table_handle, sentinel_key, expected_version)
+    }
+
+    /// Returns the `keys` which have no entry in `table`, in the same order as `keys`.
+    public(friend) fun missing_keys<K: copy + drop>(table_handle: TableHandle, keys: vector<K>): vector<K> {
+        missing_keys_box<K>(table_handle, keys)
+    }
+
+    /// Store `candidate` as the value which `key` maps to if it is greater than the current value or there is
+    /// no entry for `key`, returns whether the value is updated.
+    public(friend) fun set_if_greater_u64<K: copy + drop>(table_handle: TableHandle, key: K, candidate: u64): bool {
+        set_if_greater_u64_box<K>(table_handle, key, candidate)
+    }
+
+    /// Returns a copy of the value which `key` maps to, none if there is no entry for `key`, and whether the
+    /// entry was loaded from the storage by this call (cold) rather than already cached in the transaction (warm).
+    /// The flag is informational, the read charges as `borrow`.
+    public(friend) fun borrow_with_source<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K): (Option<V>, bool) {
+        let (values, cold) = borrow_box_with_source<K, V>(table_handle, key);
+        if (vector::is_empty(&values)) {
+            (option::none(), cold)
+        } else {
+            (option::some(vector::pop_back(&mut values)), cold)
+        }
+    }
+
+    /// Remove all the entries whose BCS serialized key starts with `prefix`, returns the number of removed entries.
+    /// Both the persisted and the in-memory entries are removed, the gas scales with the entries of the table.
+    /// Aborts if the table has more than 10000 entries.
+    public(friend) fun remove_prefix<K: copy + drop>(table_handle: TableHandle, prefix: vector<u8>): u64 {
+        remove_prefix_box<K>(table_handle, prefix)
+    }
+
+    /// Add `val` to a table with `u64` keys under the next sequence number, as `push`, then remove and return the
+    /// entry with the min key if the table holds more than `capacity` entries, otherwise none. At most one entry
+    /// is evicted per push.
+    public(friend) fun push_bounded<V>(table_handle: TableHandle, val: V, capacity: u64): (Option<u64>, Option<V>) {
+        let (keys, vals) = push_bounded_box<V>(table_handle, val, capacity);
+        if (vector::is_empty(&keys)) {
+            vector::destroy_empty(vals);
+            (option::none(), option::none())
+        } else {
+            let val = vector::pop_back(&mut vals);
+            vector::destroy_empty(vals);
+            (option::some(vector::pop_back(&mut keys)), option::some(val))
+        }
+    }
+
+    /// Returns the total bytes of the keys and the values of the table, including the changes of the current
+    /// transaction, none if the storage backend can not report it.
+    public(friend) fun table_bytes(table_handle: TableHandle): Option<u64> {
+        let bytes = table_bytes_box(table_handle);
+        if (vector::is_empty(&bytes)) {
+            option::none()
+        } else {
+            option::some(vector::pop_back(&mut bytes))
+        }
+    }
+
+    /// Store `owner` as the owner of `key` if `key` is unclaimed, first claim wins.
+    /// Returns none if the claim succeeds, otherwise the current owner, the table is unchanged.
+    public(friend) fun claim_slot<K: copy + drop>(table_handle: TableHandle, key: K, owner: address): Option<address> {
+        let owners = claim_slot_box<K>(table_handle, key, owner);
+        if (vector::is_empty(&owners)) {
+            option::none()
+        } else {
+            option::some(vector::pop_back(&mut owners))
+        }
+    }
+
+    /// Opt the table in to record the tx order of the last modification of each entry.
+    /// The records are kept apart from the values, in the metadata of the table, and grow with the entries.
+    public(friend) fun enable_last_modified<K: copy + drop>(table_handle: TableHandle) {
+        enable_last_modified_box<K>(table_handle)
+    }
+
+    /// Returns the tx order of the last modification of `key`, none if there is no entry for `key`, the table
+    /// is not opted in, or the entry is not modified since. The current transaction is recorded when it is applied.
+    public(friend) fun last_modified<K: copy + drop>(table_handle: TableHandle, key: K): Option<u64> {
+        let orders = last_modified_box<K>(table_handle, key);
+        if (vector::is_empty(&orders)) {
+            option::none()
+        } else {
+            option::some(vector::pop_back(&mut orders))
+        }
+    }
+
+    /// Write `val` at the slot `slot % capacity` of the table, returns the value displaced from the slot,
+    /// none if the slot was empty. Aborts if `capacity` is 0.
+    public(friend) fun rotate<V>(table_handle: TableHandle, slot: u64, val: V, capacity: u64): Option<V> {
+        let displaced = rotate_box<V>(table_handle, slot, val, capacity);
+        if (vector::is_empty(&displaced)) {
+            vector::destroy_empty(displaced);
+            option::none()
+        } else {
+            let val = vector::pop_back(&mut displaced);
+            vector::destroy_empty(displaced);
+            option::some(val)
+        }
+    }
+
+    /// Add each of the `deltas` to the `u64` value of the key at the same position, returns the new values.
+    /// Either all the increments are applied or the call aborts.
+    public(friend) fun increment_many_u64<K: copy + drop>(table_handle: TableHandle, keys: vector<K>, deltas: vector<u64>): vector<u64> {
+        increment_many_u64_box<K>(table_handle, keys, deltas)
+    }
+
+    /// Returns a copy of the value which `key` maps to, only if the type stored with the value is `expected_tag`
+    /// and `V`, `expected_tag` is the canonical type name as `std::type_name`. Returns none if there is no entry
+    /// for `key` or the types do not match.
+    public(friend) fun borrow_typed<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K, expected_tag: vector<u8>): Option<V> {
+        let values = borrow_box_typed<K, V>(table_handle, key, expected_tag);
+        if (vector::is_empty(&values)) {
+            option::none()
+        } else {
+            option::some(vector::pop_back(&mut values))
+        }
+    }
+
     // ======================================================================================================
     // Internal API
     
@@ -145,4 +618,92 @@ module moveos_std::raw_table {
     native fun drop_unchecked_box(table_handle: TableHandle);
 
     native fun box_length(table_handle: TableHandle): u64;
+
+    native fun replace_all_box<K: copy + drop, V, B>(table_handle: TableHandle, keys: vector<K>, vals: vector<Box<V>>);
+
+    native fun contains_boxes<K: copy + drop>(table_handle: TableHandle, keys: vector<K>): vector<bool>;
+
+    native fun content_root_box<K: copy + drop>(table_handle: TableHandle): vector<u8>;
+
+    native fun decrement_u64_box<K: copy + drop>(table_handle: TableHandle, key: K, delta: u64): u64;
+
+    native fun saturating_decrement_u64_box<K: copy + drop>(table_handle: TableHandle, key: K, delta: u64): u64;
+
+    native fun new_table_count_box(): u64;
+
+    native fun iter_next_entry_box<K: copy + drop, V: copy + drop>(table_handle: TableHandle, cursor: vector<K>): (vector<K>, vector<V>);
+
+    native fun content_proof_box<K: copy + drop>(table_handle: TableHandle, key: K): vector<vector<u8>>;
+
+    native fun is_empty_box(table_handle: TableHandle): bool;
+
+    native fun dirty_keys_box(table_handle: TableHandle): vector<vector<u8>>;
+
+    native fun cas_box<K: copy + drop, V: drop>(table_handle: TableHandle, key: K, expected: V, new: V): bool;
+
+    native fun export_box<K: copy + drop, V>(table_handle: TableHandle): vector<u8>;
+
+    native fun import_box<K: copy + drop, V>(table_handle: TableHandle, data: vector<u8>);
+
+    native fun get_or_insert_box<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K, default: V): V;
+
+    native fun add_box_many_same<K: copy + drop, V: copy + drop>(table_handle: TableHandle, keys: vector<K>, val: V);
+
+    native fun pending_ops_box(table_handle: TableHandle): (vector<u8>, vector<vector<u8>>, vector<vector<u8>>);
+
+    native fun transfer_u64_box<K: copy + drop>(table_handle: TableHandle, from_key: K, to_key: K, amount: u64);
+
+    native fun is_readonly_context_box(): bool;
+
+    native fun last_inserted_box<K: copy + drop, V: copy + drop>(table_handle: TableHandle): (vector<K>, vector<V>);
+
+    native fun enable_last_inserted_box<K: copy + drop>(table_handle: TableHandle);
+
+    native fun move_box<K: copy + drop, V>(from_handle: TableHandle, to_handle: TableHandle, key: K);
+
+    native fun batch_ops_box<K: copy + drop, V>(table_handle: TableHandle, tags: vector<u8>, keys: vector<vector<u8>>, values: vector<vector<u8>>);
+
+    native fun reserve_handle_box(): address;
+
+    native fun push_box<V>(table_handle: TableHandle, val: V): u64;
+
+    native fun init_box<K: copy + drop, V: drop>(table_handle: TableHandle, key: K, val: V): bool;
+
+    native fun multi_read_box(handles: vector<address>, keys: vector<vector<u8>>): (vector<bool>, vector<vector<u8>>);
+
+    native fun create_and_add_box<K: copy + drop, V, B>(table_handle: TableHandle, key: K, val: Box<V>);
+
+    native fun table_layouts_box(table_handle: TableHandle): (vector<u8>, vector<u8>);
+
+    native fun toggle_bool_box<K: copy + drop>(table_handle: TableHandle, key: K): bool;
+
+    native fun borrow_if_version_box<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K, expected_version: u64): vector<V>;
+
+    native fun merge_box<K: copy + drop, V: drop>(table_handle: TableHandle, key: K, reducer: u8, operand: V);
+
+    native fun destroy_if_version_box<K: copy + drop>(table_handle: TableHandle, sentinel_key: K, expected_version: u64): bool;
+
+    native fun missing_keys_box<K: copy + drop>(table_handle: TableHandle, keys: vector<K>): vector<K>;
+
+    native fun set_if_greater_u64_box<K: copy + drop>(table_handle: TableHandle, key: K, candidate: u64): bool;
+
+    native fun borrow_box_with_source<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K): (vector<V>, bool);
+
+    native fun remove_prefix_box<K: copy + drop>(table_handle: TableHandle, prefix: vector<u8>): u64;
+
+    native fun push_bounded_box<V>(table_handle: TableHandle, val: V, capacity: u64): (vector<u64>, vector<V>);
+
+    native fun table_bytes_box(table_handle: TableHandle): vector<u64>;
+
+    native fun claim_slot_box<K: copy + drop>(table_handle: TableHandle, key: K, owner: address): vector<address>;
+
+    native fun enable_last_modified_box<K: copy + drop>(table_handle: TableHandle);
+
+    native fun last_modified_box<K: copy + drop>(table_handle: TableHandle, key: K): vector<u64>;
+
+    native fun rotate_box<V>(table_handle: TableHandle, slot: u64, val: V, capacity: u64): vector<V>;
+
+    native fun increment_many_u64_box<K: copy + drop>(table_handle: TableHandle, keys: vector<K>, deltas: vector<u64>): vector<u64>;
+
+    native fun borrow_box_typed<K: copy + drop, V: copy + drop>(table_handle: TableHandle, key: K, expected_tag: vector<u8>): vector<V>;
 }
@@ -9,7 +9,8 @@
 /// struct itself, while the operations are implemented as native functions. No traversal is provided.
 
 module moveos_std::table {
-    use moveos_std::raw_table;
+    use std::option::{Self, Option};
+    use moveos_std::raw_table::{Self, TableOp, TableRead};
     use moveos_std::object::{Self, UID, ObjectID};
 
     friend moveos_std::account_storage;
@@ -41,6 +42,12 @@ module moveos_std::table {
         raw_table::add<K, V>(object::object_id_to_table_handle(table.handle), key, val)
     }
 
+    /// Add an entry for each of the `keys`, all mapping to a copy of `val`.
+    /// Aborts if any of the `keys` already exists or `keys` contains duplicates, no entry is added then.
+    public fun add_many_same<K: copy + drop, V: copy + drop>(table: &mut Table<K, V>, keys: vector<K>, val: V) {
+        raw_table::add_many_same<K, V>(object::object_id_to_table_handle(table.handle), keys, val)
+    }
+
     /// Acquire an immutable reference to the value which `key` maps to.
     /// Aborts if there is no entry for `key`.
     public fun borrow<K: copy + drop, V>(table: &Table<K, V>, key: K): &V {
@@ -71,6 +78,249 @@ module moveos_std::table {
         raw_table::upsert<K, V>(object::object_id_to_table_handle(table.handle), key, value)
     }
 
+    /// Replace the value which `key` maps to with `new` if it currently equals `expected`.
+    /// Returns true if the value is replaced, false if it does not equal `expected` or there is no entry for `key`.
+    public fun compare_and_swap<K: copy + drop, V: drop>(table: &mut Table<K, V>, key: K, expected: V, new: V): bool {
+        raw_table::compare_and_swap<K, V>(object::object_id_to_table_handle(table.handle), key, expected, new)
+    }
+
+    /// Returns a snapshot of all the entries of `table`, which can be restored by `import_entries`.
+    /// The snapshot is deterministic, the cost is proportional to the table size.
+    /// Aborts if the table has more than 10000 entries.
+    public fun export_entries<K: copy + drop, V>(table: &Table<K, V>): vector<u8> {
+        raw_table::export_entries<K, V>(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Add all the entries of a snapshot returned by `export_entries` to `table`.
+    /// Aborts if `table` is not empty, or if the snapshot is malformed.
+    public fun import_entries<K: copy + drop, V>(table: &mut Table<K, V>, data: vector<u8>) {
+        raw_table::import_entries<K, V>(object::object_id_to_table_handle(table.handle), data)
+    }
+
+    /// Returns the value which `key` maps to, inserting the pair (`key`, `default`) first if there is no entry for `key`.
+    /// Unlike `upsert`, an existing value is never overwritten.
+    public fun get_or_insert<K: copy + drop, V: copy + drop>(table: &mut Table<K, V>, key: K, default: V): V {
+        raw_table::get_or_insert<K, V>(object::object_id_to_table_handle(table.handle), key, default)
+    }
+
+    /// Move `amount` from the value which `from_key` maps to, to the value which `to_key` maps to.
+    /// Aborts if there is no entry for either key, if the value of `from_key` is less than `amount`,
+    /// or if the value of `to_key` would overflow. Either both values are updated or none.
+    public fun transfer_u64<K: copy + drop>(table: &mut Table<K, u64>, from_key: K, to_key: K, amount: u64) {
+        raw_table::transfer_u64<K>(object::object_id_to_table_handle(table.handle), from_key, to_key, amount)
+    }
+
+    /// Opt `table` in to persist the key inserted last, so `last_inserted` sees the entries inserted by
+    /// the earlier transactions. It is opt-in because every insertion writes the key alongside the value.
+    public fun enable_last_inserted<K: copy + drop, V>(table: &mut Table<K, V>) {
+        raw_table::enable_last_inserted<K>(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Returns the entry inserted last into `table`, or (none, none) if there is no such entry.
+    /// Only the entries inserted since `table` is opted in by `enable_last_inserted` are considered.
+    public fun last_inserted<K: copy + drop, V: copy + drop>(table: &Table<K, V>): (Option<K>, Option<V>) {
+        raw_table::last_inserted<K, V>(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Move the entry of `key` from `from_table` to `to_table`, the stored bytes are transferred as is.
+    /// Aborts if there is no entry for `key` in `from_table`, or if `to_table` already has one.
+    public fun move_entry<K: copy + drop, V>(from_table: &mut Table<K, V>, to_table: &mut Table<K, V>, key: K) {
+        raw_table::move_entry<K, V>(
+            object::object_id_to_table_handle(from_table.handle),
+            object::object_id_to_table_handle(to_table.handle),
+            key,
+        )
+    }
+
+    /// Returns an operation for `batch_ops` which adds the entry (`key`, `value`).
+    public fun new_op<K: copy + drop, V: drop>(key: K, value: V): TableOp {
+        raw_table::new_op(key, value)
+    }
+
+    /// Returns an operation for `batch_ops` which replaces the value of `key` with `value`.
+    public fun modify_op<K: copy + drop, V: drop>(key: K, value: V): TableOp {
+        raw_table::modify_op(key, value)
+    }
+
+    /// Returns an operation for `batch_ops` which removes the entry of `key`.
+    public fun delete_op<K: copy + drop>(key: K): TableOp {
+        raw_table::delete_op(key)
+    }
+
+    /// Apply all the `ops` to `table`, either all of them or none.
+    /// Aborts if a key has more than one operation, a new key already exists, a modified or deleted key does not exist,
+    /// or an operation is not built for the key and value types of `table`.
+    public fun batch_ops<K: copy + drop, V>(table: &mut Table<K, V>, ops: vector<TableOp>) {
+        raw_table::batch_ops<K, V>(object::object_id_to_table_handle(table.handle), ops)
+    }
+
+    /// Add `val` to `table` under the next sequence number, and return the number. The numbers start from 0,
+    /// the number of a removed entry is never reused, and the keys added after a push in the same transaction are skipped.
+    /// Aborts if the number is taken by a key added in an earlier transaction.
+    public fun push<V>(table: &mut Table<u64, V>, val: V): u64 {
+        raw_table::push<V>(object::object_id_to_table_handle(table.handle), val)
+    }
+
+    /// Add an entry for `key` mapping to `val` only if `table` has no entries, the first write wins and only once.
+    /// Returns true if the entry is added, false if `table` is not empty, leaving it unchanged.
+    public fun init_once<K: copy + drop, V: drop>(table: &mut Table<K, V>, key: K, val: V): bool {
+        raw_table::init_once<K, V>(object::object_id_to_table_handle(table.handle), key, val)
+    }
+
+    /// Returns a read of the entry of `key` in `table`, for `multi_read`.
+    public fun table_read<K: copy + drop, V>(table: &Table<K, V>, key: K): TableRead {
+        raw_table::new_table_read<K>(object::object_id_to_table_handle(table.handle), key)
+    }
+
+    /// Returns the BCS bytes of the value of each read, across any tables, in the request order,
+    /// None if there is no entry. All the reads see the same state of the tables.
+    public fun multi_read(reads: vector<TableRead>): vector<Option<vector<u8>>> {
+        raw_table::multi_read(reads)
+    }
+
+    /// Add an entry for `key` mapping to `val`, creating `table` first if it does not exist yet,
+    /// for the first write to a lazily created child table. Aborts if `table` has an entry for `key`.
+    public fun create_and_add<K: copy + drop, V>(table: &mut Table<K, V>, key: K, val: V) {
+        raw_table::create_and_add<K, V>(object::object_id_to_table_handle(table.handle), key, val)
+    }
+
+    /// Returns the BCS bytes of the `MoveTypeLayout` of the keys and of the values of `table`, for generic
+    /// off-chain decoding. The value layout is empty if `table` has no values.
+    /// Aborts if `table` is not created yet.
+    public fun layouts<K: copy + drop, V>(table: &Table<K, V>): (vector<u8>, vector<u8>) {
+        raw_table::table_layouts(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Flip the flag of `key` in `table` and return the new value.
+    /// Aborts if there is no entry for `key`.
+    public fun toggle<K: copy + drop>(table: &mut Table<K, bool>, key: K): bool {
+        raw_table::toggle_bool<K>(object::object_id_to_table_handle(table.handle), key)
+    }
+
+    /// Returns a copy of the value which `key` maps to if its version equals `expected_version`, otherwise none,
+    /// for the reads which cache a value until its version changes. The version is the leading `u64` of the
+    /// value, such as the first field of a struct, keeping it up to date is the caller's responsibility.
+    public fun borrow_if_version<K: copy + drop, V: copy + drop>(table: &Table<K, V>, key: K, expected_version: u64): Option<V> {
+        raw_table::borrow_if_version<K, V>(object::object_id_to_table_handle(table.handle), key, expected_version)
+    }
+
+    /// Add `amount` to the value of `key` in `table`, or add `amount` as the value if there is no entry for `key`.
+    /// Aborts if the sum exceeds the max `u64`.
+    public fun merge_sum<K: copy + drop>(table: &mut Table<K, u64>, key: K, amount: u64) {
+        raw_table::merge_sum_u64<K>(object::object_id_to_table_handle(table.handle), key, amount)
+    }
+
+    /// Keep the larger of `value` and the value of `key` in `table`, or add `value` if there is no entry for `key`.
+    public fun merge_max<K: copy + drop>(table: &mut Table<K, u64>, key: K, value: u64) {
+        raw_table::merge_max_u64<K>(object::object_id_to_table_handle(table.handle), key, value)
+    }
+
+    /// Keep the smaller of `value` and the value of `key` in `table`, or add `value` if there is no entry for `key`.
+    public fun merge_min<K: copy + drop>(table: &mut Table<K, u64>, key: K, value: u64) {
+        raw_table::merge_min_u64<K>(object::object_id_to_table_handle(table.handle), key, value)
+    }
+
+    /// Append `bytes` to the value of `key` in `table`, or add `bytes` as the value if there is no entry for `key`.
+    public fun merge_append<K: copy + drop>(table: &mut Table<K, vector<u8>>, key: K, bytes: vector<u8>) {
+        raw_table::merge_append_bytes<K>(object::object_id_to_table_handle(table.handle), key, bytes)
+    }
+
+    /// Destroy a possibly non-empty table if the version of the sentinel entry of `sentinel_key` equals
+    /// `expected_version`, so a table changed since the caller's last read is not destroyed by accident.
+    /// The version is the leading `u64` of the sentinel value, as in `borrow_if_version`.
+    /// Returns none if the table is destroyed, otherwise returns the table back unchanged.
+    /// Aborts if the table has more than 10000 entries.
+    public fun destroy_if_version<K: copy + drop, V: drop>(table: Table<K, V>, sentinel_key: K, expected_version: u64): Option<Table<K, V>> {
+        if (raw_table::destroy_if_version<K>(object::object_id_to_table_handle(table.handle), sentinel_key, expected_version)) {
+            let Table { handle: _ } = table;
+            option::none()
+        } else {
+            option::some(table)
+        }
+    }
+
+    /// Returns the `keys` which have no entry in `table`, in the same order as `keys`,
+    /// to handle a partial presence before a batch of inserts.
+    public fun missing_keys<K: copy + drop, V>(table: &Table<K, V>, keys: vector<K>): vector<K> {
+        raw_table::missing_keys<K>(object::object_id_to_table_handle(table.handle), keys)
+    }
+
+    /// Store `candidate` as the value of `key` in `table` if it is greater than the current value or there is
+    /// no entry for `key`, returns whether the value is updated. A high-water mark, such as the highest bid.
+    public fun set_if_greater<K: copy + drop>(table: &mut Table<K, u64>, key: K, candidate: u64): bool {
+        raw_table::set_if_greater_u64<K>(object::object_id_to_table_handle(table.handle), key, candidate)
+    }
+
+    /// Returns a copy of the value which `key` maps to, none if there is no entry for `key`, and whether the
+    /// entry was loaded from the storage by this call (cold) rather than already read in this transaction (warm).
+    /// The flag is informational, for the caching layers reasoning about access costs, the read charges as `borrow`.
+    public fun borrow_with_source<K: copy + drop, V: copy + drop>(table: &Table<K, V>, key: K): (Option<V>, bool) {
+        raw_table::borrow_with_source<K, V>(object::object_id_to_table_handle(table.handle), key)
+    }
+
+    /// Remove all the entries of `table` whose BCS serialized key starts with `prefix`, returns the number
+    /// of removed entries. With a struct key led by the user address, the prefix of the address removes all
+    /// the items of the user at once. Aborts if the table has more than 10000 entries.
+    public fun remove_prefix<K: copy + drop, V: drop>(table: &mut Table<K, V>, prefix: vector<u8>): u64 {
+        raw_table::remove_prefix<K>(object::object_id_to_table_handle(table.handle), prefix)
+    }
+
+    /// Add `val` to `table` under the next sequence number, as `push`, then remove and return the oldest entry,
+    /// the one with the min key, if `table` holds more than `capacity` entries, otherwise none.
+    /// Keeps the last `capacity` values of a bounded log without manual bookkeeping.
+    public fun push_bounded<V>(table: &mut Table<u64, V>, val: V, capacity: u64): (Option<u64>, Option<V>) {
+        raw_table::push_bounded<V>(object::object_id_to_table_handle(table.handle), val, capacity)
+    }
+
+    /// Returns the bytes `table` occupies in the storage, the total of the BCS bytes of its keys and values
+    /// including the changes of the current transaction, for the storage rent or the quotas.
+    /// Returns none if the storage backend can not report it.
+    public fun table_bytes<K: copy + drop, V>(table: &Table<K, V>): Option<u64> {
+        raw_table::table_bytes(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Store `owner` as the owner of `key` in `table` if `key` is unclaimed, the first claim wins.
+    /// Returns none if the claim succeeds, otherwise the current owner, `table` is unchanged.
+    /// For the first-come-first-served registrations, such as a name service.
+    public fun claim_slot<K: copy + drop>(table: &mut Table<K, address>, key: K, owner: address): Option<address> {
+        raw_table::claim_slot<K>(object::object_id_to_table_handle(table.handle), key, owner)
+    }
+
+    /// Opt `table` in to record the tx order of the last modification of each entry, for the audit trails.
+    /// It is opt-in because the records take storage alongside the values.
+    public fun enable_last_modified<K: copy + drop, V>(table: &mut Table<K, V>) {
+        raw_table::enable_last_modified<K>(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Returns the tx order of the last modification of `key` in `table`, none if there is no entry for `key`,
+    /// `table` is not opted in, or the entry is not modified since it is opted in.
+    /// The modifications of the current transaction are recorded when it is applied, so they are not seen yet.
+    public fun last_modified<K: copy + drop, V>(table: &Table<K, V>, key: K): Option<u64> {
+        raw_table::last_modified<K>(object::object_id_to_table_handle(table.handle), key)
+    }
+
+    /// Write `val` at the slot `slot % capacity` of `table` and return the value displaced from the slot,
+    /// none if the slot was empty. A direct-mapped cache of `capacity` slots without Move bookkeeping.
+    /// Aborts if `capacity` is 0.
+    public fun rotate<V>(table: &mut Table<u64, V>, slot: u64, val: V, capacity: u64): Option<V> {
+        raw_table::rotate<V>(object::object_id_to_table_handle(table.handle), slot, val, capacity)
+    }
+
+    /// Add each of the `deltas` to the counter of the key at the same position in `table`, and return the new
+    /// counters in the order of the keys, for the batched updates such as a leaderboard.
+    /// Aborts without any change if the lengths differ, a key has no entry, or a counter overflows.
+    public fun increment_many<K: copy + drop>(table: &mut Table<K, u64>, keys: vector<K>, deltas: vector<u64>): vector<u64> {
+        raw_table::increment_many_u64<K>(object::object_id_to_table_handle(table.handle), keys, deltas)
+    }
+
+    /// Returns a copy of the value which `key` maps to, only if the value is stored with the type `V`.
+    /// Returns none if there is no entry for `key` or the stored value has another type, such as a value
+    /// imported or moved in from a table of another value type, instead of aborting on the type mismatch.
+    public fun borrow_typed<K: copy + drop, V: copy + drop>(table: &Table<K, V>, key: K): Option<V> {
+        let expected_tag = std::ascii::into_bytes(std::type_name::into_string(std::type_name::get<V>()));
+        raw_table::borrow_typed<K, V>(object::object_id_to_table_handle(table.handle), key, expected_tag)
+    }
+
     /// Remove from `table` and return the value which `key` maps to.
     /// Aborts if there is no entry for `key`.
     public fun remove<K: copy + drop, V>(table: &mut Table<K, V>, key: K): V {
@@ -82,6 +332,42 @@ module moveos_std::table {
         raw_table::contains<K>(object::object_id_to_table_handle(table.handle), key)
     }
 
+    /// Returns whether `table` contains an entry for each of the `keys`, in the same order as `keys`.
+    public fun contains_all<K: copy + drop, V>(table: &Table<K, V>, keys: vector<K>): vector<bool> {
+        raw_table::contains_all<K>(object::object_id_to_table_handle(table.handle), keys)
+    }
+
+    /// Subtract `delta` from the value which `key` maps to, and return the new value.
+    /// Aborts if there is no entry for `key`, or if the value is less than `delta`.
+    public fun decrement_u64<K: copy + drop>(table: &mut Table<K, u64>, key: K, delta: u64): u64 {
+        raw_table::decrement_u64<K>(object::object_id_to_table_handle(table.handle), key, delta)
+    }
+
+    /// Subtract `delta` from the value which `key` maps to, flooring at zero, and return the new value.
+    /// Aborts if there is no entry for `key`.
+    public fun saturating_decrement_u64<K: copy + drop>(table: &mut Table<K, u64>, key: K, delta: u64): u64 {
+        raw_table::saturating_decrement_u64<K>(object::object_id_to_table_handle(table.handle), key, delta)
+    }
+
+    /// Returns the BCS bytes of the keys of `table` added, modified or removed earlier in the current transaction,
+    /// in ascending order of the key bytes. An entry changed back to its loaded content is not included.
+    /// The result only reflects the in-flight state of the transaction, it is discarded after the transaction.
+    public fun dirty_keys<K: copy + drop, V>(table: &Table<K, V>): vector<vector<u8>> {
+        raw_table::dirty_keys(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Returns the operations of the current transaction on `table`, which are committed with the transaction,
+    /// in ascending order of the key bytes. A value changed back to its loaded content has no operation.
+    public fun pending_ops<K: copy + drop, V>(table: &Table<K, V>): vector<TableOp> {
+        raw_table::pending_ops(object::object_id_to_table_handle(table.handle))
+    }
+
+    /// Returns the tag, the key bytes and the value bytes of the operation.
+    /// The tag is 0 for new, 1 for modify and 2 for delete, the value of a delete operation is empty.
+    public fun unpack_table_op(op: TableOp): (u8, vector<u8>, vector<u8>) {
+        raw_table::unpack_table_op(op)
+    }
+
     /// Destroy a table. Aborts if the table is not empty.
     public fun destroy_empty<K: copy + drop, V>(table: Table<K, V>) {
         let Table { handle } = table;
@@ -94,8 +380,9 @@ module moveos_std::table {
     }
 
     /// Returns true iff the table is empty (if `length` returns `0`)
+    /// The emptiness is checked without counting the entries, so it is cheaper than `length`.
     public fun is_empty<K: copy + drop, V>(table: &Table<K, V>): bool {
-        raw_table::length(object::object_id_to_table_handle(table.handle)) == 0
+        raw_table::is_empty(object::object_id_to_table_handle(table.handle))
     }
 
     /// Drop a possibly non-empty table.
@@ -105,6 +392,12 @@ module moveos_std::table {
         raw_table::drop_unchecked(object::object_id_to_table_handle(handle))
     }
 
+    /// Returns the number of tables created in the current transaction, not including the pre-existing ones.
+    /// A table is counted once it is first accessed in the transaction.
+    public fun new_table_count(): u64 {
+        raw_table::new_table_count()
+    }
+
 
     #[test_only]
     /// Testing only: allows to drop a table even if it is not empty.
@@ -140,6 +433,838 @@ module moveos_std::table {
         drop_unchecked(t);
     }
 
+    #[test(sender = @0x42)]
+    fun test_contains_all(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u8>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(contains_all(&t, vector[]) == vector[], error_code);
+        add(&mut t, 1, 10);
+        add(&mut t, 3, 30);
+        assert!(contains_all(&t, vector[3, 2, 1]) == vector[true, false, true], error_code);
+        remove(&mut t, 3);
+        assert!(contains_all(&t, vector[3, 1, 1]) == vector[false, true, true], error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_decrement_u64(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        assert!(decrement_u64(&mut t, 1, 3) == 7, error_code);
+        assert!(decrement_u64(&mut t, 1, 0) == 7, error_code);
+        assert!(decrement_u64(&mut t, 1, 7) == 0, error_code);
+        assert!(*borrow(&t, 1) == 0, error_code);
+        assert!(decrement_u64(&mut t, 1, 0) == 0, error_code);
+
+        drop_unchecked(t);
+    }
+
+    // The underflow aborts with `invalid_argument(E_UNDERFLOW)`
+    #[test(sender = @0x42)]
+    #[expected_failure(abort_code = 65542, location = moveos_std::raw_table)]
+    fun test_decrement_u64_underflow(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        add(&mut t, 1, 10);
+        decrement_u64(&mut t, 1, 11);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure(abort_code = 393218, location = moveos_std::raw_table)]
+    fun test_decrement_u64_not_found(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        decrement_u64(&mut t, 1, 1);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_saturating_decrement_u64(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        assert!(saturating_decrement_u64(&mut t, 1, 4) == 6, error_code);
+        assert!(saturating_decrement_u64(&mut t, 1, 11) == 0, error_code);
+        assert!(*borrow(&t, 1) == 0, error_code);
+        assert!(saturating_decrement_u64(&mut t, 1, 1) == 0, error_code);
+        assert!(saturating_decrement_u64(&mut t, 1, 0) == 0, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_is_empty(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(is_empty(&t), error_code);
+        add(&mut t, 1, 10);
+        assert!(!is_empty(&t), error_code);
+        remove(&mut t, 1);
+        assert!(is_empty(&t), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_dirty_keys(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(dirty_keys(&t) == vector[], error_code);
+        add(&mut t, 2, 20);
+        add(&mut t, 1, 10);
+        assert!(!contains(&t, 3), error_code);
+        assert!(dirty_keys(&t) == vector[std::bcs::to_bytes(&1u64), std::bcs::to_bytes(&2u64)], error_code);
+        // An entry added and removed in the transaction is not changed.
+        remove(&mut t, 2);
+        assert!(dirty_keys(&t) == vector[std::bcs::to_bytes(&1u64)], error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_pending_ops(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(std::vector::is_empty(&pending_ops(&t)), error_code);
+        add(&mut t, 2, 20);
+        add(&mut t, 1, 10);
+        add(&mut t, 3, 30);
+        remove(&mut t, 3);
+        let ops = pending_ops(&t);
+        assert!(std::vector::length(&ops) == 2, error_code);
+        let (tag, key, value) = unpack_table_op(std::vector::remove(&mut ops, 0));
+        assert!(tag == 0, error_code);
+        assert!(key == std::bcs::to_bytes(&1u64), error_code);
+        assert!(value == std::bcs::to_bytes(&10u64), error_code);
+        let (tag, key, value) = unpack_table_op(std::vector::remove(&mut ops, 0));
+        assert!(tag == 0, error_code);
+        assert!(key == std::bcs::to_bytes(&2u64), error_code);
+        assert!(value == std::bcs::to_bytes(&20u64), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure(abort_code = 393218, location = moveos_std::raw_table)]
+    fun test_saturating_decrement_u64_not_found(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        saturating_decrement_u64(&mut t, 1, 1);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_replace_all(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        add(&mut t, 2, 20);
+        raw_table::replace_all<u64, u64>(object::object_id_to_table_handle(t.handle), vector[2, 3], vector[200, 300]);
+        assert!(!contains(&t, 1), error_code);
+        assert!(*borrow(&t, 2) == 200, error_code);
+        assert!(*borrow(&t, 3) == 300, error_code);
+        assert!(length(&t) == 2, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_replace_all_type_change(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let handle = object::object_id_to_table_handle(t.handle);
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        raw_table::replace_all<u64, u8>(handle, vector[1, 2], vector[11u8, 22u8]);
+        assert!(*raw_table::borrow<u64, u8>(handle, 1) == 11u8, error_code);
+        assert!(*raw_table::borrow<u64, u8>(handle, 2) == 22u8, error_code);
+        assert!(length(&t) == 2, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure(abort_code = 524289, location = moveos_std::raw_table)]
+    fun test_replace_all_duplicate_keys_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        add(&mut t, 1, 10);
+        raw_table::replace_all<u64, u64>(object::object_id_to_table_handle(t.handle), vector[2, 2], vector[20, 30]);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_new_table_count(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t1 = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let t2 = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(new_table_count() == 0, error_code);
+        add(&mut t1, 1, 10);
+        add(&mut t2, 1, 10);
+        add(&mut t2, 2, 20);
+        assert!(new_table_count() == 2, error_code);
+
+        drop_unchecked(t1);
+        drop_unchecked(t2);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_compare_and_swap(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, u8>(uid);
+        let key: u64 = 111;
+        let error_code: u64 = 1;
+        assert!(!compare_and_swap(&mut t, key, 0, 12), error_code);
+        assert!(!contains(&t, key), error_code);
+        add(&mut t, key, 12);
+        assert!(!compare_and_swap(&mut t, key, 23, 34), error_code);
+        assert!(*borrow(&t, key) == 12, error_code);
+        assert!(compare_and_swap(&mut t, key, 12, 23), error_code);
+        assert!(*borrow(&t, key) == 23, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_export_import_entries(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u8>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 2, 20);
+        add(&mut t, 1, 10);
+        let data = export_entries(&t);
+
+        let restored = new<u64, u8>(object::new_uid_for_test(&mut tx_context));
+        import_entries(&mut restored, data);
+        assert!(length(&restored) == 2, error_code);
+        assert!(*borrow(&restored, 1) == 10, error_code);
+        assert!(*borrow(&restored, 2) == 20, error_code);
+        assert!(export_entries(&restored) == data, error_code);
+
+        drop_unchecked(t);
+        drop_unchecked(restored);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_import_entries_not_empty_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u8>(object::new_uid_for_test(&mut tx_context));
+        add(&mut t, 1, 10);
+        let data = export_entries(&t);
+        import_entries(&mut t, data);
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_get_or_insert(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, u8>(uid);
+        let key: u64 = 111;
+        let error_code: u64 = 1;
+        assert!(get_or_insert(&mut t, key, 12) == 12, error_code);
+        assert!(*borrow(&t, key) == 12, error_code);
+        assert!(get_or_insert(&mut t, key, 23) == 12, error_code);
+        assert!(*borrow(&t, key) == 12, error_code);
+        assert!(length(&t) == 1, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_add_many_same(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, bool>(uid);
+        let error_code: u64 = 1;
+        add_many_same(&mut t, vector[1, 2, 3], true);
+        assert!(length(&t) == 3, error_code);
+        assert!(*borrow(&t, 1) && *borrow(&t, 2) && *borrow(&t, 3), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_add_many_same_key_exist_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, bool>(uid);
+        add(&mut t, 2, false);
+        add_many_same(&mut t, vector[1, 2, 3], true);
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_transfer_u64(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, u64>(uid);
+        let error_code: u64 = 1;
+        add(&mut t, 1, 100);
+        add(&mut t, 2, 5);
+        transfer_u64(&mut t, 1, 2, 30);
+        assert!(*borrow(&t, 1) == 70, error_code);
+        assert!(*borrow(&t, 2) == 35, error_code);
+        transfer_u64(&mut t, 1, 1, 70);
+        assert!(*borrow(&t, 1) == 70, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_transfer_u64_insufficient_balance_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, u64>(uid);
+        add(&mut t, 1, 10);
+        add(&mut t, 2, 0);
+        transfer_u64(&mut t, 1, 2, 11);
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_last_inserted(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, u64>(uid);
+        let error_code: u64 = 1;
+        add(&mut t, 3, 30);
+        let (key, val) = last_inserted(&t);
+        assert!(std::option::is_none(&key) && std::option::is_none(&val), error_code);
+        enable_last_inserted(&mut t);
+        enable_last_inserted(&mut t);
+        let (key, val) = last_inserted(&t);
+        assert!(std::option::is_none(&key) && std::option::is_none(&val), error_code);
+        add(&mut t, 2, 20);
+        add(&mut t, 1, 10);
+        let (key, val) = last_inserted(&t);
+        assert!(std::option::destroy_some(key) == 1, error_code);
+        assert!(std::option::destroy_some(val) == 10, error_code);
+        remove(&mut t, 1);
+        let (key, _) = last_inserted(&t);
+        assert!(std::option::is_none(&key), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_move_entry(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let from = new<u64, vector<u8>>(object::new_uid_for_test(&mut tx_context));
+        let to = new<u64, vector<u8>>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        let value = b"moved value";
+        add(&mut from, 1, value);
+        move_entry(&mut from, &mut to, 1);
+        assert!(!contains(&from, 1), error_code);
+        assert!(length(&from) == 0 && length(&to) == 1, error_code);
+        assert!(std::bcs::to_bytes(borrow(&to, 1)) == std::bcs::to_bytes(&value), error_code);
+
+        drop_unchecked(from);
+        drop_unchecked(to);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_move_entry_already_exists_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let from = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let to = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        add(&mut from, 1, 1);
+        add(&mut to, 1, 2);
+        move_entry(&mut from, &mut to, 1);
+        drop_unchecked(from);
+        drop_unchecked(to);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_batch_ops(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 1);
+        add(&mut t, 2, 2);
+        batch_ops(&mut t, vector[new_op(3, 3u64), modify_op(1, 10u64), delete_op<u64>(2)]);
+        assert!(length(&t) == 2, error_code);
+        assert!(*borrow(&t, 1) == 10, error_code);
+        assert!(!contains(&t, 2), error_code);
+        assert!(*borrow(&t, 3) == 3, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_batch_ops_remove_absent_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        add(&mut t, 1, 1);
+        batch_ops(&mut t, vector[modify_op(1, 10u64), delete_op<u64>(2)]);
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_push(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(push(&mut t, 10) == 0, error_code);
+        assert!(push(&mut t, 11) == 1, error_code);
+        add(&mut t, 5, 15);
+        assert!(push(&mut t, 16) == 6, error_code);
+        let _ = remove(&mut t, 6);
+        assert!(push(&mut t, 17) == 7, error_code);
+        assert!(*borrow(&t, 0) == 10 && *borrow(&t, 7) == 17, error_code);
+        assert!(length(&t) == 4, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test]
+    fun test_reserve_handle() {
+        let error_code: u64 = 1;
+        let handle1 = raw_table::reserve_handle();
+        let handle2 = raw_table::reserve_handle();
+        assert!(handle1 != handle2, error_code);
+        let table_handle = raw_table::new_table_handle(handle1);
+        assert!(!raw_table::contains<u64>(table_handle, 1), error_code);
+        raw_table::add<u64, u64>(table_handle, 1, 1);
+        assert!(*raw_table::borrow<u64, u64>(table_handle, 1) == 1, error_code);
+        raw_table::drop_unchecked(table_handle);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_init_once(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(init_once(&mut t, 1, 10), error_code);
+        assert!(!init_once(&mut t, 2, 20), error_code);
+        assert!(!init_once(&mut t, 1, 30), error_code);
+        assert!(length(&t) == 1 && *borrow(&t, 1) == 10, error_code);
+        let _ = remove(&mut t, 1);
+        assert!(init_once(&mut t, 2, 20), error_code);
+        assert!(*borrow(&t, 2) == 20, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_multi_read(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t1 = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let t2 = new<u8, vector<u8>>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t1, 1, 10);
+        add(&mut t2, 2, b"rooch");
+        let results = multi_read(vector[table_read(&t2, 2u8), table_read(&t1, 2), table_read(&t1, 1)]);
+        assert!(std::vector::length(&results) == 3, error_code);
+        assert!(*std::vector::borrow(&results, 0) == std::option::some(moveos_std::bcs::to_bytes(&b"rooch")), error_code);
+        assert!(std::option::is_none(std::vector::borrow(&results, 1)), error_code);
+        assert!(*std::vector::borrow(&results, 2) == std::option::some(moveos_std::bcs::to_bytes(&10u64)), error_code);
+
+        drop_unchecked(t1);
+        drop_unchecked(t2);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_create_and_add(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        create_and_add(&mut t, 1, 10);
+        create_and_add(&mut t, 2, 20);
+        assert!(length(&t) == 2, error_code);
+        assert!(*borrow(&t, 1) == 10 && *borrow(&t, 2) == 20, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_create_and_add_already_exists_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        create_and_add(&mut t, 1, 10);
+        create_and_add(&mut t, 1, 20);
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_layouts(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        let (key_layout, value_layout) = layouts(&t);
+        // The BCS bytes of `MoveTypeLayout::U64`
+        assert!(key_layout == x"02", error_code);
+        assert!(value_layout == x"02", error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_toggle(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, bool>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, false);
+        assert!(toggle(&mut t, 1), error_code);
+        assert!(*borrow(&t, 1), error_code);
+        assert!(!toggle(&mut t, 1), error_code);
+        assert!(!*borrow(&t, 1), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_toggle_not_found_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, bool>(object::new_uid_for_test(&mut tx_context));
+        toggle(&mut t, 1);
+        drop_unchecked(t);
+    }
+
+    #[test_only]
+    struct VersionedValue has store, copy, drop {
+        version: u64,
+        value: u64,
+    }
+
+    #[test(sender = @0x42)]
+    fun test_borrow_if_version(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, VersionedValue>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, VersionedValue { version: 2, value: 10 });
+        assert!(borrow_if_version(&t, 1, 2) == std::option::some(VersionedValue { version: 2, value: 10 }), error_code);
+        assert!(std::option::is_none(&borrow_if_version(&t, 1, 1)), error_code);
+        assert!(std::option::is_none(&borrow_if_version(&t, 2, 2)), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_merge(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        merge_sum(&mut t, 1, 5);
+        merge_sum(&mut t, 1, 7);
+        assert!(*borrow(&t, 1) == 12, error_code);
+        merge_max(&mut t, 1, 10);
+        assert!(*borrow(&t, 1) == 12, error_code);
+        merge_max(&mut t, 1, 20);
+        assert!(*borrow(&t, 1) == 20, error_code);
+        merge_min(&mut t, 1, 3);
+        assert!(*borrow(&t, 1) == 3, error_code);
+        merge_min(&mut t, 2, 8);
+        assert!(*borrow(&t, 2) == 8, error_code);
+
+        let bytes = new<u64, vector<u8>>(object::new_uid_for_test(&mut tx_context));
+        merge_append(&mut bytes, 1, b"ab");
+        merge_append(&mut bytes, 1, b"cd");
+        assert!(*borrow(&bytes, 1) == b"abcd", error_code);
+
+        drop_unchecked(t);
+        drop_unchecked(bytes);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_merge_sum_overflow_failure(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        merge_sum(&mut t, 1, 18446744073709551615);
+        merge_sum(&mut t, 1, 1);
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_destroy_if_version(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, VersionedValue>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 0, VersionedValue { version: 3, value: 0 });
+        add(&mut t, 1, VersionedValue { version: 1, value: 10 });
+
+        let kept = destroy_if_version(t, 0, 2);
+        assert!(option::is_some(&kept), error_code);
+        let t = option::destroy_some(kept);
+        assert!(length(&t) == 2, error_code);
+        let kept = destroy_if_version(t, 2, 3);
+        assert!(option::is_some(&kept), error_code);
+        let t = option::destroy_some(kept);
+
+        option::destroy_none(destroy_if_version(t, 0, 3));
+    }
+
+    #[test(sender = @0x42)]
+    fun test_missing_keys(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        add(&mut t, 3, 30);
+        assert!(missing_keys(&t, vector[4, 1, 2, 3]) == vector[4, 2], error_code);
+        assert!(missing_keys(&t, vector[1, 3]) == vector[], error_code);
+        assert!(missing_keys(&t, vector[]) == vector[], error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_set_if_greater(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(set_if_greater(&mut t, 1, 10), error_code);
+        assert!(*borrow(&t, 1) == 10, error_code);
+        assert!(!set_if_greater(&mut t, 1, 10), error_code);
+        assert!(!set_if_greater(&mut t, 1, 5), error_code);
+        assert!(*borrow(&t, 1) == 10, error_code);
+        assert!(set_if_greater(&mut t, 1, 11), error_code);
+        assert!(*borrow(&t, 1) == 11, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_borrow_with_source(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        let (value, cold) = borrow_with_source(&t, 1);
+        assert!(option::is_none(&value) && cold, error_code);
+        let (value, cold) = borrow_with_source(&t, 1);
+        assert!(option::is_none(&value) && !cold, error_code);
+        add(&mut t, 2, 20);
+        let (value, cold) = borrow_with_source(&t, 2);
+        assert!(value == option::some(20) && !cold, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test_only]
+    struct UserItemKey has copy, drop {
+        user: address,
+        item: u64,
+    }
+
+    #[test(sender = @0x42)]
+    fun test_remove_prefix(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<UserItemKey, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, UserItemKey { user: @0x1, item: 1 }, 10);
+        add(&mut t, UserItemKey { user: @0x1, item: 2 }, 20);
+        add(&mut t, UserItemKey { user: @0x2, item: 1 }, 30);
+        assert!(remove_prefix(&mut t, moveos_std::bcs::to_bytes(&@0x1)) == 2, error_code);
+        assert!(length(&t) == 1, error_code);
+        assert!(!contains(&t, UserItemKey { user: @0x1, item: 1 }), error_code);
+        assert!(contains(&t, UserItemKey { user: @0x2, item: 1 }), error_code);
+        assert!(remove_prefix(&mut t, moveos_std::bcs::to_bytes(&@0x1)) == 0, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_push_bounded(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        let (key, val) = push_bounded(&mut t, 10, 2);
+        assert!(option::is_none(&key) && option::is_none(&val), error_code);
+        let (key, val) = push_bounded(&mut t, 11, 2);
+        assert!(option::is_none(&key) && option::is_none(&val), error_code);
+        let (key, val) = push_bounded(&mut t, 12, 2);
+        assert!(key == option::some(0) && val == option::some(10), error_code);
+        let (key, val) = push_bounded(&mut t, 13, 2);
+        assert!(key == option::some(1) && val == option::some(11), error_code);
+        assert!(length(&t) == 2, error_code);
+        assert!(*borrow(&t, 2) == 12 && *borrow(&t, 3) == 13, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_push_bounded_skips_removed_entries(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        let _ = push_bounded(&mut t, 10, 2);
+        let _ = push_bounded(&mut t, 11, 2);
+        let (key, _) = push_bounded(&mut t, 12, 2);
+        assert!(key == option::some(0), error_code);
+        // The head is 1 now, remove it so the next eviction skips to 2.
+        let _ = remove(&mut t, 1);
+        let (key, val) = push_bounded(&mut t, 13, 1);
+        assert!(key == option::some(2) && val == option::some(12), error_code);
+        assert!(length(&t) == 1, error_code);
+        assert!(*borrow(&t, 3) == 13, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_table_bytes(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(table_bytes(&t) == option::some(0), error_code);
+        add(&mut t, 1, 10);
+        add(&mut t, 2, 20);
+        assert!(table_bytes(&t) == option::some(32), error_code);
+        let _ = remove(&mut t, 1);
+        assert!(table_bytes(&t) == option::some(16), error_code);
+        let i = 10;
+        while (i < 110) {
+            add(&mut t, i, i);
+            i = i + 1;
+        };
+        // Each entry is an 8 bytes key and an 8 bytes value.
+        assert!(table_bytes(&t) == option::some(16 * 101), error_code);
+        upsert(&mut t, 2, 22);
+        let _ = remove(&mut t, 50);
+        assert!(table_bytes(&t) == option::some(16 * 100), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_claim_slot(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<vector<u8>, address>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(option::is_none(&claim_slot(&mut t, b"alice", @0x11)), error_code);
+        assert!(claim_slot(&mut t, b"alice", @0x22) == option::some(@0x11), error_code);
+        assert!(*borrow(&t, b"alice") == @0x11, error_code);
+        assert!(option::is_none(&claim_slot(&mut t, b"bob", @0x22)), error_code);
+        assert!(length(&t) == 2, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_last_modified(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        assert!(option::is_none(&last_modified(&t, 1)), error_code);
+        enable_last_modified(&mut t);
+        enable_last_modified(&mut t);
+        add(&mut t, 2, 20);
+        assert!(option::is_none(&last_modified(&t, 2)), error_code);
+        assert!(length(&t) == 2, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_rotate(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(option::is_none(&rotate(&mut t, 1, 10, 4)), error_code);
+        assert!(option::is_none(&rotate(&mut t, 2, 20, 4)), error_code);
+        assert!(rotate(&mut t, 5, 50, 4) == option::some(10), error_code);
+        assert!(*borrow(&t, 1) == 50, error_code);
+        assert!(length(&t) == 2, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_rotate_zero_capacity(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let _ = rotate(&mut t, 1, 10, 0);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_increment_many(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        add(&mut t, 1, 10);
+        add(&mut t, 2, 20);
+        let new_values = increment_many(&mut t, vector[1, 2, 1], vector[1, 2, 3]);
+        assert!(new_values == vector[11, 22, 14], error_code);
+        assert!(*borrow(&t, 1) == 14, error_code);
+        assert!(*borrow(&t, 2) == 22, error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    #[expected_failure]
+    fun test_increment_many_overflow(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        add(&mut t, 1, 10);
+        add(&mut t, 2, 18446744073709551615);
+        let _ = increment_many(&mut t, vector[1, 2], vector[1, 1]);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_borrow_typed(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, u64>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        assert!(option::is_none(&borrow_typed(&t, 1)), error_code);
+        add(&mut t, 1, 10);
+        assert!(borrow_typed(&t, 1) == option::some(10), error_code);
+        let handle = object::object_id_to_table_handle(t.handle);
+        assert!(option::is_none(&raw_table::borrow_typed<u64, u64>(handle, 1, b"u8")), error_code);
+        assert!(option::is_none(&borrow_typed(&t, 2)), error_code);
+
+        drop_unchecked(t);
+    }
+
+    #[test(sender = @0x42)]
+    fun test_borrow_typed_struct(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let t = new<u64, VersionedValue>(object::new_uid_for_test(&mut tx_context));
+        let error_code: u64 = 1;
+        let value = VersionedValue { version: 1, value: 10 };
+        add(&mut t, 1, value);
+        // The tag is the canonical type name, with the address of the struct in full.
+        assert!(borrow_typed(&t, 1) == option::some(value), error_code);
+        let handle = object::object_id_to_table_handle(t.handle);
+        let tag = b"0x2::table::VersionedValue";
+        assert!(option::is_none(&raw_table::borrow_typed<u64, VersionedValue>(handle, 1, tag)), error_code);
+        assert!(option::is_none(&borrow_typed(&t, 2)), error_code);
+
+        drop_unchecked(t);
+    }
+
     #[test(sender = @0x42)]
     fun test_borrow_with_default(sender: address) {
         let tx_context = moveos_std::tx_context::new_test_context(sender);
@@ -172,6 +1297,24 @@ module moveos_std::table {
         drop_unchecked(t);
     }
 
+    #[test_only]
+    struct UnitValue has store, drop {}
+
+    #[test(sender = @0x42)]
+    fun test_unit_like_value(sender: address) {
+        let tx_context = moveos_std::tx_context::new_test_context(sender);
+        let uid = object::new_uid_for_test(&mut tx_context);
+        let t = new<u64, UnitValue>(uid);
+        let key: u64 = 100;
+        let error_code: u64 = 1;
+        assert!(!contains(&t, key), error_code);
+        add(&mut t, key, UnitValue {});
+        assert!(contains(&t, key), error_code);
+        let UnitValue {} = remove(&mut t, key);
+        assert!(!contains(&t, key), error_code);
+        drop_unchecked(t);
+    }
+
     #[test(sender = @0x42)]
     #[expected_failure]
     fun test_add_key_exist_failure(sender: address) {
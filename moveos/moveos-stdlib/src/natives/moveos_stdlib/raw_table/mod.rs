@@ -11,7 +11,7 @@ use move_binary_format::errors::{PartialVMError, PartialVMResult};
 use move_core_types::{
     account_address::AccountAddress,
     effects::Op,
-    gas_algebra::{InternalGas, InternalGasPerByte, NumBytes},
+    gas_algebra::{InternalGas, InternalGasPerArg, InternalGasPerByte, NumArgs, NumBytes},
     language_storage::TypeTag,
     value::MoveTypeLayout,
     vm_status::StatusCode,
@@ -24,18 +24,21 @@ use move_vm_types::{
     loaded_data::runtime_types::Type,
     natives::function::NativeResult,
     pop_arg,
-    values::{GlobalValue, Struct, Value},
+    values::{GlobalValue, Reference, Struct, Value, Vector},
 };
 use moveos_types::{
+    h256::{sha3_256_of, H256},
     moveos_std::{object::ObjectID, raw_table::TableInfo},
-    state::TableTypeInfo,
-    state_resolver::StateResolver,
+    state::{State, TableTypeInfo},
+    state_resolver::{StateResolver, GLOBAL_OBJECT_STORAGE_HANDLE},
 };
 use parking_lot::RwLock;
 use smallvec::smallvec;
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet, VecDeque},
+    ops::Bound,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 /// The native table context extension. This needs to be attached to the NativeContextExtensions
@@ -44,15 +47,61 @@ use std::{
 #[derive(Tid)]
 pub struct NativeTableContext<'a> {
     resolver: &'a dyn StateResolver,
-    //tx_hash: [u8; 32],
+    /// The hash of the current transaction, the seed of the reserved table handles.
+    tx_hash: H256,
     table_data: Arc<RwLock<TableData>>,
+    /// The max number of entries of a table, the inserts beyond it abort. None means unlimited.
+    max_entries_per_table: Option<u64>,
+    /// Whether the context is created for a read-only session, such as a view call.
+    read_only: bool,
+    /// The resolver loads slower than this are logged at warn. None means disabled.
+    slow_load_threshold: Option<Duration>,
 }
 
 /// Ensure the error codes in this file is consistent with the error code in raw_table.move
 const E_ALREADY_EXISTS: u64 = 1;
 const E_NOT_FOUND: u64 = 2;
 const E_DUPLICATE_OPERATION: u64 = 3;
-const _E_NOT_EMPTY: u64 = 4; // This is not used, just used to keep consistent with raw_table.move
+const E_NOT_EMPTY: u64 = 4;
+const E_LENGTH_NOT_MATCH: u64 = 5;
+const E_UNDERFLOW: u64 = 6;
+const E_INVALID_SNAPSHOT: u64 = 7;
+const E_OVERFLOW: u64 = 8;
+const E_TOO_MANY_ENTRIES: u64 = 9;
+const E_INVALID_OPERATION: u64 = 10;
+
+/// Ensure the operation tags in this file is consistent with the `TableOp` doc in raw_table.move
+const TABLE_OP_NEW: u8 = 0;
+const TABLE_OP_MODIFY: u8 = 1;
+const TABLE_OP_DELETE: u8 = 2;
+
+/// Ensure the reducer tags in this file is consistent with the reducer tags in raw_table.move
+const REDUCER_SUM_U64: u8 = 0;
+const REDUCER_MAX_U64: u8 = 1;
+const REDUCER_MIN_U64: u8 = 2;
+const REDUCER_APPEND_BYTES: u8 = 3;
+
+/// The domain separator of the reserved table handle derivation.
+const RESERVED_HANDLE_DOMAIN: &[u8] = b"moveos_std::raw_table::reserve_handle";
+
+/// The domain separator of the handle of the metadata table of a table, which keeps the opt-in records of the table.
+const METADATA_HANDLE_DOMAIN: &[u8] = b"moveos_std::raw_table::metadata";
+
+/// The flag of the metadata header which opts the table in to persist the key inserted last.
+const METADATA_LAST_INSERTED: u64 = 1;
+
+/// The flag of the metadata header which opts the table in to record the last modified tx order of its entries.
+const METADATA_LAST_MODIFIED: u64 = 2;
+
+/// The domain separator of the handle of the table which records the next sequence number of a table for `push_box`.
+const NEXT_SEQUENCE_HANDLE_DOMAIN: &[u8] = b"moveos_std::raw_table::next_sequence";
+
+/// The max number of entries `replace_all_box` removes, a larger table is emptied in several transactions first.
+/// The natives which scan a whole table, such as `content_root_box` or `export_box`, are capped at it too.
+const MAX_REPLACE_ALL_ENTRIES: u64 = 10_000;
+
+/// The page size used when listing the entries of a table from the resolver.
+const LIST_TABLE_ITEMS_PAGE_SIZE: usize = 1000;
 
 // ===========================================================================================
 // Private Data Structures and Constants
@@ -64,6 +113,8 @@ pub struct TableData {
     new_tables: BTreeMap<ObjectID, TableTypeInfo>,
     removed_tables: BTreeSet<ObjectID>,
     tables: BTreeMap<ObjectID, Table>,
+    /// The number of table handles reserved in the session.
+    reserved_handles: u64,
 }
 
 /// A structure representing runtime table value.
@@ -74,14 +125,46 @@ pub struct TableRuntimeValue {
     /// This is the Box<V> value in MoveVM memory
     /// It can be GlobalValue::None
     box_value: GlobalValue,
+    /// The serialized value loaded from the resolver, None if the value did not exist
+    original_value: Option<Vec<u8>>,
+    /// Set when the value is moved in verbatim from another table
+    verbatim_value: Option<VerbatimValue>,
+}
+
+/// The serialized value moved in verbatim from another table.
+pub struct VerbatimValue {
+    /// The bytes as stored in the source table
+    bytes: Vec<u8>,
+    /// The value's own serialization at the time of the move
+    serialized: Vec<u8>,
+}
+
+impl VerbatimValue {
+    /// Returns the bytes of the source table if the value still serializes to `serialized`,
+    /// otherwise the value is modified after the move and `serialized` is returned.
+    pub fn or_serialized(self, serialized: Vec<u8>) -> Vec<u8> {
+        if self.serialized == serialized {
+            self.bytes
+        } else {
+            serialized
+        }
+    }
 }
 
 impl TableRuntimeValue {
-    pub fn new(value_layout: MoveTypeLayout, value_type: TypeTag, box_value: GlobalValue) -> Self {
+    /// Create a value loaded from the resolver, `original_value` is the serialized value without the Box wrapper.
+    pub fn new(
+        value_layout: MoveTypeLayout,
+        value_type: TypeTag,
+        box_value: GlobalValue,
+        original_value: Vec<u8>,
+    ) -> Self {
         debug_assert!(box_value.exists().unwrap());
         Self {
             value_layout_and_type: Some((value_layout, value_type)),
             box_value,
+            original_value: Some(original_value),
+            verbatim_value: None,
         }
     }
 
@@ -89,9 +172,17 @@ impl TableRuntimeValue {
         Self {
             value_layout_and_type: None,
             box_value: GlobalValue::none(),
+            original_value: None,
+            verbatim_value: None,
         }
     }
 
+    /// Returns true if the value is added, modified or removed since it was loaded.
+    /// A value modified back to its original content is not dirty.
+    pub fn is_dirty(&self) -> PartialVMResult<bool> {
+        Ok(self.serialize_value()? != self.original_value)
+    }
+
     pub fn exists(&self) -> PartialVMResult<bool> {
         Ok(self.value_layout_and_type.is_some() && self.box_value.exists()?)
     }
@@ -142,6 +233,70 @@ impl TableRuntimeValue {
         Ok(value)
     }
 
+    /// Serialize the value without the Box wrapper.
+    /// Returns None if the value does not exist.
+    pub fn serialize_value(&self) -> PartialVMResult<Option<Vec<u8>>> {
+        if !self.exists()? {
+            return Ok(None);
+        }
+        let (value_layout, _value_type) = self
+            .value_layout_and_type
+            .as_ref()
+            .expect("The value_layout_and_type must exist if the value exists");
+        let box_value = self
+            .box_value
+            .borrow_global()?
+            .value_as::<Reference>()?
+            .read_ref()?;
+        let mut fields = box_value.value_as::<Struct>()?.unpack()?;
+        let val = fields
+            .next()
+            .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+        serialize(value_layout, &val).map(Some)
+    }
+
+    /// Returns the bytes to store for the value, None if the value does not exist.
+    /// The bytes loaded from the resolver or moved in verbatim are kept as is if the value is not
+    /// modified since, even if they are not the canonical serialization of the value.
+    fn stored_bytes(&self) -> PartialVMResult<Option<Vec<u8>>> {
+        let serialized = match self.serialize_value()? {
+            Some(serialized) => serialized,
+            None => return Ok(None),
+        };
+        if let Some(verbatim_value) = &self.verbatim_value {
+            if verbatim_value.serialized == serialized {
+                return Ok(Some(verbatim_value.bytes.clone()));
+            }
+        }
+        if let (Some(original_value), Some((value_layout, _value_type))) =
+            (&self.original_value, &self.value_layout_and_type)
+        {
+            let original_serialized =
+                serialize(value_layout, &deserialize(value_layout, original_value)?)?;
+            if original_serialized == serialized {
+                return Ok(Some(original_value.clone()));
+            }
+        }
+        Ok(Some(serialized))
+    }
+
+    /// Take the verbatim value, the change set keeps its bytes if the value is not modified after the move.
+    pub fn take_verbatim_value(&mut self) -> Option<VerbatimValue> {
+        self.verbatim_value.take()
+    }
+
+    /// Remove the value without checking its type.
+    /// The layout and the type are reset, so a value of another type can be moved in afterwards.
+    /// Returns false if the value does not exist.
+    pub fn remove_unchecked(&mut self) -> PartialVMResult<bool> {
+        if !self.exists()? {
+            return Ok(false);
+        }
+        self.box_value.move_from()?;
+        self.value_layout_and_type = None;
+        Ok(true)
+    }
+
     pub fn move_from(&mut self, expect_value_type: TypeTag) -> PartialVMResult<Value> {
         let value = self.box_value.move_from()?;
         match &self.value_layout_and_type {
@@ -162,16 +317,20 @@ impl TableRuntimeValue {
         Ok(value)
     }
 
-    pub fn into_effect(self) -> Option<(MoveTypeLayout, TypeTag, Op<Value>)> {
-        let op_opt = self.box_value.into_effect();
-        match (op_opt, self.value_layout_and_type) {
-            (Some(op), Some((value_layout, value_type))) => Some((value_layout, value_type, op)),
-            (None, None) => None,
-            (None, Some(_)) => {
-                // The box_value is loaded, but do not change, so no effect
-                None
+    /// Returns the effect of the value with the layout and the type of the new or modified value.
+    /// A removed value has no layout and type, see `remove_unchecked`.
+    pub fn into_effect(self) -> Option<Op<(MoveTypeLayout, TypeTag, Value)>> {
+        // None if the box_value is not loaded, or loaded but not changed.
+        let op = self.box_value.into_effect()?;
+        match (op, self.value_layout_and_type) {
+            (Op::Delete, _) => Some(Op::Delete),
+            (Op::New(val), Some((value_layout, value_type))) => {
+                Some(Op::New((value_layout, value_type, val)))
+            }
+            (Op::Modify(val), Some((value_layout, value_type))) => {
+                Some(Op::Modify((value_layout, value_type, val)))
             }
-            (Some(_op), None) => {
+            (_op, None) => {
                 unreachable!("Cannot have op without value_layout_and_type")
             }
         }
@@ -184,6 +343,14 @@ pub struct Table {
     key_layout: MoveTypeLayout,
     content: BTreeMap<Vec<u8>, TableRuntimeValue>,
     size_increment: i64,
+    /// Whether all the persisted entries are loaded into the content.
+    entries_loaded: bool,
+    /// The metadata table of the table, loaded on the first access to its entries in the session, see
+    /// `metadata_handle`. None until loaded, Some(None) if the table has no metadata, such as a metadata table.
+    metadata: Option<Option<Box<Table>>>,
+    /// The max key pushed or inserted in a table with `u64` keys, None until `push_box` reads the next sequence
+    /// number, Some(None) if no number is taken yet.
+    max_u64_key: Option<Option<u64>>,
 }
 
 // =========================================================================================
@@ -196,27 +363,175 @@ impl<'a> NativeTableContext<'a> {
         Self {
             resolver,
             table_data,
+            tx_hash: H256::zero(),
+            max_entries_per_table: None,
+            read_only: false,
+            slow_load_threshold: None,
+        }
+    }
+
+    /// Log the table operations whose resolver load takes longer than `slow_load_threshold`,
+    /// with the table handle and the key. None means disabled.
+    pub fn with_slow_load_threshold(mut self, slow_load_threshold: Option<Duration>) -> Self {
+        self.slow_load_threshold = slow_load_threshold;
+        self
+    }
+
+    /// Load the table item from the resolver, and log it if the load is slow.
+    fn resolve_table_item(&self, handle: &ObjectID, key: &[u8]) -> PartialVMResult<Option<State>> {
+        let started_at = Instant::now();
+        let state = self
+            .resolver
+            .resolve_table_item(handle, key)
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            })?;
+        self.log_slow_load(started_at.elapsed(), || {
+            format!("table_handle: {}, key: 0x{}", handle, hex::encode(key))
+        });
+        Ok(state)
+    }
+
+    /// Load the table items from the resolver with one batched lookup, and log it if the load is slow.
+    fn resolve_table_items(
+        &self,
+        handle: &ObjectID,
+        keys: &[Vec<u8>],
+    ) -> PartialVMResult<Vec<Option<State>>> {
+        let started_at = Instant::now();
+        let states = self
+            .resolver
+            .resolve_table_items(handle, keys)
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            })?;
+        self.log_slow_load(started_at.elapsed(), || {
+            format!(
+                "table_handle: {}, keys: [{}]",
+                handle,
+                keys.iter()
+                    .map(|key| format!("0x{}", hex::encode(key)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        });
+        Ok(states)
+    }
+
+    fn log_slow_load(&self, elapsed: Duration, target: impl FnOnce() -> String) {
+        if matches!(self.slow_load_threshold, Some(threshold) if elapsed > threshold) {
+            log::warn!(
+                "[RawTable] slow resolver load took {:?}, {}",
+                elapsed,
+                target()
+            );
         }
     }
+
+    /// Set the hash of the current transaction, the reserved table handles are derived from it.
+    pub fn with_tx_hash(mut self, tx_hash: H256) -> Self {
+        self.tx_hash = tx_hash;
+        self
+    }
+
+    /// Mark the context as created for a read-only session.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Limit the number of entries of each table, a node-level protection against unbounded table growth.
+    /// The inserts which would exceed the limit abort, None means unlimited.
+    pub fn with_max_entries_per_table(mut self, max_entries_per_table: Option<u64>) -> Self {
+        self.max_entries_per_table = max_entries_per_table;
+        self
+    }
+
+    /// Returns the persisted `TableInfo` of the table, None if the table does not exist in the resolver.
+    fn remote_table_info(&self, handle: &ObjectID) -> PartialVMResult<Option<TableInfo>> {
+        Ok(self
+            .resolver
+            .resolve_object_state(handle)
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            })?
+            .map(|state| state.as_object::<TableInfo>())
+            .transpose()
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            })?
+            .map(|obj| obj.value))
+    }
+
+    /// Returns the persisted size of the table, not including the changes of the current session.
+    fn remote_table_size(&self, handle: &ObjectID) -> PartialVMResult<u64> {
+        Ok(self
+            .remote_table_info(handle)?
+            .map_or_else(|| 0u64, |table_info| table_info.size))
+    }
+
+    /// Returns true if the table object of the handle exists in the resolver.
+    fn remote_table_exists(&self, handle: &ObjectID) -> PartialVMResult<bool> {
+        Ok(self
+            .resolver
+            .resolve_object_state(handle)
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            })?
+            .is_some())
+    }
+
+    /// Returns true if the table holds more than `MAX_REPLACE_ALL_ENTRIES` entries, too many to scan in one native.
+    fn exceeds_max_scanned_entries(&self, table: &Table) -> PartialVMResult<bool> {
+        let table_size = (self.remote_table_size(&table.handle)? as i64) + table.size_increment;
+        Ok(table_size as u64 > MAX_REPLACE_ALL_ENTRIES)
+    }
+
+    /// Returns true if adding `new_entries` entries to the table would exceed `max_entries_per_table`.
+    fn exceeds_max_entries(&self, table: &Table, new_entries: u64) -> PartialVMResult<bool> {
+        let max_entries = match self.max_entries_per_table {
+            Some(max_entries) => max_entries,
+            None => return Ok(false),
+        };
+        let table_size = (self.remote_table_size(&table.handle)? as i64) + table.size_increment;
+        debug_assert!(table_size >= 0);
+        Ok((table_size as u64).saturating_add(new_entries) > max_entries)
+    }
 }
 
 impl TableData {
     /// Gets or creates a new table in the TableData. This initializes information about
     /// the table, like the type layout for keys and values.
+    /// A table which does not exist in the resolver is registered in the new tables of the session.
     fn get_or_create_table(
         &mut self,
         context: &NativeContext,
+        table_context: &NativeTableContext,
         handle: ObjectID,
         key_ty: &Type,
     ) -> PartialVMResult<&mut Table> {
         Ok(match self.tables.entry(handle) {
             Entry::Vacant(e) => {
                 let key_layout = type_to_type_layout(context, key_ty)?;
+                if !self.new_tables.contains_key(&handle)
+                    && !self.removed_tables.contains(&handle)
+                    && !table_context.remote_table_exists(&handle)?
+                {
+                    let key_type = type_to_type_tag(context, key_ty)?;
+                    self.new_tables.insert(handle, TableTypeInfo::new(key_type));
+                }
                 let table = Table {
                     handle,
                     key_layout,
                     content: Default::default(),
                     size_increment: 0,
+                    entries_loaded: false,
+                    metadata: if handle == GLOBAL_OBJECT_STORAGE_HANDLE {
+                        Some(None)
+                    } else {
+                        None
+                    },
+                    max_u64_key: None,
                 };
                 if log::log_enabled!(log::Level::Trace) {
                     let key_type = type_to_type_tag(context, key_ty)?;
@@ -240,6 +555,9 @@ impl TableData {
                     key_layout,
                     content: Default::default(),
                     size_increment: 0,
+                    entries_loaded: false,
+                    metadata: Some(None),
+                    max_u64_key: None,
                 };
                 e.insert(table)
             }
@@ -268,48 +586,413 @@ impl TableData {
         let TableData {
             new_tables,
             removed_tables,
-            tables,
+            mut tables,
+            reserved_handles: _,
         } = self;
+        // The metadata tables are kept in their tables in the session, and changed as tables of their own.
+        let metadata_tables = tables
+            .values_mut()
+            .filter_map(|table| table.metadata.take().flatten())
+            .collect::<Vec<_>>();
+        for metadata in metadata_tables {
+            tables.insert(metadata.handle, *metadata);
+        }
         (new_tables, removed_tables, tables)
     }
+
+    /// Record `tx_order` as the last modified tx order of the entries changed in the session, in the metadata
+    /// of the tables which opt in to it. A removed entry removes its record. Called when the session finishes,
+    /// the records are loaded and charged when the entries are accessed, see `Table::load_last_modified`.
+    pub fn stamp_last_modified(&mut self, tx_order: u64) -> PartialVMResult<()> {
+        for (handle, table) in self.tables.iter_mut() {
+            if self.removed_tables.contains(handle) {
+                continue;
+            }
+            table.stamp_last_modified(tx_order)?;
+        }
+        Ok(())
+    }
 }
 
 impl Table {
+    /// Record that the entry of `key` is inserted, the table size grows by one.
+    /// The max key tracked for `push_box` follows the insertion, and the key is persisted as the last inserted
+    /// one if the table opts in to it.
+    fn record_insertion(&mut self, key: Vec<u8>) -> PartialVMResult<()> {
+        self.size_increment += 1;
+        if let Some(max_key) = self.max_u64_key.as_mut() {
+            if let Ok(index) = decode_u64_key(&key) {
+                *max_key = (*max_key).max(Some(index));
+            }
+        }
+        if let Some(Some(metadata)) = self.metadata.as_mut() {
+            if let Some(tv) = metadata.content.get_mut(&last_inserted_key()) {
+                if tv.exists()? {
+                    write_bytes_box(tv, key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the metadata table of the table `handle`, see `metadata_handle`.
+    fn new_metadata_table(handle: &ObjectID) -> Table {
+        Table {
+            handle: metadata_handle(handle),
+            key_layout: MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+            content: Default::default(),
+            size_increment: 0,
+            entries_loaded: false,
+            metadata: Some(None),
+            max_u64_key: None,
+        }
+    }
+
+    /// Load the metadata table on the first access to the entries in the session: the header with the opt-in
+    /// flags, and the last inserted key if the table opts in to it. Returns the loaded bytes.
+    fn load_metadata(
+        &mut self,
+        table_context: &NativeTableContext,
+    ) -> PartialVMResult<Option<Option<NumBytes>>> {
+        if self.metadata.is_some() {
+            return Ok(None);
+        }
+        let mut metadata = Table::new_metadata_table(&self.handle);
+        let mut loaded = metadata.load_metadata_value(
+            table_context,
+            metadata_header_key(),
+            MoveTypeLayout::U64,
+        )?;
+        if metadata.metadata_flags()? & METADATA_LAST_INSERTED != 0 {
+            loaded = merge_loaded(
+                loaded,
+                metadata.load_metadata_value(
+                    table_context,
+                    last_inserted_key(),
+                    MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+                )?,
+            );
+        }
+        self.metadata = Some(Some(Box::new(metadata)));
+        Ok(loaded)
+    }
+
+    /// Load the value of `key` of a metadata table from the resolver, the metadata values have known layouts.
+    /// Returns the loaded bytes, None if the value is cached already.
+    fn load_metadata_value(
+        &mut self,
+        table_context: &NativeTableContext,
+        key: Vec<u8>,
+        value_layout: MoveTypeLayout,
+    ) -> PartialVMResult<Option<Option<NumBytes>>> {
+        if self.content.contains_key(&key) {
+            return Ok(None);
+        }
+        let (tv, loaded) = match table_context.resolve_table_item(&self.handle, &key)? {
+            Some(state) => {
+                let val = deserialize_and_box(&value_layout, &state.value)?;
+                let loaded_bytes = NumBytes::new(state.value.len() as u64);
+                (
+                    TableRuntimeValue::new(
+                        value_layout,
+                        state.value_type,
+                        GlobalValue::cached(val)?,
+                        state.value,
+                    ),
+                    Some(loaded_bytes),
+                )
+            }
+            None => (TableRuntimeValue::none(), None),
+        };
+        self.content.insert(key, tv);
+        Ok(Some(loaded))
+    }
+
+    /// Returns the opt-in flags in the header of a metadata table, 0 if there is no header.
+    fn metadata_flags(&mut self) -> PartialVMResult<u64> {
+        match self.content.get_mut(&metadata_header_key()) {
+            Some(tv) if tv.exists()? => read_u64_box(tv),
+            _ => Ok(0),
+        }
+    }
+
+    /// Set `flag` in the header of a metadata table, the header is loaded by `load_metadata`.
+    fn set_metadata_flag(&mut self, flag: u64) -> PartialVMResult<()> {
+        let flags = self.metadata_flags()?;
+        let header = self
+            .content
+            .get_mut(&metadata_header_key())
+            .ok_or_else(|| partial_extension_error("the metadata header is not loaded"))?;
+        if header.exists()? {
+            if flags & flag == 0 {
+                write_u64_box(header, flags | flag)?;
+            }
+        } else {
+            header
+                .move_to(
+                    Value::struct_(Struct::pack(vec![Value::u64(flag)])),
+                    MoveTypeLayout::U64,
+                    TypeTag::U64,
+                )
+                .map_err(|(err, _)| err)?;
+            self.size_increment += 1;
+        }
+        Ok(())
+    }
+
+    /// Opt the table in to persist the key inserted last, the metadata is loaded by `load_metadata`.
+    /// Returns the loaded bytes.
+    fn enable_last_inserted(
+        &mut self,
+        table_context: &NativeTableContext,
+    ) -> PartialVMResult<Option<Option<NumBytes>>> {
+        let metadata = match self.metadata.as_mut() {
+            Some(Some(metadata)) => metadata,
+            _ => return Err(partial_extension_error("the table has no metadata")),
+        };
+        let loaded = metadata.load_metadata_value(
+            table_context,
+            last_inserted_key(),
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+        )?;
+        metadata.set_metadata_flag(METADATA_LAST_INSERTED)?;
+        let tv = metadata
+            .content
+            .get_mut(&last_inserted_key())
+            .ok_or_else(|| partial_extension_error("the last inserted key is not loaded"))?;
+        if !tv.exists()? {
+            tv.move_to(
+                Value::struct_(Struct::pack(vec![Value::vector_u8(vec![])])),
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+                TypeTag::Vector(Box::new(TypeTag::U8)),
+            )
+            .map_err(|(err, _)| err)?;
+            metadata.size_increment += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Returns the key inserted last persisted in the metadata, None if the table does not opt in to it
+    /// or no entry is inserted since.
+    fn persisted_last_inserted(&mut self) -> PartialVMResult<Option<Vec<u8>>> {
+        let metadata = match self.metadata.as_mut() {
+            Some(Some(metadata)) => metadata,
+            _ => return Ok(None),
+        };
+        match metadata.content.get_mut(&last_inserted_key()) {
+            Some(tv) if tv.exists()? => {
+                let key = read_bytes_box(tv)?;
+                Ok(if key.is_empty() { None } else { Some(key) })
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Opt the table in to record the last modified tx order of its entries, the metadata is loaded by
+    /// `load_metadata`. The records of the entries accessed in the session so far are loaded too.
+    /// Returns the loaded bytes.
+    fn enable_last_modified(
+        &mut self,
+        table_context: &NativeTableContext,
+    ) -> PartialVMResult<Option<Option<NumBytes>>> {
+        match self.metadata.as_mut() {
+            Some(Some(metadata)) => metadata.set_metadata_flag(METADATA_LAST_MODIFIED)?,
+            _ => return Err(partial_extension_error("the table has no metadata")),
+        }
+        let keys = self.content.keys().cloned().collect::<Vec<_>>();
+        self.load_last_modified(table_context, &keys)
+    }
+
+    /// Load the last modified records of `keys` into the metadata if the table opts in to them, with one batched
+    /// resolver lookup. The record is loaded with its entry, so a write to the entry is charged for its record.
+    /// Returns the loaded bytes, None if the table does not opt in or the records are cached already.
+    fn load_last_modified(
+        &mut self,
+        table_context: &NativeTableContext,
+        keys: &[Vec<u8>],
+    ) -> PartialVMResult<Option<Option<NumBytes>>> {
+        let metadata = match self.metadata.as_mut() {
+            Some(Some(metadata)) => metadata,
+            _ => return Ok(None),
+        };
+        if metadata.metadata_flags()? & METADATA_LAST_MODIFIED == 0 {
+            return Ok(None);
+        }
+        let record_keys = keys
+            .iter()
+            .map(|key| last_modified_key(key))
+            .filter(|record_key| !metadata.content.contains_key(record_key))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if record_keys.is_empty() {
+            return Ok(None);
+        }
+        let states = table_context.resolve_table_items(&metadata.handle, &record_keys)?;
+        let mut loaded = Some(None);
+        for (record_key, state) in record_keys.into_iter().zip(states) {
+            let tv = match state {
+                Some(state) => {
+                    let val = deserialize_and_box(&MoveTypeLayout::U64, &state.value)?;
+                    loaded =
+                        merge_loaded(loaded, Some(Some(NumBytes::new(state.value.len() as u64))));
+                    TableRuntimeValue::new(
+                        MoveTypeLayout::U64,
+                        state.value_type,
+                        GlobalValue::cached(val)?,
+                        state.value,
+                    )
+                }
+                None => TableRuntimeValue::none(),
+            };
+            metadata.content.insert(record_key, tv);
+        }
+        Ok(loaded)
+    }
+
+    /// Returns the last modified tx order of `key` recorded in the metadata, None if there is no record.
+    /// The record is loaded by `load_last_modified`.
+    fn last_modified(&mut self, key: &[u8]) -> PartialVMResult<Option<u64>> {
+        let metadata = match self.metadata.as_mut() {
+            Some(Some(metadata)) => metadata,
+            _ => return Ok(None),
+        };
+        match metadata.content.get_mut(&last_modified_key(key)) {
+            Some(tv) if tv.exists()? => read_u64_box(tv).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Record `tx_order` for the entries changed in the session if the table opts in to it, see
+    /// `TableData::stamp_last_modified`.
+    fn stamp_last_modified(&mut self, tx_order: u64) -> PartialVMResult<()> {
+        let metadata = match self.metadata.as_mut() {
+            Some(Some(metadata)) => metadata,
+            _ => return Ok(()),
+        };
+        if metadata.metadata_flags()? & METADATA_LAST_MODIFIED == 0 {
+            return Ok(());
+        }
+        for (key, tv) in self.content.iter() {
+            if !tv.is_dirty()? {
+                continue;
+            }
+            let record = metadata
+                .content
+                .get_mut(&last_modified_key(key))
+                .ok_or_else(|| partial_extension_error("the last modified record is not loaded"))?;
+            match (tv.exists()?, record.exists()?) {
+                (true, true) => write_u64_box(record, tx_order)?,
+                (true, false) => {
+                    record
+                        .move_to(
+                            Value::struct_(Struct::pack(vec![Value::u64(tx_order)])),
+                            MoveTypeLayout::U64,
+                            TypeTag::U64,
+                        )
+                        .map_err(|(err, _)| err)?;
+                    metadata.size_increment += 1;
+                }
+                (false, true) => {
+                    record.remove_unchecked()?;
+                    metadata.size_increment -= 1;
+                }
+                (false, false) => {}
+            }
+        }
+        Ok(())
+    }
+
     fn get_or_create_global_value(
         &mut self,
         native_context: &NativeContext,
         table_context: &NativeTableContext,
         key: Vec<u8>,
     ) -> PartialVMResult<(&mut TableRuntimeValue, Option<Option<NumBytes>>)> {
+        let metadata_loaded = merge_loaded(
+            self.load_metadata(table_context)?,
+            self.load_last_modified(table_context, std::slice::from_ref(&key))?,
+        );
         Ok(match self.content.entry(key) {
             Entry::Vacant(entry) => {
                 let (tv, loaded) = match table_context
-                    .resolver
-                    .resolve_table_item(&self.handle, entry.key())
-                    .map_err(|err| {
-                        partial_extension_error(format!("remote table resolver failure: {}", err))
-                    })? {
+                    .resolve_table_item(&self.handle, entry.key())?
+                {
                     Some(value_box) => {
                         let value_layout = get_type_layout(native_context, &value_box.value_type)?;
 
                         let val = deserialize_and_box(&value_layout, &value_box.value)?;
+                        let loaded_bytes = NumBytes::new(value_box.value.len() as u64);
                         (
                             TableRuntimeValue::new(
                                 value_layout,
                                 value_box.value_type,
                                 GlobalValue::cached(val)?,
+                                value_box.value,
                             ),
-                            Some(NumBytes::new(value_box.value.len() as u64)),
+                            Some(loaded_bytes),
                         )
                     }
                     None => (TableRuntimeValue::none(), None),
                 };
-                (entry.insert(tv), Some(loaded))
+                (
+                    entry.insert(tv),
+                    merge_loaded(metadata_loaded, Some(loaded)),
+                )
             }
-            Entry::Occupied(entry) => (entry.into_mut(), None),
+            Entry::Occupied(entry) => (entry.into_mut(), metadata_loaded),
         })
     }
 
+    /// Load the values of the given keys which are not cached yet, with one batched resolver lookup.
+    /// Returns the loaded bytes of each newly cached key, None if the key does not exist, and of the metadata
+    /// if it is loaded too.
+    fn load_global_values(
+        &mut self,
+        native_context: &NativeContext,
+        table_context: &NativeTableContext,
+        keys: Vec<Vec<u8>>,
+    ) -> PartialVMResult<Vec<Option<NumBytes>>> {
+        let mut loaded = vec![];
+        if let Some(metadata_loaded) = merge_loaded(
+            self.load_metadata(table_context)?,
+            self.load_last_modified(table_context, &keys)?,
+        ) {
+            loaded.push(metadata_loaded);
+        }
+        let keys = keys
+            .into_iter()
+            .filter(|key| !self.content.contains_key(key))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if keys.is_empty() {
+            return Ok(loaded);
+        }
+        let states = table_context.resolve_table_items(&self.handle, &keys)?;
+        for (key, state) in keys.into_iter().zip(states) {
+            let tv = match state {
+                Some(value_box) => {
+                    let value_layout = get_type_layout(native_context, &value_box.value_type)?;
+                    let val = deserialize_and_box(&value_layout, &value_box.value)?;
+                    loaded.push(Some(NumBytes::new(value_box.value.len() as u64)));
+                    TableRuntimeValue::new(
+                        value_layout,
+                        value_box.value_type,
+                        GlobalValue::cached(val)?,
+                        value_box.value,
+                    )
+                }
+                None => {
+                    loaded.push(None);
+                    TableRuntimeValue::none()
+                }
+            };
+            self.content.insert(key, tv);
+        }
+        Ok(loaded)
+    }
+
     pub fn get_or_create_global_value_with_layout_fn(
         &mut self,
         resolver: &dyn StateResolver,
@@ -327,13 +1010,15 @@ impl Table {
                         let value_layout = f(&value_box.value_type)?;
 
                         let val = deserialize_and_box(&value_layout, &value_box.value)?;
+                        let loaded_bytes = NumBytes::new(value_box.value.len() as u64);
                         (
                             TableRuntimeValue::new(
                                 value_layout,
                                 value_box.value_type,
                                 GlobalValue::cached(val)?,
+                                value_box.value,
                             ),
-                            Some(NumBytes::new(value_box.value.len() as u64)),
+                            Some(loaded_bytes),
                         )
                     }
                     None => (TableRuntimeValue::none(), None),
@@ -344,6 +1029,134 @@ impl Table {
         })
     }
 
+    /// Load all the entries persisted in the resolver into the table content.
+    /// The entries already in the content are kept as they are, so the in-memory changes take precedence.
+    /// Returns the number of bytes loaded from the resolver.
+    fn load_all_entries(
+        &mut self,
+        native_context: &NativeContext,
+        table_context: &NativeTableContext,
+    ) -> PartialVMResult<NumBytes> {
+        let mut loaded_bytes = 0u64;
+        if let Some(Some(metadata_bytes)) = self.load_metadata(table_context)? {
+            loaded_bytes += u64::from(metadata_bytes);
+        }
+        if self.entries_loaded {
+            return Ok(NumBytes::new(loaded_bytes));
+        }
+        self.entries_loaded = true;
+        // A table created in the current transaction has nothing persisted yet.
+        if self.handle != GLOBAL_OBJECT_STORAGE_HANDLE
+            && table_context
+                .resolver
+                .resolve_object_state(&self.handle)
+                .map_err(|err| {
+                    partial_extension_error(format!("remote table resolver failure: {}", err))
+                })?
+                .is_none()
+        {
+            return Ok(NumBytes::new(loaded_bytes));
+        }
+        let mut cursor = None;
+        let mut loaded_keys = vec![];
+        loop {
+            let items = table_context
+                .resolver
+                .list_table_items(&self.handle, cursor.clone(), LIST_TABLE_ITEMS_PAGE_SIZE)
+                .map_err(|err| {
+                    partial_extension_error(format!("remote table resolver failure: {}", err))
+                })?;
+            let page_size = items.len();
+            for (key, state) in items {
+                if let Entry::Vacant(entry) = self.content.entry(key.clone()) {
+                    let value_layout = get_type_layout(native_context, &state.value_type)?;
+                    let val = deserialize_and_box(&value_layout, &state.value)?;
+                    loaded_bytes += state.value.len() as u64;
+                    entry.insert(TableRuntimeValue::new(
+                        value_layout,
+                        state.value_type,
+                        GlobalValue::cached(val)?,
+                        state.value,
+                    ));
+                    loaded_keys.push(key.clone());
+                }
+                cursor = Some(key);
+            }
+            if page_size < LIST_TABLE_ITEMS_PAGE_SIZE {
+                break;
+            }
+        }
+        if let Some(Some(record_bytes)) = self.load_last_modified(table_context, &loaded_keys)? {
+            loaded_bytes += u64::from(record_bytes);
+        }
+        Ok(NumBytes::new(loaded_bytes))
+    }
+
+    /// Load the entries persisted in the resolver whose key bytes start with `prefix`, skipping the keys
+    /// already in the content. The resolver lists the entries in the order of the key hashes, so all the
+    /// persisted entries are scanned page by page, but only the matching ones are deserialized.
+    /// Returns the bytes of the scanned values.
+    fn load_entries_with_prefix(
+        &mut self,
+        native_context: &NativeContext,
+        table_context: &NativeTableContext,
+        prefix: &[u8],
+    ) -> PartialVMResult<NumBytes> {
+        let mut scanned_bytes = 0u64;
+        if let Some(Some(metadata_bytes)) = self.load_metadata(table_context)? {
+            scanned_bytes += u64::from(metadata_bytes);
+        }
+        if self.entries_loaded
+            || (self.handle != GLOBAL_OBJECT_STORAGE_HANDLE
+                && !table_context.remote_table_exists(&self.handle)?)
+        {
+            return Ok(NumBytes::new(scanned_bytes));
+        }
+        let mut cursor = None;
+        let mut loaded_keys = vec![];
+        loop {
+            let items = table_context
+                .resolver
+                .list_table_items(&self.handle, cursor.clone(), LIST_TABLE_ITEMS_PAGE_SIZE)
+                .map_err(|err| {
+                    partial_extension_error(format!("remote table resolver failure: {}", err))
+                })?;
+            let page_size = items.len();
+            for (key, state) in items {
+                scanned_bytes += state.value.len() as u64;
+                if key.starts_with(prefix) {
+                    if let Entry::Vacant(entry) = self.content.entry(key.clone()) {
+                        let value_layout = get_type_layout(native_context, &state.value_type)?;
+                        let val = deserialize_and_box(&value_layout, &state.value)?;
+                        entry.insert(TableRuntimeValue::new(
+                            value_layout,
+                            state.value_type,
+                            GlobalValue::cached(val)?,
+                            state.value,
+                        ));
+                        loaded_keys.push(key.clone());
+                    }
+                }
+                cursor = Some(key);
+            }
+            if page_size < LIST_TABLE_ITEMS_PAGE_SIZE {
+                break;
+            }
+        }
+        if let Some(Some(record_bytes)) = self.load_last_modified(table_context, &loaded_keys)? {
+            scanned_bytes += u64::from(record_bytes);
+        }
+        Ok(NumBytes::new(scanned_bytes))
+    }
+
+    /// Returns the type of the first value of the table in the session, None if there is no value.
+    fn session_value_type(&self) -> Option<TypeTag> {
+        self.content
+            .values()
+            .find_map(|tv| tv.value_layout_and_type.as_ref())
+            .map(|(_value_layout, value_type)| value_type.clone())
+    }
+
     pub fn get_global_value(&self, key: &Vec<u8>) -> Option<&TableRuntimeValue> {
         self.content.get(key)
     }
@@ -365,6 +1178,7 @@ impl Table {
             key_layout,
             content,
             size_increment,
+            ..
         } = self;
         (handle, key_layout, content, size_increment)
     }
@@ -379,7 +1193,7 @@ impl Table {
 
 /// Returns all natives for tables.
 pub fn table_natives(table_addr: AccountAddress, gas_params: GasParameters) -> NativeFunctionTable {
-    let natives: [(&str, &str, NativeFunction); 7] = [
+    let natives: [(&str, &str, NativeFunction); 51] = [
         (
             "raw_table",
             "add_box",
@@ -393,7 +1207,7 @@ pub fn table_natives(table_addr: AccountAddress, gas_params: GasParameters) -> N
         (
             "raw_table",
             "borrow_box_mut",
-            make_native_borrow_box(gas_params.common.clone(), gas_params.borrow_box),
+            make_native_borrow_box(gas_params.common.clone(), gas_params.borrow_box.clone()),
         ),
         (
             "raw_table",
@@ -403,7 +1217,7 @@ pub fn table_natives(table_addr: AccountAddress, gas_params: GasParameters) -> N
         (
             "raw_table",
             "contains_box",
-            make_native_contains_box(gas_params.common, gas_params.contains_box),
+            make_native_contains_box(gas_params.common.clone(), gas_params.contains_box),
         ),
         (
             "raw_table",
@@ -415,78 +1229,333 @@ pub fn table_natives(table_addr: AccountAddress, gas_params: GasParameters) -> N
             "box_length",
             make_native_box_length(gas_params.box_length),
         ),
-    ];
-
-    native_functions::make_table_from_iter(table_addr, natives)
-}
-
-#[derive(Debug, Clone)]
-pub struct CommonGasParameters {
-    pub load_base: InternalGas,
-    pub load_per_byte: InternalGasPerByte,
-    pub load_failure: InternalGas,
-}
-
-impl CommonGasParameters {
-    fn calculate_load_cost(&self, loaded: Option<Option<NumBytes>>) -> InternalGas {
-        self.load_base
-            + match loaded {
-                Some(Some(num_bytes)) => self.load_per_byte * num_bytes,
-                Some(None) => self.load_failure,
-                None => 0.into(),
-            }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct AddBoxGasParameters {
-    pub base: InternalGas,
-    pub per_byte_serialized: InternalGasPerByte,
-}
-
-fn native_add_box(
-    common_gas_params: &CommonGasParameters,
-    gas_params: &AddBoxGasParameters,
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    //0 K Type
-    //1 V Type
-    //2 Box<V> Type
-    assert_eq!(ty_args.len(), 3);
-    assert_eq!(args.len(), 3);
-
-    let table_context = context.extensions().get::<NativeTableContext>();
-    let mut table_data = table_context.table_data.write();
-
-    let val = args.pop_back().unwrap();
-    let key = args.pop_back().unwrap();
-    let handle = get_table_handle(&mut args)?;
-
-    let mut cost = gas_params.base;
-
-    let table = table_data.get_or_create_table(context, handle, &ty_args[0])?;
-
-    let key_bytes = serialize(&table.key_layout, &key)?;
-    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
-
-    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
-    cost += common_gas_params.calculate_load_cost(loaded);
-    let value_layout = type_to_type_layout(context, &ty_args[1])?;
-    let value_type = type_to_type_tag(context, &ty_args[1])?;
-    match tv.move_to(val, value_layout, value_type) {
-        Ok(_) => {
-            table.size_increment += 1;
-            Ok(NativeResult::ok(cost, smallvec![]))
-        }
-        Err(_) => Ok(NativeResult::err(
-            cost,
-            moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
-        )),
-    }
-}
-
+        (
+            "raw_table",
+            "replace_all_box",
+            make_native_replace_all_box(gas_params.common.clone(), gas_params.replace_all_box),
+        ),
+        (
+            "raw_table",
+            "contains_boxes",
+            make_native_contains_boxes(gas_params.common.clone(), gas_params.contains_boxes),
+        ),
+        (
+            "raw_table",
+            "content_root_box",
+            make_native_content_root_box(gas_params.common.clone(), gas_params.content_root_box),
+        ),
+        (
+            "raw_table",
+            "decrement_u64_box",
+            make_native_decrement_u64_box(gas_params.common.clone(), gas_params.decrement_u64_box),
+        ),
+        (
+            "raw_table",
+            "saturating_decrement_u64_box",
+            make_native_saturating_decrement_u64_box(
+                gas_params.common.clone(),
+                gas_params.saturating_decrement_u64_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "new_table_count_box",
+            make_native_new_table_count_box(gas_params.new_table_count_box),
+        ),
+        (
+            "raw_table",
+            "iter_next_entry_box",
+            make_native_iter_next_entry_box(
+                gas_params.common.clone(),
+                gas_params.iter_next_entry_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "content_proof_box",
+            make_native_content_proof_box(gas_params.common.clone(), gas_params.content_proof_box),
+        ),
+        (
+            "raw_table",
+            "is_empty_box",
+            make_native_is_empty_box(gas_params.is_empty_box),
+        ),
+        (
+            "raw_table",
+            "dirty_keys_box",
+            make_native_dirty_keys_box(gas_params.dirty_keys_box),
+        ),
+        (
+            "raw_table",
+            "cas_box",
+            make_native_cas_box(gas_params.common.clone(), gas_params.cas_box),
+        ),
+        (
+            "raw_table",
+            "export_box",
+            make_native_export_box(gas_params.common.clone(), gas_params.export_box),
+        ),
+        (
+            "raw_table",
+            "import_box",
+            make_native_import_box(gas_params.common.clone(), gas_params.import_box),
+        ),
+        (
+            "raw_table",
+            "get_or_insert_box",
+            make_native_get_or_insert_box(gas_params.common.clone(), gas_params.get_or_insert_box),
+        ),
+        (
+            "raw_table",
+            "add_box_many_same",
+            make_native_add_box_many_same(gas_params.common.clone(), gas_params.add_box_many_same),
+        ),
+        (
+            "raw_table",
+            "pending_ops_box",
+            make_native_pending_ops_box(gas_params.pending_ops_box),
+        ),
+        (
+            "raw_table",
+            "transfer_u64_box",
+            make_native_transfer_u64_box(gas_params.common.clone(), gas_params.transfer_u64_box),
+        ),
+        (
+            "raw_table",
+            "is_readonly_context_box",
+            make_native_is_readonly_context_box(gas_params.is_readonly_context_box),
+        ),
+        (
+            "raw_table",
+            "last_inserted_box",
+            make_native_last_inserted_box(gas_params.common.clone(), gas_params.last_inserted_box),
+        ),
+        (
+            "raw_table",
+            "enable_last_inserted_box",
+            make_native_enable_last_inserted_box(
+                gas_params.common.clone(),
+                gas_params.enable_last_inserted_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "move_box",
+            make_native_move_box(gas_params.common.clone(), gas_params.move_box),
+        ),
+        (
+            "raw_table",
+            "batch_ops_box",
+            make_native_batch_ops_box(gas_params.common.clone(), gas_params.batch_ops_box),
+        ),
+        (
+            "raw_table",
+            "reserve_handle_box",
+            make_native_reserve_handle_box(gas_params.reserve_handle_box),
+        ),
+        (
+            "raw_table",
+            "push_box",
+            make_native_push_box(gas_params.common.clone(), gas_params.push_box),
+        ),
+        (
+            "raw_table",
+            "init_box",
+            make_native_init_box(gas_params.common.clone(), gas_params.init_box),
+        ),
+        (
+            "raw_table",
+            "multi_read_box",
+            make_native_multi_read_box(gas_params.multi_read_box),
+        ),
+        (
+            "raw_table",
+            "create_and_add_box",
+            make_native_create_and_add_box(
+                gas_params.common.clone(),
+                gas_params.create_and_add_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "table_layouts_box",
+            make_native_table_layouts_box(gas_params.table_layouts_box),
+        ),
+        (
+            "raw_table",
+            "toggle_bool_box",
+            make_native_toggle_bool_box(gas_params.common.clone(), gas_params.toggle_bool_box),
+        ),
+        (
+            "raw_table",
+            "borrow_if_version_box",
+            make_native_borrow_if_version_box(
+                gas_params.common.clone(),
+                gas_params.borrow_if_version_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "merge_box",
+            make_native_merge_box(gas_params.common.clone(), gas_params.merge_box),
+        ),
+        (
+            "raw_table",
+            "destroy_if_version_box",
+            make_native_destroy_if_version_box(
+                gas_params.common.clone(),
+                gas_params.destroy_if_version_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "missing_keys_box",
+            make_native_missing_keys_box(gas_params.common.clone(), gas_params.missing_keys_box),
+        ),
+        (
+            "raw_table",
+            "set_if_greater_u64_box",
+            make_native_set_if_greater_u64_box(
+                gas_params.common.clone(),
+                gas_params.set_if_greater_u64_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "borrow_box_with_source",
+            make_native_borrow_box_with_source(gas_params.common.clone(), gas_params.borrow_box),
+        ),
+        (
+            "raw_table",
+            "remove_prefix_box",
+            make_native_remove_prefix_box(gas_params.common.clone(), gas_params.remove_prefix_box),
+        ),
+        (
+            "raw_table",
+            "push_bounded_box",
+            make_native_push_bounded_box(gas_params.common.clone(), gas_params.push_bounded_box),
+        ),
+        (
+            "raw_table",
+            "table_bytes_box",
+            make_native_table_bytes_box(gas_params.table_bytes_box),
+        ),
+        (
+            "raw_table",
+            "claim_slot_box",
+            make_native_claim_slot_box(gas_params.common.clone(), gas_params.claim_slot_box),
+        ),
+        (
+            "raw_table",
+            "enable_last_modified_box",
+            make_native_enable_last_modified_box(
+                gas_params.common.clone(),
+                gas_params.enable_last_modified_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "last_modified_box",
+            make_native_last_modified_box(gas_params.common.clone(), gas_params.last_modified_box),
+        ),
+        (
+            "raw_table",
+            "rotate_box",
+            make_native_rotate_box(gas_params.common.clone(), gas_params.rotate_box),
+        ),
+        (
+            "raw_table",
+            "increment_many_u64_box",
+            make_native_increment_many_u64_box(
+                gas_params.common.clone(),
+                gas_params.increment_many_u64_box,
+            ),
+        ),
+        (
+            "raw_table",
+            "borrow_box_typed",
+            make_native_borrow_box_typed(gas_params.common.clone(), gas_params.borrow_box_typed),
+        ),
+    ];
+
+    native_functions::make_table_from_iter(table_addr, natives)
+}
+
+#[derive(Debug, Clone)]
+pub struct CommonGasParameters {
+    pub load_base: InternalGas,
+    pub load_per_byte: InternalGasPerByte,
+    pub load_failure: InternalGas,
+}
+
+impl CommonGasParameters {
+    fn calculate_load_cost(&self, loaded: Option<Option<NumBytes>>) -> InternalGas {
+        self.load_base
+            + match loaded {
+                Some(Some(num_bytes)) => self.load_per_byte * num_bytes,
+                Some(None) => self.load_failure,
+                None => 0.into(),
+            }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AddBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+fn native_add_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &AddBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    //2 Box<V> Type
+    assert_eq!(ty_args.len(), 3);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let val = args.pop_back().unwrap();
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(table, 1)?;
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    if exceeds_max_entries && !tv.exists()? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    match tv.move_to(val, value_layout, value_type) {
+        Ok(_) => {
+            table.record_insertion(key_bytes)?;
+            Ok(NativeResult::ok(cost, smallvec![]))
+        }
+        Err(_) => Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
+        )),
+    }
+}
+
 pub fn make_native_add_box(
     common_gas_params: CommonGasParameters,
     gas_params: AddBoxGasParameters,
@@ -520,7 +1589,7 @@ fn native_borrow_box(
     let key = args.pop_back().unwrap();
     let handle = get_table_handle(&mut args)?;
 
-    let table = table_data.get_or_create_table(context, handle, &ty_args[0])?;
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
 
     let mut cost = gas_params.base;
 
@@ -572,7 +1641,7 @@ fn native_contains_box(
     let key = args.pop_back().unwrap();
     let handle = get_table_handle(&mut args)?;
 
-    let table = table_data.get_or_create_table(context, handle, &ty_args[0])?;
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
 
     let mut cost = gas_params.base;
 
@@ -628,7 +1697,7 @@ fn native_remove_box(
     let key = args.pop_back().unwrap();
     let handle = get_table_handle(&mut args)?;
 
-    let table = table_data.get_or_create_table(context, handle, &ty_args[0])?;
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
 
     let mut cost = gas_params.base;
 
@@ -678,14 +1747,7 @@ fn native_box_length(
 
     let handle = get_table_handle(&mut args)?;
 
-    let remote_table_size = table_context
-        .resolver
-        .resolve_object_state(&handle)
-        .map_err(|err| partial_extension_error(format!("remote table resolver failure: {}", err)))?
-        .map(|state| state.as_object::<TableInfo>())
-        .transpose()
-        .map_err(|err| partial_extension_error(format!("remote table resolver failure: {}", err)))?
-        .map_or_else(|| 0u64, |obj| obj.value.size);
+    let remote_table_size = table_context.remote_table_size(&handle)?;
 
     let size_increment = if table_data.exist_table(&handle) {
         table_data.borrow_table(&handle).unwrap().size_increment
@@ -746,48 +1808,4193 @@ pub fn make_native_drop_unchecked_box(gas_params: DropUncheckedBoxGasParameters)
 }
 
 #[derive(Debug, Clone)]
-pub struct GasParameters {
-    pub common: CommonGasParameters,
-    pub add_box: AddBoxGasParameters,
-    pub borrow_box: BorrowBoxGasParameters,
-    pub contains_box: ContainsBoxGasParameters,
-    pub remove_box: RemoveGasParameters,
-    pub drop_unchecked_box: DropUncheckedBoxGasParameters,
-    pub box_length: BoxLengthGasParameters,
-}
-
-impl GasParameters {
-    pub fn zeros() -> Self {
-        Self {
-            common: CommonGasParameters {
-                load_base: 0.into(),
-                load_per_byte: 0.into(),
-                load_failure: 0.into(),
-            },
-            add_box: AddBoxGasParameters {
-                base: 0.into(),
-                per_byte_serialized: 0.into(),
-            },
-            borrow_box: BorrowBoxGasParameters {
-                base: 0.into(),
-                per_byte_serialized: 0.into(),
-            },
-            contains_box: ContainsBoxGasParameters {
-                base: 0.into(),
-                per_byte_serialized: 0.into(),
-            },
-            remove_box: RemoveGasParameters {
-                base: 0.into(),
-                per_byte_serialized: 0.into(),
-            },
-            drop_unchecked_box: DropUncheckedBoxGasParameters { base: 0.into() },
-            box_length: BoxLengthGasParameters { base: 0.into() },
-        }
-    }
+pub struct ReplaceAllBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub per_entry: InternalGasPerArg,
 }
 
-// =========================================================================================
-// Helpers
+/// Remove all the entries of the table, then map each of the `keys` to the value at the same index.
+/// The removed entries are enumerated from the resolver, so the change set deletes them.
+/// Aborts with `E_LENGTH_NOT_MATCH` if the lengths differ, `E_ALREADY_EXISTS` if `keys` contains duplicates,
+/// and `E_TOO_MANY_ENTRIES` if the table holds more than `MAX_REPLACE_ALL_ENTRIES` entries or the keys
+/// exceed the max entries of a table, leaving the table unchanged.
+fn native_replace_all_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ReplaceAllBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    //2 Box<V> Type
+    assert_eq!(ty_args.len(), 3);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let vals = pop_arg!(args, Vector);
+    let keys = pop_arg!(args, Vector);
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    let len = keys.elem_views().len();
+    if len != vals.elem_views().len() {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_argument(E_LENGTH_NOT_MATCH),
+        ));
+    }
+    let keys = keys.unpack(&ty_args[0], len as u64)?;
+    let vals = vals.unpack(&ty_args[2], len as u64)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    // Validate all the keys before touching the table, so a failure leaves no partial change.
+    let mut keys_bytes = Vec::with_capacity(len);
+    let mut unique_keys = BTreeSet::new();
+    for key in keys.iter() {
+        let key_bytes = serialize(&table.key_layout, key)?;
+        cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+        if !unique_keys.insert(key_bytes.clone()) {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
+            ));
+        }
+        keys_bytes.push(key_bytes);
+    }
+    if matches!(table_context.max_entries_per_table, Some(max_entries) if len as u64 > max_entries)
+    {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+
+    // The table is loaded to remove its entries, so its size is bounded before loading it.
+    if table_context.exceeds_max_scanned_entries(table)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+
+    let loaded_bytes = table.load_all_entries(context, table_context)?;
+    cost += common_gas_params.calculate_load_cost(Some(Some(loaded_bytes)));
+
+    let mut removed = 0u64;
+    for tv in table.content.values_mut() {
+        if tv.remove_unchecked()? {
+            removed += 1;
+        }
+    }
+    table.size_increment -= removed as i64;
+
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    for (key_bytes, val) in keys_bytes.into_iter().zip(vals) {
+        let (tv, _) =
+            table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+        tv.move_to(val, value_layout.clone(), value_type.clone())
+            .map_err(|(err, _)| err)?;
+        table.record_insertion(key_bytes)?;
+    }
+    cost += gas_params.per_entry * NumArgs::new(removed + len as u64);
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+pub fn make_native_replace_all_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: ReplaceAllBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_replace_all_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ContainsBoxesGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+fn native_contains_boxes(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ContainsBoxesGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let keys = pop_arg!(args, Vector);
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let len = keys.elem_views().len();
+    let keys = keys.unpack(&ty_args[0], len as u64)?;
+    let mut keys_bytes = Vec::with_capacity(len);
+    for key in keys.iter() {
+        let key_bytes = serialize(&table.key_layout, key)?;
+        cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+        keys_bytes.push(key_bytes);
+    }
+
+    // The cached keys are answered directly, the others are loaded with one batched lookup.
+    let loaded = table.load_global_values(context, table_context, keys_bytes.clone())?;
+    for loaded_bytes in loaded {
+        cost += common_gas_params.calculate_load_cost(Some(loaded_bytes));
+    }
+
+    let exists = keys_bytes
+        .iter()
+        .map(|key_bytes| match table.get_global_value(key_bytes) {
+            Some(tv) => tv.exists(),
+            None => Ok(false),
+        })
+        .collect::<PartialVMResult<Vec<bool>>>()?;
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Value::vector_bool(exists)],
+    ))
+}
+
+pub fn make_native_contains_boxes(
+    common_gas_params: CommonGasParameters,
+    gas_params: ContainsBoxesGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_contains_boxes(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentRootBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_hashed: InternalGasPerByte,
+}
+
+/// Compute the merkle root over all the entries of the table, the cost is proportional to the table size.
+/// Aborts with `E_TOO_MANY_ENTRIES` if the table holds more than `MAX_REPLACE_ALL_ENTRIES` entries.
+///
+/// The hashing scheme:
+///   * leaf = sha3_256(0x00 || len(key) || key || len(value) || value), the lengths are u64 little endian,
+///     the value is the BCS bytes of `V` without the Box wrapper.
+///   * node = sha3_256(0x01 || left || right), an odd node is promoted to the next level unchanged.
+///   * the leaves are in ascending order of the BCS bytes of the key, the root of an empty table is 32 zero bytes.
+fn native_content_root_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ContentRootBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    if table_context.exceeds_max_scanned_entries(table)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    let loaded_bytes = table.load_all_entries(context, table_context)?;
+    cost += common_gas_params.calculate_load_cost(Some(Some(loaded_bytes)));
+
+    let mut leaves = vec![];
+    for (key, tv) in table.content.iter() {
+        if let Some(value) = tv.serialize_value()? {
+            cost += gas_params.per_byte_hashed * NumBytes::new((key.len() + value.len()) as u64);
+            leaves.push(content_leaf_hash(key, &value));
+        }
+    }
+    let root = content_root_of(leaves);
+
+    Ok(NativeResult::ok(cost, smallvec![Value::vector_u8(root)]))
+}
+
+fn content_leaf_hash(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(1 + 8 + key.len() + 8 + value.len());
+    buffer.push(0u8);
+    buffer.extend_from_slice(&(key.len() as u64).to_le_bytes());
+    buffer.extend_from_slice(key);
+    buffer.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    buffer.extend_from_slice(value);
+    sha3_256_of(&buffer).0.to_vec()
+}
+
+fn content_root_of(mut level: Vec<Vec<u8>>) -> Vec<u8> {
+    if level.is_empty() {
+        return vec![0u8; 32];
+    }
+    while level.len() > 1 {
+        level = content_parent_level(level);
+    }
+    level.pop().expect("The level must have one element")
+}
+
+fn content_parent_level(level: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => {
+                let mut buffer = Vec::with_capacity(1 + left.len() + right.len());
+                buffer.push(1u8);
+                buffer.extend_from_slice(left);
+                buffer.extend_from_slice(right);
+                sha3_256_of(&buffer).0.to_vec()
+            }
+            [single] => single.clone(),
+            _ => unreachable!("chunks(2) returns one or two elements"),
+        })
+        .collect()
+}
+
+pub fn make_native_content_root_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: ContentRootBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_content_root_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct DecrementU64BoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Subtract `delta` from the `u64` value of `key` and return the new value.
+/// If `saturating` is true, the value floors at zero, otherwise an underflow aborts with `E_UNDERFLOW`.
+fn native_decrement_u64_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &DecrementU64BoxGasParameters,
+    saturating: bool,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let delta = pop_arg!(args, u64);
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    if !tv.exists()? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::not_found(E_NOT_FOUND),
+        ));
+    }
+
+    let current = read_u64_box(tv)?;
+    let new_value = match current.checked_sub(delta) {
+        Some(new_value) => new_value,
+        None if saturating => 0,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_argument(E_UNDERFLOW),
+            ))
+        }
+    };
+
+    write_u64_box(tv, new_value)?;
+
+    Ok(NativeResult::ok(cost, smallvec![Value::u64(new_value)]))
+}
+
+pub fn make_native_decrement_u64_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: DecrementU64BoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_decrement_u64_box(
+                &common_gas_params,
+                &gas_params,
+                false,
+                context,
+                ty_args,
+                args,
+            )
+        },
+    )
+}
+
+pub fn make_native_saturating_decrement_u64_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: DecrementU64BoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_decrement_u64_box(
+                &common_gas_params,
+                &gas_params,
+                true,
+                context,
+                ty_args,
+                args,
+            )
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct NewTableCountBoxGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_new_table_count_box(
+    gas_params: &NewTableCountBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert!(args.is_empty());
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let table_data = table_context.table_data.read();
+
+    let count = Value::u64(table_data.new_tables.len() as u64);
+    let cost = gas_params.base;
+
+    Ok(NativeResult::ok(cost, smallvec![count]))
+}
+
+pub fn make_native_new_table_count_box(
+    gas_params: NewTableCountBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_new_table_count_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct IterNextEntryBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Returns the first entry after the cursor key. The persisted entries come first, in the order of the
+/// resolver, then the entries added in the session, in ascending order of the BCS bytes of the keys.
+/// The cursor is a vector with zero or one key, an empty cursor starts from the first entry.
+/// The result is a key vector and a value vector, both are empty if there is no more entry.
+/// The next persisted entry is listed from the resolver by the cursor and merged with the in-memory changes,
+/// so a call does not load the whole table.
+fn native_iter_next_entry_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &IterNextEntryBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let cursor = pop_arg!(args, Vector);
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let cursor_len = cursor.elem_views().len();
+    let cursor_bytes = match cursor.unpack(&ty_args[0], cursor_len as u64)?.pop() {
+        Some(cursor_key) => {
+            let cursor_bytes = serialize(&table.key_layout, &cursor_key)?;
+            cost += gas_params.per_byte_serialized * NumBytes::new(cursor_bytes.len() as u64);
+            Some(cursor_bytes)
+        }
+        None => None,
+    };
+
+    // The persisted entries are iterated first in the order of the resolver, then the entries added in the
+    // session in the order of the key bytes. A cursor persisted in the resolver continues the first pass.
+    let persisted_cursor = match &cursor_bytes {
+        Some(cursor_bytes) => match table.content.get(cursor_bytes) {
+            Some(tv) => tv.original_value.is_some(),
+            None => {
+                let state = table_context.resolve_table_item(&handle, cursor_bytes)?;
+                cost += common_gas_params.calculate_load_cost(Some(
+                    state
+                        .as_ref()
+                        .map(|state| NumBytes::new(state.value.len() as u64)),
+                ));
+                state.is_some()
+            }
+        },
+        None => true,
+    };
+
+    let mut next_key = None;
+    if persisted_cursor
+        && (handle == GLOBAL_OBJECT_STORAGE_HANDLE || table_context.remote_table_exists(&handle)?)
+    {
+        let mut remote_cursor = cursor_bytes.clone();
+        // The next persisted entry is listed one at a time, skipping the ones removed in the session.
+        while let Some((key, state)) = table_context
+            .resolver
+            .list_table_items(&handle, remote_cursor.clone(), 1)
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            })?
+            .pop()
+        {
+            cost += common_gas_params
+                .calculate_load_cost(Some(Some(NumBytes::new(state.value.len() as u64))));
+            match table.content.entry(key.clone()) {
+                Entry::Occupied(entry) => {
+                    if entry.get().exists()? {
+                        next_key = Some(key);
+                        break;
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    let value_layout = get_type_layout(context, &state.value_type)?;
+                    let val = deserialize_and_box(&value_layout, &state.value)?;
+                    entry.insert(TableRuntimeValue::new(
+                        value_layout,
+                        state.value_type,
+                        GlobalValue::cached(val)?,
+                        state.value,
+                    ));
+                    next_key = Some(key);
+                    break;
+                }
+            }
+            remote_cursor = Some(key);
+        }
+        if let Some(key) = &next_key {
+            cost += common_gas_params.calculate_load_cost(
+                table.load_last_modified(table_context, std::slice::from_ref(key))?,
+            );
+        }
+    }
+    if next_key.is_none() {
+        let lower_bound = match cursor_bytes {
+            Some(cursor_bytes) if !persisted_cursor => Bound::Excluded(cursor_bytes),
+            _ => Bound::Unbounded,
+        };
+        for (key_bytes, tv) in table.content.range((lower_bound, Bound::Unbounded)) {
+            if tv.original_value.is_none() && tv.exists()? {
+                next_key = Some(key_bytes.clone());
+                break;
+            }
+        }
+    }
+    let next_entry = match next_key {
+        Some(next_key) => table.content.get(&next_key).map(|tv| (next_key, tv)),
+        None => None,
+    };
+
+    let (keys, values) = match next_entry {
+        Some((key_bytes, tv)) => {
+            let value_type = type_to_type_tag(context, &ty_args[1])?;
+            let box_value = tv
+                .borrow_global(value_type)?
+                .value_as::<Reference>()?
+                .read_ref()?;
+            let value = box_value
+                .value_as::<Struct>()?
+                .unpack()?
+                .next()
+                .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+            let value_bytes = tv.serialize_value()?.unwrap_or_default();
+            cost += gas_params.per_byte_serialized
+                * NumBytes::new((key_bytes.len() + value_bytes.len()) as u64);
+            let key = deserialize(&table.key_layout, &key_bytes)?;
+            (vec![key], vec![value])
+        }
+        None => (vec![], vec![]),
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![
+            Vector::pack(&ty_args[0], keys)?,
+            Vector::pack(&ty_args[1], values)?
+        ],
+    ))
+}
+
+pub fn make_native_iter_next_entry_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: IterNextEntryBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_iter_next_entry_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentProofBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub per_proof_node: InternalGasPerArg,
+}
+
+/// Returns the merkle proof of the entry of `key` against the root computed by `content_root_box`.
+/// Each proof node is a direction byte followed by the 32 bytes sibling hash, from the leaf level to the root:
+///   * 0x00: the sibling is on the left, parent = sha3_256(0x01 || sibling || current)
+///   * 0x01: the sibling is on the right, parent = sha3_256(0x01 || current || sibling)
+/// A level where the node is promoted unchanged has no proof node.
+/// Aborts with `E_NOT_FOUND` if there is no entry for `key`, and with `E_TOO_MANY_ENTRIES` if the table
+/// holds more than `MAX_REPLACE_ALL_ENTRIES` entries.
+fn native_content_proof_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ContentProofBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    if table_context.exceeds_max_scanned_entries(table)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    let loaded_bytes = table.load_all_entries(context, table_context)?;
+    cost += common_gas_params.calculate_load_cost(Some(Some(loaded_bytes)));
+
+    let mut leaves = vec![];
+    let mut leaf_index = None;
+    for (entry_key, tv) in table.content.iter() {
+        if let Some(value) = tv.serialize_value()? {
+            if *entry_key == key_bytes {
+                leaf_index = Some(leaves.len());
+            }
+            leaves.push(content_leaf_hash(entry_key, &value));
+        }
+    }
+    let leaf_index = match leaf_index {
+        Some(leaf_index) => leaf_index,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::not_found(E_NOT_FOUND),
+            ))
+        }
+    };
+
+    let proof = content_proof_of(leaves, leaf_index);
+    cost += gas_params.per_proof_node * NumArgs::new(proof.len() as u64);
+
+    let proof = proof.into_iter().map(Value::vector_u8).collect::<Vec<_>>();
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&Type::Vector(Box::new(Type::U8)), proof)?],
+    ))
+}
+
+fn content_proof_of(mut level: Vec<Vec<u8>>, mut index: usize) -> Vec<Vec<u8>> {
+    let mut proof = vec![];
+    while level.len() > 1 {
+        let sibling_index = index ^ 1;
+        if let Some(sibling) = level.get(sibling_index) {
+            let mut node = Vec::with_capacity(1 + sibling.len());
+            node.push(if sibling_index < index { 0u8 } else { 1u8 });
+            node.extend_from_slice(sibling);
+            proof.push(node);
+        }
+        level = content_parent_level(level);
+        index /= 2;
+    }
+    proof
+}
+
+pub fn make_native_content_proof_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: ContentProofBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_content_proof_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct IsEmptyBoxGasParameters {
+    pub base: InternalGas,
+}
+
+/// Returns true if the table has no entries, without counting them.
+/// An existing in-memory entry means the table is not empty. Otherwise all the touched entries are removed,
+/// so the table is empty if the resolver has no entries, or if all the persisted entries are removed.
+fn native_is_empty_box(
+    gas_params: &IsEmptyBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let table_data = table_context.table_data.read();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let is_empty = is_table_empty(&table_data, table_context, &handle)?;
+
+    Ok(NativeResult::ok(
+        gas_params.base,
+        smallvec![Value::bool(is_empty)],
+    ))
+}
+
+/// Returns true if the table has no entries, merging the in-memory changes with the resolver.
+fn is_table_empty(
+    table_data: &TableData,
+    table_context: &NativeTableContext,
+    handle: &ObjectID,
+) -> PartialVMResult<bool> {
+    let size_increment = match table_data.tables.get(handle) {
+        Some(table) => {
+            for tv in table.content.values() {
+                if tv.exists()? {
+                    return Ok(false);
+                }
+            }
+            table.size_increment
+        }
+        None => 0i64,
+    };
+
+    if size_increment == 0 {
+        return table_context
+            .resolver
+            .resolve_table_is_empty(handle)
+            .map_err(|err| {
+                partial_extension_error(format!("remote table resolver failure: {}", err))
+            });
+    }
+    let remote_table_size = table_context.remote_table_size(handle)?;
+    Ok((remote_table_size as i64) + size_increment == 0)
+}
+
+pub fn make_native_is_empty_box(gas_params: IsEmptyBoxGasParameters) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_is_empty_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct DirtyKeysBoxGasParameters {
+    pub base: InternalGas,
+    pub per_dirty_key: InternalGasPerArg,
+}
+
+/// Returns the serialized keys of the entries added, modified or removed earlier in the current transaction,
+/// in ascending order of the key bytes. The in-flight state is read as it is, nothing is committed.
+fn native_dirty_keys_box(
+    gas_params: &DirtyKeysBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let table_data = table_context.table_data.read();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let mut dirty_keys = vec![];
+    if let Some(table) = table_data.tables.get(&handle) {
+        for (key, tv) in table.content.iter() {
+            if tv.is_dirty()? {
+                dirty_keys.push(Value::vector_u8(key.clone()));
+            }
+        }
+    }
+    let cost = gas_params.base + gas_params.per_dirty_key * NumArgs::new(dirty_keys.len() as u64);
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&Type::Vector(Box::new(Type::U8)), dirty_keys)?],
+    ))
+}
+
+pub fn make_native_dirty_keys_box(gas_params: DirtyKeysBoxGasParameters) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_dirty_keys_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct CasBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Replace the value of `key` with `new` if its serialized bytes equal the serialized `expected`.
+/// Returns false without any change if the value does not match or does not exist.
+fn native_cas_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &CasBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 4);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let new_val = args.pop_back().unwrap();
+    let expected = args.pop_back().unwrap();
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let expected_bytes = serialize(&value_layout, &expected)?;
+    let new_bytes = serialize(&value_layout, &new_val)?;
+    cost += gas_params.per_byte_serialized
+        * NumBytes::new((expected_bytes.len() + new_bytes.len()) as u64);
+
+    if tv.serialize_value()? != Some(expected_bytes) {
+        return Ok(NativeResult::ok(cost, smallvec![Value::bool(false)]));
+    }
+
+    tv.move_from(value_type.clone())?;
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![new_val])),
+        value_layout,
+        value_type,
+    )
+    .map_err(|(err, _)| err)?;
+
+    Ok(NativeResult::ok(cost, smallvec![Value::bool(true)]))
+}
+
+pub fn make_native_cas_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: CasBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_cas_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Serialize all the entries of the table into a snapshot, the cost is proportional to the table size.
+/// Aborts with `E_TOO_MANY_ENTRIES` if the table holds more than `MAX_REPLACE_ALL_ENTRIES` entries.
+///
+/// The snapshot format:
+///   * the concatenation of `len(key) || key || len(value) || value` of every entry, the lengths are u64 little endian,
+///     the value is the BCS bytes of `V` without the Box wrapper.
+///   * the entries are in ascending order of the BCS bytes of the key, the snapshot of an empty table is empty.
+fn native_export_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ExportBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    if table_context.exceeds_max_scanned_entries(table)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    let loaded_bytes = table.load_all_entries(context, table_context)?;
+    cost += common_gas_params.calculate_load_cost(Some(Some(loaded_bytes)));
+
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let mut snapshot = vec![];
+    for (key, tv) in table.content.iter() {
+        if !tv.exists()? {
+            continue;
+        }
+        // Check the value type, the values of the snapshot are imported as `V`.
+        tv.borrow_global(value_type.clone())?;
+        let value = tv.serialize_value()?.unwrap_or_default();
+        snapshot.extend_from_slice(&(key.len() as u64).to_le_bytes());
+        snapshot.extend_from_slice(key);
+        snapshot.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        snapshot.extend_from_slice(&value);
+    }
+    cost += gas_params.per_byte_serialized * NumBytes::new(snapshot.len() as u64);
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Value::vector_u8(snapshot)],
+    ))
+}
+
+pub fn make_native_export_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: ExportBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_export_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_deserialized: InternalGasPerByte,
+}
+
+/// Add all the entries of a snapshot produced by `export_box` to an empty table.
+/// Aborts with `E_NOT_EMPTY` if the table has entries, and with `E_INVALID_SNAPSHOT` if the snapshot is malformed,
+/// the keys are not in strictly ascending order, or a key or value does not deserialize as `K` or `V`.
+fn native_import_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ImportBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let snapshot = pop_arg!(args, Vec<u8>);
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost =
+        gas_params.base + gas_params.per_byte_deserialized * NumBytes::new(snapshot.len() as u64);
+
+    if !is_table_empty(&table_data, table_context, &handle)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_NOT_EMPTY),
+        ));
+    }
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+
+    let entries = match parse_snapshot(&snapshot, &table.key_layout, &value_layout) {
+        Some(entries) => entries,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_argument(E_INVALID_SNAPSHOT),
+            ))
+        }
+    };
+    if table_context.exceeds_max_entries(table, entries.len() as u64)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+
+    for (key_bytes, val) in entries {
+        let (tv, loaded) =
+            table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+        cost += common_gas_params.calculate_load_cost(loaded);
+        tv.move_to(
+            Value::struct_(Struct::pack(vec![val])),
+            value_layout.clone(),
+            value_type.clone(),
+        )
+        .map_err(|(err, _)| err)?;
+        table.record_insertion(key_bytes)?;
+    }
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+/// Split the snapshot into the key bytes and the value bytes of the entries.
+/// Returns None if the snapshot is truncated or the keys are not in strictly ascending order.
+fn split_snapshot(snapshot: &[u8]) -> Option<Vec<(&[u8], &[u8])>> {
+    fn read_chunk<'a>(snapshot: &'a [u8], offset: &mut usize) -> Option<&'a [u8]> {
+        let len_end = offset.checked_add(8)?;
+        let len = u64::from_le_bytes(snapshot.get(*offset..len_end)?.try_into().ok()?);
+        let end = len_end.checked_add(usize::try_from(len).ok()?)?;
+        let chunk = snapshot.get(len_end..end)?;
+        *offset = end;
+        Some(chunk)
+    }
+
+    let mut entries: Vec<(&[u8], &[u8])> = vec![];
+    let mut offset = 0;
+    while offset < snapshot.len() {
+        let key = read_chunk(snapshot, &mut offset)?;
+        let value = read_chunk(snapshot, &mut offset)?;
+        if let Some((last_key, _)) = entries.last() {
+            if *last_key >= key {
+                return None;
+            }
+        }
+        entries.push((key, value));
+    }
+    Some(entries)
+}
+
+/// Parse the snapshot into the key bytes and the deserialized values.
+/// Returns None if the snapshot is not a canonical snapshot of `K` and `V`.
+fn parse_snapshot(
+    snapshot: &[u8],
+    key_layout: &MoveTypeLayout,
+    value_layout: &MoveTypeLayout,
+) -> Option<Vec<(Vec<u8>, Value)>> {
+    split_snapshot(snapshot)?
+        .into_iter()
+        .map(|(key, value)| {
+            Value::simple_deserialize(key, key_layout)?;
+            let val = Value::simple_deserialize(value, value_layout)?;
+            Some((key.to_vec(), val))
+        })
+        .collect()
+}
+
+/// The difference between two snapshots of a table produced by `export_box`,
+/// the keys and the values are the BCS bytes, in ascending order of the keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableSnapshotDiff {
+    /// The entries only in the new snapshot, with the new values.
+    pub added: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The entries only in the old snapshot, with the old values.
+    pub removed: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The entries in both snapshots with different values, as the key, the old value and the new value.
+    pub changed: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+}
+
+impl TableSnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two snapshots of a table produced by `export_box`, for off-chain tooling.
+/// The snapshots are walked together in key order, the values are compared by their bytes.
+/// Returns an error if either snapshot is malformed.
+pub fn diff_table_snapshots(old: &[u8], new: &[u8]) -> anyhow::Result<TableSnapshotDiff> {
+    let old_entries = split_snapshot(old)
+        .ok_or_else(|| anyhow::anyhow!("The old table snapshot is malformed"))?;
+    let new_entries = split_snapshot(new)
+        .ok_or_else(|| anyhow::anyhow!("The new table snapshot is malformed"))?;
+
+    let mut diff = TableSnapshotDiff::default();
+    let mut old_iter = old_entries.into_iter().peekable();
+    let mut new_iter = new_entries.into_iter().peekable();
+    loop {
+        match (old_iter.peek(), new_iter.peek()) {
+            (Some((old_key, _)), Some((new_key, _))) if old_key == new_key => {
+                let (key, old_value) = old_iter.next().unwrap();
+                let (_, new_value) = new_iter.next().unwrap();
+                if old_value != new_value {
+                    diff.changed
+                        .push((key.to_vec(), old_value.to_vec(), new_value.to_vec()));
+                }
+            }
+            (Some((old_key, _)), Some((new_key, _))) if old_key < new_key => {
+                let (key, value) = old_iter.next().unwrap();
+                diff.removed.push((key.to_vec(), value.to_vec()));
+            }
+            (Some(_), None) => {
+                let (key, value) = old_iter.next().unwrap();
+                diff.removed.push((key.to_vec(), value.to_vec()));
+            }
+            (_, Some(_)) => {
+                let (key, value) = new_iter.next().unwrap();
+                diff.added.push((key.to_vec(), value.to_vec()));
+            }
+            (None, None) => break,
+        }
+    }
+    Ok(diff)
+}
+
+pub fn make_native_import_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: ImportBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_import_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct GetOrInsertBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Returns the value of `key`, inserting `default` first if there is no entry for `key`.
+/// Only the insert writes to the table, it is a `New` op in the change set if the key is not persisted.
+fn native_get_or_insert_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &GetOrInsertBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let default = args.pop_back().unwrap();
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(table, 1)?;
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let inserted = !tv.exists()?;
+    if inserted && exceeds_max_entries {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    if inserted {
+        let value_layout = type_to_type_layout(context, &ty_args[1])?;
+        let default_bytes = serialize(&value_layout, &default)?;
+        cost += gas_params.per_byte_serialized * NumBytes::new(default_bytes.len() as u64);
+        tv.move_to(
+            Value::struct_(Struct::pack(vec![default])),
+            value_layout,
+            value_type.clone(),
+        )
+        .map_err(|(err, _)| err)?;
+    }
+
+    let box_value = tv
+        .borrow_global(value_type)?
+        .value_as::<Reference>()?
+        .read_ref()?;
+    let value = box_value
+        .value_as::<Struct>()?
+        .unpack()?
+        .next()
+        .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+    if inserted {
+        table.record_insertion(key_bytes)?;
+    }
+
+    Ok(NativeResult::ok(cost, smallvec![value]))
+}
+
+pub fn make_native_get_or_insert_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: GetOrInsertBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_get_or_insert_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct AddBoxManySameGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Add an entry for each of the `keys`, all mapping to the same value.
+/// The value is serialized once, and each entry is deserialized from the shared bytes.
+/// Aborts with `E_ALREADY_EXISTS` if any key exists or `keys` contains duplicates, leaving the table unchanged.
+fn native_add_box_many_same(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &AddBoxManySameGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let val = args.pop_back().unwrap();
+    let keys = pop_arg!(args, Vector);
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let value_bytes = serialize(&value_layout, &val)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(value_bytes.len() as u64);
+
+    let len = keys.elem_views().len();
+    let keys = keys.unpack(&ty_args[0], len as u64)?;
+    let mut keys_bytes = Vec::with_capacity(len);
+    let mut unique_keys = BTreeSet::new();
+    for key in keys.iter() {
+        let key_bytes = serialize(&table.key_layout, key)?;
+        cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+        if !unique_keys.insert(key_bytes.clone()) {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
+            ));
+        }
+        keys_bytes.push(key_bytes);
+    }
+
+    let loaded = table.load_global_values(context, table_context, keys_bytes.clone())?;
+    for loaded_bytes in loaded {
+        cost += common_gas_params.calculate_load_cost(Some(loaded_bytes));
+    }
+
+    // Check all the keys before touching the table, so a failure leaves no partial change.
+    for key_bytes in keys_bytes.iter() {
+        if let Some(tv) = table.get_global_value(key_bytes) {
+            if tv.exists()? {
+                return Ok(NativeResult::err(
+                    cost,
+                    moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
+                ));
+            }
+        }
+    }
+    if table_context.exceeds_max_entries(table, keys_bytes.len() as u64)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+
+    for key_bytes in keys_bytes {
+        let (tv, _) =
+            table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+        tv.move_to(
+            deserialize_and_box(&value_layout, &value_bytes)?,
+            value_layout.clone(),
+            value_type.clone(),
+        )
+        .map_err(|(err, _)| err)?;
+        table.record_insertion(key_bytes)?;
+    }
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+pub fn make_native_add_box_many_same(
+    common_gas_params: CommonGasParameters,
+    gas_params: AddBoxManySameGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_add_box_many_same(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingOpsBoxGasParameters {
+    pub base: InternalGas,
+    pub per_op: InternalGasPerArg,
+    pub per_byte: InternalGasPerByte,
+}
+
+/// Returns the operations of the current transaction on the table as three vectors of the same length:
+/// the operation tags, the serialized keys and the serialized values, in ascending order of the key bytes.
+/// The value of a delete operation is empty. The in-flight state is read as it is, nothing is committed.
+fn native_pending_ops_box(
+    gas_params: &PendingOpsBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let table_data = table_context.table_data.read();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    let mut tags = vec![];
+    let mut keys = vec![];
+    let mut values = vec![];
+    if let Some(table) = table_data.tables.get(&handle) {
+        for (key, tv) in table.content.iter() {
+            let (tag, value) = match (&tv.original_value, tv.serialize_value()?) {
+                (None, Some(value)) => (TABLE_OP_NEW, value),
+                (Some(original_value), Some(value)) if *original_value != value => {
+                    (TABLE_OP_MODIFY, value)
+                }
+                (Some(_), None) => (TABLE_OP_DELETE, vec![]),
+                _ => continue,
+            };
+            cost += gas_params.per_op * NumArgs::new(1)
+                + gas_params.per_byte * NumBytes::new((key.len() + value.len()) as u64);
+            tags.push(tag);
+            keys.push(Value::vector_u8(key.clone()));
+            values.push(Value::vector_u8(value));
+        }
+    }
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![
+            Value::vector_u8(tags),
+            Vector::pack(&Type::Vector(Box::new(Type::U8)), keys)?,
+            Vector::pack(&Type::Vector(Box::new(Type::U8)), values)?
+        ],
+    ))
+}
+
+pub fn make_native_pending_ops_box(gas_params: PendingOpsBoxGasParameters) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_pending_ops_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferU64BoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Move `amount` from the `u64` value of `from_key` to the `u64` value of `to_key`.
+/// Both values are checked before any is written, so either both are updated or none.
+/// Aborts with `E_NOT_FOUND` if either key is absent, `E_UNDERFLOW` if the balance of `from_key`
+/// is less than `amount`, and `E_OVERFLOW` if the value of `to_key` would overflow.
+fn native_transfer_u64_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &TransferU64BoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 4);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let amount = pop_arg!(args, u64);
+    let to_key = args.pop_back().unwrap();
+    let from_key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let from_key_bytes = serialize(&table.key_layout, &from_key)?;
+    let to_key_bytes = serialize(&table.key_layout, &to_key)?;
+    cost += gas_params.per_byte_serialized
+        * NumBytes::new((from_key_bytes.len() + to_key_bytes.len()) as u64);
+
+    let mut balances = Vec::with_capacity(2);
+    for key_bytes in [&from_key_bytes, &to_key_bytes] {
+        let (tv, loaded) =
+            table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+        cost += common_gas_params.calculate_load_cost(loaded);
+        if !tv.exists()? {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::not_found(E_NOT_FOUND),
+            ));
+        }
+        balances.push(read_u64_box(tv)?);
+    }
+    let (from_balance, to_balance) = (balances[0], balances[1]);
+
+    let new_from_balance = match from_balance.checked_sub(amount) {
+        Some(new_from_balance) => new_from_balance,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_argument(E_UNDERFLOW),
+            ))
+        }
+    };
+    // Transfer to the same key leaves the balance unchanged.
+    if from_key_bytes == to_key_bytes {
+        return Ok(NativeResult::ok(cost, smallvec![]));
+    }
+    let new_to_balance = match to_balance.checked_add(amount) {
+        Some(new_to_balance) => new_to_balance,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_argument(E_OVERFLOW),
+            ))
+        }
+    };
+
+    for (key_bytes, new_balance) in [
+        (from_key_bytes, new_from_balance),
+        (to_key_bytes, new_to_balance),
+    ] {
+        let (tv, _) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+        write_u64_box(tv, new_balance)?;
+    }
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+fn read_u64_box(tv: &mut TableRuntimeValue) -> PartialVMResult<u64> {
+    tv.borrow_global(TypeTag::U64)?
+        .value_as::<Reference>()?
+        .read_ref()?
+        .value_as::<Struct>()?
+        .unpack()?
+        .next()
+        .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?
+        .value_as::<u64>()
+}
+
+fn read_bytes_box(tv: &mut TableRuntimeValue) -> PartialVMResult<Vec<u8>> {
+    tv.borrow_global(TypeTag::Vector(Box::new(TypeTag::U8)))?
+        .value_as::<Reference>()?
+        .read_ref()?
+        .value_as::<Struct>()?
+        .unpack()?
+        .next()
+        .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?
+        .value_as::<Vec<u8>>()
+}
+
+fn write_bytes_box(tv: &mut TableRuntimeValue, value: Vec<u8>) -> PartialVMResult<()> {
+    let value_type = TypeTag::Vector(Box::new(TypeTag::U8));
+    tv.move_from(value_type.clone())?;
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![Value::vector_u8(value)])),
+        MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+        value_type,
+    )
+    .map_err(|(err, _)| err)
+}
+
+fn write_u64_box(tv: &mut TableRuntimeValue, value: u64) -> PartialVMResult<()> {
+    tv.move_from(TypeTag::U64)?;
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![Value::u64(value)])),
+        MoveTypeLayout::U64,
+        TypeTag::U64,
+    )
+    .map_err(|(err, _)| err)
+}
+
+pub fn make_native_transfer_u64_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: TransferU64BoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_transfer_u64_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct IsReadonlyContextBoxGasParameters {
+    pub base: InternalGas,
+}
+
+/// Returns whether the table context is created for a read-only session.
+fn native_is_readonly_context_box(
+    gas_params: &IsReadonlyContextBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert!(ty_args.is_empty());
+    assert!(args.is_empty());
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+
+    Ok(NativeResult::ok(
+        gas_params.base,
+        smallvec![Value::bool(table_context.read_only)],
+    ))
+}
+
+pub fn make_native_is_readonly_context_box(
+    gas_params: IsReadonlyContextBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_is_readonly_context_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct LastInsertedBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Returns the entry inserted last into the table, if it still exists.
+/// The key inserted last is persisted in the metadata of a table which opts in by `enable_last_inserted_box`,
+/// the result is empty for a table which does not, or if no entry is inserted since the opt-in.
+/// The result is a key vector and a value vector, both are empty if there is no such entry.
+fn native_last_inserted_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &LastInsertedBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    cost += common_gas_params.calculate_load_cost(table.load_metadata(table_context)?);
+    let (keys, values) = match table.persisted_last_inserted()? {
+        Some(key_bytes) => {
+            let (tv, loaded) =
+                table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+            cost += common_gas_params.calculate_load_cost(loaded);
+            if tv.exists()? {
+                let value_type = type_to_type_tag(context, &ty_args[1])?;
+                let box_value = tv
+                    .borrow_global(value_type)?
+                    .value_as::<Reference>()?
+                    .read_ref()?;
+                let value = box_value
+                    .value_as::<Struct>()?
+                    .unpack()?
+                    .next()
+                    .ok_or_else(|| {
+                        partial_extension_error("Box<V> should have one field of type V")
+                    })?;
+                let value_bytes = tv.serialize_value()?.unwrap_or_default();
+                cost += gas_params.per_byte_serialized
+                    * NumBytes::new((key_bytes.len() + value_bytes.len()) as u64);
+                let key = deserialize(&table.key_layout, &key_bytes)?;
+                (vec![key], vec![value])
+            } else {
+                (vec![], vec![])
+            }
+        }
+        None => (vec![], vec![]),
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![
+            Vector::pack(&ty_args[0], keys)?,
+            Vector::pack(&ty_args[1], values)?
+        ],
+    ))
+}
+
+pub fn make_native_last_inserted_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: LastInsertedBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_last_inserted_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct EnableLastInsertedBoxGasParameters {
+    pub base: InternalGas,
+}
+
+/// Opt the table in to persist the key inserted last in its metadata, see `last_inserted_box`.
+/// Enabling an enabled table does nothing.
+fn native_enable_last_inserted_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &EnableLastInsertedBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    cost += common_gas_params.calculate_load_cost(table.load_metadata(table_context)?);
+    cost += common_gas_params.calculate_load_cost(table.enable_last_inserted(table_context)?);
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+pub fn make_native_enable_last_inserted_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: EnableLastInsertedBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_enable_last_inserted_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct MoveBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Move the entry of `key` from the source table to the destination table, both with the same key and value types.
+/// The stored bytes are transferred as is without reserializing the value, so the destination keeps
+/// the exact encoding of the source, unless the value is modified after the move.
+/// Aborts if there is no entry for `key` in the source table, or if the destination table already has one.
+fn native_move_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &MoveBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let key = args.pop_back().unwrap();
+    let to_handle = get_table_handle(&mut args)?;
+    let from_handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    let to_table =
+        table_data.get_or_create_table(context, table_context, to_handle, &ty_args[0])?;
+    let key_bytes = serialize(&to_table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(to_table, 1)?;
+    let (to_tv, loaded) =
+        to_table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    if to_tv.exists()? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
+        ));
+    }
+    if exceeds_max_entries {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let from_table =
+        table_data.get_or_create_table(context, table_context, from_handle, &ty_args[0])?;
+    let (from_tv, loaded) =
+        from_table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    let (bytes, serialized) = match (from_tv.stored_bytes()?, from_tv.serialize_value()?) {
+        (Some(bytes), Some(serialized)) => (bytes, serialized),
+        _ => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::not_found(E_NOT_FOUND),
+            ))
+        }
+    };
+    cost += gas_params.per_byte_serialized * NumBytes::new(bytes.len() as u64);
+    let box_value = from_tv.move_from(value_type.clone())?;
+    from_table.size_increment -= 1;
+
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let to_table =
+        table_data.get_or_create_table(context, table_context, to_handle, &ty_args[0])?;
+    let (to_tv, _) =
+        to_table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    to_tv
+        .move_to(box_value, value_layout, value_type)
+        .map_err(|(err, _)| err)?;
+    to_tv.verbatim_value = Some(VerbatimValue { bytes, serialized });
+    to_table.record_insertion(key_bytes)?;
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+pub fn make_native_move_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: MoveBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_move_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchOpsBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_deserialized: InternalGasPerByte,
+}
+
+/// Apply a batch of operations on the table, given as three vectors of the same length in the form `pending_ops_box`
+/// returns: the operation tags, the serialized keys and the serialized values. The value of a delete operation is ignored.
+/// The whole batch is checked before any operation is applied, so either all the operations are applied or none.
+/// Aborts with `E_LENGTH_NOT_MATCH` if the vectors have different lengths, `E_INVALID_OPERATION` if a tag is unknown
+/// or a key or value does not deserialize as `K` or `V`, `E_DUPLICATE_OPERATION` if a key has more than one operation,
+/// `E_ALREADY_EXISTS` if a new key exists, `E_NOT_FOUND` if a modified or deleted key is absent,
+/// and `E_TOO_MANY_ENTRIES` if the table would exceed the max number of entries.
+fn native_batch_ops_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &BatchOpsBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 4);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let values = pop_arg!(args, Vector);
+    let keys = pop_arg!(args, Vector);
+    let tags = pop_arg!(args, Vec<u8>);
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    let len = tags.len();
+    if keys.elem_views().len() != len || values.elem_views().len() != len {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_argument(E_LENGTH_NOT_MATCH),
+        ));
+    }
+    let bytes_type = Type::Vector(Box::new(Type::U8));
+    let keys = keys
+        .unpack(&bytes_type, len as u64)?
+        .into_iter()
+        .map(|key| key.value_as::<Vec<u8>>())
+        .collect::<PartialVMResult<Vec<_>>>()?;
+    let values = values
+        .unpack(&bytes_type, len as u64)?
+        .into_iter()
+        .map(|value| value.value_as::<Vec<u8>>())
+        .collect::<PartialVMResult<Vec<_>>>()?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+
+    // Check the whole batch before touching the table, so a failure leaves no partial change.
+    let mut ops = Vec::with_capacity(len);
+    let mut unique_keys = BTreeSet::new();
+    for ((tag, key_bytes), value_bytes) in tags.into_iter().zip(keys).zip(values) {
+        cost += gas_params.per_byte_deserialized
+            * NumBytes::new((key_bytes.len() + value_bytes.len()) as u64);
+        // The value of a delete operation is None, the value of an invalid operation is Some(None).
+        let value = match tag {
+            TABLE_OP_NEW | TABLE_OP_MODIFY => {
+                Some(Value::simple_deserialize(&value_bytes, &value_layout))
+            }
+            TABLE_OP_DELETE => None,
+            _ => Some(None),
+        };
+        if matches!(value, Some(None))
+            || Value::simple_deserialize(&key_bytes, &table.key_layout).is_none()
+        {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_argument(E_INVALID_OPERATION),
+            ));
+        }
+        let value = value.flatten();
+        if !unique_keys.insert(key_bytes.clone()) {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_argument(E_DUPLICATE_OPERATION),
+            ));
+        }
+        ops.push((tag, key_bytes, value));
+    }
+
+    let loaded = table.load_global_values(
+        context,
+        table_context,
+        ops.iter()
+            .map(|(_, key_bytes, _)| key_bytes.clone())
+            .collect(),
+    )?;
+    for loaded_bytes in loaded {
+        cost += common_gas_params.calculate_load_cost(Some(loaded_bytes));
+    }
+
+    let (mut new_entries, mut deleted_entries) = (0u64, 0u64);
+    for (tag, key_bytes, _) in ops.iter() {
+        let (tv, _) =
+            table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+        let exists = tv.exists()?;
+        if exists {
+            // Check the value type, the existing values are replaced or removed as `V`.
+            tv.borrow_global(value_type.clone())?;
+        }
+        match (*tag, exists) {
+            (TABLE_OP_NEW, true) => {
+                return Ok(NativeResult::err(
+                    cost,
+                    moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
+                ))
+            }
+            (TABLE_OP_MODIFY | TABLE_OP_DELETE, false) => {
+                return Ok(NativeResult::err(
+                    cost,
+                    moveos_types::move_std::error::not_found(E_NOT_FOUND),
+                ))
+            }
+            (TABLE_OP_NEW, false) => new_entries += 1,
+            (TABLE_OP_DELETE, true) => deleted_entries += 1,
+            _ => {}
+        }
+    }
+    if table_context.exceeds_max_entries(table, new_entries.saturating_sub(deleted_entries))? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+
+    for (tag, key_bytes, value) in ops {
+        let (tv, _) =
+            table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+        if tag != TABLE_OP_NEW {
+            tv.move_from(value_type.clone())?;
+        }
+        match value {
+            None => table.size_increment -= 1,
+            Some(value) => {
+                tv.move_to(
+                    Value::struct_(Struct::pack(vec![value])),
+                    value_layout.clone(),
+                    value_type.clone(),
+                )
+                .map_err(|(err, _)| err)?;
+                if tag == TABLE_OP_NEW {
+                    table.record_insertion(key_bytes)?;
+                }
+            }
+        }
+    }
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+pub fn make_native_batch_ops_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: BatchOpsBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_batch_ops_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ReserveHandleBoxGasParameters {
+    pub base: InternalGas,
+}
+
+/// Reserve a table handle, without creating the table. The table is created by the first operation on
+/// the handle, such as `add_box`.
+/// The handle is derived as `sha3_256(tx_hash || RESERVED_HANDLE_DOMAIN || bcs(index))`, where `index` is
+/// the number of handles reserved before in the session, so reserving twice yields distinct handles.
+/// The domain keeps the reserved handles apart from the object ids derived by `tx_context::derive_id`.
+fn native_reserve_handle_box(
+    gas_params: &ReserveHandleBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert!(args.is_empty());
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let index = table_data.reserved_handles;
+    table_data.reserved_handles += 1;
+    let handle = reserved_handle(&table_context.tx_hash, index);
+
+    Ok(NativeResult::ok(
+        gas_params.base,
+        smallvec![Value::address(handle)],
+    ))
+}
+
+/// The handle reserved at `index` in the transaction `tx_hash`, see `native_reserve_handle_box`.
+pub fn reserved_handle(tx_hash: &H256, index: u64) -> AccountAddress {
+    let mut bytes = tx_hash.0.to_vec();
+    bytes.extend_from_slice(RESERVED_HANDLE_DOMAIN);
+    bytes.extend_from_slice(&index.to_le_bytes());
+    AccountAddress::new(sha3_256_of(&bytes).0)
+}
+
+pub fn make_native_reserve_handle_box(gas_params: ReserveHandleBoxGasParameters) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_reserve_handle_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct PushBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Add `val` to a table with `u64` keys under the next sequence number, and return the number.
+/// The next sequence number is persisted in a metadata entry of a separate table, see `next_sequence_handle`,
+/// so a push reads one entry and the number of a removed entry is never reused. The numbers start from 0,
+/// the keys inserted in the session after the first push are tracked, so a later push skips them.
+/// A table pushed to before the number is persisted loads all its entries once to find its max key.
+/// Aborts with `E_ALREADY_EXISTS` if the number is taken by a key added otherwise, and with `E_OVERFLOW`
+/// if the numbers are exhausted.
+fn native_push_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &PushBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 V Type
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let val = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &Type::U64)?;
+    if table.key_layout != MoveTypeLayout::U64 {
+        return Err(partial_extension_error(
+            "push_box requires a table with u64 keys",
+        ));
+    }
+
+    match push_value(
+        common_gas_params,
+        gas_params.per_byte_serialized,
+        context,
+        table_context,
+        &mut table_data,
+        handle,
+        &ty_args[0],
+        val,
+        &mut cost,
+    )? {
+        Ok(index) => Ok(NativeResult::ok(cost, smallvec![Value::u64(index)])),
+        Err(abort_code) => Ok(NativeResult::err(cost, abort_code)),
+    }
+}
+
+/// Add `val` to the table `handle` with `u64` keys under the next sequence number, as `push_box`, charging
+/// to `cost`. Returns the number, or the abort code if the value can not be added.
+#[allow(clippy::too_many_arguments)]
+fn push_value(
+    common_gas_params: &CommonGasParameters,
+    per_byte_serialized: InternalGasPerByte,
+    context: &NativeContext,
+    table_context: &NativeTableContext,
+    table_data: &mut TableData,
+    handle: ObjectID,
+    value_ty: &Type,
+    val: Value,
+    cost: &mut InternalGas,
+) -> PartialVMResult<Result<u64, u64>> {
+    let max_key = match table_data
+        .get_or_create_table(context, table_context, handle, &Type::U64)?
+        .max_u64_key
+    {
+        Some(max_key) => max_key,
+        None => {
+            let sequence_table = table_data.get_or_create_table_with_key_layout(
+                next_sequence_handle(&handle),
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+            )?;
+            let (tv, loaded) = sequence_table.get_or_create_global_value(
+                context,
+                table_context,
+                next_sequence_key(),
+            )?;
+            *cost += common_gas_params.calculate_load_cost(loaded);
+            let persisted_next = if tv.exists()? {
+                Some(read_u64_box(tv)?)
+            } else {
+                None
+            };
+            let table =
+                table_data.get_or_create_table(context, table_context, handle, &Type::U64)?;
+            let max_key = match persisted_next {
+                Some(next) => next.checked_sub(1),
+                None => {
+                    // The table is pushed to before the next sequence number is persisted.
+                    let loaded_bytes = table.load_all_entries(context, table_context)?;
+                    *cost += common_gas_params.calculate_load_cost(Some(Some(loaded_bytes)));
+                    let mut max_key = None;
+                    for (key_bytes, tv) in table.content.iter_mut() {
+                        if tv.exists()? {
+                            max_key = max_key.max(Some(decode_u64_key(key_bytes)?));
+                        }
+                    }
+                    max_key
+                }
+            };
+            table.max_u64_key = Some(max_key);
+            max_key
+        }
+    };
+    // The persisted next number is one more than the index, so the index stays below `u64::MAX`.
+    let index = match max_key {
+        Some(max_key) if max_key >= u64::MAX - 1 => {
+            return Ok(Err(moveos_types::move_std::error::invalid_state(
+                E_OVERFLOW,
+            )))
+        }
+        Some(max_key) => max_key + 1,
+        None => 0,
+    };
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &Type::U64)?;
+    let key_bytes = index.to_le_bytes().to_vec();
+    *cost += per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+    if table_context.exceeds_max_entries(table, 1)? {
+        return Ok(Err(moveos_types::move_std::error::invalid_state(
+            E_TOO_MANY_ENTRIES,
+        )));
+    }
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    *cost += common_gas_params.calculate_load_cost(loaded);
+    if tv.exists()? {
+        return Ok(Err(moveos_types::move_std::error::already_exists(
+            E_ALREADY_EXISTS,
+        )));
+    }
+    let value_layout = type_to_type_layout(context, value_ty)?;
+    let value_type = type_to_type_tag(context, value_ty)?;
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![val])),
+        value_layout,
+        value_type,
+    )
+    .map_err(|(err, _)| err)?;
+    table.record_insertion(key_bytes)?;
+
+    let sequence_table = table_data.get_or_create_table_with_key_layout(
+        next_sequence_handle(&handle),
+        MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+    )?;
+    let (tv, loaded) =
+        sequence_table.get_or_create_global_value(context, table_context, next_sequence_key())?;
+    *cost += common_gas_params.calculate_load_cost(loaded);
+    if tv.exists()? {
+        write_u64_box(tv, index + 1)?;
+    } else {
+        tv.move_to(
+            Value::struct_(Struct::pack(vec![Value::u64(index + 1)])),
+            MoveTypeLayout::U64,
+            TypeTag::U64,
+        )
+        .map_err(|(err, _)| err)?;
+        sequence_table.record_insertion(next_sequence_key())?;
+    }
+
+    Ok(Ok(index))
+}
+
+/// The handle of the metadata table of the table `handle`, which keeps the opt-in records of the table.
+/// The metadata table is loaded with its table, its entries are keyed by the BCS of a `vector<u8>`.
+pub fn metadata_handle(handle: &ObjectID) -> ObjectID {
+    let mut bytes = handle.to_bytes();
+    bytes.extend_from_slice(METADATA_HANDLE_DOMAIN);
+    ObjectID::from(AccountAddress::new(sha3_256_of(&bytes).0))
+}
+
+/// The key of the metadata header, which holds the opt-in flags as a `u64`, the BCS of the empty `vector<u8>`.
+fn metadata_header_key() -> Vec<u8> {
+    bcs::to_bytes(&Vec::<u8>::new()).expect("Serialize the key bytes should not fail")
+}
+
+/// The key of the metadata entry which holds the BCS key inserted last as a `vector<u8>`.
+fn last_inserted_key() -> Vec<u8> {
+    bcs::to_bytes(&vec![1u8]).expect("Serialize the key bytes should not fail")
+}
+
+/// The key of the metadata entry which holds the last modified tx order of the entry `key` as a `u64`,
+/// the BCS of the key bytes after a zero byte as a `vector<u8>`.
+fn last_modified_key(key: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0u8];
+    bytes.extend_from_slice(key);
+    bcs::to_bytes(&bytes).expect("Serialize the key bytes should not fail")
+}
+
+/// Merge the loads of two lookups, charged as one load of the bytes of both.
+fn merge_loaded(
+    first: Option<Option<NumBytes>>,
+    second: Option<Option<NumBytes>>,
+) -> Option<Option<NumBytes>> {
+    match (first, second) {
+        (None, loaded) | (loaded, None) => loaded,
+        (Some(None), Some(None)) => Some(None),
+        (Some(first), Some(second)) => Some(Some(
+            first.unwrap_or_else(|| NumBytes::new(0)) + second.unwrap_or_else(|| NumBytes::new(0)),
+        )),
+    }
+}
+
+/// The handle of the table which records the next sequence number of `push_box` for the table `handle`.
+pub fn next_sequence_handle(handle: &ObjectID) -> ObjectID {
+    let mut bytes = handle.to_bytes();
+    bytes.extend_from_slice(NEXT_SEQUENCE_HANDLE_DOMAIN);
+    ObjectID::from(AccountAddress::new(sha3_256_of(&bytes).0))
+}
+
+/// The key of the next sequence number entry, the BCS of the empty `vector<u8>`.
+fn next_sequence_key() -> Vec<u8> {
+    bcs::to_bytes(&Vec::<u8>::new()).expect("Serialize the key bytes should not fail")
+}
+
+/// The key of the head sequence number entry of `push_bounded_box`, the BCS of the `vector<u8>` "head".
+fn head_sequence_key() -> Vec<u8> {
+    bcs::to_bytes(&b"head".to_vec()).expect("Serialize the key bytes should not fail")
+}
+
+/// Decode the BCS bytes of a `u64` key.
+fn decode_u64_key(key_bytes: &[u8]) -> PartialVMResult<u64> {
+    key_bytes
+        .try_into()
+        .map(u64::from_le_bytes)
+        .map_err(|_| partial_extension_error("the key of the table is not a u64"))
+}
+
+pub fn make_native_push_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: PushBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_push_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct InitBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Add an entry for `key` mapping to `val` only if the table has no entries, and return whether it is added.
+/// The table is left unchanged and `val` is dropped if the table is not empty.
+/// The base cost covers the emptiness check, the insert is charged as `add_box`.
+fn native_init_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &InitBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let val = args.pop_back().unwrap();
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    if !is_table_empty(&table_data, table_context, &handle)? {
+        return Ok(NativeResult::ok(cost, smallvec![Value::bool(false)]));
+    }
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    if table_context.exceeds_max_entries(table, 1)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![val])),
+        value_layout,
+        value_type,
+    )
+    .map_err(|(err, _)| err)?;
+    table.record_insertion(key_bytes)?;
+
+    Ok(NativeResult::ok(cost, smallvec![Value::bool(true)]))
+}
+
+pub fn make_native_init_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: InitBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_init_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct MultiReadBoxGasParameters {
+    pub base: InternalGas,
+    pub per_read: InternalGasPerArg,
+    pub per_byte: InternalGasPerByte,
+}
+
+/// Read the serialized value of each `(handles[i], keys[i])` across any tables, as two vectors in the
+/// request order: whether the entry exists, and the value bytes, empty if it does not.
+/// All the reads see the same state, the in-memory entries of the current session first, then the resolver
+/// with one batched lookup per table. Nothing is cached, so the reads do not need the value types.
+/// Aborts with `E_LENGTH_NOT_MATCH` if `handles` and `keys` have different lengths.
+fn native_multi_read_box(
+    gas_params: &MultiReadBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let table_data = table_context.table_data.read();
+
+    let keys = pop_arg!(args, Vector);
+    let handles = pop_arg!(args, Vector);
+
+    let mut cost = gas_params.base;
+
+    let len = handles.elem_views().len();
+    if keys.elem_views().len() != len {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_argument(E_LENGTH_NOT_MATCH),
+        ));
+    }
+    let handles = handles
+        .unpack(&Type::Address, len as u64)?
+        .into_iter()
+        .map(|handle| handle.value_as::<AccountAddress>().map(ObjectID::from))
+        .collect::<PartialVMResult<Vec<_>>>()?;
+    let keys = keys
+        .unpack(&Type::Vector(Box::new(Type::U8)), len as u64)?
+        .into_iter()
+        .map(|key| key.value_as::<Vec<u8>>())
+        .collect::<PartialVMResult<Vec<_>>>()?;
+    cost += gas_params.per_read * NumArgs::new(len as u64);
+
+    let mut results: Vec<Option<Option<Vec<u8>>>> = vec![None; len];
+    // The reads which miss the in-memory entries, grouped by table for the batched lookups.
+    let mut remote_reads: BTreeMap<ObjectID, Vec<usize>> = BTreeMap::new();
+    for (index, (handle, key)) in handles.iter().zip(&keys).enumerate() {
+        match table_data
+            .tables
+            .get(handle)
+            .and_then(|table| table.content.get(key))
+        {
+            Some(tv) => results[index] = Some(tv.serialize_value()?),
+            None => remote_reads.entry(*handle).or_default().push(index),
+        }
+    }
+    for (handle, indexes) in remote_reads {
+        let remote_keys = indexes
+            .iter()
+            .map(|index| keys[*index].clone())
+            .collect::<Vec<_>>();
+        let states = table_context.resolve_table_items(&handle, &remote_keys)?;
+        for (index, state) in indexes.into_iter().zip(states) {
+            results[index] = Some(state.map(|state| state.value));
+        }
+    }
+
+    let mut found = Vec::with_capacity(len);
+    let mut values = Vec::with_capacity(len);
+    for (key, result) in keys.iter().zip(results) {
+        let value = result.expect("Every read is resolved");
+        cost += gas_params.per_byte
+            * NumBytes::new((key.len() + value.as_ref().map_or(0, Vec::len)) as u64);
+        found.push(value.is_some());
+        values.push(Value::vector_u8(value.unwrap_or_default()));
+    }
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![
+            Value::vector_bool(found),
+            Vector::pack(&Type::Vector(Box::new(Type::U8)), values)?
+        ],
+    ))
+}
+
+pub fn make_native_multi_read_box(gas_params: MultiReadBoxGasParameters) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_multi_read_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateAndAddBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub new_table: InternalGas,
+}
+
+/// Add an entry for `key` mapping to `val`, creating the table first if it does not exist, in one native.
+/// The table exists if it is created before in the session or its table object is in the resolver,
+/// otherwise it is registered in the new tables of the session with the key type by `get_or_create_table`,
+/// so the first entry is never written to a table which is not created.
+/// Aborts with `E_ALREADY_EXISTS` if the table exists and has an entry for `key`.
+/// The creation is charged `new_table` on top of the insert, which is charged as `add_box`.
+fn native_create_and_add_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &CreateAndAddBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    //2 Box<V> Type
+    assert_eq!(ty_args.len(), 3);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let val = args.pop_back().unwrap();
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    // The table is registered in the new tables of the session when it is first loaded.
+    let registered = table_data.new_tables.contains_key(&handle);
+    table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+    if !registered && table_data.new_tables.contains_key(&handle) {
+        cost += gas_params.new_table;
+    }
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(table, 1)?;
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    if exceeds_max_entries && !tv.exists()? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    match tv.move_to(val, value_layout, value_type) {
+        Ok(_) => {
+            table.record_insertion(key_bytes)?;
+            Ok(NativeResult::ok(cost, smallvec![]))
+        }
+        Err(_) => Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::already_exists(E_ALREADY_EXISTS),
+        )),
+    }
+}
+
+pub fn make_native_create_and_add_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: CreateAndAddBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_create_and_add_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct TableLayoutsBoxGasParameters {
+    pub base: InternalGas,
+}
+
+/// Returns the BCS bytes of the `MoveTypeLayout` of the keys and of the values of the table, from the types
+/// kept in the `TableInfo` of the table when it is created. For a table created in the session, the key type is
+/// the one it is created with and the value type the one of its first value. The value layout is empty if the
+/// value type is not known, such as for a table without values.
+/// Aborts with `E_NOT_FOUND` if the table does not exist or its key type is not known.
+fn native_table_layouts_box(
+    gas_params: &TableLayoutsBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let table_data = table_context.table_data.read();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let cost = gas_params.base;
+
+    let (key_type, value_type) = if table_data.removed_tables.contains(&handle) {
+        (None, None)
+    } else if let Some(type_info) = table_data.new_tables.get(&handle) {
+        (
+            Some(type_info.key_type.clone()),
+            table_data
+                .tables
+                .get(&handle)
+                .and_then(|table| table.session_value_type()),
+        )
+    } else {
+        match table_context.remote_table_info(&handle)? {
+            Some(table_info) => {
+                let decode_err = |err: anyhow::Error| {
+                    partial_extension_error(format!("decode table type failed: {}", err))
+                };
+                (
+                    table_info.key_type().map_err(decode_err)?,
+                    table_info.value_type().map_err(decode_err)?,
+                )
+            }
+            None => (None, None),
+        }
+    };
+    let key_type = match key_type {
+        Some(key_type) => key_type,
+        None => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::not_found(E_NOT_FOUND),
+            ))
+        }
+    };
+    let key_layout = bcs::to_bytes(&get_type_layout(context, &key_type)?)
+        .map_err(|err| partial_extension_error(format!("serialize key layout failed: {}", err)))?;
+    let value_layout = match value_type {
+        Some(value_type) => {
+            bcs::to_bytes(&get_type_layout(context, &value_type)?).map_err(|err| {
+                partial_extension_error(format!("serialize value layout failed: {}", err))
+            })?
+        }
+        None => vec![],
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Value::vector_u8(key_layout), Value::vector_u8(value_layout)],
+    ))
+}
+
+pub fn make_native_table_layouts_box(gas_params: TableLayoutsBoxGasParameters) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_table_layouts_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ToggleBoolBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub write: InternalGas,
+}
+
+/// Flip the `bool` value of `key` and return the new value.
+/// Aborts with `E_NOT_FOUND` if there is no entry for `key`.
+/// The value is read and written with the `bool` layout directly, the write is charged the fixed `write` cost.
+fn native_toggle_bool_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ToggleBoolBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    if !tv.exists()? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::not_found(E_NOT_FOUND),
+        ));
+    }
+
+    let new_value = !tv
+        .borrow_global(TypeTag::Bool)?
+        .value_as::<Reference>()?
+        .read_ref()?
+        .value_as::<Struct>()?
+        .unpack()?
+        .next()
+        .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?
+        .value_as::<bool>()?;
+    tv.move_from(TypeTag::Bool)?;
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![Value::bool(new_value)])),
+        MoveTypeLayout::Bool,
+        TypeTag::Bool,
+    )
+    .map_err(|(err, _)| err)?;
+    cost += gas_params.write;
+
+    Ok(NativeResult::ok(cost, smallvec![Value::bool(new_value)]))
+}
+
+pub fn make_native_toggle_bool_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: ToggleBoolBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_toggle_bool_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct BorrowIfVersionBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub compare: InternalGas,
+}
+
+/// Returns a copy of the value of `key` if its version equals `expected_version`, as a vector of zero or one value.
+/// The version is the leading `u64` of the BCS bytes of the value, such as the first field of a struct, carrying it
+/// is the caller's responsibility. Returns empty if there is no entry for `key`, the version does not match,
+/// or the value is too short to carry a version.
+fn native_borrow_if_version_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &BorrowIfVersionBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let expected_version = pop_arg!(args, u64);
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    let version = match tv.serialize_value()? {
+        Some(value_bytes) => value_bytes
+            .get(..8)
+            .map(|version_bytes| u64::from_le_bytes(version_bytes.try_into().unwrap())),
+        None => None,
+    };
+    cost += gas_params.compare;
+
+    let values = if version == Some(expected_version) {
+        let value_type = type_to_type_tag(context, &ty_args[1])?;
+        let value = tv
+            .borrow_global(value_type)?
+            .value_as::<Reference>()?
+            .read_ref()?
+            .value_as::<Struct>()?
+            .unpack()?
+            .next()
+            .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+        vec![value]
+    } else {
+        vec![]
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&ty_args[1], values)?],
+    ))
+}
+
+pub fn make_native_borrow_if_version_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: BorrowIfVersionBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_borrow_if_version_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub write: InternalGas,
+}
+
+/// Merge `operand` into the value of `key` with the built-in `reducer`, or add `operand` as the value if there
+/// is no entry for `key`. The reducers of `u64` values are `REDUCER_SUM_U64`, `REDUCER_MAX_U64` and `REDUCER_MIN_U64`,
+/// `REDUCER_APPEND_BYTES` appends to a `vector<u8>` value.
+/// Aborts with `E_INVALID_OPERATION` if the reducer is unknown or the value type does not match it,
+/// and with `E_OVERFLOW` if the sum exceeds `u64`.
+fn native_merge_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &MergeBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 4);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let operand = args.pop_back().unwrap();
+    let reducer = pop_arg!(args, u8);
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let value_layout = type_to_type_layout(context, &ty_args[1])?;
+    let reducer_layout = match reducer {
+        REDUCER_SUM_U64 | REDUCER_MAX_U64 | REDUCER_MIN_U64 => MoveTypeLayout::U64,
+        REDUCER_APPEND_BYTES => MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+        _ => {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_argument(E_INVALID_OPERATION),
+            ))
+        }
+    };
+    if value_layout != reducer_layout {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_argument(E_INVALID_OPERATION),
+        ));
+    }
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    let operand_bytes = serialize(&value_layout, &operand)?;
+    cost += gas_params.per_byte_serialized
+        * NumBytes::new((key_bytes.len() + operand_bytes.len()) as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(table, 1)?;
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let inserted = !tv.exists()?;
+    let merged = if inserted {
+        if exceeds_max_entries {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+            ));
+        }
+        operand
+    } else {
+        let existing = tv
+            .borrow_global(value_type.clone())?
+            .value_as::<Reference>()?
+            .read_ref()?
+            .value_as::<Struct>()?
+            .unpack()?
+            .next()
+            .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+        let merged = match reducer {
+            REDUCER_SUM_U64 => existing
+                .value_as::<u64>()?
+                .checked_add(operand.value_as::<u64>()?)
+                .map(Value::u64),
+            REDUCER_MAX_U64 => Some(Value::u64(
+                existing.value_as::<u64>()?.max(operand.value_as::<u64>()?),
+            )),
+            REDUCER_MIN_U64 => Some(Value::u64(
+                existing.value_as::<u64>()?.min(operand.value_as::<u64>()?),
+            )),
+            _ => {
+                let mut bytes = existing.value_as::<Vec<u8>>()?;
+                bytes.extend(operand.value_as::<Vec<u8>>()?);
+                Some(Value::vector_u8(bytes))
+            }
+        };
+        match merged {
+            Some(merged) => {
+                tv.move_from(value_type.clone())?;
+                merged
+            }
+            None => {
+                return Ok(NativeResult::err(
+                    cost,
+                    moveos_types::move_std::error::invalid_argument(E_OVERFLOW),
+                ))
+            }
+        }
+    };
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![merged])),
+        value_layout,
+        value_type,
+    )
+    .map_err(|(err, _)| err)?;
+    cost += gas_params.write;
+    if inserted {
+        table.record_insertion(key_bytes)?;
+    }
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+pub fn make_native_merge_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: MergeBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_merge_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct DestroyIfVersionBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub per_entry: InternalGasPerArg,
+}
+
+/// Remove all the entries and the table if the version of the sentinel entry of `sentinel_key` equals
+/// `expected_version`, returns whether the table is destroyed. The version is the leading `u64` of the BCS bytes
+/// of the sentinel value, as in `borrow_if_version_box`. Returns false without changes if there is no sentinel entry
+/// or its version does not match. Aborts with `E_DUPLICATE_OPERATION` if the table is already removed,
+/// and with `E_TOO_MANY_ENTRIES` if the table holds more than `MAX_REPLACE_ALL_ENTRIES` entries.
+fn native_destroy_if_version_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &DestroyIfVersionBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let expected_version = pop_arg!(args, u64);
+    let sentinel_key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    if table_data.removed_tables.contains(&handle) {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::not_found(E_DUPLICATE_OPERATION),
+        ));
+    }
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let key_bytes = serialize(&table.key_layout, &sentinel_key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    let version = match tv.serialize_value()? {
+        Some(value_bytes) => value_bytes
+            .get(..8)
+            .map(|version_bytes| u64::from_le_bytes(version_bytes.try_into().unwrap())),
+        None => None,
+    };
+    if version != Some(expected_version) {
+        return Ok(NativeResult::ok(cost, smallvec![Value::bool(false)]));
+    }
+
+    if table_context.exceeds_max_scanned_entries(table)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    // The entries are enumerated to remove them, so the removal is part of the change set.
+    let loaded_bytes = table.load_all_entries(context, table_context)?;
+    cost += common_gas_params.calculate_load_cost(Some(Some(loaded_bytes)));
+    let mut removed = 0u64;
+    for tv in table.content.values_mut() {
+        if tv.remove_unchecked()? {
+            removed += 1;
+        }
+    }
+    table.size_increment -= removed as i64;
+    cost += gas_params.per_entry * NumArgs::new(removed);
+    table_data.removed_tables.insert(handle);
+
+    Ok(NativeResult::ok(cost, smallvec![Value::bool(true)]))
+}
+
+pub fn make_native_destroy_if_version_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: DestroyIfVersionBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_destroy_if_version_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct MissingKeysBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Returns the `keys` which have no entry in the table, in the order of `keys`.
+/// The existence is checked as in `contains_boxes`, the cached keys are answered directly and the others
+/// are looked up in one batch.
+fn native_missing_keys_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &MissingKeysBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let keys = pop_arg!(args, Vector);
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let len = keys.elem_views().len();
+    let keys = keys.unpack(&ty_args[0], len as u64)?;
+    let mut keys_bytes = Vec::with_capacity(len);
+    for key in keys.iter() {
+        let key_bytes = serialize(&table.key_layout, key)?;
+        cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+        keys_bytes.push(key_bytes);
+    }
+
+    let loaded = table.load_global_values(context, table_context, keys_bytes.clone())?;
+    for loaded_bytes in loaded {
+        cost += common_gas_params.calculate_load_cost(Some(loaded_bytes));
+    }
+
+    let mut missing_keys = Vec::new();
+    for (key, key_bytes) in keys.into_iter().zip(keys_bytes.iter()) {
+        let exists = match table.get_global_value(key_bytes) {
+            Some(tv) => tv.exists()?,
+            None => false,
+        };
+        if !exists {
+            missing_keys.push(key);
+        }
+    }
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&ty_args[0], missing_keys)?],
+    ))
+}
+
+pub fn make_native_missing_keys_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: MissingKeysBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_missing_keys_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct SetIfGreaterU64BoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub write: InternalGas,
+}
+
+/// Store `candidate` as the `u64` value of `key` if it is greater than the stored value or there is no entry
+/// for `key`, returns whether the value is updated. Returns false without change otherwise.
+fn native_set_if_greater_u64_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &SetIfGreaterU64BoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let candidate = pop_arg!(args, u64);
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(table, 1)?;
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    if tv.exists()? {
+        if read_u64_box(tv)? >= candidate {
+            return Ok(NativeResult::ok(cost, smallvec![Value::bool(false)]));
+        }
+        write_u64_box(tv, candidate)?;
+    } else {
+        if exceeds_max_entries {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+            ));
+        }
+        tv.move_to(
+            Value::struct_(Struct::pack(vec![Value::u64(candidate)])),
+            MoveTypeLayout::U64,
+            TypeTag::U64,
+        )
+        .map_err(|(err, _)| err)?;
+        table.record_insertion(key_bytes)?;
+    }
+    cost += gas_params.write;
+
+    Ok(NativeResult::ok(cost, smallvec![Value::bool(true)]))
+}
+
+pub fn make_native_set_if_greater_u64_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: SetIfGreaterU64BoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_set_if_greater_u64_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+/// Returns a copy of the value of `key` as a vector of zero or one value, and whether the entry was loaded
+/// from the resolver by this call (cold) rather than already cached in the table content (warm).
+/// A missing entry returns empty, it is cold on the first lookup in the transaction. Charges as `borrow_box`.
+fn native_borrow_box_with_source(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &BorrowBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+    let cold = loaded.is_some();
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let values = match tv.borrow_global(value_type) {
+        Ok(ref_val) => {
+            let value = ref_val
+                .value_as::<Reference>()?
+                .read_ref()?
+                .value_as::<Struct>()?
+                .unpack()?
+                .next()
+                .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+            vec![value]
+        }
+        Err(_) => vec![],
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&ty_args[1], values)?, Value::bool(cold)],
+    ))
+}
+
+pub fn make_native_borrow_box_with_source(
+    common_gas_params: CommonGasParameters,
+    gas_params: BorrowBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_borrow_box_with_source(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct RemovePrefixBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub per_entry: InternalGasPerArg,
+}
+
+/// Remove all the entries whose serialized key starts with `prefix`, returns the number of removed entries.
+/// The persisted entries with the prefix are loaded first, so they are marked deleted as well as the entries
+/// only in memory. The resolver does not order the entries by key, so the table is scanned page by page.
+/// Aborts with `E_TOO_MANY_ENTRIES` if the table holds more than `MAX_REPLACE_ALL_ENTRIES` entries.
+fn native_remove_prefix_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &RemovePrefixBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let prefix = pop_arg!(args, Vec<u8>);
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+    cost += gas_params.per_byte_serialized * NumBytes::new(prefix.len() as u64);
+
+    if table_context.exceeds_max_scanned_entries(table)? {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    let scanned_bytes = table.load_entries_with_prefix(context, table_context, &prefix)?;
+    cost += common_gas_params.calculate_load_cost(Some(Some(scanned_bytes)));
+
+    let mut removed = 0u64;
+    for (_, tv) in table
+        .content
+        .range_mut(prefix.clone()..)
+        .take_while(|(key, _)| key.starts_with(&prefix))
+    {
+        if tv.remove_unchecked()? {
+            removed += 1;
+        }
+    }
+    table.size_increment -= removed as i64;
+    cost += gas_params.per_entry * NumArgs::new(removed);
+
+    Ok(NativeResult::ok(cost, smallvec![Value::u64(removed)]))
+}
+
+pub fn make_native_remove_prefix_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: RemovePrefixBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_remove_prefix_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct PushBoundedBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub evict: InternalGas,
+}
+
+/// Add `val` to a table with `u64` keys under the next sequence number, as `push_box`, then remove the oldest
+/// entry if the table holds more than `capacity` entries, so the table keeps the last `capacity` values as
+/// a ring buffer. Returns the key and the value of the evicted entry as vectors of zero or one element.
+/// At most one entry is evicted per push.
+/// The head sequence number, one past the last evicted key, is persisted next to the next sequence number,
+/// so the oldest entry is found by key without listing the table. A table bounded before the head is
+/// persisted loads all its entries once to find its min key.
+fn native_push_bounded_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &PushBoundedBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 V Type
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let capacity = pop_arg!(args, u64);
+    let val = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &Type::U64)?;
+    if table.key_layout != MoveTypeLayout::U64 {
+        return Err(partial_extension_error(
+            "push_bounded_box requires a table with u64 keys",
+        ));
+    }
+
+    let index = match push_value(
+        common_gas_params,
+        gas_params.per_byte_serialized,
+        context,
+        table_context,
+        &mut table_data,
+        handle,
+        &ty_args[0],
+        val,
+        &mut cost,
+    )? {
+        Ok(index) => index,
+        Err(abort_code) => return Ok(NativeResult::err(cost, abort_code)),
+    };
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &Type::U64)?;
+    let len = (table_context.remote_table_size(&handle)? as i64) + table.size_increment;
+    debug_assert!(len >= 0);
+    if len as u64 <= capacity {
+        return Ok(NativeResult::ok(
+            cost,
+            smallvec![
+                Vector::pack(&Type::U64, vec![])?,
+                Vector::pack(&ty_args[0], vec![])?
+            ],
+        ));
+    }
+
+    let sequence_table = table_data.get_or_create_table_with_key_layout(
+        next_sequence_handle(&handle),
+        MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+    )?;
+    let (tv, loaded) =
+        sequence_table.get_or_create_global_value(context, table_context, head_sequence_key())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+    let persisted_head = if tv.exists()? {
+        Some(read_u64_box(tv)?)
+    } else {
+        None
+    };
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &Type::U64)?;
+    let head = match persisted_head {
+        Some(head) => head,
+        None => {
+            // The table is bounded before the head sequence number is persisted.
+            let loaded_bytes = table.load_all_entries(context, table_context)?;
+            cost += common_gas_params.calculate_load_cost(Some(Some(loaded_bytes)));
+            let mut min_key = index;
+            for (key_bytes, tv) in table.content.iter_mut() {
+                if tv.exists()? {
+                    min_key = min_key.min(decode_u64_key(key_bytes)?);
+                }
+            }
+            min_key
+        }
+    };
+
+    // The entries between the head and the pushed one may be removed otherwise, skip them by key.
+    let mut oldest = head;
+    let value_type = type_to_type_tag(context, &ty_args[0])?;
+    let value = loop {
+        let (tv, loaded) = table.get_or_create_global_value(
+            context,
+            table_context,
+            oldest.to_le_bytes().to_vec(),
+        )?;
+        cost += common_gas_params.calculate_load_cost(loaded);
+        if tv.exists()? {
+            break tv
+                .move_from(value_type)?
+                .value_as::<Struct>()?
+                .unpack()?
+                .next()
+                .ok_or_else(|| {
+                    partial_extension_error("Box<V> should have one field of type V")
+                })?;
+        }
+        if oldest >= index {
+            return Err(partial_extension_error(
+                "push_bounded_box found no entry between the head and the pushed one",
+            ));
+        }
+        oldest += 1;
+    };
+    table.size_increment -= 1;
+    cost += gas_params.evict;
+
+    let sequence_table = table_data.get_or_create_table_with_key_layout(
+        next_sequence_handle(&handle),
+        MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+    )?;
+    let (tv, _) =
+        sequence_table.get_or_create_global_value(context, table_context, head_sequence_key())?;
+    if tv.exists()? {
+        write_u64_box(tv, oldest + 1)?;
+    } else {
+        tv.move_to(
+            Value::struct_(Struct::pack(vec![Value::u64(oldest + 1)])),
+            MoveTypeLayout::U64,
+            TypeTag::U64,
+        )
+        .map_err(|(err, _)| err)?;
+        sequence_table.record_insertion(head_sequence_key())?;
+    }
+    let (keys, values) = (vec![Value::u64(oldest)], vec![value]);
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![
+            Vector::pack(&Type::U64, keys)?,
+            Vector::pack(&ty_args[0], values)?
+        ],
+    ))
+}
+
+pub fn make_native_push_bounded_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: PushBoundedBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_push_bounded_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct TableBytesBoxGasParameters {
+    pub base: InternalGas,
+    pub per_entry_scanned: InternalGasPerArg,
+    pub per_byte_scanned: InternalGasPerByte,
+}
+
+impl TableBytesBoxGasParameters {
+    /// The cost of counting the bytes of a table whose `entries` persisted entries hold `bytes` bytes,
+    /// the resolver scans all of them.
+    pub fn scan_cost(&self, entries: u64, bytes: u64) -> InternalGas {
+        self.base
+            + self.per_entry_scanned * NumArgs::new(entries)
+            + self.per_byte_scanned * NumBytes::new(bytes)
+    }
+}
+
+/// Returns the total bytes of the keys and the values of the table as a vector of zero or one value,
+/// the bytes persisted in the resolver adjusted by the in-memory changes. Returns empty if the resolver
+/// can not report the persisted bytes.
+/// The resolver scans every persisted entry, so the cost grows with the entries and the bytes scanned.
+fn native_table_bytes_box(
+    gas_params: &TableBytesBoxGasParameters,
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let table_data = table_context.table_data.read();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let persisted_bytes = table_context
+        .resolver
+        .resolve_table_bytes(&handle)
+        .map_err(|err| {
+            partial_extension_error(format!("remote table resolver failure: {}", err))
+        })?;
+    let mut cost = gas_params.base;
+    let table_bytes = match persisted_bytes {
+        Some(persisted_bytes) => {
+            cost = gas_params.scan_cost(table_context.remote_table_size(&handle)?, persisted_bytes);
+            let mut table_bytes = persisted_bytes as i64;
+            if let Some(table) = table_data.tables.get(&handle) {
+                for (key, tv) in table.content.iter() {
+                    let entry_bytes = |value: Option<&Vec<u8>>| {
+                        value.map_or(0, |value| (key.len() + value.len()) as i64)
+                    };
+                    table_bytes += entry_bytes(tv.stored_bytes()?.as_ref())
+                        - entry_bytes(tv.original_value.as_ref());
+                }
+            }
+            vec![table_bytes.max(0) as u64]
+        }
+        None => vec![],
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Value::vector_u64(table_bytes)],
+    ))
+}
+
+pub fn make_native_table_bytes_box(gas_params: TableBytesBoxGasParameters) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_table_bytes_box(&gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ClaimSlotBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Store `owner` as the `address` value of `key` if there is no entry for `key`.
+/// Returns empty if the claim succeeds, otherwise the current owner as a vector of one address,
+/// the table is unchanged.
+fn native_claim_slot_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &ClaimSlotBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let owner = pop_arg!(args, AccountAddress);
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(table, 1)?;
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    if tv.exists()? {
+        let current_owner = tv
+            .borrow_global(TypeTag::Address)?
+            .value_as::<Reference>()?
+            .read_ref()?
+            .value_as::<Struct>()?
+            .unpack()?
+            .next()
+            .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+        return Ok(NativeResult::ok(
+            cost,
+            smallvec![Vector::pack(&Type::Address, vec![current_owner])?],
+        ));
+    }
+    if exceeds_max_entries {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+        ));
+    }
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![Value::address(owner)])),
+        MoveTypeLayout::Address,
+        TypeTag::Address,
+    )
+    .map_err(|(err, _)| err)?;
+    table.record_insertion(key_bytes)?;
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&Type::Address, vec![])?],
+    ))
+}
+
+pub fn make_native_claim_slot_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: ClaimSlotBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_claim_slot_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct EnableLastModifiedBoxGasParameters {
+    pub base: InternalGas,
+}
+
+/// Opt the table in to record the last modified tx order of its entries in its metadata, see
+/// `TableData::stamp_last_modified`. The values of the table are not changed.
+/// Enabling an enabled table does nothing.
+fn native_enable_last_modified_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &EnableLastModifiedBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 1);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    cost += common_gas_params.calculate_load_cost(table.load_metadata(table_context)?);
+    cost += common_gas_params.calculate_load_cost(table.enable_last_modified(table_context)?);
+
+    Ok(NativeResult::ok(cost, smallvec![]))
+}
+
+pub fn make_native_enable_last_modified_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: EnableLastModifiedBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_enable_last_modified_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct LastModifiedBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+}
+
+/// Returns the tx order of the last modification of `key` as a vector of zero or one value.
+/// Empty if there is no entry for `key`, the table is not opted in, or the entry is not modified since.
+/// The modifications of the current transaction are recorded when it is applied, so they are not seen yet.
+fn native_last_modified_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &LastModifiedBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 2);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    cost += common_gas_params.calculate_load_cost(table.load_metadata(table_context)?);
+    cost += common_gas_params.calculate_load_cost(
+        table.load_last_modified(table_context, std::slice::from_ref(&key_bytes))?,
+    );
+
+    let last_modified = table
+        .last_modified(&key_bytes)?
+        .map(Value::u64)
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&Type::U64, last_modified)?],
+    ))
+}
+
+pub fn make_native_last_modified_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: LastModifiedBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_last_modified_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct RotateBoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub write: InternalGas,
+}
+
+/// Write `val` at the slot `slot % capacity` of a table with `u64` keys, a direct-mapped cache.
+/// Returns the displaced value as a vector of zero or one value, empty if the slot was empty.
+/// Aborts with `E_INVALID_OPERATION` if `capacity` is 0.
+fn native_rotate_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &RotateBoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 V Type
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 4);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let capacity = pop_arg!(args, u64);
+    let val = args.pop_back().unwrap();
+    let slot = pop_arg!(args, u64);
+    let handle = get_table_handle(&mut args)?;
+
+    let mut cost = gas_params.base;
+
+    if capacity == 0 {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_argument(E_INVALID_OPERATION),
+        ));
+    }
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &Type::U64)?;
+    if table.key_layout != MoveTypeLayout::U64 {
+        return Err(partial_extension_error(
+            "rotate_box requires a table with u64 keys",
+        ));
+    }
+
+    let key_bytes = (slot % capacity).to_le_bytes().to_vec();
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let exceeds_max_entries = table_context.exceeds_max_entries(table, 1)?;
+    let (tv, loaded) =
+        table.get_or_create_global_value(context, table_context, key_bytes.clone())?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    let value_layout = type_to_type_layout(context, &ty_args[0])?;
+    let value_type = type_to_type_tag(context, &ty_args[0])?;
+    let displaced = if tv.exists()? {
+        let value = tv
+            .move_from(value_type.clone())?
+            .value_as::<Struct>()?
+            .unpack()?
+            .next()
+            .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+        vec![value]
+    } else {
+        if exceeds_max_entries {
+            return Ok(NativeResult::err(
+                cost,
+                moveos_types::move_std::error::invalid_state(E_TOO_MANY_ENTRIES),
+            ));
+        }
+        vec![]
+    };
+    tv.move_to(
+        Value::struct_(Struct::pack(vec![val])),
+        value_layout,
+        value_type,
+    )
+    .map_err(|(err, _)| err)?;
+    if displaced.is_empty() {
+        table.record_insertion(key_bytes)?;
+    }
+    cost += gas_params.write;
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&ty_args[0], displaced)?],
+    ))
+}
+
+pub fn make_native_rotate_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: RotateBoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_rotate_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct IncrementManyU64BoxGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub per_entry: InternalGas,
+}
+
+/// Add each of the `deltas` to the `u64` value of the key at the same position, and return the new values
+/// in the order of the keys. A repeated key is incremented once per occurrence.
+/// All the new values are computed before any is written, so either all the increments are applied or none.
+/// Aborts with `E_LENGTH_NOT_MATCH` if the vectors have different lengths, `E_NOT_FOUND` if there is no entry
+/// for a key, and `E_OVERFLOW` if a new value overflows.
+fn native_increment_many_u64_box(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &IncrementManyU64BoxGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    assert_eq!(ty_args.len(), 1);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let deltas = pop_arg!(args, Vec<u64>);
+    let keys = pop_arg!(args, Vector);
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let len = keys.elem_views().len();
+    if len != deltas.len() {
+        return Ok(NativeResult::err(
+            cost,
+            moveos_types::move_std::error::invalid_argument(E_LENGTH_NOT_MATCH),
+        ));
+    }
+    let keys = keys.unpack(&ty_args[0], len as u64)?;
+    let mut keys_bytes = Vec::with_capacity(len);
+    for key in keys.iter() {
+        let key_bytes = serialize(&table.key_layout, key)?;
+        cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+        keys_bytes.push(key_bytes);
+    }
+
+    let loaded = table.load_global_values(context, table_context, keys_bytes.clone())?;
+    for loaded_bytes in loaded {
+        cost += common_gas_params.calculate_load_cost(Some(loaded_bytes));
+    }
+
+    let mut updated = BTreeMap::new();
+    let mut new_values = Vec::with_capacity(len);
+    for (key_bytes, delta) in keys_bytes.into_iter().zip(deltas) {
+        let current = match updated.get(&key_bytes) {
+            Some(current) => *current,
+            None => match table.content.get_mut(&key_bytes) {
+                Some(tv) if tv.exists()? => read_u64_box(tv)?,
+                _ => {
+                    return Ok(NativeResult::err(
+                        cost,
+                        moveos_types::move_std::error::not_found(E_NOT_FOUND),
+                    ))
+                }
+            },
+        };
+        let new_value = match current.checked_add(delta) {
+            Some(new_value) => new_value,
+            None => {
+                return Ok(NativeResult::err(
+                    cost,
+                    moveos_types::move_std::error::invalid_argument(E_OVERFLOW),
+                ))
+            }
+        };
+        updated.insert(key_bytes, new_value);
+        new_values.push(new_value);
+    }
+
+    for (key_bytes, new_value) in updated {
+        let tv = table
+            .content
+            .get_mut(&key_bytes)
+            .expect("The value of the key is loaded");
+        write_u64_box(tv, new_value)?;
+        cost += gas_params.per_entry;
+    }
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Value::vector_u64(new_values)],
+    ))
+}
+
+pub fn make_native_increment_many_u64_box(
+    common_gas_params: CommonGasParameters,
+    gas_params: IncrementManyU64BoxGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_increment_many_u64_box(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct BorrowBoxTypedGasParameters {
+    pub base: InternalGas,
+    pub per_byte_serialized: InternalGasPerByte,
+    pub per_tag_byte: InternalGasPerByte,
+}
+
+/// Returns a copy of the value of `key` as a vector of zero or one value, only if the type stored with the
+/// value is `expected_tag`, the canonical name of the type as `std::type_name`, and is the type `V`.
+/// Returns empty if there is no entry for `key` or the types do not match, instead of the type mismatch error
+/// of `borrow_box`, so a table with values of different types is read without type confusion.
+fn native_borrow_box_typed(
+    common_gas_params: &CommonGasParameters,
+    gas_params: &BorrowBoxTypedGasParameters,
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    //0 K Type
+    //1 V Type
+    assert_eq!(ty_args.len(), 2);
+    assert_eq!(args.len(), 3);
+
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.write();
+
+    let expected_tag = pop_arg!(args, Vec<u8>);
+    let key = args.pop_back().unwrap();
+    let handle = get_table_handle(&mut args)?;
+
+    let table = table_data.get_or_create_table(context, table_context, handle, &ty_args[0])?;
+
+    let mut cost = gas_params.base;
+
+    let key_bytes = serialize(&table.key_layout, &key)?;
+    cost += gas_params.per_byte_serialized * NumBytes::new(key_bytes.len() as u64);
+
+    let (tv, loaded) = table.get_or_create_global_value(context, table_context, key_bytes)?;
+    cost += common_gas_params.calculate_load_cost(loaded);
+
+    if !tv.exists()? {
+        return Ok(NativeResult::ok(
+            cost,
+            smallvec![Vector::pack(&ty_args[1], vec![])?],
+        ));
+    }
+    cost += gas_params.per_tag_byte * NumBytes::new(expected_tag.len() as u64);
+    let (_, stored_type) = tv.value_layout_and_type.as_ref().ok_or_else(|| {
+        PartialVMError::new(StatusCode::MISSING_DATA)
+            .with_message("The value_layout_and_type must exist if the value exists".to_owned())
+    })?;
+    let value_type = type_to_type_tag(context, &ty_args[1])?;
+    let values = if stored_type.to_canonical_string().as_bytes() == expected_tag.as_slice()
+        && *stored_type == value_type
+    {
+        let value = tv
+            .borrow_global(value_type)?
+            .value_as::<Reference>()?
+            .read_ref()?
+            .value_as::<Struct>()?
+            .unpack()?
+            .next()
+            .ok_or_else(|| partial_extension_error("Box<V> should have one field of type V"))?;
+        vec![value]
+    } else {
+        vec![]
+    };
+
+    Ok(NativeResult::ok(
+        cost,
+        smallvec![Vector::pack(&ty_args[1], values)?],
+    ))
+}
+
+pub fn make_native_borrow_box_typed(
+    common_gas_params: CommonGasParameters,
+    gas_params: BorrowBoxTypedGasParameters,
+) -> NativeFunction {
+    Arc::new(
+        move |context, ty_args, args| -> PartialVMResult<NativeResult> {
+            native_borrow_box_typed(&common_gas_params, &gas_params, context, ty_args, args)
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct GasParameters {
+    pub common: CommonGasParameters,
+    pub add_box: AddBoxGasParameters,
+    pub borrow_box: BorrowBoxGasParameters,
+    pub contains_box: ContainsBoxGasParameters,
+    pub remove_box: RemoveGasParameters,
+    pub drop_unchecked_box: DropUncheckedBoxGasParameters,
+    pub box_length: BoxLengthGasParameters,
+    pub replace_all_box: ReplaceAllBoxGasParameters,
+    pub contains_boxes: ContainsBoxesGasParameters,
+    pub content_root_box: ContentRootBoxGasParameters,
+    pub decrement_u64_box: DecrementU64BoxGasParameters,
+    pub saturating_decrement_u64_box: DecrementU64BoxGasParameters,
+    pub new_table_count_box: NewTableCountBoxGasParameters,
+    pub iter_next_entry_box: IterNextEntryBoxGasParameters,
+    pub content_proof_box: ContentProofBoxGasParameters,
+    pub is_empty_box: IsEmptyBoxGasParameters,
+    pub dirty_keys_box: DirtyKeysBoxGasParameters,
+    pub cas_box: CasBoxGasParameters,
+    pub export_box: ExportBoxGasParameters,
+    pub import_box: ImportBoxGasParameters,
+    pub get_or_insert_box: GetOrInsertBoxGasParameters,
+    pub add_box_many_same: AddBoxManySameGasParameters,
+    pub pending_ops_box: PendingOpsBoxGasParameters,
+    pub transfer_u64_box: TransferU64BoxGasParameters,
+    pub is_readonly_context_box: IsReadonlyContextBoxGasParameters,
+    pub last_inserted_box: LastInsertedBoxGasParameters,
+    pub enable_last_inserted_box: EnableLastInsertedBoxGasParameters,
+    pub move_box: MoveBoxGasParameters,
+    pub batch_ops_box: BatchOpsBoxGasParameters,
+    pub reserve_handle_box: ReserveHandleBoxGasParameters,
+    pub push_box: PushBoxGasParameters,
+    pub init_box: InitBoxGasParameters,
+    pub multi_read_box: MultiReadBoxGasParameters,
+    pub create_and_add_box: CreateAndAddBoxGasParameters,
+    pub table_layouts_box: TableLayoutsBoxGasParameters,
+    pub toggle_bool_box: ToggleBoolBoxGasParameters,
+    pub borrow_if_version_box: BorrowIfVersionBoxGasParameters,
+    pub merge_box: MergeBoxGasParameters,
+    pub destroy_if_version_box: DestroyIfVersionBoxGasParameters,
+    pub missing_keys_box: MissingKeysBoxGasParameters,
+    pub set_if_greater_u64_box: SetIfGreaterU64BoxGasParameters,
+    pub remove_prefix_box: RemovePrefixBoxGasParameters,
+    pub push_bounded_box: PushBoundedBoxGasParameters,
+    pub table_bytes_box: TableBytesBoxGasParameters,
+    pub claim_slot_box: ClaimSlotBoxGasParameters,
+    pub enable_last_modified_box: EnableLastModifiedBoxGasParameters,
+    pub last_modified_box: LastModifiedBoxGasParameters,
+    pub rotate_box: RotateBoxGasParameters,
+    pub increment_many_u64_box: IncrementManyU64BoxGasParameters,
+    pub borrow_box_typed: BorrowBoxTypedGasParameters,
+}
+
+impl GasParameters {
+    pub fn zeros() -> Self {
+        Self {
+            common: CommonGasParameters {
+                load_base: 0.into(),
+                load_per_byte: 0.into(),
+                load_failure: 0.into(),
+            },
+            add_box: AddBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            borrow_box: BorrowBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            contains_box: ContainsBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            remove_box: RemoveGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            drop_unchecked_box: DropUncheckedBoxGasParameters { base: 0.into() },
+            box_length: BoxLengthGasParameters { base: 0.into() },
+            replace_all_box: ReplaceAllBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                per_entry: 0.into(),
+            },
+            contains_boxes: ContainsBoxesGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            content_root_box: ContentRootBoxGasParameters {
+                base: 0.into(),
+                per_byte_hashed: 0.into(),
+            },
+            decrement_u64_box: DecrementU64BoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            saturating_decrement_u64_box: DecrementU64BoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            new_table_count_box: NewTableCountBoxGasParameters { base: 0.into() },
+            iter_next_entry_box: IterNextEntryBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            content_proof_box: ContentProofBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                per_proof_node: 0.into(),
+            },
+            is_empty_box: IsEmptyBoxGasParameters { base: 0.into() },
+            dirty_keys_box: DirtyKeysBoxGasParameters {
+                base: 0.into(),
+                per_dirty_key: 0.into(),
+            },
+            cas_box: CasBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            export_box: ExportBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            import_box: ImportBoxGasParameters {
+                base: 0.into(),
+                per_byte_deserialized: 0.into(),
+            },
+            get_or_insert_box: GetOrInsertBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            add_box_many_same: AddBoxManySameGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            pending_ops_box: PendingOpsBoxGasParameters {
+                base: 0.into(),
+                per_op: 0.into(),
+                per_byte: 0.into(),
+            },
+            transfer_u64_box: TransferU64BoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            is_readonly_context_box: IsReadonlyContextBoxGasParameters { base: 0.into() },
+            last_inserted_box: LastInsertedBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            enable_last_inserted_box: EnableLastInsertedBoxGasParameters { base: 0.into() },
+            move_box: MoveBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            batch_ops_box: BatchOpsBoxGasParameters {
+                base: 0.into(),
+                per_byte_deserialized: 0.into(),
+            },
+            reserve_handle_box: ReserveHandleBoxGasParameters { base: 0.into() },
+            push_box: PushBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            init_box: InitBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            multi_read_box: MultiReadBoxGasParameters {
+                base: 0.into(),
+                per_read: 0.into(),
+                per_byte: 0.into(),
+            },
+            create_and_add_box: CreateAndAddBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                new_table: 0.into(),
+            },
+            table_layouts_box: TableLayoutsBoxGasParameters { base: 0.into() },
+            toggle_bool_box: ToggleBoolBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                write: 0.into(),
+            },
+            borrow_if_version_box: BorrowIfVersionBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                compare: 0.into(),
+            },
+            merge_box: MergeBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                write: 0.into(),
+            },
+            destroy_if_version_box: DestroyIfVersionBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                per_entry: 0.into(),
+            },
+            missing_keys_box: MissingKeysBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            set_if_greater_u64_box: SetIfGreaterU64BoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                write: 0.into(),
+            },
+            remove_prefix_box: RemovePrefixBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                per_entry: 0.into(),
+            },
+            push_bounded_box: PushBoundedBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                evict: 0.into(),
+            },
+            table_bytes_box: TableBytesBoxGasParameters {
+                base: 0.into(),
+                per_entry_scanned: 0.into(),
+                per_byte_scanned: 0.into(),
+            },
+            claim_slot_box: ClaimSlotBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            enable_last_modified_box: EnableLastModifiedBoxGasParameters { base: 0.into() },
+            last_modified_box: LastModifiedBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+            },
+            rotate_box: RotateBoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                write: 0.into(),
+            },
+            increment_many_u64_box: IncrementManyU64BoxGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                per_entry: 0.into(),
+            },
+            borrow_box_typed: BorrowBoxTypedGasParameters {
+                base: 0.into(),
+                per_byte_serialized: 0.into(),
+                per_tag_byte: 0.into(),
+            },
+        }
+    }
+}
+
+// =========================================================================================
+// Helpers
 
 // The handle type in Move is `&ObjectID`. This function extracts the address from `ObjectID`.
 fn get_table_handle(args: &mut VecDeque<Value>) -> PartialVMResult<ObjectID> {
@@ -804,15 +6011,19 @@ pub fn serialize(layout: &MoveTypeLayout, val: &Value) -> PartialVMResult<Vec<u8
     })
 }
 
-// Deserialize a value and box it to `moveos_std::raw_table::Box<V>`.
-fn deserialize_and_box(layout: &MoveTypeLayout, bytes: &[u8]) -> PartialVMResult<Value> {
-    let value = Value::simple_deserialize(bytes, layout).ok_or_else(|| {
+fn deserialize(layout: &MoveTypeLayout, bytes: &[u8]) -> PartialVMResult<Value> {
+    Value::simple_deserialize(bytes, layout).ok_or_else(|| {
         partial_extension_error(format!(
             "cannot deserialize table key or value, layout:{:?}, bytes:{:?}",
             layout,
             hex::encode(bytes)
         ))
-    })?;
+    })
+}
+
+// Deserialize a value and box it to `moveos_std::raw_table::Box<V>`.
+fn deserialize_and_box(layout: &MoveTypeLayout, bytes: &[u8]) -> PartialVMResult<Value> {
+    let value = deserialize(layout, bytes)?;
     Ok(Value::struct_(Struct::pack(vec![value])))
 }
 
@@ -835,3 +6046,6 @@ fn get_type_layout(context: &NativeContext, type_tag: &TypeTag) -> PartialVMResu
         .get_type_layout(type_tag)
         .map_err(|e| e.to_partial())
 }
+
+#[cfg(test)]
+mod tests;
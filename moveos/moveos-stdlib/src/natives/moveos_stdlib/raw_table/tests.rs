@@ -0,0 +1,138 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+fn cached_u64(value: u64) -> TableRuntimeValue {
+    let bytes = bcs::to_bytes(&value).unwrap();
+    let val = deserialize_and_box(&MoveTypeLayout::U64, &bytes).unwrap();
+    TableRuntimeValue::new(
+        MoveTypeLayout::U64,
+        TypeTag::U64,
+        GlobalValue::cached(val).unwrap(),
+        bytes,
+    )
+}
+
+#[test]
+fn test_move_keeps_stored_bytes() {
+    // The source value is stored with bytes which are not its own serialization, as a value written with a
+    // legacy encoding would be.
+    let stored = vec![7u8, 0, 0, 0, 0, 0, 0, 0, 0xff];
+    let mut from_tv = cached_u64(7);
+    from_tv.verbatim_value = Some(VerbatimValue {
+        bytes: stored.clone(),
+        serialized: bcs::to_bytes(&7u64).unwrap(),
+    });
+
+    // Move the value as `move_box` does.
+    let (bytes, serialized) = (
+        from_tv.stored_bytes().unwrap().unwrap(),
+        from_tv.serialize_value().unwrap().unwrap(),
+    );
+    assert_eq!(bytes, stored);
+    let box_value = from_tv.move_from(TypeTag::U64).unwrap();
+    let mut to_tv = TableRuntimeValue::none();
+    to_tv
+        .move_to(box_value, MoveTypeLayout::U64, TypeTag::U64)
+        .map_err(|(err, _)| err)
+        .unwrap();
+    to_tv.verbatim_value = Some(VerbatimValue { bytes, serialized });
+
+    // The destination stores the bytes of the source, not the reserialized value.
+    assert_eq!(to_tv.stored_bytes().unwrap(), Some(stored.clone()));
+    let serialized = to_tv.serialize_value().unwrap().unwrap();
+    assert_ne!(serialized, stored);
+    let verbatim_value = to_tv.take_verbatim_value().unwrap();
+    assert_eq!(verbatim_value.or_serialized(serialized), stored);
+}
+
+#[test]
+fn test_modified_verbatim_value_is_reserialized() {
+    let verbatim_value = VerbatimValue {
+        bytes: vec![7u8, 0, 0, 0, 0, 0, 0, 0, 0xff],
+        serialized: bcs::to_bytes(&7u64).unwrap(),
+    };
+    let modified = bcs::to_bytes(&8u64).unwrap();
+    assert_eq!(verbatim_value.or_serialized(modified.clone()), modified);
+}
+
+#[test]
+fn test_table_bytes_scan_cost() {
+    let gas_params = TableBytesBoxGasParameters {
+        base: 10.into(),
+        per_entry_scanned: 3.into(),
+        per_byte_scanned: 2.into(),
+    };
+    assert_eq!(gas_params.scan_cost(0, 0), 10.into());
+    assert_eq!(
+        gas_params.scan_cost(1000, 16_000),
+        (10 + 3 * 1000 + 2 * 16_000).into()
+    );
+}
+
+#[test]
+fn test_stamp_last_modified() {
+    let handle = ObjectID::from(AccountAddress::from_hex_literal("0x42").unwrap());
+    let key = |index: u64| bcs::to_bytes(&index).unwrap();
+
+    let mut metadata = Table::new_metadata_table(&handle);
+    metadata
+        .content
+        .insert(metadata_header_key(), cached_u64(METADATA_LAST_MODIFIED));
+    // The entry 1 is inserted, 2 is removed, 3 is loaded and not changed.
+    metadata
+        .content
+        .insert(last_modified_key(&key(1)), TableRuntimeValue::none());
+    metadata
+        .content
+        .insert(last_modified_key(&key(2)), cached_u64(7));
+    metadata
+        .content
+        .insert(last_modified_key(&key(3)), cached_u64(7));
+
+    let mut table = Table {
+        handle,
+        key_layout: MoveTypeLayout::U64,
+        content: Default::default(),
+        size_increment: 0,
+        entries_loaded: false,
+        metadata: Some(Some(Box::new(metadata))),
+        max_u64_key: None,
+        value_layout: None,
+    };
+    let mut inserted = TableRuntimeValue::none();
+    inserted
+        .move_to(
+            Value::struct_(Struct::pack(vec![Value::u64(10)])),
+            MoveTypeLayout::U64,
+            TypeTag::U64,
+        )
+        .map_err(|(err, _)| err)
+        .unwrap();
+    table.content.insert(key(1), inserted);
+    let mut removed = cached_u64(20);
+    assert!(removed.remove_unchecked().unwrap());
+    table.content.insert(key(2), removed);
+    table.content.insert(key(3), cached_u64(30));
+
+    let mut table_data = TableData::default();
+    table_data.tables.insert(handle, table);
+    table_data.stamp_last_modified(42).unwrap();
+
+    let table = table_data.tables.get_mut(&handle).unwrap();
+    assert_eq!(table.last_modified(&key(1)).unwrap(), Some(42));
+    assert_eq!(table.last_modified(&key(2)).unwrap(), None);
+    assert_eq!(table.last_modified(&key(3)).unwrap(), Some(7));
+
+    // The records are changed in the metadata table of the table, not in a table of their own.
+    let (_, _, tables) = table_data.into_inner();
+    let metadata = tables.get(&metadata_handle(&handle)).unwrap();
+    assert_eq!(metadata.size_increment, 0);
+    assert!(metadata
+        .get_global_value(&last_modified_key(&key(2)))
+        .unwrap()
+        .is_dirty()
+        .unwrap());
+    assert_eq!(tables.len(), 2);
+}
@@ -299,4 +299,8 @@ impl StateResolver for MoveOSStore {
     ) -> std::result::Result<Vec<(Vec<u8>, State)>, Error> {
         self.statedb.list_table_items(handle, cursor, limit)
     }
+
+    fn resolve_table_bytes(&self, handle: &ObjectID) -> std::result::Result<Option<u64>, Error> {
+        StateResolver::resolve_table_bytes(&self.statedb, handle)
+    }
 }
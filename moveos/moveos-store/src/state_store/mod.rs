@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod statedb;
+pub mod write_behind;
 
 use anyhow::Result;
 use moveos_types::h256::H256;
@@ -31,6 +31,9 @@ use std::collections::BTreeMap;
 
 use crate::state_store::NodeDBStore;
 
+/// The number of entries read per page when scanning a table.
+const TABLE_BYTES_PAGE_SIZE: usize = 1000;
+
 pub struct TreeTable<NS> {
     smt: SMTree<Vec<u8>, State, NS>,
 }
@@ -263,7 +266,24 @@ impl StateDBStore {
                     .put_changes(table_change.entries.into_iter())?;
                 // TODO: do we need to update the size of global table?
             } else {
-                let (mut object, table) = self.get_as_table_or_create(table_handle)?;
+                let (mut object, table) = match self.get_as_table(table_handle)? {
+                    Some(table) => table,
+                    None => {
+                        // The types are kept when the table is created, the value type is the one of
+                        // the first new entry.
+                        let key_type = state_change_set
+                            .new_tables
+                            .get(&table_handle)
+                            .map(|type_info| &type_info.key_type);
+                        let value_type = table_change.entries.values().find_map(|op| match op {
+                            Op::New(state) => Some(&state.value_type),
+                            _ => None,
+                        });
+                        let (mut object, table) = self.create_table(table_handle)?;
+                        object.value = object.value.with_types(key_type, value_type)?;
+                        (object, table)
+                    }
+                };
                 let new_state_root = table.put_changes(table_change.entries.into_iter())?;
                 object.value.state_root = AccountAddress::new(new_state_root.into());
                 let curr_table_size: i64 = object.value.size as i64;
@@ -316,6 +336,30 @@ impl StateDBStore {
         }
     }
 
+    /// Returns the total bytes of the keys and the values of the table, 0 if the table does not exist.
+    /// All the entries of the table are scanned.
+    pub fn table_bytes(&self, handle: &ObjectID) -> Result<u64, Error> {
+        if handle != &state_resolver::GLOBAL_OBJECT_STORAGE_HANDLE
+            && self.get_as_table(*handle)?.is_none()
+        {
+            return Ok(0);
+        }
+        let mut total_bytes = 0u64;
+        let mut cursor = None;
+        loop {
+            let items = self.resolve_list_state(handle, cursor.clone(), TABLE_BYTES_PAGE_SIZE)?;
+            let page_size = items.len();
+            for (key, state) in items {
+                total_bytes += (key.len() + state.value.len()) as u64;
+                cursor = Some(key);
+            }
+            if page_size < TABLE_BYTES_PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(total_bytes)
+    }
+
     // rebuild statedb via StateSet from dump
     pub fn apply(&self, state_set: StateSet) -> Result<H256> {
         let mut state_root = H256::zero();
@@ -394,4 +438,8 @@ impl StateResolver for StateDBStore {
     ) -> std::result::Result<Vec<StateKV>, Error> {
         self.resolve_list_state(handle, cursor, limit)
     }
+
+    fn resolve_table_bytes(&self, handle: &ObjectID) -> std::result::Result<Option<u64>, Error> {
+        self.table_bytes(handle).map(Some)
+    }
 }
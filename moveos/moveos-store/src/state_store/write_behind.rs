@@ -0,0 +1,366 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::state_store::statedb::StateDBStore;
+use anyhow::{Error, Result};
+use move_core_types::{account_address::AccountAddress, effects::ChangeSet, effects::Op};
+use moveos_types::moveos_std::object::{ObjectEntity, ObjectID};
+use moveos_types::moveos_std::raw_table::TableInfo;
+use moveos_types::state::{State, StateChangeSet};
+use moveos_types::state_resolver::{StateKV, StateResolver, GLOBAL_OBJECT_STORAGE_HANDLE};
+use parking_lot::{Condvar, Mutex, RwLock};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// The writes which are applied in memory but not persisted yet.
+/// Every write is tagged with the sequence number of the change set it comes from,
+/// so the background writer only clears the writes it has persisted.
+/// A persisted change set stays pending until it is cleared, so every pending write holds the same
+/// value as the inner store once persisted, and the reads never depend on whether it is persisted yet.
+#[derive(Default)]
+struct PendingState {
+    entries: BTreeMap<ObjectID, BTreeMap<Vec<u8>, (u64, Option<State>)>>,
+    /// The size of each table after each change set which changed it, absolute rather than an increment,
+    /// so it is not counted twice once the change set is persisted.
+    table_sizes: BTreeMap<ObjectID, BTreeMap<u64, u64>>,
+    removed_tables: BTreeMap<ObjectID, u64>,
+    error: Option<Error>,
+}
+
+impl PendingState {
+    fn insert(
+        &mut self,
+        inner: &StateDBStore,
+        sequence: u64,
+        state_change_set: &StateChangeSet,
+    ) -> Result<()> {
+        // Resolve all the sizes first, so nothing is inserted if one fails.
+        let mut updated_table_sizes = vec![];
+        for (handle, table_change) in &state_change_set.changes {
+            if *handle != GLOBAL_OBJECT_STORAGE_HANDLE && table_change.size_increment != 0 {
+                let updated_table_size =
+                    self.table_size(inner, handle)? as i64 + table_change.size_increment;
+                debug_assert!(updated_table_size >= 0);
+                updated_table_sizes.push((*handle, updated_table_size as u64));
+            }
+        }
+        for (handle, updated_table_size) in updated_table_sizes {
+            self.table_sizes
+                .entry(handle)
+                .or_default()
+                .insert(sequence, updated_table_size);
+        }
+        for (handle, table_change) in &state_change_set.changes {
+            let entries = self.entries.entry(*handle).or_default();
+            for (key, op) in &table_change.entries {
+                let state = match op {
+                    Op::New(state) | Op::Modify(state) => Some(state.clone()),
+                    Op::Delete => None,
+                };
+                entries.insert(key.clone(), (sequence, state));
+            }
+        }
+        for handle in &state_change_set.removed_tables {
+            self.removed_tables.insert(*handle, sequence);
+        }
+        Ok(())
+    }
+
+    /// The size of the table with all the applied change sets. The inner store has every change
+    /// of the table size which is not pending, as the writer only clears the persisted ones.
+    fn table_size(&self, inner: &StateDBStore, handle: &ObjectID) -> Result<u64> {
+        if let Some(size) = self.pending_table_size(handle) {
+            return Ok(size);
+        }
+        match self.resolve_item(inner, &GLOBAL_OBJECT_STORAGE_HANDLE, &handle.to_bytes())? {
+            Some(state) => Ok(state.as_object::<TableInfo>()?.value.size),
+            None => Ok(0),
+        }
+    }
+
+    /// Whether the write of the change set `sequence` to the table is superseded by a pending removal
+    /// of the table. The inner store removes a table after the other changes of the same change set.
+    fn is_removed(&self, handle: &ObjectID, sequence: u64) -> bool {
+        self.removed_tables
+            .get(handle)
+            .map_or(false, |removed_sequence| sequence <= *removed_sequence)
+    }
+
+    /// The latest pending size of the table, None if it is not changed since it is persisted or removed.
+    fn pending_table_size(&self, handle: &ObjectID) -> Option<u64> {
+        self.table_sizes
+            .get(handle)
+            .and_then(|sizes| sizes.last_key_value())
+            .filter(|(sequence, _)| !self.is_removed(handle, **sequence))
+            .map(|(_, size)| *size)
+    }
+
+    /// Resolve the item with the pending writes, without updating the size of a table object.
+    /// The object of a removed table is removed too, as well as the items of the table.
+    fn resolve_item(
+        &self,
+        inner: &StateDBStore,
+        handle: &ObjectID,
+        key: &[u8],
+    ) -> Result<Option<State>> {
+        let removed_handle = if *handle == GLOBAL_OBJECT_STORAGE_HANDLE {
+            ObjectID::from_bytes(key).ok()
+        } else {
+            Some(*handle)
+        };
+        let removed_sequence =
+            removed_handle.and_then(|removed_handle| self.removed_tables.get(&removed_handle));
+        match self
+            .entries
+            .get(handle)
+            .and_then(|entries| entries.get(key))
+        {
+            Some((sequence, state))
+                if removed_sequence
+                    .map_or(true, |removed_sequence| sequence > removed_sequence) =>
+            {
+                Ok(state.clone())
+            }
+            _ if removed_sequence.is_some() => Ok(None),
+            _ => inner.resolve_table_item(handle, key),
+        }
+    }
+
+    /// Remove the writes of the change sets up to `sequence`, they are persisted.
+    fn clear(&mut self, sequence: u64) {
+        self.entries.retain(|_, entries| {
+            entries.retain(|_, (entry_sequence, _)| *entry_sequence > sequence);
+            !entries.is_empty()
+        });
+        self.table_sizes.retain(|_, sizes| {
+            sizes.retain(|size_sequence, _| *size_sequence > sequence);
+            !sizes.is_empty()
+        });
+        self.removed_tables
+            .retain(|_, removed_sequence| *removed_sequence > sequence);
+    }
+
+    fn is_table_pending(&self, handle: &ObjectID) -> bool {
+        self.entries.contains_key(handle)
+            || self.table_sizes.contains_key(handle)
+            || self.removed_tables.contains_key(handle)
+    }
+}
+
+struct Shared {
+    inner: Arc<StateDBStore>,
+    pending: RwLock<PendingState>,
+    pending_writes: AtomicUsize,
+    /// Cleared when the writer thread exits, including by a panic, so `flush` does not wait for it forever.
+    writer_alive: AtomicBool,
+    flushed: (Mutex<()>, Condvar),
+}
+
+impl Shared {
+    fn notify_flushed(&self) {
+        let (lock, condvar) = &self.flushed;
+        let _guard = lock.lock();
+        condvar.notify_all();
+    }
+}
+
+/// Marks the writer as stopped when the writer thread exits or unwinds.
+struct WriterGuard(Arc<Shared>);
+
+impl Drop for WriterGuard {
+    fn drop(&mut self) {
+        self.0.writer_alive.store(false, Ordering::SeqCst);
+        self.0.notify_flushed();
+    }
+}
+
+/// A StateResolver which applies the change sets in memory and persists them to the inner StateDBStore
+/// in a background thread.
+/// The reads see all the applied change sets (read-your-writes), even if they are not persisted yet.
+/// The applied but not persisted change sets are lost if the process crashes, call `flush` before shutdown.
+/// The state root of a table object is only updated after the table changes are persisted,
+/// the table size is always up to date.
+/// It is not wired into `MoveOSStore`, whose `apply_change_set` returns the new state root of each
+/// transaction and so must persist synchronously. A node opts in by building it over its `StateDBStore`
+/// and serving the table reads from it.
+pub struct WriteBehindResolver {
+    shared: Arc<Shared>,
+    sender: Option<Sender<(u64, StateChangeSet)>>,
+    writer: Option<JoinHandle<()>>,
+    next_sequence: Mutex<u64>,
+}
+
+impl WriteBehindResolver {
+    pub fn new(inner: Arc<StateDBStore>) -> Self {
+        let shared = Arc::new(Shared {
+            inner,
+            pending: RwLock::new(PendingState::default()),
+            pending_writes: AtomicUsize::new(0),
+            writer_alive: AtomicBool::new(true),
+            flushed: (Mutex::new(()), Condvar::new()),
+        });
+        let (sender, receiver) = channel::<(u64, StateChangeSet)>();
+        let writer_guard = WriterGuard(shared.clone());
+        let writer = std::thread::spawn(move || {
+            let writer_shared = &writer_guard.0;
+            while let Ok((sequence, state_change_set)) = receiver.recv() {
+                // Persist without the lock, the change set stays pending until it is cleared,
+                // so the readers are not blocked by the disk write.
+                let result = writer_shared
+                    .inner
+                    .apply_change_set(ChangeSet::new(), state_change_set);
+                {
+                    let mut pending = writer_shared.pending.write();
+                    if let Err(e) = result {
+                        pending.error.get_or_insert(e.context(format!(
+                            "WriteBehindResolver persist change set {} failed",
+                            sequence
+                        )));
+                    }
+                    pending.clear(sequence);
+                }
+                writer_shared.pending_writes.fetch_sub(1, Ordering::SeqCst);
+                writer_shared.notify_flushed();
+            }
+        });
+        Self {
+            shared,
+            sender: Some(sender),
+            writer: Some(writer),
+            next_sequence: Mutex::new(0),
+        }
+    }
+
+    /// Apply the change sets in memory, and queue them to be persisted in the background.
+    /// The ChangeSet only creates the account storages, it is applied synchronously after the pending writes are flushed.
+    pub fn apply_change_set(
+        &self,
+        change_set: ChangeSet,
+        state_change_set: StateChangeSet,
+    ) -> Result<()> {
+        if !change_set.accounts().is_empty() {
+            self.flush()?;
+            self.shared
+                .inner
+                .apply_change_set(change_set, state_change_set)?;
+            return Ok(());
+        }
+        let mut next_sequence = self.next_sequence.lock();
+        let sequence = *next_sequence;
+        self.shared
+            .pending
+            .write()
+            .insert(&self.shared.inner, sequence, &state_change_set)?;
+        *next_sequence += 1;
+        self.shared.pending_writes.fetch_add(1, Ordering::SeqCst);
+        self.sender
+            .as_ref()
+            .expect("The sender only be taken when drop")
+            .send((sequence, state_change_set))
+            .map_err(|e| anyhow::anyhow!("WriteBehindResolver writer stopped: {:?}", e))?;
+        Ok(())
+    }
+
+    /// The number of change sets which are applied but not persisted yet.
+    pub fn pending_writes(&self) -> usize {
+        self.shared.pending_writes.load(Ordering::SeqCst)
+    }
+
+    /// Block until all the applied change sets are persisted.
+    /// Returns the first error of the background writer, if any, or an error if the writer has stopped
+    /// with change sets not persisted, such as after a panic.
+    pub fn flush(&self) -> Result<()> {
+        {
+            let (lock, condvar) = &self.shared.flushed;
+            let mut guard = lock.lock();
+            while self.pending_writes() > 0 {
+                if !self.shared.writer_alive.load(Ordering::SeqCst) {
+                    anyhow::bail!(
+                        "WriteBehindResolver writer stopped with {} change sets not persisted",
+                        self.pending_writes()
+                    );
+                }
+                condvar.wait(&mut guard);
+            }
+        }
+        match self.shared.pending.write().error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn resolve_table_object(
+        &self,
+        pending: &PendingState,
+        key: &[u8],
+        state: Option<State>,
+    ) -> Result<Option<State>> {
+        let table_handle = match ObjectID::from_bytes(key) {
+            Ok(table_handle) => table_handle,
+            Err(_) => return Ok(state),
+        };
+        let table_size = match pending.pending_table_size(&table_handle) {
+            Some(size) => size,
+            None => return Ok(state),
+        };
+        let mut object = match state {
+            Some(state) => state.as_object::<TableInfo>()?,
+            None => {
+                ObjectEntity::new_table_object(table_handle, TableInfo::new(AccountAddress::ZERO))
+            }
+        };
+        object.value.size = table_size;
+        Ok(Some(object.into()))
+    }
+}
+
+impl StateResolver for WriteBehindResolver {
+    fn resolve_table_item(
+        &self,
+        handle: &ObjectID,
+        key: &[u8],
+    ) -> std::result::Result<Option<State>, Error> {
+        let pending = self.shared.pending.read();
+        let state = pending.resolve_item(&self.shared.inner, handle, key)?;
+        if *handle == GLOBAL_OBJECT_STORAGE_HANDLE {
+            self.resolve_table_object(&pending, key, state)
+        } else {
+            Ok(state)
+        }
+    }
+
+    /// The pending writes are not in the SMT order, so listing a table with pending writes
+    /// waits for them to be persisted.
+    fn list_table_items(
+        &self,
+        handle: &ObjectID,
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+    ) -> std::result::Result<Vec<StateKV>, Error> {
+        if self.shared.pending.read().is_table_pending(handle) {
+            self.flush()?;
+        }
+        self.shared.inner.list_table_items(handle, cursor, limit)
+    }
+
+    /// The pending writes of the table are persisted first, so the inner store sees them.
+    fn resolve_table_bytes(&self, handle: &ObjectID) -> std::result::Result<Option<u64>, Error> {
+        if self.shared.pending.read().is_table_pending(handle) {
+            self.flush()?;
+        }
+        StateResolver::resolve_table_bytes(self.shared.inner.as_ref(), handle)
+    }
+}
+
+impl Drop for WriteBehindResolver {
+    fn drop(&mut self) {
+        // Close the channel, the writer persists the queued change sets and exits.
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
@@ -3,3 +3,4 @@
 
 mod test_state_store;
 mod test_store;
+mod test_write_behind;
@@ -5,12 +5,15 @@ use crate::MoveOSStore;
 use anyhow::Result;
 use move_core_types::account_address::AccountAddress;
 use move_core_types::effects::{AccountChangeSet, ChangeSet, Op};
+use move_core_types::language_storage::TypeTag;
 use moveos_types::h256::H256;
 use moveos_types::move_std::string::MoveString;
 use moveos_types::move_types::random_type_tag;
 use moveos_types::moveos_std::context;
 use moveos_types::moveos_std::object::{NamedTableID, ObjectID};
-use moveos_types::state::{MoveState, State, StateChangeSet, TableChange, TableTypeInfo};
+use moveos_types::moveos_std::raw_table::TableInfo;
+use moveos_types::state::{MoveState, MoveType, State, StateChangeSet, TableChange, TableTypeInfo};
+use moveos_types::state_resolver::{StateResolver, GLOBAL_OBJECT_STORAGE_HANDLE};
 use rand::{thread_rng, Rng};
 use smt::NodeStore;
 use std::str::FromStr;
@@ -147,6 +150,100 @@ fn test_statedb() {
     assert_eq!(state.unwrap(), value.into());
 }
 
+#[test]
+fn test_statedb_empty_value() {
+    let moveos_store = MoveOSStore::mock_moveos_store().unwrap();
+
+    let mut table_change_set = StateChangeSet::default();
+    let table_handle = ObjectID::ONE;
+    let mut table_change = TableChange::default();
+    let key = MoveString::from_str("test_key").unwrap();
+    let value = State::new(vec![], random_type_tag());
+
+    table_change
+        .entries
+        .insert(key.to_bytes(), Op::New(value.clone()));
+    table_change_set.changes.insert(table_handle, table_change);
+    moveos_store
+        .get_state_store()
+        .apply_change_set(ChangeSet::new(), table_change_set)
+        .unwrap();
+
+    // The empty value is present, it must not be resolved as a missing value.
+    let state = moveos_store
+        .get_state_store()
+        .resolve_state(&table_handle, &key.to_bytes())
+        .unwrap();
+    assert_eq!(state, Some(value));
+}
+
+#[test]
+fn test_statedb_table_bytes() {
+    let moveos_store = MoveOSStore::mock_moveos_store().unwrap();
+    let table_handle = ObjectID::ONE;
+    assert_eq!(
+        moveos_store
+            .get_state_store()
+            .table_bytes(&table_handle)
+            .unwrap(),
+        0
+    );
+
+    let mut table_change_set = StateChangeSet::default();
+    let mut table_change = TableChange::default();
+    let mut expected_bytes = 0u64;
+    for (key, value) in [("key1", "value1"), ("key2", "value22")] {
+        let key = MoveString::from_str(key).unwrap().to_bytes();
+        let value: State = MoveString::from_str(value).unwrap().into();
+        expected_bytes += (key.len() + value.value.len()) as u64;
+        table_change.entries.insert(key, Op::New(value));
+    }
+    table_change_set.changes.insert(table_handle, table_change);
+    moveos_store
+        .get_state_store()
+        .apply_change_set(ChangeSet::new(), table_change_set)
+        .unwrap();
+
+    assert_eq!(
+        moveos_store
+            .get_state_store()
+            .table_bytes(&table_handle)
+            .unwrap(),
+        expected_bytes
+    );
+}
+
+#[test]
+fn test_statedb_table_types() -> Result<()> {
+    let moveos_store = MoveOSStore::mock_moveos_store()?;
+    let table_handle = ObjectID::ONE;
+
+    let mut table_change_set = StateChangeSet::default();
+    table_change_set
+        .new_tables
+        .insert(table_handle, TableTypeInfo::new(MoveString::type_tag()));
+    let mut table_change = TableChange::default();
+    table_change.entries.insert(
+        MoveString::from_str("key")?.to_bytes(),
+        Op::New(State::new(1u64.to_bytes(), TypeTag::U64)),
+    );
+    table_change_set.changes.insert(table_handle, table_change);
+    moveos_store
+        .get_state_store()
+        .apply_change_set(ChangeSet::new(), table_change_set)?;
+
+    // The key and the value types are kept in the table object when the table is created.
+    let table_info = moveos_store
+        .get_state_store()
+        .resolve_table_item(&GLOBAL_OBJECT_STORAGE_HANDLE, &table_handle.to_bytes())?
+        .expect("the table object should exist")
+        .as_object::<TableInfo>()?
+        .value;
+    assert_eq!(table_info.key_type()?, Some(MoveString::type_tag()));
+    assert_eq!(table_info.value_type()?, Some(TypeTag::U64));
+    Ok(())
+}
+
 #[test]
 fn test_reopen() {
     let moveos_store = MoveOSStore::mock_moveos_store().unwrap();
@@ -0,0 +1,157 @@
+// Copyright (c) RoochNetwork
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::state_store::statedb::StateDBStore;
+use crate::state_store::write_behind::WriteBehindResolver;
+use crate::MoveOSDB;
+use anyhow::Result;
+use move_core_types::effects::{ChangeSet, Op};
+use moveos_types::move_std::string::MoveString;
+use moveos_types::moveos_std::object::ObjectID;
+use moveos_types::moveos_std::raw_table::TableInfo;
+use moveos_types::state::{MoveState, StateChangeSet, TableChange};
+use moveos_types::state_resolver::{StateResolver, GLOBAL_OBJECT_STORAGE_HANDLE};
+use std::str::FromStr;
+use std::sync::Arc;
+
+fn table_change_set(
+    table_handle: ObjectID,
+    key: &MoveString,
+    value: Option<MoveString>,
+) -> StateChangeSet {
+    let mut table_change = TableChange::default();
+    let op = match value {
+        Some(value) => Op::New(value.into()),
+        None => Op::Delete,
+    };
+    table_change.entries.insert(key.to_bytes(), op);
+    let mut state_change_set = StateChangeSet::default();
+    state_change_set.changes.insert(table_handle, table_change);
+    state_change_set
+}
+
+#[test]
+fn test_write_behind_read_your_writes() -> Result<()> {
+    let moveosdb = MoveOSDB::mock_moveosdb()?;
+    let statedb = Arc::new(StateDBStore::new(moveosdb.node_store.clone()));
+    let resolver = WriteBehindResolver::new(statedb.clone());
+
+    let table_handle = ObjectID::ONE;
+    let key = MoveString::from_str("test_key")?;
+    let value = MoveString::from_str("test_value")?;
+
+    resolver.apply_change_set(
+        ChangeSet::new(),
+        table_change_set(table_handle, &key, Some(value.clone())),
+    )?;
+    assert_eq!(
+        resolver.resolve_table_item(&table_handle, &key.to_bytes())?,
+        Some(value.clone().into())
+    );
+
+    resolver.flush()?;
+    assert_eq!(resolver.pending_writes(), 0);
+    assert_eq!(
+        statedb.resolve_table_item(&table_handle, &key.to_bytes())?,
+        Some(value.into())
+    );
+
+    resolver.apply_change_set(ChangeSet::new(), table_change_set(table_handle, &key, None))?;
+    assert_eq!(
+        resolver.resolve_table_item(&table_handle, &key.to_bytes())?,
+        None
+    );
+    resolver.flush()?;
+    assert_eq!(
+        statedb.resolve_table_item(&table_handle, &key.to_bytes())?,
+        None
+    );
+    Ok(())
+}
+
+fn table_size(resolver: &dyn StateResolver, table_handle: ObjectID) -> Result<u64> {
+    let state = resolver
+        .resolve_table_item(&GLOBAL_OBJECT_STORAGE_HANDLE, &table_handle.to_bytes())?
+        .expect("the table object should exist");
+    Ok(state.as_object::<TableInfo>()?.value.size)
+}
+
+#[test]
+fn test_write_behind_table_size() -> Result<()> {
+    let moveosdb = MoveOSDB::mock_moveosdb()?;
+    let statedb = Arc::new(StateDBStore::new(moveosdb.node_store.clone()));
+    let resolver = WriteBehindResolver::new(statedb.clone());
+
+    let table_handle = ObjectID::ONE;
+    for (index, key) in ["key1", "key2"].into_iter().enumerate() {
+        let key = MoveString::from_str(key)?;
+        let mut state_change_set = table_change_set(table_handle, &key, Some(key.clone()));
+        state_change_set
+            .changes
+            .get_mut(&table_handle)
+            .expect("the table change should exist")
+            .size_increment = 1;
+        resolver.apply_change_set(ChangeSet::new(), state_change_set)?;
+        assert_eq!(table_size(&resolver, table_handle)?, index as u64 + 1);
+    }
+
+    // The persisted size is not counted twice.
+    resolver.flush()?;
+    assert_eq!(table_size(&resolver, table_handle)?, 2);
+    assert_eq!(table_size(statedb.as_ref(), table_handle)?, 2);
+    Ok(())
+}
+
+#[test]
+fn test_write_behind_removed_table() -> Result<()> {
+    let moveosdb = MoveOSDB::mock_moveosdb()?;
+    let statedb = Arc::new(StateDBStore::new(moveosdb.node_store.clone()));
+    let resolver = WriteBehindResolver::new(statedb.clone());
+
+    let table_handle = ObjectID::ONE;
+    let key1 = MoveString::from_str("key1")?;
+    let key2 = MoveString::from_str("key2")?;
+    let mut state_change_set = table_change_set(table_handle, &key1, Some(key1.clone()));
+    state_change_set
+        .changes
+        .get_mut(&table_handle)
+        .expect("the table change should exist")
+        .size_increment = 1;
+    resolver.apply_change_set(ChangeSet::new(), state_change_set)?;
+    resolver.flush()?;
+
+    // The removed table and its items are not resolved, even before the removal is persisted.
+    let mut state_change_set = StateChangeSet::default();
+    state_change_set.removed_tables.insert(table_handle);
+    resolver.apply_change_set(ChangeSet::new(), state_change_set)?;
+    assert_eq!(
+        resolver.resolve_table_item(&GLOBAL_OBJECT_STORAGE_HANDLE, &table_handle.to_bytes())?,
+        None
+    );
+    assert_eq!(
+        resolver.resolve_table_item(&table_handle, &key1.to_bytes())?,
+        None
+    );
+
+    // A table created again after the removal starts empty.
+    let mut state_change_set = table_change_set(table_handle, &key2, Some(key2.clone()));
+    state_change_set
+        .changes
+        .get_mut(&table_handle)
+        .expect("the table change should exist")
+        .size_increment = 1;
+    resolver.apply_change_set(ChangeSet::new(), state_change_set)?;
+    assert_eq!(table_size(&resolver, table_handle)?, 1);
+    assert_eq!(
+        resolver.resolve_table_item(&table_handle, &key1.to_bytes())?,
+        None
+    );
+
+    resolver.flush()?;
+    assert_eq!(table_size(statedb.as_ref(), table_handle)?, 1);
+    assert_eq!(
+        statedb.resolve_table_item(&table_handle, &key1.to_bytes())?,
+        None
+    );
+    Ok(())
+}
@@ -3,12 +3,15 @@
 
 use crate::{
     addresses::MOVEOS_STD_ADDRESS,
+    moveos_std::object::ObjectID,
     state::{MoveStructState, MoveStructType},
 };
+use anyhow::{ensure, Result};
 use move_core_types::{
     account_address::AccountAddress,
     ident_str,
     identifier::IdentStr,
+    language_storage::TypeTag,
     value::{MoveStructLayout, MoveTypeLayout},
 };
 use serde::{Deserialize, Serialize};
@@ -19,8 +22,11 @@ pub const MODULE_NAME: &IdentStr = ident_str!("raw_table");
 pub struct TableInfo {
     //TODO use u256?
     pub state_root: AccountAddress,
-    //TODO keep Table Key TypeTag at here
     pub size: u64,
+    /// The BCS bytes of the `TypeTag` of the keys, kept when the table is created, empty if it is not known.
+    pub key_type: Vec<u8>,
+    /// The BCS bytes of the `TypeTag` of the values, kept when the table is created, empty if it is not known.
+    pub value_type: Vec<u8>,
 }
 
 impl TableInfo {
@@ -28,8 +34,41 @@ impl TableInfo {
         TableInfo {
             state_root,
             size: 0u64,
+            key_type: vec![],
+            value_type: vec![],
         }
     }
+
+    /// Keep the key and the value types of the table, None if the type is not known.
+    pub fn with_types(
+        mut self,
+        key_type: Option<&TypeTag>,
+        value_type: Option<&TypeTag>,
+    ) -> Result<Self> {
+        self.key_type = key_type.map(bcs::to_bytes).transpose()?.unwrap_or_default();
+        self.value_type = value_type
+            .map(bcs::to_bytes)
+            .transpose()?
+            .unwrap_or_default();
+        Ok(self)
+    }
+
+    /// Returns the key type kept when the table is created, None if it is not known.
+    pub fn key_type(&self) -> Result<Option<TypeTag>> {
+        Self::decode_type(&self.key_type)
+    }
+
+    /// Returns the value type kept when the table is created, None if it is not known.
+    pub fn value_type(&self) -> Result<Option<TypeTag>> {
+        Self::decode_type(&self.value_type)
+    }
+
+    fn decode_type(bytes: &[u8]) -> Result<Option<TypeTag>> {
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(bcs::from_bytes(bytes)?))
+    }
 }
 
 impl MoveStructType for TableInfo {
@@ -40,6 +79,60 @@ impl MoveStructType for TableInfo {
 
 impl MoveStructState for TableInfo {
     fn struct_layout() -> MoveStructLayout {
-        MoveStructLayout::new(vec![MoveTypeLayout::Address, MoveTypeLayout::U64])
+        MoveStructLayout::new(vec![
+            MoveTypeLayout::Address,
+            MoveTypeLayout::U64,
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+        ])
+    }
+}
+
+/// Returns the flat storage key of a table entry, for the external KV backends which store all the tables
+/// in one key space. The layout is the 32 bytes of the table handle (the `ObjectID` of the table, which has
+/// the same layout as the Move `TableHandle`) followed by the BCS bytes of the user key, without a length
+/// prefix. The handle has a fixed length, so the composition is unambiguous and the entries of a table
+/// share the handle as the key prefix.
+pub fn storage_key(handle: &ObjectID, key: &[u8]) -> Vec<u8> {
+    let mut storage_key = Vec::with_capacity(ObjectID::LENGTH + key.len());
+    storage_key.extend_from_slice(&handle.to_bytes());
+    storage_key.extend_from_slice(key);
+    storage_key
+}
+
+/// Split a storage key composed by `storage_key` into the table handle and the BCS bytes of the user key.
+pub fn split_storage_key(storage_key: &[u8]) -> Result<(ObjectID, &[u8])> {
+    ensure!(
+        storage_key.len() >= ObjectID::LENGTH,
+        "Invalid storage key, length:{}",
+        storage_key.len()
+    );
+    let (handle, key) = storage_key.split_at(ObjectID::LENGTH);
+    Ok((ObjectID::from_bytes(handle)?, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_key_round_trip() {
+        let handle = ObjectID::from(AccountAddress::random());
+        for key in [vec![], vec![0u8], bcs::to_bytes(&42u64).unwrap()] {
+            let storage_key = storage_key(&handle, &key);
+            assert_eq!(storage_key.len(), ObjectID::LENGTH + key.len());
+            assert_eq!(
+                &storage_key[..ObjectID::LENGTH],
+                handle.to_bytes().as_slice()
+            );
+            let (split_handle, split_key) = split_storage_key(&storage_key).unwrap();
+            assert_eq!(split_handle, handle);
+            assert_eq!(split_key, key.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_split_storage_key_too_short() {
+        assert!(split_storage_key(&[0u8; ObjectID::LENGTH - 1]).is_err());
     }
 }
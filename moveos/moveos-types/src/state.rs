@@ -1,6 +1,7 @@
 // Copyright (c) RoochNetwork
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::h256;
 use crate::moveos_std::object::{AnnotatedObject, ObjectEntity, ObjectID, RawObject};
 use anyhow::{bail, ensure, Result};
 use move_core_types::{
@@ -506,6 +507,132 @@ impl StateChangeSet {
         let table_change = self.get_or_insert_table_change(handle);
         table_change.entries.insert(key, op);
     }
+
+    /// Serialize the change set for persistence: a format version byte, the BCS encoded change set,
+    /// and a sha3-256 checksum of the preceding bytes.
+    /// The maps are ordered, so the same change set always serializes to the same bytes.
+    pub fn serialize_versioned(&self) -> Vec<u8> {
+        let change_set = StateChangeSetV1::from(self);
+        let mut bytes = vec![STATE_CHANGE_SET_FORMAT_VERSION];
+        bytes.extend(bcs::to_bytes(&change_set).expect("Serialize StateChangeSet should success"));
+        let checksum = h256::sha3_256_of(&bytes);
+        bytes.extend_from_slice(checksum.as_bytes());
+        bytes
+    }
+
+    /// Deserialize the bytes returned by `serialize_versioned`.
+    /// Errors if the format version is not supported or the checksum does not match.
+    pub fn deserialize_versioned(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() > 1 + h256::LENGTH,
+            "The versioned StateChangeSet is too short: {} bytes",
+            bytes.len()
+        );
+        let (content, checksum) = bytes.split_at(bytes.len() - h256::LENGTH);
+        ensure!(
+            h256::sha3_256_of(content).as_bytes() == checksum,
+            "The versioned StateChangeSet checksum mismatch, the data is corrupted"
+        );
+        match content[0] {
+            STATE_CHANGE_SET_FORMAT_VERSION => {
+                Ok(bcs::from_bytes::<StateChangeSetV1>(&content[1..])?.into())
+            }
+            version => bail!(
+                "Unsupported StateChangeSet format version {}, expect {}",
+                version,
+                STATE_CHANGE_SET_FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+/// The current format version of `StateChangeSet::serialize_versioned`.
+pub const STATE_CHANGE_SET_FORMAT_VERSION: u8 = 1;
+
+/// The persisted layout of the format version 1, it must never change once released,
+/// a new layout requires a new format version.
+#[derive(Serialize, Deserialize)]
+struct StateChangeSetV1 {
+    new_tables: BTreeMap<ObjectID, TableTypeInfo>,
+    removed_tables: BTreeSet<ObjectID>,
+    changes: BTreeMap<ObjectID, TableChangeV1>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TableChangeV1 {
+    entries: BTreeMap<Vec<u8>, OpV1>,
+    size_increment: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+enum OpV1 {
+    New(State),
+    Modify(State),
+    Delete,
+}
+
+impl From<&StateChangeSet> for StateChangeSetV1 {
+    fn from(change_set: &StateChangeSet) -> Self {
+        Self {
+            new_tables: change_set.new_tables.clone(),
+            removed_tables: change_set.removed_tables.clone(),
+            changes: change_set
+                .changes
+                .iter()
+                .map(|(handle, table_change)| {
+                    let entries = table_change
+                        .entries
+                        .iter()
+                        .map(|(key, op)| {
+                            let op = match op {
+                                Op::New(state) => OpV1::New(state.clone()),
+                                Op::Modify(state) => OpV1::Modify(state.clone()),
+                                Op::Delete => OpV1::Delete,
+                            };
+                            (key.clone(), op)
+                        })
+                        .collect();
+                    let table_change = TableChangeV1 {
+                        entries,
+                        size_increment: table_change.size_increment,
+                    };
+                    (*handle, table_change)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<StateChangeSetV1> for StateChangeSet {
+    fn from(change_set: StateChangeSetV1) -> Self {
+        Self {
+            new_tables: change_set.new_tables,
+            removed_tables: change_set.removed_tables,
+            changes: change_set
+                .changes
+                .into_iter()
+                .map(|(handle, table_change)| {
+                    let entries = table_change
+                        .entries
+                        .into_iter()
+                        .map(|(key, op)| {
+                            let op = match op {
+                                OpV1::New(state) => Op::New(state),
+                                OpV1::Modify(state) => Op::Modify(state),
+                                OpV1::Delete => Op::Delete,
+                            };
+                            (key, op)
+                        })
+                        .collect();
+                    let table_change = TableChange {
+                        entries,
+                        size_increment: table_change.size_increment,
+                    };
+                    (handle, table_change)
+                })
+                .collect(),
+        }
+    }
 }
 /// A change of a single table.
 #[derive(Default, Clone, Debug)]
@@ -531,3 +658,47 @@ impl StateSet {
         self.state_sets.insert(k, v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_state_change_set() -> StateChangeSet {
+        let handle = ObjectID::from(AccountAddress::random());
+        let mut change_set = StateChangeSet::default();
+        change_set
+            .new_tables
+            .insert(handle, TableTypeInfo::new(TypeTag::U64));
+        change_set
+            .removed_tables
+            .insert(ObjectID::from(AccountAddress::random()));
+        change_set.add_op(handle, vec![1], Op::New(State::new(vec![1], TypeTag::U8)));
+        change_set.add_op(
+            handle,
+            vec![2],
+            Op::Modify(State::new(vec![2], TypeTag::U8)),
+        );
+        change_set.add_op(handle, vec![3], Op::Delete);
+        change_set.get_or_insert_table_change(handle).size_increment = 1;
+        change_set
+    }
+
+    #[test]
+    fn test_state_change_set_versioned_serde() {
+        let change_set = mock_state_change_set();
+        let bytes = change_set.serialize_versioned();
+        assert_eq!(bytes[0], STATE_CHANGE_SET_FORMAT_VERSION);
+        let deserialized = StateChangeSet::deserialize_versioned(&bytes).unwrap();
+        assert_eq!(deserialized.serialize_versioned(), bytes);
+    }
+
+    #[test]
+    fn test_state_change_set_versioned_corrupted() {
+        let mut bytes = mock_state_change_set().serialize_versioned();
+        let mut unsupported_version = bytes.clone();
+        unsupported_version[0] = STATE_CHANGE_SET_FORMAT_VERSION + 1;
+        assert!(StateChangeSet::deserialize_versioned(&unsupported_version).is_err());
+        bytes[1] ^= 0xff;
+        assert!(StateChangeSet::deserialize_versioned(&bytes).is_err());
+    }
+}
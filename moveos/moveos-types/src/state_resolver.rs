@@ -5,6 +5,7 @@ use crate::{
     access_path::AccessPath,
     moveos_std::move_module::MoveModule,
     moveos_std::object::{AnnotatedObject, NamedTableID, ObjectID},
+    moveos_std::raw_table::TableInfo,
     state::{AnnotatedState, State},
 };
 use anyhow::{ensure, Result};
@@ -27,6 +28,8 @@ pub type AnnotatedStateKV = (Vec<u8>, AnnotatedState);
 /// otherwise it will get the data from the table state tree.
 /// The key can be an ObjectID or an arbitrary key of a table.
 pub trait StateResolver {
+    /// Returns None only if the key is absent. A present value is always `Some`, even if its serialized bytes are empty,
+    /// so a backend must not use empty bytes to mean a missing value.
     fn resolve_table_item(
         &self,
         handle: &ObjectID,
@@ -40,10 +43,38 @@ pub trait StateResolver {
         limit: usize,
     ) -> Result<Vec<StateKV>, anyhow::Error>;
 
+    /// Resolve multiple items of a table in one call, the result is in the same order as the keys.
+    /// The default implementation resolves the items one by one, a backend can override it with a batched lookup.
+    fn resolve_table_items(
+        &self,
+        handle: &ObjectID,
+        keys: &[Vec<u8>],
+    ) -> Result<Vec<Option<State>>, anyhow::Error> {
+        keys.iter()
+            .map(|key| self.resolve_table_item(handle, key))
+            .collect()
+    }
+
     // get object data from global state tree.
     fn resolve_object_state(&self, object: &ObjectID) -> Result<Option<State>, anyhow::Error> {
         self.resolve_table_item(&GLOBAL_OBJECT_STORAGE_HANDLE, &object.to_bytes())
     }
+
+    /// Returns true if the table has no persisted entries.
+    /// The default implementation checks the size of the table object, a table without object is empty.
+    fn resolve_table_is_empty(&self, handle: &ObjectID) -> Result<bool, anyhow::Error> {
+        Ok(self
+            .resolve_object_state(handle)?
+            .map(|state| state.as_object::<TableInfo>())
+            .transpose()?
+            .map_or(true, |table| table.value.size == 0))
+    }
+
+    /// Returns the total bytes of the keys and the values persisted in the table, 0 if the table does not exist.
+    /// Returns None if the backend can not report it, which is the default.
+    fn resolve_table_bytes(&self, _handle: &ObjectID) -> Result<Option<u64>, anyhow::Error> {
+        Ok(None)
+    }
 }
 
 /// A proxy type for proxy the StateResolver to MoveResolver
@@ -117,6 +148,22 @@ where
     ) -> Result<Vec<StateKV>, anyhow::Error> {
         self.0.list_table_items(handle, cursor, limit)
     }
+
+    fn resolve_table_items(
+        &self,
+        handle: &ObjectID,
+        keys: &[Vec<u8>],
+    ) -> Result<Vec<Option<State>>, anyhow::Error> {
+        self.0.resolve_table_items(handle, keys)
+    }
+
+    fn resolve_table_is_empty(&self, handle: &ObjectID) -> Result<bool, anyhow::Error> {
+        self.0.resolve_table_is_empty(handle)
+    }
+
+    fn resolve_table_bytes(&self, handle: &ObjectID) -> Result<Option<u64>, anyhow::Error> {
+        self.0.resolve_table_bytes(handle)
+    }
 }
 
 pub trait MoveOSResolver: MoveResolver<Err = anyhow::Error> + StateResolver {}
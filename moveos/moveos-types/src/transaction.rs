@@ -264,6 +264,17 @@ pub struct VerifiedMoveOSTransaction {
     pub action: VerifiedMoveAction,
     pub pre_execute_functions: Vec<FunctionCall>,
     pub post_execute_functions: Vec<FunctionCall>,
+    /// The tx order assigned by the sequencer, None if the transaction is not sequenced.
+    /// The tables which record the last modified tx order of their entries need it.
+    #[serde(default)]
+    pub tx_order: Option<u64>,
+}
+
+impl VerifiedMoveOSTransaction {
+    pub fn with_tx_order(mut self, tx_order: u64) -> Self {
+        self.tx_order = Some(tx_order);
+        self
+    }
 }
 
 /// RawTransactionOutput is the execution result of a MoveOS transaction
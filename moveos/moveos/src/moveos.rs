@@ -38,6 +38,7 @@ use moveos_types::transaction::{
 use moveos_types::{h256::H256, transaction::FunctionCall};
 use moveos_verifier::metadata::load_module_metadata;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct GasPaymentAccount {
@@ -62,6 +63,10 @@ impl MoveStructState for GasPaymentAccount {
 
 pub struct MoveOSConfig {
     pub vm_config: VMConfig,
+    /// The max number of entries of a table, the inserts beyond it abort. None means unlimited.
+    pub max_entries_per_table: Option<u64>,
+    /// The table resolver loads slower than this are logged at warn. None means disabled.
+    pub slow_table_load_threshold: Option<Duration>,
 }
 
 impl std::fmt::Debug for MoveOSConfig {
@@ -75,6 +80,8 @@ impl std::fmt::Debug for MoveOSConfig {
                 "vm_config.paranoid_type_checks",
                 &self.vm_config.paranoid_type_checks,
             )
+            .field("max_entries_per_table", &self.max_entries_per_table)
+            .field("slow_table_load_threshold", &self.slow_table_load_threshold)
             .finish()
     }
 }
@@ -88,6 +95,8 @@ impl Clone for MoveOSConfig {
                 max_binary_format_version: self.vm_config.max_binary_format_version,
                 paranoid_type_checks: self.vm_config.paranoid_type_checks,
             },
+            max_entries_per_table: self.max_entries_per_table,
+            slow_table_load_threshold: self.slow_table_load_threshold,
         }
     }
 }
@@ -107,7 +116,9 @@ impl MoveOS {
         system_pre_execute_functions: Vec<FunctionCall>,
         system_post_execute_functions: Vec<FunctionCall>,
     ) -> Result<Self> {
-        let vm = MoveOSVM::new(natives, config.vm_config)?;
+        let vm = MoveOSVM::new(natives, config.vm_config)?
+            .with_max_entries_per_table(config.max_entries_per_table)
+            .with_slow_table_load_threshold(config.slow_table_load_threshold);
         Ok(Self {
             vm,
             db: MoveOSResolverProxy(db),
@@ -206,6 +217,7 @@ impl MoveOS {
             action: verified_action,
             pre_execute_functions,
             post_execute_functions,
+            tx_order: None,
         })
     }
 
@@ -215,6 +227,7 @@ impl MoveOS {
             action,
             pre_execute_functions,
             post_execute_functions,
+            tx_order,
         } = tx;
         let tx_hash = ctx.tx_hash();
         if log::log_enabled!(log::Level::Debug) {
@@ -232,7 +245,10 @@ impl MoveOS {
         let system_env = ctx.map.clone();
         let cost_table = initial_cost_schedule();
         let gas_meter = MoveOSGasMeter::new(cost_table, ctx.max_gas_amount);
-        let mut session = self.vm.new_session(&self.db, ctx, gas_meter);
+        let mut session = self
+            .vm
+            .new_session(&self.db, ctx, gas_meter)
+            .with_tx_order(tx_order);
 
         // system pre_execute
         // we do not charge gas for system_pre_execute function
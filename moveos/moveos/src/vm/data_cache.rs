@@ -19,7 +19,9 @@ use move_vm_types::{
     loaded_data::runtime_types::Type,
     values::{GlobalValue, Reference, Struct, Value},
 };
-use moveos_stdlib::natives::moveos_stdlib::raw_table::{serialize, TableData, TableRuntimeValue};
+use moveos_stdlib::natives::moveos_stdlib::raw_table::{
+    serialize, TableData, TableRuntimeValue, VerbatimValue,
+};
 use moveos_types::{
     move_std::string::MoveString,
     moveos_std::move_module::MoveModule,
@@ -272,14 +274,16 @@ pub fn into_change_set(table_data: Arc<RwLock<TableData>>) -> PartialVMResult<St
     for (handle, table) in tables {
         let (_, _, content, size_increment) = table.into_inner();
         let mut entries = BTreeMap::new();
-        for (key, table_value) in content {
-            let (value_layout, value_type, op) = match table_value.into_effect() {
-                Some((value_layout, value_type, op)) => (value_layout, value_type, op),
+        for (key, mut table_value) in content {
+            let verbatim_value = table_value.take_verbatim_value();
+            let op = match table_value.into_effect() {
+                Some(op) => op,
                 None => continue,
             };
             match op {
-                Op::New(box_val) => {
-                    let bytes = unbox_and_serialize(&value_layout, box_val)?;
+                Op::New((value_layout, value_type, box_val)) => {
+                    let bytes =
+                        keep_verbatim(unbox_and_serialize(&value_layout, box_val)?, verbatim_value);
                     entries.insert(
                         key,
                         Op::New(State {
@@ -288,8 +292,9 @@ pub fn into_change_set(table_data: Arc<RwLock<TableData>>) -> PartialVMResult<St
                         }),
                     );
                 }
-                Op::Modify(val) => {
-                    let bytes = unbox_and_serialize(&value_layout, val)?;
+                Op::Modify((value_layout, value_type, val)) => {
+                    let bytes =
+                        keep_verbatim(unbox_and_serialize(&value_layout, val)?, verbatim_value);
                     entries.insert(
                         key,
                         Op::Modify(State {
@@ -322,6 +327,14 @@ pub fn into_change_set(table_data: Arc<RwLock<TableData>>) -> PartialVMResult<St
     })
 }
 
+// Keep the bytes of a value moved in verbatim from another table, if it is not modified after the move.
+fn keep_verbatim(bytes: Vec<u8>, verbatim_value: Option<VerbatimValue>) -> Vec<u8> {
+    match verbatim_value {
+        Some(verbatim_value) => verbatim_value.or_serialized(bytes),
+        None => bytes,
+    }
+}
+
 // Unbox a value of `moveos_std::raw_table::Box<V>` to V and serialize it.
 fn unbox_and_serialize(layout: &MoveTypeLayout, box_val: Value) -> PartialVMResult<Vec<u8>> {
     let mut fields = box_val.value_as::<Struct>()?.unpack()?;
@@ -38,6 +38,7 @@ use moveos_stdlib::natives::moveos_stdlib::{
 use moveos_types::transaction::RawTransactionOutput;
 use moveos_types::{
     function_return_value::FunctionReturnValue,
+    h256::H256,
     move_std::string::MoveString,
     move_types::FunctionId,
     moveos_std::context::Context,
@@ -50,11 +51,15 @@ use moveos_types::{
 };
 use moveos_verifier::verifier::INIT_FN_NAME_IDENTIFIER;
 use parking_lot::RwLock;
-use std::{borrow::Borrow, sync::Arc};
+use std::{borrow::Borrow, sync::Arc, time::Duration};
 
 /// MoveOSVM is a wrapper of MoveVM with MoveOS specific features.
 pub struct MoveOSVM {
     inner: MoveVM,
+    /// The max number of entries of a table, None means unlimited.
+    max_entries_per_table: Option<u64>,
+    /// The table resolver loads slower than this are logged, None means disabled.
+    slow_table_load_threshold: Option<Duration>,
 }
 
 impl MoveOSVM {
@@ -64,16 +69,41 @@ impl MoveOSVM {
     ) -> VMResult<Self> {
         Ok(Self {
             inner: MoveVM::new_with_config(natives, vm_config)?,
+            max_entries_per_table: None,
+            slow_table_load_threshold: None,
         })
     }
 
+    /// Limit the number of entries of each table in the sessions, None means unlimited.
+    pub fn with_max_entries_per_table(mut self, max_entries_per_table: Option<u64>) -> Self {
+        self.max_entries_per_table = max_entries_per_table;
+        self
+    }
+
+    /// Log the table resolver loads slower than the threshold in the sessions, None means disabled.
+    pub fn with_slow_table_load_threshold(
+        mut self,
+        slow_table_load_threshold: Option<Duration>,
+    ) -> Self {
+        self.slow_table_load_threshold = slow_table_load_threshold;
+        self
+    }
+
     pub fn new_session<'r, S: MoveOSResolver, G: SwitchableGasMeter>(
         &self,
         remote: &'r S,
         ctx: TxContext,
         gas_meter: G,
     ) -> MoveOSSession<'r, '_, S, G> {
-        MoveOSSession::new(&self.inner, remote, ctx, gas_meter, false)
+        MoveOSSession::new(
+            &self.inner,
+            remote,
+            ctx,
+            gas_meter,
+            false,
+            self.max_entries_per_table,
+            self.slow_table_load_threshold,
+        )
     }
 
     pub fn new_genesis_session<'r, S: MoveOSResolver>(
@@ -86,7 +116,15 @@ impl MoveOSVM {
         let mut gas_meter = MoveOSGasMeter::new(cost_table, ctx.max_gas_amount);
         gas_meter.set_metering(false);
         // Genesis session do not need to execute pre_execute and post_execute function
-        MoveOSSession::new(&self.inner, remote, ctx, gas_meter, false)
+        MoveOSSession::new(
+            &self.inner,
+            remote,
+            ctx,
+            gas_meter,
+            false,
+            self.max_entries_per_table,
+            self.slow_table_load_threshold,
+        )
     }
 
     pub fn new_readonly_session<'r, S: MoveOSResolver, G: SwitchableGasMeter>(
@@ -95,7 +133,15 @@ impl MoveOSVM {
         ctx: TxContext,
         gas_meter: G,
     ) -> MoveOSSession<'r, '_, S, G> {
-        MoveOSSession::new(&self.inner, remote, ctx, gas_meter, true)
+        MoveOSSession::new(
+            &self.inner,
+            remote,
+            ctx,
+            gas_meter,
+            true,
+            self.max_entries_per_table,
+            self.slow_table_load_threshold,
+        )
     }
 }
 
@@ -110,6 +156,10 @@ pub struct MoveOSSession<'r, 'l, S, G> {
     pub(crate) table_data: Arc<RwLock<TableData>>,
     pub(crate) gas_meter: G,
     pub(crate) read_only: bool,
+    pub(crate) max_entries_per_table: Option<u64>,
+    pub(crate) slow_table_load_threshold: Option<Duration>,
+    /// The tx order of the transaction, the opted-in tables record it as the last modified tx order.
+    pub(crate) tx_order: Option<u64>,
 }
 
 impl<'r, 'l, S, G> MoveOSSession<'r, 'l, S, G>
@@ -123,20 +173,39 @@ where
         ctx: TxContext,
         gas_meter: G,
         read_only: bool,
+        max_entries_per_table: Option<u64>,
+        slow_table_load_threshold: Option<Duration>,
     ) -> Self {
         let ctx = Context::new(ctx);
         let table_data = Arc::new(RwLock::new(TableData::default()));
         Self {
             vm,
             remote,
-            session: Self::new_inner_session(vm, remote, table_data.clone()),
+            session: Self::new_inner_session(
+                vm,
+                remote,
+                ctx.tx_context.tx_hash(),
+                table_data.clone(),
+                max_entries_per_table,
+                slow_table_load_threshold,
+                read_only,
+            ),
             ctx,
             table_data,
             gas_meter,
             read_only,
+            max_entries_per_table,
+            slow_table_load_threshold,
+            tx_order: None,
         }
     }
 
+    /// Set the tx order of the transaction, the writes to the opted-in tables record it when the session finishes.
+    pub fn with_tx_order(mut self, tx_order: Option<u64>) -> Self {
+        self.tx_order = tx_order;
+        self
+    }
+
     /// Re spawn a new session with the same context.
     pub fn respawn(self, env: SimpleMap<MoveString, Any>) -> Self {
         //FIXME
@@ -146,7 +215,15 @@ where
         let ctx = Context::new(self.ctx.tx_context.spawn(env));
         let table_data = Arc::new(RwLock::new(TableData::default()));
         Self {
-            session: Self::new_inner_session(self.vm, self.remote, table_data.clone()),
+            session: Self::new_inner_session(
+                self.vm,
+                self.remote,
+                ctx.tx_context.tx_hash(),
+                table_data.clone(),
+                self.max_entries_per_table,
+                self.slow_table_load_threshold,
+                self.read_only,
+            ),
             ctx,
             table_data,
             ..self
@@ -156,11 +233,21 @@ where
     fn new_inner_session(
         vm: &'l MoveVM,
         remote: &'r S,
+        tx_hash: H256,
         table_data: Arc<RwLock<TableData>>,
+        max_entries_per_table: Option<u64>,
+        slow_table_load_threshold: Option<Duration>,
+        read_only: bool,
     ) -> Session<'r, 'l, MoveosDataCache<'r, 'l, S>> {
         let mut extensions = NativeContextExtensions::default();
 
-        extensions.add(NativeTableContext::new(remote, table_data.clone()));
+        extensions.add(
+            NativeTableContext::new(remote, table_data.clone())
+                .with_tx_hash(tx_hash)
+                .with_max_entries_per_table(max_entries_per_table)
+                .with_slow_load_threshold(slow_table_load_threshold)
+                .with_read_only(read_only),
+        );
         extensions.add(NativeModuleContext::new(remote));
         extensions.add(NativeEventContext::default());
 
@@ -449,6 +536,9 @@ where
             table_data,
             gas_meter: _,
             read_only,
+            max_entries_per_table: _,
+            slow_table_load_threshold: _,
+            tx_order,
         } = self;
         let (changeset, raw_events, mut extensions) = session.finish_with_extensions()?;
         //We do not use the event API from data_cache. Instead, we use the NativeEventContext
@@ -457,6 +547,12 @@ where
         let raw_events = event_context.into_events();
         drop(extensions);
 
+        if let Some(tx_order) = tx_order {
+            table_data
+                .write()
+                .stamp_last_modified(tx_order)
+                .map_err(|e| e.finish(Location::Undefined))?;
+        }
         let state_changeset =
             into_change_set(table_data).map_err(|e| e.finish(Location::Undefined))?;
 